@@ -2,8 +2,11 @@ use crate::alter_table_tm_es::{
   AlterTableClosed, AlterTableCommit, AlterTablePrepare, AlterTablePrepared,
   AlterTableTMPayloadTypes,
 };
-use crate::common::TNodePath;
-use crate::common::{cur_timestamp, mk_t, BasicIOCtx, Timestamp};
+use crate::common::{
+  btree_multimap_insert, mk_qid, ColName, ColSource, OrigP, ReadRegion, TNodePath,
+};
+use crate::common::{mk_t, BasicIOCtx, HybridClock, Timestamp};
+use crate::expression::{convert_col_valn, full_key_bound, range_row_region_intersection};
 use crate::message as msg;
 use crate::server::ServerContextBase;
 use crate::sql_ast::proc;
@@ -12,7 +15,7 @@ use crate::stmpaxos2pc_rm::{
   STMPaxos2PCRMOuter,
 };
 use crate::stmpaxos2pc_tm::TMMessage;
-use crate::tablet::{TabletContext, TabletPLm};
+use crate::tablet::{ColConversion, RequestedReadProtected, TabletContext, TabletPLm};
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
 
@@ -77,6 +80,10 @@ impl RMServerContext<AlterTableRMPayloadTypes> for TabletContext {
   fn is_leader(&self) -> bool {
     TabletContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -89,6 +96,22 @@ pub struct AlterTableRMInner {
   pub prepared_timestamp: Timestamp,
 }
 
+impl AlterTableRMInner {
+  /// Returns whether the ReadRegion requested in `new` (if any) has been persisted.
+  fn is_read_protected(&self, ctx: &TabletContext) -> bool {
+    if let Some(col_name) = rewritten_col(&self.alter_op) {
+      let read_region = full_col_read_region(ctx, col_name);
+      if let Some(read_regions) = ctx.read_protected.get(&self.prepared_timestamp) {
+        read_regions.contains(&read_region)
+      } else {
+        false
+      }
+    } else {
+      true
+    }
+  }
+}
+
 pub type AlterTableRMES = STMPaxos2PCRMOuter<AlterTableRMPayloadTypes, AlterTableRMInner>;
 pub type AlterTableRMAction = STMPaxos2PCRMAction<AlterTableRMPayloadTypes>;
 
@@ -99,16 +122,44 @@ impl STMPaxos2PCRMInner<AlterTableRMPayloadTypes> for AlterTableRMInner {
     payload: AlterTablePrepare,
   ) -> AlterTableRMInner {
     // Construct the `preparing_timestamp`
-    let mut timestamp = cur_timestamp(io_ctx, ctx.tablet_config.timestamp_suffix_divisor);
-    let col_name = &payload.alter_op.col_name;
-    timestamp = max(timestamp, ctx.table_schema.val_cols.get_lat(col_name));
-    for (_, req) in ctx.waiting_locked_cols.iter().chain(ctx.inserting_locked_cols.iter()) {
-      if req.cols.contains(col_name) {
+    let mut timestamp = ctx.clock.now(io_ctx);
+    if let proc::AlterOp::RenameTable(_) = &payload.alter_op {
+      // Renaming the Table affects all columns, so we proceed like DropTable does.
+      timestamp = max(timestamp, ctx.table_schema.val_cols.get_latest_lat());
+      timestamp = max(timestamp, ctx.presence_timestamp.clone());
+      for (_, req) in ctx.waiting_locked_cols.iter().chain(ctx.inserting_locked_cols.iter()) {
         timestamp = max(timestamp, req.timestamp.clone());
       }
+    } else {
+      let cols = payload.alter_op.cols();
+      for col_name in &cols {
+        timestamp = max(timestamp, ctx.table_schema.val_cols.get_lat(col_name));
+      }
+      for (_, req) in ctx.waiting_locked_cols.iter().chain(ctx.inserting_locked_cols.iter()) {
+        if cols.iter().any(|col_name| req.cols.contains(col_name)) {
+          timestamp = max(timestamp, req.timestamp.clone());
+        }
+      }
     }
     timestamp = timestamp.add(mk_t(1));
 
+    // When renaming a column or changing its type, its data is rewritten at the commit
+    // timestamp. Thus, we request a ReadRegion over the column at `timestamp` so that we wait
+    // for all prior writes to it to finish and prevent any new ones from starting.
+    if let Some(col_name) = rewritten_col(&payload.alter_op) {
+      let read_region = full_col_read_region(ctx, col_name);
+      let protect_qid = mk_qid(io_ctx.rand());
+      btree_multimap_insert(
+        &mut ctx.waiting_read_protected,
+        &timestamp,
+        RequestedReadProtected {
+          orig_p: OrigP::new(protect_qid.clone()),
+          query_id: protect_qid,
+          read_region,
+        },
+      );
+    }
+
     AlterTableRMInner { alter_op: payload.alter_op, prepared_timestamp: timestamp }
   }
 
@@ -129,7 +180,7 @@ impl STMPaxos2PCRMInner<AlterTableRMPayloadTypes> for AlterTableRMInner {
     ctx: &mut TabletContext,
     _: &mut IO,
   ) -> Option<AlterTableRMPrepared> {
-    if ctx.pause_ddl() {
+    if ctx.pause_ddl() || !self.is_read_protected(ctx) {
       None
     } else {
       Some(AlterTableRMPrepared {
@@ -163,11 +214,62 @@ impl STMPaxos2PCRMInner<AlterTableRMPayloadTypes> for AlterTableRMInner {
     _: &mut IO,
     committed_plm: &RMCommittedPLm<AlterTableRMPayloadTypes>,
   ) {
-    ctx.table_schema.val_cols.write(
-      &self.alter_op.col_name,
-      self.alter_op.maybe_col_type.clone(),
-      committed_plm.payload.timestamp.clone(),
-    );
+    let timestamp = &committed_plm.payload.timestamp;
+    match &self.alter_op {
+      proc::AlterOp::AlterColumn(op) => {
+        ctx.table_schema.val_cols.write(&op.col_name, op.maybe_col_type.clone(), timestamp.clone());
+        ctx.table_schema.set_default(&op.col_name, timestamp, op.maybe_default.clone());
+      }
+      proc::AlterOp::RenameColumn(op) => {
+        // Recall that writes to the old column with a Timestamp between `prepared_timestamp`
+        // and `timestamp` can still be granted GlobalLockedCols, so we protect it up to here.
+        let read_region = full_col_read_region(ctx, &op.old_col_name);
+        btree_multimap_insert(&mut ctx.read_protected, timestamp, read_region);
+
+        // Update the `table_schema`. Rather than copying the data over to the new column, we
+        // make the old column its `ColSource`, which reads then resolve through.
+        let (old_col_timestamp, col_type) = ctx
+          .table_schema
+          .val_cols
+          .static_read_version(&op.old_col_name, timestamp)
+          .unwrap()
+          .clone();
+        let default = ctx.table_schema.get_default_static(&op.old_col_name, timestamp);
+        ctx.table_schema.val_cols.write(&op.old_col_name, None, timestamp.clone());
+        ctx.table_schema.val_cols.write(&op.new_col_name, col_type, timestamp.clone());
+        ctx.table_schema.set_default(&op.new_col_name, timestamp, default);
+        let source = ColSource { col: op.old_col_name.clone(), col_timestamp: old_col_timestamp };
+        ctx.table_schema.set_source(&op.new_col_name, timestamp, source);
+      }
+      proc::AlterOp::RenameTable(op) => {
+        let prior_table_path =
+          std::mem::replace(&mut ctx.this_table_path, op.new_table_path.clone());
+        ctx.prior_table_paths.insert(timestamp.clone(), prior_table_path);
+      }
+      proc::AlterOp::ChangeColumnType(op) => {
+        // As for RENAME COLUMN, we protect the column up to `timestamp`.
+        let read_region = full_col_read_region(ctx, &op.col_name);
+        btree_multimap_insert(&mut ctx.read_protected, timestamp, read_region);
+
+        // Update the `table_schema`. Until the existing data is rewritten (which the Leader
+        // does in the background with a `ColConversion`), the prior version of the column is
+        // the `ColSource` of the new one, so that reads convert the values lazily.
+        let (prior_col_timestamp, _) =
+          ctx.table_schema.val_cols.static_read_version(&op.col_name, timestamp).unwrap().clone();
+        let default = ctx.table_schema.get_default_static(&op.col_name, timestamp);
+        ctx.table_schema.val_cols.write(&op.col_name, Some(op.col_type.clone()), timestamp.clone());
+        let default = convert_col_valn(&op.col_type, default);
+        ctx.table_schema.set_default(&op.col_name, timestamp, default);
+        let source = ColSource { col: op.col_name.clone(), col_timestamp: prior_col_timestamp };
+        ctx.table_schema.set_source(&op.col_name, timestamp, source);
+        ctx.col_conversions.push(ColConversion {
+          col_name: op.col_name.clone(),
+          col_type: op.col_type.clone(),
+          timestamp: timestamp.clone(),
+          after_key: None,
+        });
+      }
+    }
   }
 
   fn mk_aborted_plm<IO: BasicIOCtx>(
@@ -184,3 +286,22 @@ impl STMPaxos2PCRMInner<AlterTableRMPayloadTypes> for AlterTableRMInner {
     self.clone()
   }
 }
+
+/// Returns the ValCol whose existing data is rewritten when `alter_op` commits, if any.
+fn rewritten_col(alter_op: &proc::AlterOp) -> Option<&ColName> {
+  match alter_op {
+    proc::AlterOp::RenameColumn(op) => Some(&op.old_col_name),
+    proc::AlterOp::ChangeColumnType(op) => Some(&op.col_name),
+    _ => None,
+  }
+}
+
+/// Computes the `ReadRegion` covering `col_name` across all rows in this Tablet.
+fn full_col_read_region(ctx: &TabletContext, col_name: &ColName) -> ReadRegion {
+  let key_cols = &ctx.table_schema.key_cols;
+  let row_region = vec![full_key_bound(key_cols)];
+  ReadRegion {
+    row_region: range_row_region_intersection(key_cols, &ctx.this_tablet_key_range, row_region),
+    val_col_region: vec![col_name.clone()],
+  }
+}
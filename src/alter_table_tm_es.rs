@@ -1,7 +1,9 @@
 use crate::common::{
-  cur_timestamp, mk_t, BasicIOCtx, GeneralTraceMessage, GossipData, GossipDataView, Timestamp,
+  mk_t, BasicIOCtx, GeneralTraceMessage, GossipData, GossipDataView, HybridClock, Timestamp,
 };
 use crate::common::{EndpointId, RequestId, TNodePath, TSubNodePath, TablePath};
+use crate::create_table_tm_es::next_gen;
+use crate::expression::convert_col_valn;
 use crate::master::{MasterContext, MasterPLm};
 use crate::message as msg;
 use crate::server::ServerContextBase;
@@ -118,6 +120,10 @@ impl TMServerContext<AlterTableTMPayloadTypes> for MasterContext {
   fn is_leader(&self) -> bool {
     MasterContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -184,7 +190,7 @@ impl STMPaxos2PCTMInner<AlterTableTMPayloadTypes> for AlterTableTMInner {
     io_ctx: &mut IO,
     prepared: &BTreeMap<TNodePath, AlterTablePrepared>,
   ) -> AlterTableTMCommitted {
-    let mut timestamp_hint = cur_timestamp(io_ctx, ctx.master_config.timestamp_suffix_divisor);
+    let mut timestamp_hint = ctx.clock.now(io_ctx);
     for (_, prepared) in prepared {
       timestamp_hint = max(timestamp_hint, prepared.timestamp.clone());
     }
@@ -199,23 +205,66 @@ impl STMPaxos2PCTMInner<AlterTableTMPayloadTypes> for AlterTableTMInner {
     io_ctx: &mut IO,
     committed_plm: &TMCommittedPLm<AlterTableTMPayloadTypes>,
   ) -> BTreeMap<TNodePath, AlterTableCommit> {
+    // Compute the RMs before applying the AlterOp, since a RENAME TABLE removes `table_path`.
+    let rms = get_rms::<IO>(&ctx.gossip.get(), &self.table_path);
     let timestamp = ctx.gossip.update(|gossip| {
-      let (gen, _) = gossip.table_generation.get_last_version(&self.table_path).unwrap();
+      let full_gen = gossip.table_generation.get_last_version(&self.table_path).unwrap().clone();
+      let (gen, _) = &full_gen;
       let table_schema = gossip.db_schema.get_mut(&(self.table_path.clone(), gen.clone())).unwrap();
 
       // Compute the timestamp to commit at
       let mut timestamp = committed_plm.payload.timestamp_hint.clone();
       timestamp = max(timestamp, gossip.table_generation.get_lat(&self.table_path).add(mk_t(1)));
-      timestamp =
-        max(timestamp, table_schema.val_cols.get_lat(&self.alter_op.col_name).add(mk_t(1)));
+      for col_name in self.alter_op.cols() {
+        timestamp = max(timestamp, table_schema.val_cols.get_lat(&col_name).add(mk_t(1)));
+      }
+      if let proc::AlterOp::RenameTable(op) = &self.alter_op {
+        timestamp =
+          max(timestamp, gossip.table_generation.get_lat(&op.new_table_path).add(mk_t(1)));
+      }
 
       // Apply the AlterOp
-      gossip.table_generation.update_lat(&self.table_path, timestamp.clone());
-      table_schema.val_cols.write(
-        &self.alter_op.col_name,
-        self.alter_op.maybe_col_type.clone(),
-        timestamp.clone(),
-      );
+      match &self.alter_op {
+        proc::AlterOp::AlterColumn(op) => {
+          gossip.table_generation.update_lat(&self.table_path, timestamp.clone());
+          table_schema.val_cols.write(&op.col_name, op.maybe_col_type.clone(), timestamp.clone());
+          table_schema.set_default(&op.col_name, &timestamp, op.maybe_default.clone());
+        }
+        proc::AlterOp::RenameColumn(op) => {
+          gossip.table_generation.update_lat(&self.table_path, timestamp.clone());
+          let col_type = table_schema.val_cols.get_last_version(&op.old_col_name).cloned();
+          let default = table_schema.get_default_static(&op.old_col_name, &timestamp);
+          table_schema.val_cols.write(&op.old_col_name, None, timestamp.clone());
+          table_schema.val_cols.write(&op.new_col_name, col_type, timestamp.clone());
+          table_schema.set_default(&op.new_col_name, &timestamp, default);
+        }
+        proc::AlterOp::RenameTable(op) => {
+          // Move the Table to `new_table_path` under a fresh `Gen`, keeping the `ShardingGen`
+          // so that the Tablets continue to accept QueryPlans with the new `TablePath`.
+          let table_schema = table_schema.clone();
+          let (_, sharding_gen) = &full_gen;
+          let new_gen =
+            next_gen(gossip.table_generation.get_last_present_version(&op.new_table_path));
+          let new_full_gen = (new_gen.clone(), sharding_gen.clone());
+          let shards = gossip.sharding_config.get(&(self.table_path.clone(), full_gen.clone()));
+          let shards = shards.unwrap().clone();
+          gossip.table_generation.write(&self.table_path, None, timestamp.clone());
+          gossip.table_generation.write(
+            &op.new_table_path,
+            Some(new_full_gen.clone()),
+            timestamp.clone(),
+          );
+          gossip.db_schema.insert((op.new_table_path.clone(), new_gen), table_schema);
+          gossip.sharding_config.insert((op.new_table_path.clone(), new_full_gen), shards);
+        }
+        proc::AlterOp::ChangeColumnType(op) => {
+          gossip.table_generation.update_lat(&self.table_path, timestamp.clone());
+          let default = table_schema.get_default_static(&op.col_name, &timestamp);
+          table_schema.val_cols.write(&op.col_name, Some(op.col_type.clone()), timestamp.clone());
+          let default = convert_col_valn(&op.col_type, default);
+          table_schema.set_default(&op.col_name, &timestamp, default);
+        }
+      }
 
       timestamp
     });
@@ -250,7 +299,7 @@ impl STMPaxos2PCTMInner<AlterTableTMPayloadTypes> for AlterTableTMInner {
 
     // Return Commit messages
     let mut commits = BTreeMap::<TNodePath, AlterTableCommit>::new();
-    for rm in get_rms::<IO>(&ctx.gossip.get(), &self.table_path) {
+    for rm in rms {
       commits.insert(rm.clone(), AlterTableCommit { timestamp: timestamp.clone() });
     }
     commits
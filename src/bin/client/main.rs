@@ -7,14 +7,15 @@ use log::LevelFilter;
 use rand::{RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use runiversal::cast;
+use runiversal::client::running_queries_result;
 use runiversal::common::{
   mk_rid, rand_string, ColName, ColType, ColVal, GossipData, InternalMode, PaxosGroupId,
   QueryResult, TablePath, TableView, Timestamp,
 };
 use runiversal::common::{EndpointId, RequestId};
 use runiversal::message as msg;
-use runiversal::net::{send_msg, start_acceptor_thread, GenericInputTrait, SendAction};
-use runiversal::sql_parser::is_ddl;
+use runiversal::net::{mk_address, send_msg, start_acceptor_thread, GenericInputTrait, SendAction};
+use runiversal::sql_parser::{is_ddl, is_read_only, parse_admin_command, AdminCommand};
 use runiversal::test_utils::mk_seed;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
@@ -26,23 +27,38 @@ use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
 // The Threading Model is the same as the Transact Server.
 
+/// The longest we wait for the system to respond to a query.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The longest we wait for a node to answer an `ExternalMetadataRequest`.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many times we rediscover the Leaders and resend a query that failed because
+/// Leadership moved.
+const MAX_REROUTES: u32 = 3;
+
 fn main() {
   // Setup CLI parsing
   let matches = App::new("rUniversalDB")
     .version("1.0")
     .author("Pasindu M. <pasindumuth@gmail.com>")
-    .arg(arg!(-i --ip <VALUE>).required(true).help("The IP address of the current host."))
+    .arg(arg!(-i --ip <VALUE>).required(true).help(
+      "The address (IP, optionally followed by ':<port>') of the current host. \
+       The port defaults to 1610.",
+    ))
     .arg(
       arg!(-m --mips <VALUE>)
         .required(false)
-        .help("A space separate list (in quotes) of Master IPs."),
+        .help("A space separate list (in quotes) of Master addresses."),
     )
     .arg(arg!(-e --entry_mip <VALUE>).required(false).help(
-      "If '-m' is not specified, this Master IP address is used to connect to \
-       the system for communication by the command prompt.",
+      "If '-m' is not specified, these addresses (a space separated list, in quotes) \
+       are used to discover the system for communication by the command prompt. \
+       They can be the addresses of any nodes in the system.",
     ))
     .get_matches();
 
@@ -50,15 +66,15 @@ fn main() {
   Builder::new().filter_level(LevelFilter::Off).init();
 
   // Get required arguments
-  let this_ip = matches.value_of("ip").unwrap().to_string();
-  let mut state = ClientState::new(this_ip);
+  let this_address = mk_address(matches.value_of("ip").unwrap());
+  let mut state = ClientState::new(this_address);
 
   // If `mips` is specified, we interpret this as a one-off execution of the program where
   // we merely need to start the Masters.
-  if let Some(master_ips_str) = matches.value_of("mips") {
-    let master_eids: Vec<_> = master_ips_str
+  if let Some(master_addresses_str) = matches.value_of("mips") {
+    let master_eids: Vec<_> = master_addresses_str
       .split(" ")
-      .map(|ip| EndpointId::new(ip.to_string(), InternalMode::Internal))
+      .map(|address| EndpointId::new(mk_address(address), InternalMode::Internal))
       .collect();
 
     // Send out the `StartMaster` message
@@ -79,10 +95,16 @@ fn main() {
     for _ in 0..master_eids.len() {
       to_server_receiver.recv().unwrap();
     }
-  } else if let Some(master_ip) = matches.value_of("entry_mip") {
+  } else if let Some(seed_addresses_str) = matches.value_of("entry_mip") {
     // Otherwise, enter the read loop.
-    state.initialize_connection(master_ip.to_string());
-    state.start_loop();
+    state.seed_eids = seed_addresses_str
+      .split(" ")
+      .map(|address| EndpointId::new(mk_address(address), InternalMode::Internal))
+      .collect();
+    match state.refresh_leaders() {
+      Ok(()) => state.start_loop(),
+      Err(error_message) => println!("{}", error_message),
+    }
   } else {
     println!("Please specify either '-m' or '-e'.");
   }
@@ -127,17 +149,21 @@ struct ClientState {
   rand: XorShiftRng,
   /// The EndpointId of this node
   this_eid: EndpointId,
-  /// The Master EndpointIds we tried starting the Master with
+  /// The nodes we discover the system from if none of the `master_eids` respond.
+  seed_eids: Vec<EndpointId>,
+  /// The Master EndpointIds, as of the last `ExternalMetadataResponse`.
   master_eids: Vec<EndpointId>,
-  /// The EndpointId that most communication should use.
+  /// The EndpointId that most communication should use. DQL is sent to
+  /// `target_slave_eids[slave_idx]`.
   opt_target_master_eid: Option<EndpointId>,
-  opt_target_slave_eid: Option<EndpointId>,
+  target_slave_eids: Vec<EndpointId>,
+  slave_idx: usize,
   /// The CLI command prompt (which maintaining command history, cursor state, etc).
   read_loop: Editor<()>,
 }
 
 impl ClientState {
-  fn new(this_ip: String) -> ClientState {
+  fn new(this_address: String) -> ClientState {
     // The mpsc channel for passing data to the Server Thread from all FromNetwork Threads.
     let (to_server_sender, to_server_receiver) = mpsc::channel::<GenericInput>();
     // Maps the IP addresses to a FromServer Queue, used to send data to Outgoing Connections.
@@ -146,11 +172,11 @@ impl ClientState {
     let mut rand = XorShiftRng::from_entropy();
 
     // Start the Accepting Thread
-    start_acceptor_thread(&to_server_sender, this_ip.clone());
+    start_acceptor_thread(&to_server_sender, this_address.clone());
 
     // The EndpointId of this node
     let this_internal_mode = InternalMode::External { salt: rand_string(&mut rand) };
-    let this_eid = EndpointId::new(this_ip, this_internal_mode.clone());
+    let this_eid = EndpointId::new(this_address, this_internal_mode.clone());
 
     ClientState {
       to_server_sender,
@@ -158,9 +184,11 @@ impl ClientState {
       out_conn_map,
       rand,
       this_eid,
+      seed_eids: vec![],
       master_eids: vec![],
       opt_target_master_eid: None,
-      opt_target_slave_eid: None,
+      target_slave_eids: vec![],
+      slave_idx: 0,
       read_loop: Editor::new(),
     }
   }
@@ -200,43 +228,153 @@ impl ClientState {
     }
   }
 
-  /// Contacts `master_ip` to solicit the `self.master_eids`,
-  /// `self.opt_target_master_eid`, and `self.opt_traget_slave_eid`.
-  fn initialize_connection(&mut self, master_ip: String) {
-    let request_id = mk_rid(&mut self.rand);
-    let master_eid = EndpointId::new(master_ip.to_string(), InternalMode::Internal);
-    self.send(
-      &master_eid,
-      SendAction::new(
-        msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
-          msg::MasterExternalReq::ExternalMetadataRequest(msg::ExternalMetadataRequest {
+  /// Fetches the metadata of the system from the first node that responds, trying the
+  /// Master Leader, then the other Master nodes, and then `self.seed_eids`. This also
+  /// populates `self.master_eids`, `self.opt_target_master_eid`, and `self.target_slave_eids`.
+  fn fetch_metadata(&mut self) -> Result<msg::ExternalMetadataResponse, String> {
+    let mut candidates = Vec::<EndpointId>::new();
+    candidates.extend(self.opt_target_master_eid.clone());
+    for eid in self.master_eids.iter().chain(self.seed_eids.iter()) {
+      if !candidates.contains(eid) {
+        candidates.push(eid.clone());
+      }
+    }
+
+    for eid in candidates {
+      let request_id = mk_rid(&mut self.rand);
+      self.send(
+        &eid,
+        SendAction::new(
+          msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+            msg::MasterExternalReq::ExternalMetadataRequest(msg::ExternalMetadataRequest {
+              sender_eid: self.this_eid.clone(),
+              request_id: request_id.clone(),
+            }),
+          )),
+          None,
+        ),
+      );
+
+      // Wait for a response, moving onto the next node if it does not respond.
+      if let Some(network_input) = block_until_network_response_timeout(
+        self.to_server_receiver.as_ref(),
+        &request_id,
+        DISCOVERY_TIMEOUT,
+      ) {
+        let external_msg = cast!(msg::NetworkMessage::External, network_input.message).unwrap();
+        let resp = cast!(msg::ExternalMessage::ExternalMetadataResponse, external_msg).unwrap();
+
+        // Populate ClientState
+        self.master_eids = resp.gossip_data.get().master_address_config.clone();
+        self.target_slave_eids.clear();
+        for (gid, lid) in &resp.leader_map {
+          if let PaxosGroupId::Slave(_) = gid {
+            self.target_slave_eids.push(lid.eid.clone());
+          } else {
+            // And choose the only Master Leadership.
+            self.opt_target_master_eid = Some(lid.eid.clone());
+          }
+        }
+        return Ok(resp);
+      }
+    }
+    Err("Could not reach any node in the system.".to_string())
+  }
+
+  /// Rediscovers the current Leaderships, and targets those.
+  fn refresh_leaders(&mut self) -> Result<(), String> {
+    self.fetch_metadata()?;
+    Ok(())
+  }
+
+  /// Sends `query` as a DDL Query to the Master or as a DQL Query to a Slave, and waits for
+  /// the response. If Leadership moved, we rediscover the Leaders and resend the query. After
+  /// a timeout, we only do this if running the query twice has no effect.
+  fn execute(&mut self, query: &str) -> Result<msg::ExternalMessage, String> {
+    let resendable = !is_ddl(query) && is_read_only(query);
+    let mut num_reroutes = 0;
+    loop {
+      let request_id = mk_rid(&mut self.rand);
+      if is_ddl(query) {
+        // Send this message as a DDL Query to the Master.
+        let network_msg = msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::PerformExternalDDLQuery(msg::PerformExternalDDLQuery {
             sender_eid: self.this_eid.clone(),
             request_id: request_id.clone(),
+            query: query.to_string(),
           }),
-        )),
-        None,
-      ),
-    );
-
-    // Send and wait for a response
-    let message =
-      block_until_network_response(self.to_server_receiver.as_ref(), &request_id).message;
-    let external_msg = cast!(msg::NetworkMessage::External, message).unwrap();
-    let resp = cast!(msg::ExternalMessage::ExternalMetadataResponse, external_msg).unwrap();
-
-    // Populate ClientState
-    self.master_eids = resp.gossip_data.get().master_address_config.clone();
-    for (gid, lid) in resp.leader_map {
-      if let PaxosGroupId::Slave(_) = gid {
-        // For simplicity, choose some random Slave Leadership.
-        self.opt_target_slave_eid = Some(lid.eid);
+        ));
+        self.send(&self.get_master()?.clone(), SendAction::new(network_msg, None));
       } else {
-        // And choose the only Master Leadership.
-        self.opt_target_master_eid = Some(lid.eid);
+        // Otherwise, send this message as a DQL Query to the Slave.
+        let network_msg = msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::PerformExternalQuery(msg::PerformExternalQuery {
+            sender_eid: self.this_eid.clone(),
+            request_id: request_id.clone(),
+            query: query.to_string(),
+            params: vec![],
+            opt_timeout_ms: None,
+            opt_max_retries: None,
+            priority: msg::TransactionPriority::Normal,
+            opt_max_staleness_ms: None,
+          }),
+        ));
+        self.send(&self.get_slave()?, SendAction::new(network_msg, None));
+      };
+
+      // Wait for a response
+      let opt_external = block_until_network_response_timeout(
+        self.to_server_receiver.as_ref(),
+        &request_id,
+        RESPONSE_TIMEOUT,
+      )
+      .map(|network_input| cast!(msg::NetworkMessage::External, network_input.message).unwrap());
+
+      match opt_external {
+        Some(external) if !is_not_leader(&external) => return Ok(external),
+        opt_external => {
+          // Move onto the next Slave Leader in case the one we used is gone, and
+          // rediscover the Leaders.
+          self.slave_idx += 1;
+          self.refresh_leaders()?;
+
+          let retryable = opt_external.is_some() || resendable;
+          if !retryable || num_reroutes == MAX_REROUTES {
+            return opt_external.ok_or(
+              "Timed out waiting for a response. The query might still take effect.".to_string(),
+            );
+          }
+          num_reroutes += 1;
+        }
       }
     }
   }
 
+  /// Sends the Admin request constructed by `mk_req` to every Slave Leader, returning
+  /// their responses.
+  fn admin_request<F: Fn(RequestId) -> msg::SlaveExternalReq>(
+    &mut self,
+    mk_req: F,
+  ) -> Result<Vec<msg::ExternalMessage>, String> {
+    let mut responses = Vec::<msg::ExternalMessage>::new();
+    for slave_eid in self.target_slave_eids.clone() {
+      let request_id = mk_rid(&mut self.rand);
+      let network_msg =
+        msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(mk_req(request_id.clone())));
+      self.send(&slave_eid, SendAction::new(network_msg, None));
+
+      // Wait for a response
+      let network_input = block_until_network_response_timeout(
+        self.to_server_receiver.as_ref(),
+        &request_id,
+        RESPONSE_TIMEOUT,
+      )
+      .ok_or(format!("Timed out waiting for a response from {}.", slave_eid.address))?;
+      responses.push(cast!(msg::NetworkMessage::External, network_input.message).unwrap());
+    }
+    Ok(responses)
+  }
+
   /// Processes a line submitted by the user.
   fn handle_input(&mut self, input: String) -> Result<LoopAction, String> {
     // Quit
@@ -248,15 +386,16 @@ impl ClientState {
       let mut it = input.split(" ");
       it.next();
       self.opt_target_master_eid =
-        Some(EndpointId::new(it.next().unwrap().to_string(), InternalMode::Internal));
+        Some(EndpointId::new(mk_address(it.next().unwrap()), InternalMode::Internal));
       Ok(LoopAction::DoNothing)
     }
     // Set the remote Slave Node to communicate with.
     else if input.starts_with("slave_target ") {
       let mut it = input.split(" ");
       it.next();
-      self.opt_target_slave_eid =
-        Some(EndpointId::new(it.next().unwrap().to_string(), InternalMode::Internal));
+      self.target_slave_eids =
+        vec![EndpointId::new(mk_address(it.next().unwrap()), InternalMode::Internal)];
+      self.slave_idx = 0;
       Ok(LoopAction::DoNothing)
     }
     // Display metadata that we pull continuous from the Master Group.
@@ -281,20 +420,7 @@ impl ClientState {
     }
     // Query and display metadata from the system
     else if input.starts_with("\\dt") {
-      let request_id = mk_rid(&mut self.rand);
-      let network_msg = msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
-        msg::MasterExternalReq::ExternalMetadataRequest(msg::ExternalMetadataRequest {
-          sender_eid: self.this_eid.clone(),
-          request_id: request_id.clone(),
-        }),
-      ));
-
-      // Send and wait for a response
-      self.send(self.get_master()?, SendAction::new(network_msg, None));
-      let message =
-        block_until_network_response(self.to_server_receiver.as_ref(), &request_id).message;
-      let external_msg = cast!(msg::NetworkMessage::External, message).unwrap();
-      let resp = cast!(msg::ExternalMessage::ExternalMetadataResponse, external_msg).unwrap();
+      let resp = self.fetch_metadata()?;
       let gossip_data = resp.gossip_data;
       let timestamp = gossip_data.get().table_generation.get_latest_lat();
 
@@ -317,6 +443,43 @@ impl ClientState {
       };
       Ok(LoopAction::Print(display))
     }
+    // List or kill the requests running in the system.
+    else if let Some(command) = parse_admin_command(&input) {
+      let sender_eid = self.this_eid.clone();
+      let display = match command? {
+        AdminCommand::ShowQueries => {
+          let mut queries = Vec::<msg::RunningQuery>::new();
+          for external in self.admin_request(|request_id| {
+            msg::SlaveExternalReq::ExternalShowQueries(msg::ExternalShowQueries {
+              sender_eid: sender_eid.clone(),
+              request_id,
+            })
+          })? {
+            let resp = cast!(msg::ExternalMessage::ExternalShowQueriesResponse, external).unwrap();
+            queries.extend(resp.queries);
+          }
+          format!("{}", format_table(running_queries_result(queries)))
+        }
+        AdminCommand::KillQuery { request_id: target_request_id } => {
+          let target_request_id = RequestId(target_request_id);
+          let mut result = msg::KillQueryResult::NotFound;
+          for external in self.admin_request(|request_id| {
+            msg::SlaveExternalReq::ExternalKillQuery(msg::ExternalKillQuery {
+              sender_eid: sender_eid.clone(),
+              request_id,
+              target_request_id: target_request_id.clone(),
+            })
+          })? {
+            let resp = cast!(msg::ExternalMessage::ExternalKillQueryResponse, external).unwrap();
+            if resp.result == msg::KillQueryResult::Killed {
+              result = resp.result;
+            }
+          }
+          format!("{:?}", result)
+        }
+      };
+      Ok(LoopAction::Print(display))
+    }
     // Send a normal DQL or DQL Query (based on what the `opt_target_eid` is).
     else {
       let mut next_loop_action = LoopAction::DoNothing;
@@ -325,49 +488,22 @@ impl ClientState {
       // executed one-at-a-time. This is convenient for quickly setting up multiple
       // tables with some initial data.
       for input in input.split("-- Separate") {
-        let request_id = mk_rid(&mut self.rand);
-        if is_ddl(&input) {
-          // Send this message as a DDL Query to the Master.
-          let network_msg = msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
-            msg::MasterExternalReq::PerformExternalDDLQuery(msg::PerformExternalDDLQuery {
-              sender_eid: self.this_eid.clone(),
-              request_id: request_id.clone(),
-              query: input.to_string(),
-            }),
-          ));
-          self.send(self.get_master()?, SendAction::new(network_msg, None));
-        } else {
-          // Otherwise, send this message as a DQL Query to the Slave.
-          let network_msg = msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
-            msg::SlaveExternalReq::PerformExternalQuery(msg::PerformExternalQuery {
-              sender_eid: self.this_eid.clone(),
-              request_id: request_id.clone(),
-              query: input.to_string(),
-            }),
-          ));
-          self.send(self.get_slave()?, SendAction::new(network_msg, None));
-        };
-
         // Send and wait for a response
-        let message =
-          block_until_network_response(self.to_server_receiver.as_ref(), &request_id).message;
+        let external = self.execute(input)?;
 
         // Display the output
-        if let Some(display) = match message {
-          msg::NetworkMessage::External(external) => match external {
-            msg::ExternalMessage::ExternalQuerySuccess(success) => {
-              Some(format!("{}", format_table(success.result)))
-            }
-            msg::ExternalMessage::ExternalQueryAborted(aborted) => {
-              Some(format!("Failed with error: {:#?}", aborted.payload))
-            }
-            msg::ExternalMessage::ExternalDDLQuerySuccess(_) => None,
-            msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => {
-              Some(format!("Failed with error: {:#?}", aborted.payload))
-            }
-            _ => Some(format!("{:#?}", external)),
-          },
-          message => Some(format!("{:#?}", message)),
+        if let Some(display) = match external {
+          msg::ExternalMessage::ExternalQuerySuccess(success) => {
+            Some(format!("{}", format_table(success.result)))
+          }
+          msg::ExternalMessage::ExternalQueryAborted(aborted) => {
+            Some(format!("Failed with error: {:#?}", aborted.payload))
+          }
+          msg::ExternalMessage::ExternalDDLQuerySuccess(_) => None,
+          msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => {
+            Some(format!("Failed with error: {:#?}", aborted.payload))
+          }
+          _ => Some(format!("{:#?}", external)),
         } {
           // We are only interested in printing the results of the final query.
           next_loop_action = LoopAction::Print(display);
@@ -382,13 +518,17 @@ impl ClientState {
     get_eid(&self.opt_target_master_eid)
   }
 
-  fn get_slave(&self) -> Result<&EndpointId, String> {
-    get_eid(&self.opt_target_slave_eid)
+  fn get_slave(&self) -> Result<EndpointId, String> {
+    if self.target_slave_eids.is_empty() {
+      Err("There are no Slaves to send the query to.".to_string())
+    } else {
+      Ok(self.target_slave_eids[self.slave_idx % self.target_slave_eids.len()].clone())
+    }
   }
 
   /// A convenience function for sending data to `eid`.
   fn send(&self, eid: &EndpointId, action: SendAction) {
-    send_msg(&self.out_conn_map, eid, action, &self.this_eid.mode);
+    send_msg(&self.out_conn_map, eid, action, &self.this_eid);
   }
 }
 
@@ -422,6 +562,9 @@ pub fn block_until_generic_response(
           msg::ExternalMessage::ExternalShardingSuccess(res) => &res.request_id == rid,
           msg::ExternalMessage::ExternalShardingAborted(res) => &res.request_id == rid,
           msg::ExternalMessage::ExternalMetadataResponse(res) => &res.request_id == rid,
+          msg::ExternalMessage::ExternalShowQueriesResponse(res) => &res.request_id == rid,
+          msg::ExternalMessage::ExternalKillQueryResponse(res) => &res.request_id == rid,
+          msg::ExternalMessage::ExternalTransferLeadershipResponse(res) => &res.request_id == rid,
         },
         _ => {
           debug_assert!(false);
@@ -436,6 +579,44 @@ pub fn block_until_generic_response(
   }
 }
 
+/// Like `block_until_network_response`, except that this returns `None` if a matching
+/// response does not arrive within `timeout`.
+pub fn block_until_network_response_timeout(
+  receiver: Option<&Receiver<GenericInput>>,
+  rid: &RequestId,
+  timeout: Duration,
+) -> Option<NetworkInput> {
+  let deadline = Instant::now() + timeout;
+  loop {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    match receiver.unwrap().recv_timeout(remaining) {
+      Ok(GenericInput::NetworkInput(network_input)) => {
+        if let msg::NetworkMessage::External(message) = &network_input.message {
+          if message.request_id() == rid {
+            return Some(network_input);
+          }
+        }
+      }
+      Ok(GenericInput::None) => {}
+      Err(_) => return None,
+    }
+  }
+}
+
+/// Returns true iff `external` is an abort because the query was sent to a node that is
+/// not (or is no longer) a Leader.
+fn is_not_leader(external: &msg::ExternalMessage) -> bool {
+  match external {
+    msg::ExternalMessage::ExternalQueryAborted(aborted) => {
+      aborted.payload == msg::ExternalAbortedData::NotLeader
+    }
+    msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => {
+      aborted.payload == msg::ExternalDDLQueryAbortData::NotLeader
+    }
+    _ => false,
+  }
+}
+
 /// Read messages from `receiver` until one arrives with a matching `rid`.
 pub fn block_until_network_response(
   receiver: Option<&Receiver<GenericInput>>,
@@ -525,6 +706,7 @@ fn format_table(result: QueryResult) -> String {
     for col in cols {
       let col_val_str = match col {
         Some(ColVal::Int(val)) => val.to_string(),
+        Some(ColVal::BigInt(val)) => val.to_string(),
         Some(ColVal::Bool(val)) => val.to_string(),
         Some(ColVal::String(val)) => format!("\"{}\"", val),
         None => "NULL".to_string(),
@@ -11,7 +11,8 @@ use crossterm::{event, execute};
 use rand::{RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use runiversal::common::{
-  mk_rid, ColType, ColVal, EndpointId, GossipData, LeaderMap, PaxosGroupId, PaxosGroupIdTrait,
+  mk_rid, ColType, ColVal, EndpointId, GossipData, HashBucket, LeaderMap, PaxosGroupId,
+  PaxosGroupIdTrait,
 };
 use runiversal::message as msg;
 use runiversal::net::{send_msg, SendAction};
@@ -91,7 +92,7 @@ impl MetadataMonitor {
               request_id: request_id.clone(),
             }),
           ));
-          send_msg(&out_conn_map, &master_eid, SendAction::new(network_msg, None), &this_eid.mode);
+          send_msg(&out_conn_map, &master_eid, SendAction::new(network_msg, None), &this_eid);
 
           // Anticipate the response.
           if let GenericInput::NetworkInput(network_input) =
@@ -252,6 +253,7 @@ struct ShardingRow {
   slave_group_id: String,
   range_start: String,
   range_end: String,
+  hash_bucket: String,
 }
 
 #[derive(Tabled)]
@@ -267,20 +269,20 @@ fn paxos_group_table(gossip_data: &GossipData, leader_map: &LeaderMap) -> String
 
   // Add in Master data.
   let eids = gossip_data.get().master_address_config;
-  let ips: Vec<_> = eids.iter().map(|eid| eid.ip.clone()).collect();
+  let addresses: Vec<_> = eids.iter().map(|eid| eid.address.clone()).collect();
   paxos_group_rows.push(PaxosGroupRow {
     replicated_group: "Master".to_string(),
-    leader: leader_map.get(&PaxosGroupId::Master).unwrap().eid.ip.clone(),
-    members: ips.join(", "),
+    leader: leader_map.get(&PaxosGroupId::Master).unwrap().eid.address.clone(),
+    members: addresses.join(", "),
   });
 
   // Add in Slaves data.
   for (sid, eids) in gossip_data.get().slave_address_config {
-    let ips: Vec<_> = eids.iter().map(|eid| eid.ip.clone()).collect();
+    let addresses: Vec<_> = eids.iter().map(|eid| eid.address.clone()).collect();
     paxos_group_rows.push(PaxosGroupRow {
       replicated_group: format!("Slave {}", sid.0),
-      leader: leader_map.get(&sid.to_gid()).unwrap().eid.ip.clone(),
-      members: ips.join(", "),
+      leader: leader_map.get(&sid.to_gid()).unwrap().eid.address.clone(),
+      members: addresses.join(", "),
     });
   }
 
@@ -292,11 +294,20 @@ fn range_bound_str(range_bound: &Option<ColVal>) -> String {
   match range_bound {
     None => "Unbounded".to_string(),
     Some(ColVal::Int(val)) => val.to_string(),
+    Some(ColVal::BigInt(val)) => val.to_string(),
     Some(ColVal::Bool(val)) => val.to_string(),
     Some(ColVal::String(val)) => val.to_string(),
   }
 }
 
+/// Convert the `hash` of `TabletKeyRange` into a string.
+fn hash_bucket_str(hash: &Option<HashBucket>) -> String {
+  match hash {
+    None => "None".to_string(),
+    Some(hash) => format!("{}/{}", hash.bucket, hash.num_buckets),
+  }
+}
+
 /// Create a Display Table containing the sharding data.
 fn sharding_table(gossip_data: &GossipData) -> String {
   let timestamp = gossip_data.get().table_generation.get_latest_lat();
@@ -315,6 +326,7 @@ fn sharding_table(gossip_data: &GossipData) -> String {
         slave_group_id: sid.0.clone(),
         range_start: range_bound_str(&range.start),
         range_end: range_bound_str(&range.end),
+        hash_bucket: hash_bucket_str(&range.hash),
       });
     }
   }
@@ -324,8 +336,8 @@ fn sharding_table(gossip_data: &GossipData) -> String {
 
 /// Create a display string for the set of FreeNodes in the system.
 fn free_nodes(free_node_eids: Vec<EndpointId>) -> String {
-  let ips: Vec<_> = free_node_eids.into_iter().map(|eid| eid.ip).collect();
-  format!("FreeNodes: {}\n", ips.join(", "))
+  let addresses: Vec<_> = free_node_eids.into_iter().map(|eid| eid.address).collect();
+  format!("FreeNodes: {}\n", addresses.join(", "))
 }
 
 /// Convert `col_type` to a `String`.
@@ -334,6 +346,7 @@ pub fn col_type_str(col_type: &ColType) -> String {
     ColType::Int => "Int".to_string(),
     ColType::Bool => "Bool".to_string(),
     ColType::String => "String".to_string(),
+    ColType::BigInt => "BigInt".to_string(),
   }
 }
 
@@ -3,14 +3,20 @@
 use crate::simulation::{SimConfig, SimpleBundle, Simulation};
 use rand::RngCore;
 use rand_xorshift::XorShiftRng;
-use runiversal::common::{Gen, LeadershipId};
+use runiversal::common::{EndpointId, Gen, LeadershipId};
 use runiversal::message as msg;
+use runiversal::node::get_prod_configs;
+use runiversal::paxos::PaxosConfig;
 use std::iter::FromIterator;
 
 mod simulation;
 
 fn main() {
-  test();
+  if std::env::args().nth(1).as_deref() == Some("bench") {
+    bench();
+  } else {
+    test();
+  }
 }
 
 /**
@@ -29,14 +35,27 @@ fn test() {
   println!("test_leader_partition");
   test_leader_partition();
 
+  println!("test_flapping_partition");
+  test_flapping_partition();
+
   println!("test_general_partition");
   test_general_partition();
 
   println!("Test Successful!");
 }
 
+/// Get the PaxosConfig used in production
+fn prod_paxos_config() -> PaxosConfig {
+  get_prod_configs().paxos_config
+}
+
 fn default_config() -> SimConfig {
-  SimConfig { target_temp_blocked_frac: 0.5, max_pause_time_ms: 2000 }
+  SimConfig {
+    target_temp_blocked_frac: 0.5,
+    max_pause_time_ms: 2000,
+    paxos_config: prod_paxos_config(),
+    inserts_per_ms: 0,
+  }
 }
 
 fn print_stats(sim: &Simulation) {
@@ -45,6 +64,16 @@ fn print_stats(sim: &Simulation) {
   }
 }
 
+/// Returns the latest Leadership in the `global_paxos_log`.
+fn latest_lid(sim: &Simulation) -> LeadershipId {
+  for entry in sim.global_paxos_log.iter().rev() {
+    if let msg::PLEntry::LeaderChanged(leader_changed) = entry {
+      return leader_changed.lid.clone();
+    }
+  }
+  LeadershipId { gen: Gen(0), eid: sim.address_config[0].clone() }
+}
+
 // -----------------------------------------------------------------------------------------------
 //  test_basic
 // -----------------------------------------------------------------------------------------------
@@ -68,18 +97,8 @@ fn test_leader_partition() {
   sim.simulate_n_ms(10000);
   print_stats(&sim);
 
-  // Find the latest Leader
-  let lid = LeadershipId { gen: Gen(0), eid: sim.address_config[0].clone() };
-  let mut latest_leader_changed = msg::LeaderChanged { lid };
-  for entry in sim.global_paxos_log.iter().rev() {
-    if let msg::PLEntry::LeaderChanged(leader_changed) = entry {
-      latest_leader_changed = leader_changed.clone();
-      break;
-    }
-  }
-
-  // Partition out this Leader
-  let leader_eid = latest_leader_changed.lid.eid;
+  // Partition out the latest Leader
+  let leader_eid = latest_lid(&sim).eid;
   let eids = sim.address_config.clone();
   for eid in eids {
     sim.block_queue_permanently(leader_eid.clone(), eid.clone());
@@ -194,7 +213,12 @@ fn verify_leadership_changes(sim: &Simulation, expected_changes: u32) {
 /// Loop around for some time, creating and changing network partition. Verify that
 /// the algorithm is safe and that new `PLEntry`s constantly get added.
 fn test_general_partition() {
-  let sim_config = SimConfig { target_temp_blocked_frac: 0.0, max_pause_time_ms: 0 };
+  let sim_config = SimConfig {
+    target_temp_blocked_frac: 0.0,
+    max_pause_time_ms: 0,
+    paxos_config: prod_paxos_config(),
+    inserts_per_ms: 0,
+  };
   let mut sim = Simulation::new([0; 16], 5, sim_config);
   let all_indices: Vec<usize> = (0..sim.address_config.len()).collect();
 
@@ -237,3 +261,134 @@ fn test_general_partition() {
   verify_leadership_changes(&sim, 5);
   print_stats(&sim);
 }
+
+// -----------------------------------------------------------------------------------------------
+//  test_flapping_partition
+// -----------------------------------------------------------------------------------------------
+
+/// Repeatedly cut off a minority of the Followers from the Leader for long enough that they
+/// stop hearing from it, and then reconnect them. Some Followers are fully isolated, and some
+/// only stop receiving messages from the Leader. Since the Leader can always reach a majority,
+/// verify that it is never deposed and that new `PLEntry`s constantly get added.
+fn test_flapping_partition() {
+  let sim_config = SimConfig {
+    target_temp_blocked_frac: 0.0,
+    max_pause_time_ms: 0,
+    paxos_config: prod_paxos_config(),
+    inserts_per_ms: 0,
+  };
+  let mut sim = Simulation::new([0; 16], 5, sim_config);
+  let eids = sim.address_config.clone();
+  let leader_eid = latest_lid(&sim).eid;
+
+  // Verification metadata
+  let mut num_unlive_periods = 0;
+  let mut num_periods = 0;
+  let mut last_log_len = 0;
+
+  // Simulation
+  let mut cur_time = 0;
+  while cur_time < 200000 {
+    // Choose a minority of Followers to cut off.
+    let mut followers: Vec<EndpointId> =
+      eids.iter().filter(|eid| *eid != &leader_eid).cloned().collect();
+    let mut cut_off = Vec::<EndpointId>::new();
+    let num_cut_off = (sim.rand.next_u32() as usize % (eids.len() / 2)) + 1;
+    while cut_off.len() < num_cut_off {
+      let r = sim.rand.next_u32() as usize % followers.len();
+      cut_off.push(followers.remove(r));
+    }
+
+    // Block the queues, where some Followers are isolated completely.
+    let mut blocked = Vec::<(EndpointId, EndpointId)>::new();
+    for follower_eid in &cut_off {
+      if sim.rand.next_u32() % 2 == 0 {
+        for eid in &eids {
+          if eid != follower_eid {
+            blocked.push((follower_eid.clone(), eid.clone()));
+            blocked.push((eid.clone(), follower_eid.clone()));
+          }
+        }
+      } else {
+        blocked.push((leader_eid.clone(), follower_eid.clone()));
+      }
+    }
+    for (from_eid, to_eid) in &blocked {
+      sim.block_queue_permanently(from_eid.clone(), to_eid.clone());
+    }
+
+    // Keep the partition long enough for the Followers to stop hearing from the Leader.
+    let time_for_partition = 10000 + sim.rand.next_u32() as usize % 10000;
+    sim.simulate_n_ms(time_for_partition as u32);
+    cur_time += time_for_partition;
+
+    // Update verification metadata
+    if sim.global_paxos_log.len() == last_log_len {
+      num_unlive_periods += 1;
+    }
+    num_periods += 1;
+    last_log_len = sim.global_paxos_log.len();
+
+    // Reconnect the Followers for a bit.
+    for (from_eid, to_eid) in blocked {
+      sim.unblock_queue_permanently(from_eid, to_eid);
+    }
+    let time_for_heal = sim.rand.next_u32() as usize % 3000;
+    sim.simulate_n_ms(time_for_heal as u32);
+    cur_time += time_for_heal;
+  }
+
+  // Verify that the Leader was never deposed.
+  for entry in sim.global_paxos_log.iter() {
+    if let msg::PLEntry::LeaderChanged(leader_changed) = entry {
+      panic!("Failed! The Leader was deposed by {:?}.", leader_changed.lid);
+    }
+  }
+
+  // Verify that the PaxosLog was always growing.
+  assert_eq!(
+    num_unlive_periods, 0,
+    "Failed! There were unlive periods: {:?} of {:?}.",
+    num_unlive_periods, num_periods
+  );
+  print_stats(&sim);
+}
+
+// -----------------------------------------------------------------------------------------------
+//  bench
+// -----------------------------------------------------------------------------------------------
+
+/// Measures the throughput of the PaxosDriver for different amounts of pipelining and
+/// batching. The Leader is asked to insert more `SimpleBundle`s than it can handle (the
+/// excess is dropped by the PaxosDriver), so this is the maximum throughput.
+fn bench() {
+  let duration_ms = 10000;
+  for (max_in_flight, max_batch_size) in [(1, 1), (4, 1), (1, 8), (4, 8), (8, 16)] {
+    let mut paxos_config = prod_paxos_config();
+    paxos_config.max_in_flight = max_in_flight;
+    paxos_config.max_batch_size = max_batch_size;
+    let sim_config = SimConfig {
+      target_temp_blocked_frac: 0.0,
+      max_pause_time_ms: 0,
+      paxos_config,
+      inserts_per_ms: 10,
+    };
+    let mut sim = Simulation::new([0; 16], 5, sim_config);
+    sim.simulate_n_ms(duration_ms);
+
+    // Count the inserted `SimpleBundle`s.
+    let mut num_bundles = 0;
+    for entry in &sim.global_paxos_log {
+      if let msg::PLEntry::Bundle(_) = entry {
+        num_bundles += 1;
+      }
+    }
+
+    println!(
+      "max_in_flight: {:?}, max_batch_size: {:?}, bundles/s: {:?}",
+      max_in_flight,
+      max_batch_size,
+      num_bundles * 1000 / duration_ms
+    );
+  }
+}
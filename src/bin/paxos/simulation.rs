@@ -3,7 +3,6 @@ use rand_xorshift::XorShiftRng;
 use runiversal::common::EndpointId;
 use runiversal::common::{mk_t, RangeEnds, Timestamp};
 use runiversal::message as msg;
-use runiversal::node::get_prod_configs;
 use runiversal::paxos::{PaxosConfig, PaxosContextBase, PaxosDriver, PaxosTimerEvent, UserPLEntry};
 use runiversal::simulation_utils::{add_msg, mk_paxos_eid};
 use std::cmp::min;
@@ -38,6 +37,10 @@ impl<'a> PaxosContextBase<SimpleBundle> for PaxosContext<'a> {
     self.this_eid
   }
 
+  fn now(&mut self) -> Timestamp {
+    self.current_time.clone()
+  }
+
   fn send(&mut self, eid: &EndpointId, message: msg::PaxosDriverMessage<SimpleBundle>) {
     add_msg(&mut self.queues, &mut self.nonempty_queues, message, &self.this_eid, eid);
   }
@@ -86,6 +89,11 @@ pub struct SimConfig {
   pub target_temp_blocked_frac: f32,
   /// The maximum amount of time a queue can be Paused in `Temporary` at a time.
   pub max_pause_time_ms: u32,
+  /// The `PaxosConfig` that all `PaxosDriver`s use.
+  pub paxos_config: PaxosConfig,
+  /// The number of `SimpleBundle`s that the Leader is asked to insert every ms. If this is 0,
+  /// the Leader instead inserts one `SimpleBundle` every time it learns new `PLEntry`s.
+  pub inserts_per_ms: u32,
 }
 
 #[derive(Debug)]
@@ -155,11 +163,8 @@ impl Simulation {
     }
     sim.address_config = eids.clone();
 
-    // Get the PaxosConfig used in production
-    let node_config = get_prod_configs();
-    let paxos_config = node_config.paxos_config;
-
     // Construct PaxosDrivers
+    let paxos_config = sim.config.paxos_config.clone();
     for eid in eids.clone() {
       sim.paxos_data.insert(
         eid.clone(),
@@ -278,7 +283,10 @@ impl Simulation {
     };
 
     let entries = paxos_data.paxos_driver.handle_paxos_message(&mut ctx, msg);
-    if !entries.is_empty() && paxos_data.paxos_driver.is_leader(&ctx) {
+    if self.config.inserts_per_ms == 0
+      && !entries.is_empty()
+      && paxos_data.paxos_driver.is_leader(&ctx)
+    {
       // Start inserting a new SimpleBundle
       self.next_int += 1;
       paxos_data
@@ -329,6 +337,31 @@ impl Simulation {
     }
   }
 
+  /// Asks every node that believes it is the Leader to insert `inserts_per_ms` new
+  /// `SimpleBundle`s, regardless of whether the prior ones have been learned.
+  fn run_open_loop_inserts(&mut self) {
+    for (eid, paxos_data) in &mut self.paxos_data {
+      let current_time = self.true_timestamp.clone();
+      let mut ctx = PaxosContext {
+        rand: &mut self.rand,
+        current_time, // TODO: simulate clock skew
+        queues: &mut self.queues,
+        nonempty_queues: &mut self.nonempty_queues,
+        this_eid: eid,
+        tasks: &mut paxos_data.tasks,
+      };
+
+      if paxos_data.paxos_driver.is_leader(&ctx) {
+        for _ in 0..self.config.inserts_per_ms {
+          self.next_int += 1;
+          paxos_data
+            .paxos_driver
+            .insert_bundle(&mut ctx, UserPLEntry::Bundle(SimpleBundle { val: self.next_int }));
+        }
+      }
+    }
+  }
+
   /// This function simply increments the `true_time` by 1ms and delivers 1ms worth of
   /// messages. For simplicity, we assume that this means that every non-empty queue
   /// of messages delivers about one message in this time.
@@ -346,6 +379,9 @@ impl Simulation {
     }
 
     self.run_timer_events();
+    if self.config.inserts_per_ms > 0 {
+      self.run_open_loop_inserts();
+    }
     self.update_paused_queues();
   }
 
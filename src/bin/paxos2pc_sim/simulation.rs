@@ -2,7 +2,7 @@ use crate::message as msg;
 use crate::slave::{FullSlaveInput, SlaveBundle, SlaveContext, SlaveState, SlaveTimerInput};
 use rand::{RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
-use runiversal::common::{mk_t, BasicIOCtx, GeneralTraceMessage, RangeEnds, Timestamp};
+use runiversal::common::{mk_t, BasicIOCtx, ClockId, GeneralTraceMessage, RangeEnds, Timestamp};
 use runiversal::common::{
   EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, SlaveGroupId,
 };
@@ -185,15 +185,19 @@ impl Simulation {
       leader_map.insert(sid.to_gid(), LeadershipId { gen: Gen(0), eid: eids[0].clone() });
     }
 
-    // Construct and add SlaveStates
+    // Construct and add SlaveStates, giving each a distinct node index for its `ClockId`.
+    let mut node_index = 0;
     for (sid, eids) in &slave_address_config {
       for eid in eids {
+        let clock_id = ClockId { node_index, sub_node_index: 0 };
+        node_index += 1;
         sim.slave_data.insert(
           eid.clone(),
           SlaveData {
             slave_state: SlaveState::new(SlaveContext::new(
               sid.clone(),
               eid.clone(),
+              clock_id,
               slave_config.clone(),
               slave_address_config.clone(),
               leader_map.clone(),
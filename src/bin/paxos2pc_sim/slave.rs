@@ -7,8 +7,8 @@ use crate::stm_simple_tm_es::{
   STMSimpleAborted, STMSimpleTMES, STMSimpleTMInner, STMSimpleTMPayloadTypes,
 };
 use rand::RngCore;
-use runiversal::common::{mk_t, BasicIOCtx, LeaderMap, RemoteLeaderChangedPLm, VersionedValue};
-use runiversal::common::{EndpointId, Gen, PaxosGroupIdTrait, QueryId};
+use runiversal::common::{mk_t, BasicIOCtx, ClockId, HybridClock, LeaderMap, RemoteLeaderChangedPLm};
+use runiversal::common::{EndpointId, Gen, PaxosGroupIdTrait, QueryId, VersionedValue};
 use runiversal::common::{LeadershipId, PaxosGroupId, SlaveGroupId};
 use runiversal::network_driver::{NetworkDriver, NetworkDriverContext};
 use runiversal::paxos2pc_rm;
@@ -102,6 +102,10 @@ impl paxos2pc_tm::TMServerContext<SimplePayloadTypes> for SlaveContext {
   fn is_leader(&self) -> bool {
     SlaveContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -130,6 +134,10 @@ impl paxos2pc_tm::RMServerContext<SimplePayloadTypes> for SlaveContext {
     SlaveContext::is_leader(self)
   }
 
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
+
   fn leader_map(&self) -> &LeaderMap {
     &self.leader_map.value()
   }
@@ -152,6 +160,7 @@ pub struct SlaveContext {
   pub this_sid: SlaveGroupId,
   pub this_gid: PaxosGroupId, // self.this_sid.to_gid()
   pub this_eid: EndpointId,
+  pub clock: HybridClock,
 
   /// Gossip
   pub slave_address_config: BTreeMap<SlaveGroupId, Vec<EndpointId>>,
@@ -191,17 +200,20 @@ impl SlaveContext {
   pub fn new(
     this_sid: SlaveGroupId,
     this_eid: EndpointId,
+    clock_id: ClockId,
     slave_config: SlaveConfig,
     slave_address_config: BTreeMap<SlaveGroupId, Vec<EndpointId>>,
     leader_map: LeaderMap,
   ) -> SlaveContext {
     let leader_map = VersionedValue::new(leader_map);
     let network_driver = NetworkDriver::new(&leader_map);
+    let clock = HybridClock::new(clock_id, slave_config.max_clock_offset_ms);
     SlaveContext {
       slave_config,
       this_sid: this_sid.clone(),
       this_gid: this_sid.to_gid(),
       this_eid,
+      clock,
       slave_address_config,
       leader_map,
       network_driver,
@@ -291,7 +303,7 @@ impl SlaveContext {
               SlaveForwardMsg::LeaderChanged(leader_changed.clone()),
             );
           }
-          msg::PLEntry::ReconfigBundle(_) => assert!(false),
+          msg::PLEntry::ReconfigBundle(_) | msg::PLEntry::Batch(_) => assert!(false),
         }
       }
       FullSlaveInput::SlaveTimerInput(timer_input) => {
@@ -364,6 +376,7 @@ impl SlaveContext {
             self,
             io_ctx,
             query_id.clone(),
+            None,
             SimpleTMInner {},
             prepare_payloads,
           );
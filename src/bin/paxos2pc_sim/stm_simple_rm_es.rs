@@ -3,8 +3,8 @@ use crate::slave::{SlaveContext, SlavePLm};
 use crate::stm_simple_tm_es::{
   STMSimpleClosed, STMSimpleCommit, STMSimplePrepare, STMSimplePrepared, STMSimpleTMPayloadTypes,
 };
-use runiversal::common::BasicIOCtx;
 use runiversal::common::SlaveGroupId;
+use runiversal::common::{BasicIOCtx, HybridClock};
 use runiversal::stmpaxos2pc_rm::{
   RMCommittedPLm, RMPLm, RMPayloadTypes, RMServerContext, STMPaxos2PCRMAction, STMPaxos2PCRMInner,
   STMPaxos2PCRMOuter,
@@ -67,6 +67,10 @@ impl RMServerContext<STMSimpleRMPayloadTypes> for SlaveContext {
   fn is_leader(&self) -> bool {
     SlaveContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
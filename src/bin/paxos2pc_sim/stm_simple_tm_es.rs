@@ -1,6 +1,6 @@
 use crate::message as msg;
 use crate::slave::{SlaveContext, SlavePLm};
-use runiversal::common::BasicIOCtx;
+use runiversal::common::{BasicIOCtx, HybridClock};
 use runiversal::common::{EndpointId, RequestId, SlaveGroupId};
 use runiversal::stmpaxos2pc_tm::{
   RMMessage, STMPaxos2PCTMInner, STMPaxos2PCTMOuter, TMClosedPLm, TMCommittedPLm, TMMessage, TMPLm,
@@ -103,6 +103,10 @@ impl TMServerContext<STMSimpleTMPayloadTypes> for SlaveContext {
   fn is_leader(&self) -> bool {
     SlaveContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
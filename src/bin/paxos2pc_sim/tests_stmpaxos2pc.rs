@@ -107,9 +107,11 @@ pub fn test_single(test_num: u32, seed: [u8; 16]) {
   let client_eid = mk_client_eid(0);
 
   let slave_config = SlaveConfig {
-    timestamp_suffix_divisor: 1,
+    max_clock_offset_ms: 100,
     remote_leader_changed_period_ms: 5,
     // The below are not needed
+    closed_timestamp_lag_ms: 0,
+    follower_read_wait_ms: 0,
     failure_detector_period_ms: 0,
     check_unconfirmed_eids_period_ms: 0,
   };
@@ -741,7 +741,7 @@ pub fn test_all_advanced_parallel(rand: &mut XorShiftRng) {
 
 pub fn advanced_parallel_test(seed: [u8; 16]) {
   // We create 3 clients.
-  let mut sim = mk_general_sim(seed, 3, 5, 1, 1, 0);
+  let mut sim = mk_general_sim(seed, 3, 5, 1, 0);
   let mut ctx = TestContext::new(&sim);
 
   // Setup Tables
@@ -776,6 +776,11 @@ pub fn advanced_parallel_test(seed: [u8; 16]) {
       sender_eid: client_eid.clone(),
       request_id: request_id.clone(),
       query: query.to_string(),
+      params: vec![],
+      opt_timeout_ms: None,
+      opt_max_retries: None,
+      priority: msg::TransactionPriority::Normal,
+      opt_max_staleness_ms: None,
     };
     req_map.get_mut(client_eid).unwrap().insert(request_id, perform.clone());
 
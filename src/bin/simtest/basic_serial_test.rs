@@ -8,14 +8,20 @@ use rand::{RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use runiversal::common::{mk_t, remove_item, TableSchema, Timestamp};
 use runiversal::common::{
-  ColName, ColType, EndpointId, Gen, LeadershipId, PaxosGroupIdTrait, PrimaryKey, QueryResult,
-  RequestId, SlaveGroupId, TablePath, TabletGroupId, TabletKeyRange,
+  ColName, ColType, ColVal, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait,
+  PrimaryKey, QueryId, QueryResult, RequestId, SlaveGroupId, TablePath, TabletGroupId,
+  TabletKeyRange,
 };
 use runiversal::message as msg;
 use runiversal::message::ExternalQueryError;
+use runiversal::metrics::{metrics, M_FOLLOWER_READS, M_LEASE_READS, M_TRANSACTIONS_ABORTED};
 use runiversal::paxos::PaxosConfig;
-use runiversal::test_utils::{cno, cvi, cvs, mk_seed, mk_sid, mk_tab, mk_tid};
-use std::collections::BTreeMap;
+use runiversal::shard_split_tm_es::STRange;
+use runiversal::sql_parser::MAX_HASH_PARTITIONS;
+use runiversal::sql_ast::iast;
+use runiversal::test_utils::{cno, cvbi, cvi, cvs, mk_seed, mk_sid, mk_tab, mk_tid};
+use runiversal::trace::{stitch_timeline, Span};
+use std::collections::{BTreeMap, BTreeSet};
 
 /**
  * This suite of tests consists of simple serial Transaction Processing. Only one query
@@ -40,17 +46,36 @@ pub fn test_all_basic_serial(rand: &mut XorShiftRng) {
   aggregation_test(mk_seed(rand));
   avg_aggregation_test(mk_seed(rand));
   aliased_column_resolution_test(mk_seed(rand));
+  bind_params_test(mk_seed(rand));
+  explain_test(mk_seed(rand));
+  explain_analyze_test(mk_seed(rand));
+  hash_partition_test(mk_seed(rand));
+  split_at_test(mk_seed(rand));
+  keyless_table_test(mk_seed(rand));
+  trace_test(mk_seed(rand));
   basic_add_column(mk_seed(rand));
+  add_column_default_test(mk_seed(rand));
   drop_column(mk_seed(rand));
+  rename_column_test(mk_seed(rand));
+  change_column_type_test(mk_seed(rand));
   basic_delete_test(mk_seed(rand));
   insert_delete_insert_test(mk_seed(rand));
   ghost_deleted_row_test(mk_seed(rand));
   drop_table_test(mk_seed(rand));
+  rename_table_test(mk_seed(rand));
   simple_join_test(mk_seed(rand));
   advanced_join_test(mk_seed(rand));
   join_errors_test(mk_seed(rand));
   cancellation_test(mk_seed(rand));
+  show_kill_queries_test(mk_seed(rand));
+  isolation_level_test(mk_seed(rand));
+  timeout_test(mk_seed(rand));
+  priority_test(mk_seed(rand));
+  select_for_update_test(mk_seed(rand));
+  lease_read_test(mk_seed(rand));
+  follower_read_test(mk_seed(rand));
   paxos_leader_change_test(mk_seed(rand));
+  leadership_transfer_test(mk_seed(rand));
   paxos_basic_serial_test(mk_seed(rand));
 }
 
@@ -1182,66 +1207,1664 @@ fn aliased_column_resolution_test(seed: [u8; 16]) {
   )
 }
 
+// -----------------------------------------------------------------------------------------------
+//  bind_params_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that the values bound to `$n` parameters are used, including when the same query
+/// is sent again with different values (where the Coord reuses its plan).
+fn bind_params_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  let select_query = " SELECT product_id, email
+                       FROM inventory
+                       WHERE product_id = $1;
+                     ";
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0"))]);
+    let params = vec![iast::Value::Number("0".to_string())];
+    ctx.execute_query_with_params(&mut sim, select_query, params, 10000, exp_result);
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email")]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1"))]);
+    let params = vec![iast::Value::Number("1".to_string())];
+    ctx.execute_query_with_params(&mut sim, select_query, params, 10000, exp_result);
+  }
+
+  // Bound strings are never interpreted as SQL.
+  {
+    let email = "x'); DELETE FROM inventory;";
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs(email)), Some(cvi(35))]);
+    ctx.execute_query_with_params(
+      &mut sim,
+      " INSERT INTO inventory (product_id, email, count)
+        VALUES ($1, $2, $3);
+      ",
+      vec![
+        iast::Value::Number("2".to_string()),
+        iast::Value::QuotedString(email.to_string()),
+        iast::Value::Number("35".to_string()),
+      ],
+      10000,
+      exp_result,
+    );
+  }
+
+  // Sending the wrong number of values fails.
+  {
+    let request_id = ctx.send_query_with_params(
+      &mut sim,
+      select_query,
+      vec![iast::Value::Null, iast::Value::Null],
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        assert!(matches!(payload.payload, msg::ExternalAbortedData::ParseError(_)));
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  println!("Test 'bind_params_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  isolation_level_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that queries can set their isolation level with `SET TRANSACTION`.
+fn isolation_level_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0"))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1"))]);
+    ctx.execute_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        SELECT product_id, email
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email")]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_3"))]);
+    ctx.execute_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        UPDATE inventory
+        SET email = 'my_email_3'
+        WHERE product_id = 1;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    ctx.execute_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;
+        UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_3")), Some(cvi(25))]);
+    ctx.execute_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SERIALIZABLE;
+        SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Weaker isolation levels are not supported.
+  {
+    ctx.execute_query_failure(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL READ COMMITTED;
+        SELECT product_id
+        FROM inventory;
+      ",
+      10000,
+      |abort_data| matches!(abort_data, msg::ExternalAbortedData::ParseError(_)),
+    );
+  }
+
+  // A Snapshot read still protects its ReadRegion, so a write below its timestamp that reaches
+  // the Tablet between two of its stages is aborted and retried above it, and both stages see
+  // the same data. The first statements delay the write until after the read has started.
+  {
+    let conflict_labels = || vec![("reason", "WriteRegionConflictWithSubsequentRead".to_string())];
+    let num_conflicts = metrics().counter(M_TRANSACTIONS_ABORTED, conflict_labels());
+
+    let first_request_id = ctx.send_query(
+      &mut sim,
+      " SELECT count
+        FROM inventory
+        WHERE email = (
+          SELECT email
+          FROM inventory
+          WHERE product_id = (
+            SELECT product_id
+            FROM inventory
+            WHERE count = 25));
+
+        SELECT email
+        FROM inventory
+        WHERE count = (
+          SELECT count
+          FROM inventory
+          WHERE email = (
+            SELECT email
+            FROM inventory
+            WHERE product_id = 1));
+
+        UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+    );
+    sim.simulate_n_ms(3);
+    let second_request_id = ctx.send_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        SELECT count
+        FROM inventory
+        WHERE product_id = 0;
+
+        SELECT email
+        FROM inventory
+        WHERE count = (
+          SELECT count
+          FROM inventory
+          WHERE email = (
+            SELECT email
+            FROM inventory
+            WHERE product_id = 1));
+
+        SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0;
+      ",
+    );
+
+    let mut timestamps = BTreeMap::<RequestId, Timestamp>::new();
+    for _ in 0..2 {
+      assert!(ctx.simulate_until_response(&mut sim, 10000));
+      match ctx.next_response(&mut sim) {
+        msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+          if payload.request_id == second_request_id {
+            // The last stage does not see the write.
+            let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+            exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+            assert_eq!(payload.result, exp_result);
+          }
+          timestamps.insert(payload.request_id.clone(), payload.timestamp.clone());
+        }
+        response => panic!("Incorrect Response: {:#?}", response),
+      }
+    }
+
+    // The write was retried at a higher timestamp.
+    assert!(timestamps[&first_request_id] > timestamps[&second_request_id]);
+    assert!(metrics().counter(M_TRANSACTIONS_ABORTED, conflict_labels()) > num_conflicts);
+  }
+
+  // Concurrent Snapshot writes to the same key conflict. The write with the lower timestamp
+  // reaches the Tablet last, so it is aborted with a WriteWriteConflict and then retried.
+  {
+    let conflict_labels = || vec![("reason", "WriteWriteConflict".to_string())];
+    let num_conflicts = metrics().counter(M_TRANSACTIONS_ABORTED, conflict_labels());
+
+    let first_request_id = ctx.send_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        SELECT count
+        FROM inventory
+        WHERE email = (
+          SELECT email
+          FROM inventory
+          WHERE product_id = (
+            SELECT product_id
+            FROM inventory
+            WHERE count = 25));
+
+        SELECT email
+        FROM inventory
+        WHERE count = (
+          SELECT count
+          FROM inventory
+          WHERE email = (
+            SELECT email
+            FROM inventory
+            WHERE product_id = 1));
+
+        UPDATE inventory
+        SET email = 'my_email_4'
+        WHERE product_id = 0;
+      ",
+    );
+    sim.simulate_n_ms(3);
+    let second_request_id = ctx.send_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        UPDATE inventory
+        SET email = 'my_email_5'
+        WHERE product_id = 0;
+      ",
+    );
+
+    let mut request_ids = BTreeSet::from([first_request_id, second_request_id]);
+    for _ in 0..2 {
+      assert!(ctx.simulate_until_response(&mut sim, 10000));
+      match ctx.next_response(&mut sim) {
+        msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+          assert!(request_ids.remove(&payload.request_id));
+        }
+        response => panic!("Incorrect Response: {:#?}", response),
+      }
+    }
+
+    assert!(metrics().counter(M_TRANSACTIONS_ABORTED, conflict_labels()) > num_conflicts);
+  }
+
+  // Verify that the retried write was applied last.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_4")), Some(cvi(17))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_3")), Some(cvi(25))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'isolation_level_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  timeout_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that a query that does not finish before its timeout is aborted without taking effect.
+fn timeout_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // A query cannot finish within 0ms.
+  {
+    let request_id = ctx.send_query_with_timeout(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+      0,
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        assert_eq!(payload.payload, msg::ExternalAbortedData::Timeout);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  // A query with a long enough timeout finishes.
+  {
+    let request_id = ctx.send_query_with_timeout(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory;
+      ",
+      10000,
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+        exp_result.add_row(vec![Some(cvi(0)), Some(cvi(15))]);
+        exp_result.add_row(vec![Some(cvi(1)), Some(cvi(25))]);
+        assert_eq!(payload.result, exp_result);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  println!("Test 'timeout_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  priority_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that contending queries of different priorities both eventually commit.
+fn priority_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Send a low and a high priority query that write to the same row at once.
+  {
+    let mut request_ids = BTreeSet::<RequestId>::new();
+    for priority in [msg::TransactionPriority::Low, msg::TransactionPriority::High] {
+      request_ids.insert(ctx.send_query_with_priority(
+        &mut sim,
+        " UPDATE inventory
+          SET count = count + 1
+          WHERE product_id = 0;
+        ",
+        priority,
+      ));
+    }
+
+    for _ in 0..2 {
+      assert!(ctx.simulate_until_response(&mut sim, 10000));
+      match ctx.next_response(&mut sim) {
+        msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+          assert!(request_ids.remove(&payload.request_id));
+        }
+        response => panic!("Incorrect Response: {:#?}", response),
+      }
+    }
+  }
+
+  // Verify that both updates were applied.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(17))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'priority_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  select_for_update_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests `SELECT ... FOR UPDATE` and `SELECT ... FOR SHARE`.
+fn select_for_update_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Run a read-modify-write that locks the row it reads alongside a plain write to it.
+  {
+    let mut request_ids = BTreeSet::<RequestId>::new();
+    request_ids.insert(ctx.send_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0
+        FOR UPDATE;
+
+        UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+    ));
+    request_ids.insert(ctx.send_query(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+    ));
+
+    for _ in 0..2 {
+      assert!(ctx.simulate_until_response(&mut sim, 10000));
+      match ctx.next_response(&mut sim) {
+        msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+          assert!(request_ids.remove(&payload.request_id));
+        }
+        response => panic!("Incorrect Response: {:#?}", response),
+      }
+    }
+  }
+
+  // Verify that both updates were applied, reading under Snapshot isolation with a lock.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(17))]);
+    ctx.execute_query(
+      &mut sim,
+      " SET TRANSACTION ISOLATION LEVEL SNAPSHOT;
+        SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0
+        FOR SHARE;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Only Selects that read directly from a Table can lock rows.
+  {
+    ctx.execute_query_failure(
+      &mut sim,
+      " WITH v AS (SELECT product_id FROM inventory)
+        SELECT product_id
+        FROM v
+        FOR UPDATE;
+      ",
+      10000,
+      |abort_data| match abort_data {
+        msg::ExternalAbortedData::QueryPlanningError(msg::QueryPlanningError::InvalidLock) => true,
+        _ => false,
+      },
+    );
+  }
+
+  {
+    ctx.execute_query_failure(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0
+        FOR UPDATE;
+      ",
+      10000,
+      |abort_data| matches!(abort_data, msg::ExternalAbortedData::ParseError(_)),
+    );
+  }
+
+  println!("Test 'select_for_update_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  lease_read_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that Tablet Leaders serve reads with their leader lease, and that writes that
+/// follow such reads are still applied.
+fn lease_read_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Read a row, which should be served with the leader lease.
+  let lease_reads_before = metrics().counter(M_LEASE_READS, vec![]);
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(15))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+  assert!(metrics().counter(M_LEASE_READS, vec![]) > lease_reads_before);
+
+  // Write to the row that was just read, and verify the write is visible.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'lease_read_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  follower_read_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that bounded staleness reads are served by any node of the Tablet's PaxosGroup,
+/// that they read at a stale Timestamp, and that they cannot write.
+fn follower_read_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Wait for the `closed_timestamp` to pass the writes above.
+  sim.simulate_n_ms(1000);
+
+  // Write to a row, which a sufficiently stale read should not see.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  let follower_reads_before = metrics().counter(M_FOLLOWER_READS, vec![]);
+  {
+    let request_id = ctx.send_query_with_staleness(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory;
+      ",
+      500,
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+        exp_result.add_row(vec![Some(cvi(0)), Some(cvi(15))]);
+        exp_result.add_row(vec![Some(cvi(1)), Some(cvi(25))]);
+        assert_eq!(payload.result, exp_result);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+  assert!(metrics().counter(M_FOLLOWER_READS, vec![]) > follower_reads_before);
+
+  // A bounded staleness query that writes is rejected.
+  {
+    let request_id = ctx.send_query_with_staleness(
+      &mut sim,
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+      500,
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        assert_eq!(
+          payload.payload,
+          msg::ExternalAbortedData::QueryPlanningError(msg::QueryPlanningError::StaleWrite)
+        );
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  println!("Test 'follower_read_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  explain_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tests that an EXPLAIN describes the plan without executing the query.
+fn explain_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  {
+    let request_id = ctx.send_query(
+      &mut sim,
+      " EXPLAIN
+        UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let lines: Vec<String> = payload
+          .result
+          .data
+          .rows
+          .keys()
+          .map(|row| cast!(Some, row[1].clone()).unwrap())
+          .map(|val| cast!(ColVal::String, val).unwrap())
+          .collect();
+        let has_line = |line: &str| lines.iter().any(|l| l.trim() == line);
+        assert!(lines.iter().any(|l| l.contains("Update inventory")), "{:#?}", lines);
+        assert!(has_line("Key Region: product_id = 0"), "{:#?}", lines);
+        assert!(has_line("Tier Map: {inventory: 1}"), "{:#?}", lines);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  // Explain a JOIN with a subquery.
+  {
+    setup_user_table(&mut sim, &mut ctx);
+    let request_id = ctx.send_query(
+      &mut sim,
+      " EXPLAIN
+        SELECT I.product_id, balance
+        FROM inventory AS I JOIN user AS U ON U.balance = 70 AND I.product_id = 1
+        WHERE I.count > (SELECT count(balance) FROM user);
+      ",
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let lines: Vec<String> = payload
+          .result
+          .data
+          .rows
+          .keys()
+          .map(|row| cast!(Some, row[1].clone()).unwrap())
+          .map(|val| cast!(ColVal::String, val).unwrap())
+          .collect();
+        let has_line = |line: &str| lines.iter().any(|l| l.trim() == line);
+        assert!(has_line("[root] Inner Join"), "{:#?}", lines);
+        assert!(has_line("Key Region: product_id = 1"), "{:#?}", lines);
+        assert!(has_line("Subquery #0:"), "{:#?}", lines);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  // Ensure the UPDATE was not executed.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(15))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvi(25))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'explain_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  explain_analyze_test
+// -----------------------------------------------------------------------------------------------
+
+/// Returns the lines of the `Total` section of an `EXPLAIN ANALYZE`, trimmed.
+fn explain_analyze_totals(result: &QueryResult) -> Vec<String> {
+  let lines: Vec<String> = result
+    .data
+    .rows
+    .keys()
+    .map(|row| cast!(Some, row[1].clone()).unwrap())
+    .map(|val| cast!(ColVal::String, val).unwrap())
+    .collect();
+  let total_pos = lines.iter().position(|l| l.trim().starts_with("Total:")).unwrap();
+  lines[total_pos..].iter().map(|l| l.trim().to_string()).collect()
+}
+
+/// Returns the current shards of the Table at `table_path`.
+fn table_shards(sim: &Simulation, table_path: &str) -> Vec<(TabletKeyRange, TabletGroupId)> {
+  let full_db_schema = sim.full_db_schema();
+  let table_path = TablePath(table_path.to_string());
+  let timestamp = full_db_schema.table_generation.get_lat(&table_path);
+  let full_gen = full_db_schema.table_generation.static_read(&table_path, &timestamp).unwrap();
+  full_db_schema.sharding_config.get(&(table_path, full_gen.clone())).unwrap().clone()
+}
+
+/// Returns the number of Tablets that the Table at `table_path` currently has.
+fn num_tablets(sim: &Simulation, table_path: &str) -> usize {
+  table_shards(sim, table_path).len()
+}
+
+fn explain_analyze_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  {
+    let request_id = ctx.send_query(
+      &mut sim,
+      " EXPLAIN ANALYZE
+        UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 0;
+      ",
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let totals = explain_analyze_totals(&payload.result);
+        assert!(totals.iter().any(|l| l.starts_with("Rows Read: 2 {")), "{:#?}", totals);
+        assert!(totals.contains(&"Subqueries: 0".to_string()), "{:#?}", totals);
+        // The UPDATE is sent to every Tablet, each of which responds.
+        let messages_sent = format!("Messages Sent: {}", 2 * num_tablets(&sim, "inventory"));
+        assert!(totals.contains(&messages_sent), "{:#?}", totals);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  // Ensure the UPDATE was executed.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvi(25))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Analyze a JOIN with a subquery, which fires subqueries from the JoinReadES.
+  {
+    setup_user_table(&mut sim, &mut ctx);
+    let request_id = ctx.send_query(
+      &mut sim,
+      " EXPLAIN ANALYZE
+        SELECT I.product_id, balance
+        FROM inventory AS I JOIN user AS U ON U.balance = 70 AND I.product_id = 1
+        WHERE I.count > (SELECT count(balance) FROM user);
+      ",
+    );
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim) {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        let totals = explain_analyze_totals(&payload.result);
+        assert!(!totals.contains(&"Subqueries: 0".to_string()), "{:#?}", totals);
+        assert!(!totals.iter().any(|l| l.starts_with("Rows Read: 0 ")), "{:#?}", totals);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  println!("Test 'explain_analyze_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  hash_partition_test
+// -----------------------------------------------------------------------------------------------
+
+/// Runs the `EXPLAIN ANALYZE` of `query` and returns the `Rows Read` of each Tablet that
+/// was read from.
+fn rows_read_per_tablet(sim: &mut Simulation, ctx: &mut TestContext, query: &str) -> Vec<u64> {
+  let request_id = ctx.send_query(sim, &format!("EXPLAIN ANALYZE {}", query));
+  assert!(ctx.simulate_until_response(sim, 10000));
+  match ctx.next_response(sim) {
+    msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+      assert_eq!(payload.request_id, request_id);
+      let totals = explain_analyze_totals(&payload.result);
+      let rows_read = totals.iter().find(|l| l.starts_with("Rows Read: ")).unwrap();
+      let per_tablet = &rows_read[rows_read.find('{').unwrap() + 1..rows_read.len() - 1];
+      per_tablet
+        .split(", ")
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| entry.rsplit(": ").next().unwrap().parse().unwrap())
+        .collect()
+    }
+    response => panic!("Incorrect Response: {:#?}", response),
+  }
+}
+
+fn hash_partition_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup a hash-partitioned Table.
+  ctx.send_ddl_query(
+    &mut sim,
+    " CREATE TABLE hashed (
+        id      INT PRIMARY KEY,
+        value   INT
+      ) PARTITION BY HASH(id) PARTITIONS 4;
+    ",
+    10000,
+  );
+  assert_eq!(num_tablets(&sim, "hashed"), 4);
+
+  // Insert a sequence of keys, which would all land in the same Tablet with range
+  // partitioning.
+  const NUM_ROWS: i32 = 20;
+  {
+    let values: Vec<String> = (0..NUM_ROWS).map(|i| format!("({}, {})", i, 10 * i)).collect();
+    let mut exp_result = QueryResult::new(vec![cno("id"), cno("value")]);
+    for i in 0..NUM_ROWS {
+      exp_result.add_row(vec![Some(cvi(i)), Some(cvi(10 * i))]);
+    }
+    ctx.execute_query(
+      &mut sim,
+      &format!("INSERT INTO hashed (id, value) VALUES {};", values.join(", ")),
+      10000,
+      exp_result,
+    );
+  }
+
+  // The rows should be spread over several Tablets.
+  let rows_read = rows_read_per_tablet(&mut sim, &mut ctx, "SELECT id, value FROM hashed;");
+  assert_eq!(rows_read.len(), 4);
+  assert!(rows_read.iter().filter(|rows| **rows > 0).count() > 1, "{:#?}", rows_read);
+
+  // A point lookup on the hashed column should only go to a single Tablet.
+  let rows_read =
+    rows_read_per_tablet(&mut sim, &mut ctx, "SELECT id, value FROM hashed WHERE id = 7;");
+  assert_eq!(rows_read.len(), 1);
+  {
+    let mut exp_result = QueryResult::new(vec![cno("value")]);
+    exp_result.add_row(vec![Some(cvi(70))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT value
+        FROM hashed
+        WHERE id = 7;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // A range scan has to go to every Tablet, but should still return the right rows.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("id"), cno("value")]);
+    for i in 5..10 {
+      exp_result.add_row(vec![Some(cvi(i)), Some(cvi(10 * i))]);
+    }
+    ctx.execute_query(
+      &mut sim,
+      " SELECT id, value
+        FROM hashed
+        WHERE id >= 5 AND id < 10;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Updates routed by the hashed column should also only affect the right row.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("id"), cno("value")]);
+    exp_result.add_row(vec![Some(cvi(13)), Some(cvi(131))]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE hashed
+        SET value = value + 1
+        WHERE id = 13;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+  {
+    let mut exp_result = QueryResult::new(vec![cno("id"), cno("value")]);
+    exp_result.add_row(vec![Some(cvi(12)), Some(cvi(120))]);
+    exp_result.add_row(vec![Some(cvi(13)), Some(cvi(131))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT id, value
+        FROM hashed
+        WHERE id = 12 OR id = 13;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Splitting a hash-partitioned Tablet should be rejected up front.
+  {
+    let (range, tid) = table_shards(&sim, "hashed").into_iter().next().unwrap();
+    let sid = sim.full_db_schema().tablet_address_config.get(&tid).unwrap().clone();
+    let old_range = TabletKeyRange { start: None, end: Some(cvi(10)), hash: range.hash.clone() };
+    let new_range = TabletKeyRange { start: Some(cvi(10)), end: None, hash: range.hash };
+    ctx.send_sharding_failure(
+      &mut sim,
+      msg::ShardingOp::Split(msg::SplitShardingOp {
+        table_path: TablePath("hashed".to_string()),
+        target_old: STRange { sid: sid.clone(), tid, range: old_range },
+        target_new: STRange { sid, tid: mk_tid("t_split"), range: new_range },
+      }),
+      10000,
+      |abort_data| matches!(abort_data, msg::ExternalShardingAbortData::HashPartitionedTable),
+    );
+    assert_eq!(num_tablets(&sim, "hashed"), 4);
+  }
+
+  // Too many partitions should be rejected.
+  ctx.send_ddl_query_failure(
+    &mut sim,
+    &format!(
+      " CREATE TABLE too_hashed (
+          id      INT PRIMARY KEY,
+          value   INT
+        ) PARTITION BY HASH(id) PARTITIONS {};
+      ",
+      MAX_HASH_PARTITIONS + 1
+    ),
+    10000,
+    |abort_data| matches!(abort_data, msg::ExternalDDLQueryAbortData::ParseError(_)),
+  );
+
+  println!("Test 'hash_partition_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  split_at_test
+// -----------------------------------------------------------------------------------------------
+
+fn split_at_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup a pre-split Table.
+  ctx.send_ddl_query(
+    &mut sim,
+    " CREATE TABLE split (
+        id      INT PRIMARY KEY,
+        value   INT
+      ) SPLIT AT (10), (20);
+    ",
+    10000,
+  );
+
+  // There should be one Tablet per range, each in a different SlaveGroup.
+  let mut ranges = Vec::<(Option<ColVal>, Option<ColVal>)>::new();
+  let mut sids = BTreeSet::<SlaveGroupId>::new();
+  for (range, tid) in table_shards(&sim, "split") {
+    assert!(range.hash.is_none());
+    ranges.push((range.start, range.end));
+    sids.insert(sim.full_db_schema().tablet_address_config.get(&tid).unwrap().clone());
+  }
+  ranges.sort();
+  assert_eq!(
+    ranges,
+    vec![
+      (None, Some(ColVal::Int(10))),
+      (Some(ColVal::Int(10)), Some(ColVal::Int(20))),
+      (Some(ColVal::Int(20)), None),
+    ]
+  );
+  assert_eq!(sids.len(), 3);
+
+  // Insert a row into every range, and check that every Tablet holds one.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("id"), cno("value")]);
+    exp_result.add_row(vec![Some(cvi(5)), Some(cvi(50))]);
+    exp_result.add_row(vec![Some(cvi(15)), Some(cvi(150))]);
+    exp_result.add_row(vec![Some(cvi(25)), Some(cvi(250))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO split (id, value)
+        VALUES (5, 50), (15, 150), (25, 250);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+  let rows_read = rows_read_per_tablet(&mut sim, &mut ctx, "SELECT id, value FROM split;");
+  assert_eq!(rows_read.len(), 3);
+  assert!(rows_read.iter().all(|rows| *rows > 0), "{:#?}", rows_read);
+
+  // Invalid split keys should be rejected.
+  ctx.send_ddl_query_failure(
+    &mut sim,
+    " CREATE TABLE bad_split (
+        id      INT PRIMARY KEY,
+        value   INT
+      ) SPLIT AT (20), (10);
+    ",
+    10000,
+    |abort_data| matches!(abort_data, msg::ExternalDDLQueryAbortData::ParseError(_)),
+  );
+
+  println!("Test 'split_at_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  keyless_table_test
+// -----------------------------------------------------------------------------------------------
+
+/// Tables without KeyCols hold at most one row in a single Tablet.
+fn keyless_table_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  ctx.send_ddl_query(
+    &mut sim,
+    " CREATE TABLE counter (
+        total   INT
+      );
+    ",
+    10000,
+  );
+  assert_eq!(num_tablets(&sim, "counter"), 1);
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("total")]);
+    exp_result.add_row(vec![Some(cvi(3))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO counter (total)
+        VALUES (3);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("total")]);
+    exp_result.add_row(vec![Some(cvi(3))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT total
+        FROM counter;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'keyless_table_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  trace_test
+// -----------------------------------------------------------------------------------------------
+
+fn trace_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Run an UPDATE, which is planned at the Master, written at the Tablet, and committed
+  // with Paxos2PC.
+  let request_id = ctx.send_query(
+    &mut sim,
+    " UPDATE inventory
+      SET count = count + 1
+      WHERE product_id = 0;
+    ",
+  );
+  assert!(ctx.simulate_until_response(&mut sim, 10000));
+  match ctx.next_response(&mut sim) {
+    msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+      assert_eq!(payload.request_id, request_id);
+    }
+    response => panic!("Incorrect Response: {:#?}", response),
+  }
+
+  // Let the RMs finish committing before examining the trace.
+  assert!(simulate_until_clean(&mut sim, 10000));
+
+  let lines = stitch_timeline(sim.get_spans(), &request_id).unwrap();
+  let has_span = |es_type: &str| lines.iter().any(|l| l.trim().starts_with(es_type));
+  assert!(lines[1].trim().starts_with("MSCoordES"), "{:#?}", lines);
+  assert!(has_span("MSTableWriteES"), "{:#?}", lines);
+  assert!(has_span("FinishQueryTMES"), "{:#?}", lines);
+  assert!(has_span("FinishQueryRMES"), "{:#?}", lines);
+  // Every span should be reachable from the root.
+  assert!(!lines.contains(&"Spans with missing parents".to_string()), "{:#?}", lines);
+  // The FinishQueryRMES should be indented below the FinishQueryTMES that is its parent.
+  let indent = |es_type: &str| {
+    let line = lines.iter().find(|l| l.trim().starts_with(es_type)).unwrap();
+    line.len() - line.trim_start().len()
+  };
+  assert!(indent("FinishQueryRMES") > indent("FinishQueryTMES"), "{:#?}", lines);
+
+  // Every span should survive being written to and read back from a trace log.
+  for span in sim.get_spans() {
+    assert_eq!(Span::parse_log_line(&(span.to_log_line() + "\n")).as_ref(), Some(span));
+  }
+  assert_eq!(Span::parse_log_line("t\tq0\t-\t-\tnode"), None);
+  assert_eq!(Span::parse_log_line("t\tq0\t-\t-\tnode\tES\tx\t20"), None);
+
+  // Spans whose parent is missing should be listed separately.
+  let mut spans = sim.get_spans().clone();
+  let root = spans.iter().find(|span| span.request_id.as_ref() == Some(&request_id)).unwrap();
+  let mut orphan = spans
+    .iter()
+    .find(|span| span.trace_id == root.trace_id && span.es_type == "FinishQueryRMES")
+    .unwrap()
+    .clone();
+  orphan.span_id = QueryId("orphan".to_string());
+  orphan.parent_span_id = Some(QueryId("missing".to_string()));
+  spans.push(orphan);
+  let lines = stitch_timeline(&spans, &request_id).unwrap();
+  let pos = lines.iter().position(|l| l == "Spans with missing parents").unwrap();
+  assert_eq!(lines.len(), pos + 2, "{:#?}", lines);
+  assert!(lines[pos + 1].trim().starts_with("FinishQueryRMES"), "{:#?}", lines);
+
+  // There is no timeline for a request that was never traced.
+  assert_eq!(stitch_timeline(&spans, &RequestId("missing".to_string())), None);
+
+  println!("Test 'trace_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
 // -----------------------------------------------------------------------------------------------
 //  basic_add_column
 // -----------------------------------------------------------------------------------------------
 
-fn basic_add_column(seed: [u8; 16]) {
+fn basic_add_column(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Add Column and Write to it
+
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ADD COLUMN price INT;
+      ",
+      10000,
+    );
+  }
+
+  {
+    let mut exp_result =
+      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15)), None]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25)), None]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count, price
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(100))]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE inventory
+        SET price = 100
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result =
+      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35)), Some(cvi(200))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO inventory (product_id, email, count, price)
+        VALUES (2, 'my_email_2', 35, 200);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result =
+      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15)), Some(cvi(100))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25)), None]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35)), Some(cvi(200))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count, price
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'basic_add_column' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  add_column_default_test
+// -----------------------------------------------------------------------------------------------
+
+fn add_column_default_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Add Column with a DEFAULT
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ADD COLUMN price INT DEFAULT 10;
+      ",
+      10000,
+    );
+  }
+
+  // Ensure the existing rows observe the DEFAULT.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(10))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvi(10))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, price
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Explicitly write a NULL and insert a row without the column.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), None]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE inventory
+        SET price = NULL
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO inventory (product_id, email, count)
+        VALUES (2, 'my_email_2', 35);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), None]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvi(10))]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvi(10))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, price
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Ensure the DEFAULT and explicit NULLs survive a type change.
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ALTER COLUMN price TYPE VARCHAR;
+      ",
+      10000,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(3)), Some(cvs("my_email_3")), Some(cvi(45))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO inventory (product_id, email, count)
+        VALUES (3, 'my_email_3', 45);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
+    exp_result.add_row(vec![Some(cvi(0)), None]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("10"))]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("10"))]);
+    exp_result.add_row(vec![Some(cvi(3)), Some(cvs("10"))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, price
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!(
+    "Test 'add_column_default_test' Passed! Time taken: {:?}ms",
+    sim.true_timestamp().time_ms
+  )
+}
+
+// -----------------------------------------------------------------------------------------------
+//  drop_column
+// -----------------------------------------------------------------------------------------------
+
+fn drop_column(seed: [u8; 16]) {
   let (mut sim, mut ctx) = setup(seed);
 
   // Setup Tables
   deprecated_setup_inventory_table(&mut sim, &mut ctx);
   deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
 
-  // Add Column and Write to it
+  // See if deleting a column and adding it back results in SELECTs now reading null
+  // for that column (rather than a non-null value that was previously there).
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO inventory (product_id, email, count)
+        VALUES (2, 'my_email_2', 35);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25))]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
 
+  // Drop the column
   {
     ctx.send_ddl_query(
       &mut sim,
       " ALTER TABLE inventory
-        ADD COLUMN price INT;
+        DROP COLUMN count;
       ",
       10000,
     );
   }
 
+  // Ensure we get an error if we try using it
   {
-    let mut exp_result =
-      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15)), None]);
-    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25)), None]);
+    ctx.execute_query_failure(
+      &mut sim,
+      " SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      |abort_data| match abort_data {
+        msg::ExternalAbortedData::QueryPlanningError(
+          msg::QueryPlanningError::NonExistentColumn(_),
+        ) => true,
+        _ => false,
+      },
+    );
+  }
+
+  // Add it back
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ADD COLUMN count INT;
+      ",
+      10000,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), None]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), None]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), None]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Re-populated "count"
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    ctx.execute_query(
+      &mut sim,
+      " UPDATE inventory
+        SET count = 16
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), None]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), None]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, count
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'drop_column' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  rename_column_test
+// -----------------------------------------------------------------------------------------------
+
+fn rename_column_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Rename the column
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        RENAME COLUMN count TO quantity;
+      ",
+      10000,
+    );
+  }
+
+  // Ensure we get an error if we try using the old name
+  {
+    ctx.execute_query_failure(
+      &mut sim,
+      " SELECT product_id, count
+        FROM inventory;
+      ",
+      10000,
+      |abort_data| match abort_data {
+        msg::ExternalAbortedData::QueryPlanningError(
+          msg::QueryPlanningError::NonExistentColumn(_),
+        ) => true,
+        _ => false,
+      },
+    );
+  }
+
+  // Ensure the data is available under the new name.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("quantity")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email, quantity
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("quantity")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
     ctx.execute_query(
       &mut sim,
-      " SELECT product_id, email, count, price
-        FROM inventory;
+      " UPDATE inventory
+        SET quantity = 16
+        WHERE product_id = 0;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Add the old name back and verify it is empty.
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ADD COLUMN count INT;
       ",
       10000,
-      exp_result,
     );
   }
 
   {
-    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("price")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(100))]);
+    let mut exp_result =
+      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("quantity")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), None, Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), None, Some(cvi(25))]);
     ctx.execute_query(
       &mut sim,
-      " UPDATE inventory
-        SET price = 100
-        WHERE product_id = 0;
+      " SELECT product_id, email, count, quantity
+        FROM inventory;
       ",
       10000,
       exp_result,
     );
   }
 
+  // Writing to the new column of the old name does not affect the renamed column, which
+  // still resolves its unwritten values through the old data.
   {
-    let mut exp_result =
-      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35)), Some(cvi(200))]);
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvi(7))]);
     ctx.execute_query(
       &mut sim,
-      " INSERT INTO inventory (product_id, email, count, price)
-        VALUES (2, 'my_email_2', 35, 200);
+      " UPDATE inventory
+        SET count = 7
+        WHERE product_id = 1;
       ",
       10000,
       exp_result,
@@ -1250,13 +2873,12 @@ fn basic_add_column(seed: [u8; 16]) {
 
   {
     let mut exp_result =
-      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("price")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15)), Some(cvi(100))]);
-    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25)), None]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35)), Some(cvi(200))]);
+      QueryResult::new(vec![cno("product_id"), cno("email"), cno("count"), cno("quantity")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), None, Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(7)), Some(cvi(25))]);
     ctx.execute_query(
       &mut sim,
-      " SELECT product_id, email, count, price
+      " SELECT product_id, email, count, quantity
         FROM inventory;
       ",
       10000,
@@ -1264,136 +2886,144 @@ fn basic_add_column(seed: [u8; 16]) {
     );
   }
 
-  println!("Test 'basic_add_column' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+  println!("Test 'rename_column_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
 }
 
 // -----------------------------------------------------------------------------------------------
-//  drop_column
+//  change_column_type_test
 // -----------------------------------------------------------------------------------------------
 
-fn drop_column(seed: [u8; 16]) {
+fn change_column_type_test(seed: [u8; 16]) {
   let (mut sim, mut ctx) = setup(seed);
 
   // Setup Tables
   deprecated_setup_inventory_table(&mut sim, &mut ctx);
   deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
 
-  // See if deleting a column and adding it back results in SELECTs now reading null
-  // for that column (rather than a non-null value that was previously there).
+  // Widen the column
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        ALTER COLUMN count TYPE BIGINT;
+      ",
+      10000,
+    );
+  }
+
+  // Ensure the existing data is rewritten in the background.
+  sim.simulate_n_ms(100);
+  assert!(sim.check_resources_clean(false));
 
+  // Ensure the existing data was converted.
   {
-    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvbi(15))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvbi(25))]);
     ctx.execute_query(
       &mut sim,
-      " INSERT INTO inventory (product_id, email, count)
-        VALUES (2, 'my_email_2', 35);
+      " SELECT product_id, count
+        FROM inventory;
       ",
       10000,
       exp_result,
     );
   }
 
+  // Ensure values that do not fit into an INT can now be written, and that INT
+  // literals are widened.
   {
-    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(15))]);
-    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), Some(cvi(25))]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvbi(10000000000))]);
     ctx.execute_query(
       &mut sim,
-      " SELECT product_id, email, count
-        FROM inventory;
+      " UPDATE inventory
+        SET count = 10000000000
+        WHERE product_id = 0;
       ",
       10000,
       exp_result,
     );
   }
 
-  // Drop the column
   {
-    ctx.send_ddl_query(
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvbi(26))]);
+    ctx.execute_query(
       &mut sim,
-      " ALTER TABLE inventory
-        DROP COLUMN count;
+      " UPDATE inventory
+        SET count = count + 1
+        WHERE product_id = 1 AND count < 100;
       ",
       10000,
+      exp_result,
     );
   }
 
-  // Ensure we get an error if we try using it
+  // Ensure BIGINT arithmetic that overflows is rejected rather than wrapping.
   {
     ctx.execute_query_failure(
       &mut sim,
-      " SELECT product_id, email, count
-        FROM inventory;
+      " UPDATE inventory
+        SET count = count * 10000000000
+        WHERE product_id = 0;
       ",
       10000,
       |abort_data| match abort_data {
-        msg::ExternalAbortedData::QueryPlanningError(
-          msg::QueryPlanningError::NonExistentColumn(_),
+        msg::ExternalAbortedData::QueryExecutionError(
+          msg::ExternalQueryError::RuntimeError { .. },
         ) => true,
         _ => false,
       },
     );
   }
 
-  // Add it back
+  // Converting to a STRING is also safe.
   {
     ctx.send_ddl_query(
       &mut sim,
       " ALTER TABLE inventory
-        ADD COLUMN count INT;
-      ",
-      10000,
-    );
-  }
-
-  {
-    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), None]);
-    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), None]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), None]);
-    ctx.execute_query(
-      &mut sim,
-      " SELECT product_id, email, count
-        FROM inventory;
+        ALTER COLUMN count TYPE VARCHAR;
       ",
       10000,
-      exp_result,
     );
   }
 
-  // Re-populated "count"
   {
     let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("count")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvi(16))]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("10000000000"))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("26"))]);
     ctx.execute_query(
       &mut sim,
-      " UPDATE inventory
-        SET count = 16
-        WHERE product_id = 0;
+      " SELECT product_id, count
+        FROM inventory;
       ",
       10000,
       exp_result,
     );
   }
 
+  // Ensure unsafe conversions are rejected.
   {
-    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
-    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0")), Some(cvi(16))]);
-    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1")), None]);
-    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), None]);
-    ctx.execute_query(
+    ctx.send_ddl_query_failure(
       &mut sim,
-      " SELECT product_id, email, count
-        FROM inventory;
+      " ALTER TABLE inventory
+        ALTER COLUMN count TYPE INT;
       ",
       10000,
-      exp_result,
+      |abort_data| match abort_data {
+        msg::ExternalDDLQueryAbortData::QueryPlanningError(
+          msg::QueryPlanningError::UnsafeColumnTypeConversion { .. },
+        ) => true,
+        _ => false,
+      },
     );
   }
 
-  println!("Test 'drop_column' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+  println!(
+    "Test 'change_column_type_test' Passed! Time taken: {:?}ms",
+    sim.true_timestamp().time_ms
+  )
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1708,6 +3338,88 @@ fn drop_table_test(seed: [u8; 16]) {
   println!("Test 'drop_table_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
 }
 
+// -----------------------------------------------------------------------------------------------
+//  rename_table_test
+// -----------------------------------------------------------------------------------------------
+
+fn rename_table_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Create a Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  // Rename 'inventory'
+  {
+    ctx.send_ddl_query(
+      &mut sim,
+      " ALTER TABLE inventory
+        RENAME TO stock;
+      ",
+      10000,
+    );
+  }
+
+  {
+    ctx.execute_query_failure(
+      &mut sim,
+      " SELECT count(product_id)
+        FROM inventory;
+      ",
+      10000,
+      |abort_data| match abort_data {
+        msg::ExternalAbortedData::QueryPlanningError(msg::QueryPlanningError::TablesDNE(_)) => true,
+        _ => false,
+      },
+    );
+  }
+
+  // Verify the data is available under the new name, and that it can be written to.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email"), cno("count")]);
+    exp_result.add_row(vec![Some(cvi(2)), Some(cvs("my_email_2")), Some(cvi(35))]);
+    ctx.execute_query(
+      &mut sim,
+      " INSERT INTO stock (product_id, email, count)
+        VALUES (2, 'my_email_2', 35);
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  {
+    let mut exp_result = QueryResult::new(vec![None]);
+    exp_result.add_row(vec![Some(cvi(3))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT count(product_id)
+        FROM stock;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Create 'inventory' again and verify it is empty
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+
+  {
+    let mut exp_result = QueryResult::new(vec![None]);
+    exp_result.add_row(vec![Some(cvi(0))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT count(product_id)
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!("Test 'rename_table_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
 // -----------------------------------------------------------------------------------------------
 //  Joins
 // -----------------------------------------------------------------------------------------------
@@ -2325,13 +4037,108 @@ fn cancellation_test(seed: [u8; 16]) {
   println!("Test 'cancellation_test' Passed! Time taken: {:?}ms", test_time_taken.time_ms)
 }
 
+// -----------------------------------------------------------------------------------------------
+//  show_kill_queries_test
+// -----------------------------------------------------------------------------------------------
+
+fn show_kill_queries_test(seed: [u8; 16]) {
+  let (mut sim, mut ctx) = setup(seed);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+  setup_user_table(&mut sim, &mut ctx);
+  populate_user_table_basic(&mut sim, &mut ctx);
+
+  // Killing a request that does not exist should do nothing.
+  let kill_request_id = ctx.send_kill_query(&mut sim, RequestId("unknown".to_string()));
+  assert!(ctx.simulate_until_response(&mut sim, 10000));
+  match ctx.next_response(&mut sim) {
+    msg::NetworkMessage::External(msg::ExternalMessage::ExternalKillQueryResponse(payload)) => {
+      assert_eq!(payload.request_id, kill_request_id);
+      assert_eq!(payload.result, msg::KillQueryResult::NotFound);
+    }
+    response => panic!("Incorrect Response: {:#?}", response),
+  }
+
+  // Send a multi-stage query, and then immediately list the running queries and kill it.
+  let query = "
+    UPDATE user
+    SET balance = balance + 2 * (
+      SELECT sum(count)
+      FROM inventory AS inv
+      WHERE inv.email = user.email)
+    WHERE email = 'my_email_1';
+  ";
+  let request_id = ctx.send_query(&mut sim, query);
+  let show_request_id = ctx.send_show_queries(&mut sim);
+  let kill_request_id = ctx.send_kill_query(&mut sim, request_id.clone());
+
+  // The responses of the 3 requests can arrive in any order.
+  let mut opt_queries = None;
+  let mut opt_killed = None;
+  let mut opt_aborted = None;
+  for _ in 0..3 {
+    assert!(ctx.simulate_until_response(&mut sim, 10000));
+    match ctx.next_response(&mut sim).clone() {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalShowQueriesResponse(payload)) => {
+        assert_eq!(payload.request_id, show_request_id);
+        opt_queries = Some(payload.queries);
+      }
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalKillQueryResponse(payload)) => {
+        assert_eq!(payload.request_id, kill_request_id);
+        opt_killed = Some(payload.result);
+      }
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        opt_aborted = Some(payload.payload);
+      }
+      response => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  // The query should have been listed at the Coord with its RequestId, and then killed.
+  let queries = opt_queries.unwrap();
+  let ms_coord = queries.iter().find(|query| query.es_type == "MSCoordES").unwrap();
+  assert_eq!(ms_coord.request_id, Some(request_id.clone()));
+  assert_eq!(ms_coord.root_query_id, ms_coord.query_id);
+  assert!(ms_coord.server.starts_with("Coord"));
+  assert_eq!(opt_killed, Some(msg::KillQueryResult::Killed));
+  assert_eq!(opt_aborted, Some(msg::ExternalAbortedData::Killed));
+
+  // Verify that all resources get cleaned up, and that the update did not take effect.
+  assert!(simulate_until_clean(&mut sim, 10000));
+  {
+    let mut exp_result = QueryResult::new(vec![cno("email"), cno("balance")]);
+    exp_result.add_row(vec![Some(cvs("my_email_1")), Some(cvi(60))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT email, balance
+        FROM user
+        WHERE email = 'my_email_1';
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  // Since the threshold is 0 in tests, every request is in the slow query log.
+  let slow_queries = sim.get_slow_queries();
+  let killed = slow_queries.iter().find(|slow| slow.request_id == request_id).unwrap();
+  assert_eq!(killed.aborted, Some(msg::ExternalAbortedData::Killed));
+  assert!(killed.to_log_line().ends_with(&query.split_whitespace().collect::<Vec<_>>().join(" ")));
+  assert!(slow_queries.iter().any(|slow| slow.aborted.is_none()));
+
+  println!("Test 'show_kill_queries_test' Passed! Time taken: {:?}ms", sim.true_timestamp().time_ms)
+}
+
 // -----------------------------------------------------------------------------------------------
 //  paxos_leader_change_test
 // -----------------------------------------------------------------------------------------------
 
 fn paxos_leader_change_test(seed: [u8; 16]) {
   // Create one Slave Paxos Group to test Leader change logic with.
-  let mut sim = mk_general_sim(seed, 1, 1, 5, 1, 0);
+  let mut sim = mk_general_sim(seed, 1, 1, 5, 0);
 
   // Warmup the simulation
   sim.simulate_n_ms(100);
@@ -2362,6 +4169,103 @@ fn paxos_leader_change_test(seed: [u8; 16]) {
   }
 }
 
+// -----------------------------------------------------------------------------------------------
+//  leadership_transfer_test
+// -----------------------------------------------------------------------------------------------
+
+/// Simulates until the response of the `ExternalTransferLeadership` with `request_id`
+/// arrives, and returns its result.
+fn transfer_leadership_result(
+  sim: &mut Simulation,
+  ctx: &mut TestContext,
+  request_id: RequestId,
+) -> msg::TransferLeadershipResult {
+  assert!(ctx.simulate_until_response(sim, 10000));
+  match ctx.next_response(sim) {
+    msg::NetworkMessage::External(msg::ExternalMessage::ExternalTransferLeadershipResponse(
+      payload,
+    )) => {
+      assert_eq!(payload.request_id, request_id);
+      payload.result.clone()
+    }
+    response => panic!("Incorrect Response: {:#?}", response),
+  }
+}
+
+/// Transfers the Leadership of `gid` to `target_eid` and checks that it succeeded.
+fn transfer_leadership(
+  sim: &mut Simulation,
+  ctx: &mut TestContext,
+  gid: PaxosGroupId,
+  target_eid: EndpointId,
+) {
+  let lid = sim.leader_map.get(&gid).unwrap().clone();
+  let request_id = ctx.send_transfer_leadership(sim, gid.clone(), target_eid.clone(), &lid.eid);
+  match transfer_leadership_result(sim, ctx, request_id) {
+    msg::TransferLeadershipResult::Transferred(new_lid) => {
+      assert_eq!(new_lid.eid, target_eid);
+      assert!(new_lid.gen > lid.gen);
+      assert_eq!(sim.leader_map.get(&gid).unwrap(), &new_lid);
+    }
+    result => panic!("Incorrect Result: {:#?}", result),
+  }
+}
+
+fn leadership_transfer_test(seed: [u8; 16]) {
+  let mut sim = mk_general_sim(seed, 1, 1, 5, 0);
+  let mut ctx = TestContext::new(&sim);
+
+  // Setup Tables
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  let sid = sim.full_db_schema().slave_address_config.first_key_value().unwrap().0.clone();
+  let slave_eids = sim.full_db_schema().slave_address_config.get(&sid).unwrap().clone();
+  let master_eids = sim.full_db_schema().master_address_config.clone();
+  let slave_lid = sim.leader_map.get(&sid.to_gid()).unwrap().clone();
+  let master_lid = sim.leader_map.get(&PaxosGroupId::Master).unwrap().clone();
+  let slave_target = slave_eids.iter().find(|eid| *eid != &slave_lid.eid).unwrap().clone();
+  let master_target = master_eids.iter().find(|eid| *eid != &master_lid.eid).unwrap().clone();
+
+  // Followers should reject the request.
+  let request_id =
+    ctx.send_transfer_leadership(&mut sim, sid.to_gid(), slave_target.clone(), &slave_target);
+  let result = transfer_leadership_result(&mut sim, &mut ctx, request_id);
+  assert_eq!(result, msg::TransferLeadershipResult::NotLeader);
+
+  // Targets outside of the PaxosGroup should be rejected.
+  let request_id =
+    ctx.send_transfer_leadership(&mut sim, sid.to_gid(), master_target.clone(), &slave_lid.eid);
+  let result = transfer_leadership_result(&mut sim, &mut ctx, request_id);
+  assert_eq!(result, msg::TransferLeadershipResult::InvalidTarget);
+
+  // Move the Leaderships away and then back.
+  transfer_leadership(&mut sim, &mut ctx, sid.to_gid(), slave_target);
+  transfer_leadership(&mut sim, &mut ctx, PaxosGroupId::Master, master_target);
+  transfer_leadership(&mut sim, &mut ctx, sid.to_gid(), slave_lid.eid.clone());
+  transfer_leadership(&mut sim, &mut ctx, PaxosGroupId::Master, master_lid.eid.clone());
+
+  // Verify that the system still works.
+  {
+    let mut exp_result = QueryResult::new(vec![cno("product_id"), cno("email")]);
+    exp_result.add_row(vec![Some(cvi(0)), Some(cvs("my_email_0"))]);
+    exp_result.add_row(vec![Some(cvi(1)), Some(cvs("my_email_1"))]);
+    ctx.execute_query(
+      &mut sim,
+      " SELECT product_id, email
+        FROM inventory;
+      ",
+      10000,
+      exp_result,
+    );
+  }
+
+  println!(
+    "Test 'leadership_transfer_test' Passed! Time taken: {:?}ms",
+    sim.true_timestamp().time_ms
+  );
+}
+
 fn paxos_basic_serial_test(seed: [u8; 16]) {
   let mut test_time_taken = mk_t(0);
 
@@ -2375,7 +4279,7 @@ fn paxos_basic_serial_test(seed: [u8; 16]) {
   let mut failed = 0;
   'outer: for i in 0..NUM_ITERATIONS {
     println!("    iteration {:?}", i);
-    let mut sim = mk_general_sim(seed, 1, 5, 5, 1, 0);
+    let mut sim = mk_general_sim(seed, 1, 5, 5, 0);
     let mut ctx = TestContext::new(&sim);
 
     // Test Simple Update-Select
@@ -0,0 +1,167 @@
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use runiversal::common::{
+  mk_t, BasicIOCtx, ClockId, ClockIdAllocator, EndpointId, GeneralTraceMessage, HybridClock,
+  Timestamp,
+};
+use runiversal::message as msg;
+use runiversal::metrics::{metrics, M_CLOCK_SKEW_DETECTED};
+
+/**
+ * This suite of tests checks the `HybridClock` directly, using a physical clock that
+ * is set manually.
+ */
+
+// -----------------------------------------------------------------------------------------------
+//  test_all_hybrid_clock
+// -----------------------------------------------------------------------------------------------
+
+pub fn test_all_hybrid_clock() {
+  monotonic_test();
+  update_test();
+  now_after_test();
+  now_stale_test();
+  skew_test();
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Utils
+// -----------------------------------------------------------------------------------------------
+
+/// A `BasicIOCtx` whose physical clock is set manually.
+struct ClockIOCtx {
+  time_ms: u128,
+  rand: XorShiftRng,
+}
+
+impl ClockIOCtx {
+  fn new(time_ms: u128) -> ClockIOCtx {
+    ClockIOCtx { time_ms, rand: XorShiftRng::from_seed([0; 16]) }
+  }
+}
+
+impl BasicIOCtx for ClockIOCtx {
+  type RngCoreT = XorShiftRng;
+
+  fn rand(&mut self) -> &mut Self::RngCoreT {
+    &mut self.rand
+  }
+
+  fn now(&mut self) -> Timestamp {
+    mk_t(self.time_ms)
+  }
+
+  fn send(&mut self, _: &EndpointId, _: msg::NetworkMessage) {}
+
+  fn general_trace(&mut self, _: GeneralTraceMessage) {}
+}
+
+// -----------------------------------------------------------------------------------------------
+//  monotonic_test
+// -----------------------------------------------------------------------------------------------
+
+fn monotonic_test() {
+  let mut io_ctx = ClockIOCtx::new(10);
+  let mut clock = HybridClock::new(ClockId { node_index: 0, sub_node_index: 0 }, 100);
+
+  // Timestamps are strictly increasing while the physical time stays the same.
+  let t1 = clock.now(&mut io_ctx);
+  let t2 = clock.now(&mut io_ctx);
+  assert_eq!(t1.time_ms, 10);
+  assert!(t1 < t2);
+
+  // They remain increasing if the physical time goes backwards.
+  io_ctx.time_ms = 5;
+  assert!(t2 < clock.now(&mut io_ctx));
+
+  println!("Test 'monotonic_test' Passed!");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  update_test
+// -----------------------------------------------------------------------------------------------
+
+fn update_test() {
+  let mut io_ctx = ClockIOCtx::new(10);
+  let mut clock_ids = ClockIdAllocator::new(0);
+  let mut clock1 = HybridClock::new(clock_ids.next(), 100);
+  let mut clock2 = HybridClock::new(clock_ids.next(), 100);
+
+  // Clocks with the same physical time still generate different Timestamps.
+  assert_ne!(clock1.now(&mut io_ctx), clock2.now(&mut io_ctx));
+
+  // After receiving a Timestamp, the clock generates Timestamps greater than it.
+  io_ctx.time_ms = 50;
+  let t1 = clock1.now(&mut io_ctx);
+  io_ctx.time_ms = 20;
+  clock2.update(&mut io_ctx, &t1);
+  let t2 = clock2.now(&mut io_ctx);
+  assert!(t1 < t2);
+  assert_eq!(t2.time_ms, 50);
+
+  println!("Test 'update_test' Passed!");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  now_after_test
+// -----------------------------------------------------------------------------------------------
+
+fn now_after_test() {
+  let mut io_ctx = ClockIOCtx::new(10);
+  let mut clock1 = HybridClock::new(ClockId { node_index: 0, sub_node_index: 0 }, 100);
+  let mut clock2 = HybridClock::new(ClockId { node_index: 1, sub_node_index: 0 }, 100);
+
+  // A retry Timestamp is greater than the one it replaces, and differs from every
+  // Timestamp that the other clock generates at the same physical time.
+  let t1 = clock1.now(&mut io_ctx);
+  let t2 = clock1.now_after(&mut io_ctx, &t1.add(mk_t(1)));
+  assert!(t1.add(mk_t(1)) < t2);
+  io_ctx.time_ms = t2.time_ms;
+  assert_ne!(t2, clock2.now(&mut io_ctx));
+  assert!(t2 < clock1.now(&mut io_ctx));
+
+  println!("Test 'now_after_test' Passed!");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  now_stale_test
+// -----------------------------------------------------------------------------------------------
+
+fn now_stale_test() {
+  let mut io_ctx = ClockIOCtx::new(10);
+  let mut clock = HybridClock::new(ClockId { node_index: 0, sub_node_index: 0 }, 100);
+
+  // Stale Timestamps are behind the physical time, and differ from the one that `now`
+  // generated at that time.
+  let t1 = clock.now(&mut io_ctx);
+  io_ctx.time_ms = 15;
+  let t2 = clock.now_stale(&mut io_ctx, 5);
+  assert_eq!(t2.time_ms, 10);
+  assert_ne!(t1, t2);
+
+  // They remain strictly increasing when the staleness reaches back past time 0.
+  let t3 = clock.now_stale(&mut io_ctx, 50);
+  io_ctx.time_ms = 20;
+  let t4 = clock.now_stale(&mut io_ctx, 50);
+  assert!(t2 < t3);
+  assert!(t3 < t4);
+
+  println!("Test 'now_stale_test' Passed!");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  skew_test
+// -----------------------------------------------------------------------------------------------
+
+fn skew_test() {
+  let mut io_ctx = ClockIOCtx::new(10);
+  let mut clock = HybridClock::new(ClockId { node_index: 0, sub_node_index: 0 }, 100);
+
+  // Timestamps that are too far ahead are not accepted, and are counted as skew.
+  let skew_before = metrics().counter(M_CLOCK_SKEW_DETECTED, vec![]);
+  clock.update(&mut io_ctx, &mk_t(1000));
+  assert!(metrics().counter(M_CLOCK_SKEW_DETECTED, vec![]) > skew_before);
+  assert!(clock.now(&mut io_ctx) < mk_t(1000));
+
+  println!("Test 'skew_test' Passed!");
+}
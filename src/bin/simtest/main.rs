@@ -2,6 +2,8 @@
 
 use crate::advanced_parallel_test::test_all_advanced_parallel;
 use crate::basic_serial_test::test_all_basic_serial;
+use crate::hybrid_clock_test::test_all_hybrid_clock;
+use crate::metrics_test::test_all_metrics;
 use crate::paxos_parallel_test::{
   test_all_basic_parallel, test_all_paxos_parallel, ParallelTestStats, Writer,
 };
@@ -29,6 +31,8 @@ macro_rules! cast {
 
 mod advanced_parallel_test;
 mod basic_serial_test;
+mod hybrid_clock_test;
+mod metrics_test;
 mod paxos_parallel_test;
 mod serial_test_utils;
 mod simulation;
@@ -65,6 +69,14 @@ fn main() {
   test_all_basic_serial(&mut rand);
   println!("\n");
 
+  println!("Hybrid Clock Tests:");
+  test_all_hybrid_clock();
+  println!("\n");
+
+  println!("Metrics Tests:");
+  test_all_metrics(&mut rand);
+  println!("\n");
+
   // Run parallel tests, potentially in multiple threads if requested.
   const DEFAULT_NUM_ROUNDS: u32 = 33;
   let rounds: u32 = if let Some(rounds) = matches.value_of("rounds") {
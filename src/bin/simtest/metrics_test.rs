@@ -0,0 +1,108 @@
+use crate::serial_test_utils::{
+  deprecated_populate_inventory_table_basic, deprecated_setup_inventory_table, setup,
+  simulate_until_clean,
+};
+use rand_xorshift::XorShiftRng;
+use runiversal::common::TablePath;
+use runiversal::metrics::{
+  metrics, Metrics, M_ACTIVE_ESS, M_MESSAGES_RECEIVED, M_PAXOS_PROPOSALS, M_TABLET_ROWS,
+  M_TRANSACTIONS_ABORTED, M_TRANSACTIONS_COMMITTED,
+};
+use runiversal::test_utils::mk_seed;
+
+/**
+ * This suite of tests checks the Metrics Registry, both directly and as it is updated
+ * by a running system.
+ */
+
+// -----------------------------------------------------------------------------------------------
+//  test_all_metrics
+// -----------------------------------------------------------------------------------------------
+
+pub fn test_all_metrics(rand: &mut XorShiftRng) {
+  render_test();
+  system_counters_test(mk_seed(rand));
+}
+
+// -----------------------------------------------------------------------------------------------
+//  render_test
+// -----------------------------------------------------------------------------------------------
+
+/// Checks the Prometheus text output of a registry whose metrics are set directly.
+fn render_test() {
+  let metrics = Metrics::new();
+  metrics.inc(M_PAXOS_PROPOSALS, vec![]);
+  metrics.inc(M_PAXOS_PROPOSALS, vec![]);
+  metrics.inc(M_TRANSACTIONS_ABORTED, vec![("reason", "WriteWriteConflict".to_string())]);
+  metrics.set(
+    M_ACTIVE_ESS,
+    vec![("group", "t\"0".to_string()), ("kind", "gr_query".to_string())],
+    3,
+  );
+  metrics.set(
+    M_ACTIVE_ESS,
+    vec![("group", "t\"0".to_string()), ("kind", "gr_query".to_string())],
+    2,
+  );
+
+  assert_eq!(metrics.counter(M_PAXOS_PROPOSALS, vec![]), 2);
+  assert_eq!(metrics.counter(M_TRANSACTIONS_COMMITTED, vec![]), 0);
+
+  let rendered = metrics.render();
+  let lines: Vec<&str> = rendered.lines().collect();
+  assert!(lines.contains(&"# TYPE runiversal_paxos_proposals_total counter"));
+  assert!(lines.contains(&"runiversal_paxos_proposals_total 2"));
+  assert!(lines.contains(&"runiversal_transactions_aborted_total{reason=\"WriteWriteConflict\"} 1"));
+  assert!(lines.contains(&"# TYPE runiversal_active_ess gauge"));
+  assert!(lines.contains(&"runiversal_active_ess{group=\"t\\\"0\",kind=\"gr_query\"} 2"));
+  // Metrics without any series are still described.
+  assert!(lines.contains(&"# TYPE runiversal_transactions_committed_total counter"));
+  assert!(!lines.iter().any(|line| line.starts_with("runiversal_transactions_committed_total")));
+
+  println!("Test 'render_test' Passed!");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  system_counters_test
+// -----------------------------------------------------------------------------------------------
+
+/// Checks that the global registry is updated as a `Simulation` runs queries. Since the
+/// global registry is shared by all tests, we only compare counters against their prior values.
+fn system_counters_test(seed: [u8; 16]) {
+  let committed_before = metrics().counter(M_TRANSACTIONS_COMMITTED, vec![]);
+  let external_reqs_label = || vec![("type", "slave_external_req".to_string())];
+  let external_reqs_before = metrics().counter(M_MESSAGES_RECEIVED, external_reqs_label());
+  let proposals_before = metrics().counter(M_PAXOS_PROPOSALS, vec![]);
+
+  let (mut sim, mut ctx) = setup(seed);
+  deprecated_setup_inventory_table(&mut sim, &mut ctx);
+  deprecated_populate_inventory_table_basic(&mut sim, &mut ctx);
+
+  assert!(metrics().counter(M_TRANSACTIONS_COMMITTED, vec![]) > committed_before);
+  assert!(metrics().counter(M_MESSAGES_RECEIVED, external_reqs_label()) > external_reqs_before);
+  assert!(metrics().counter(M_PAXOS_PROPOSALS, vec![]) > proposals_before);
+
+  // Once the Tablet has committed the INSERT, it should report both rows.
+  assert!(simulate_until_clean(&mut sim, 10000));
+  let full_db_schema = sim.full_db_schema();
+  let table_path = TablePath("inventory".to_string());
+  let timestamp = full_db_schema.table_generation.get_lat(&table_path);
+  let full_gen = full_db_schema.table_generation.static_read(&table_path, &timestamp).unwrap();
+  let shards = full_db_schema.sharding_config.get(&(table_path, full_gen.clone())).unwrap();
+  let (_, tid) = shards.first().unwrap();
+  assert_eq!(metrics().gauge(M_TABLET_ROWS, vec![("tablet", tid.0.clone())]), 2);
+
+  // The same values should appear in the Prometheus text output.
+  let rendered = metrics().render();
+  let lines: Vec<&str> = rendered.lines().collect();
+  let rows_line = format!("runiversal_tablet_rows{{tablet=\"{}\"}} 2", tid.0);
+  assert!(lines.contains(&rows_line.as_str()), "{}", rendered);
+  let committed = metrics().counter(M_TRANSACTIONS_COMMITTED, vec![]);
+  let committed_line = format!("runiversal_transactions_committed_total {}", committed);
+  assert!(lines.contains(&committed_line.as_str()), "{}", rendered);
+
+  println!(
+    "Test 'system_counters_test' Passed! Time taken: {:?}ms",
+    sim.true_timestamp().time_ms
+  )
+}
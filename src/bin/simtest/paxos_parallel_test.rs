@@ -676,12 +676,16 @@ impl<'a> QueryGenCtx<'a> {
           _ => panic!(),
         };
         (
-          TabletKeyRange { start: range.start.clone(), end: Some(ColVal::Int(mid.clone())) },
-          TabletKeyRange { start: Some(ColVal::Int(mid)), end: range.end.clone() },
+          TabletKeyRange {
+            start: range.start.clone(),
+            end: Some(ColVal::Int(mid.clone())),
+            hash: None,
+          },
+          TabletKeyRange { start: Some(ColVal::Int(mid)), end: range.end.clone(), hash: None },
         )
       }
-      // We do not attempt to shard a Tablet that start with a boolean key.
-      ColType::Bool => return None,
+      // We do not attempt to shard a Tablet that start with a boolean or big integer key.
+      ColType::Bool | ColType::BigInt => return None,
       ColType::String => {
         let (start_idx, end_idx) = match (&range.start, &range.end) {
           (Some(ColVal::String(start)), Some(ColVal::String(end))) => {
@@ -721,8 +725,12 @@ impl<'a> QueryGenCtx<'a> {
         };
 
         (
-          TabletKeyRange { start: range.start.clone(), end: Some(ColVal::String(mid.clone())) },
-          TabletKeyRange { start: Some(ColVal::String(mid)), end: range.end.clone() },
+          TabletKeyRange {
+            start: range.start.clone(),
+            end: Some(ColVal::String(mid.clone())),
+            hash: None,
+          },
+          TabletKeyRange { start: Some(ColVal::String(mid)), end: range.end.clone(), hash: None },
         )
       }
     };
@@ -1149,7 +1157,7 @@ pub fn parallel_test<WriterT: Writer>(
   w: &mut WriterT,
 ) -> Option<Stats> {
   w.println(format!("seed: {:?}", seed));
-  let mut sim = mk_general_sim(seed, 3, 5, num_paxos_nodes, 100, num_reconfig_free_nodes);
+  let mut sim = mk_general_sim(seed, 3, 5, num_paxos_nodes, num_reconfig_free_nodes);
 
   // Run the simulation
   let client_eids: Vec<_> = sim.get_all_responses().keys().cloned().collect();
@@ -1319,6 +1327,11 @@ pub fn parallel_test<WriterT: Writer>(
             sender_eid: client_eid.clone(),
             request_id: request_id.clone(),
             query,
+            params: vec![],
+            opt_timeout_ms: None,
+            opt_max_retries: None,
+            priority: msg::TransactionPriority::Normal,
+            opt_max_staleness_ms: None,
           };
           req_map.get_mut(client_eid).unwrap().insert(
             request_id.clone(),
@@ -1406,7 +1419,10 @@ pub fn parallel_test<WriterT: Writer>(
           msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => &aborted.request_id,
           msg::ExternalMessage::ExternalShardingSuccess(success) => &success.request_id,
           msg::ExternalMessage::ExternalShardingAborted(aborted) => &aborted.request_id,
-          msg::ExternalMessage::ExternalMetadataResponse(_) => panic!(),
+          msg::ExternalMessage::ExternalMetadataResponse(_)
+          | msg::ExternalMessage::ExternalShowQueriesResponse(_)
+          | msg::ExternalMessage::ExternalKillQueryResponse(_)
+          | msg::ExternalMessage::ExternalTransferLeadershipResponse(_) => panic!(),
         };
 
         let req = req_map.get_mut(&eid).unwrap().remove(request_id).unwrap();
@@ -1448,7 +1464,10 @@ pub fn parallel_test<WriterT: Writer>(
             msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => &aborted.request_id,
             msg::ExternalMessage::ExternalShardingSuccess(success) => &success.request_id,
             msg::ExternalMessage::ExternalShardingAborted(aborted) => &aborted.request_id,
-            msg::ExternalMessage::ExternalMetadataResponse(_) => panic!(),
+            msg::ExternalMessage::ExternalMetadataResponse(_)
+            | msg::ExternalMessage::ExternalShowQueriesResponse(_)
+            | msg::ExternalMessage::ExternalKillQueryResponse(_)
+            | msg::ExternalMessage::ExternalTransferLeadershipResponse(_) => panic!(),
           };
 
           let req = req_map.get_mut(&eid).unwrap().remove(request_id).unwrap();
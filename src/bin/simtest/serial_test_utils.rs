@@ -10,6 +10,7 @@ use runiversal::node::NodeConfig;
 use runiversal::paxos::PaxosConfig;
 use runiversal::simulation_utils::{mk_client_eid, mk_node_eid};
 use runiversal::slave::SlaveConfig;
+use runiversal::sql_ast::iast;
 use runiversal::tablet::TabletConfig;
 use runiversal::test_utils::{cno, cvi, cvs, mk_sid};
 use std::collections::BTreeMap;
@@ -97,8 +98,135 @@ impl TestContext {
     }
   }
 
+  /// Sends the DDL `query` using `sim` with a time limit of `time_limit`. Here,
+  /// we expect it to fail.
+  pub fn send_ddl_query_failure<PredT: Fn(&msg::ExternalDDLQueryAbortData) -> bool>(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    time_limit: u32,
+    abort_check: PredT,
+  ) {
+    let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
+    self.next_request_idx += 1;
+    sim.add_msg(
+      msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+        msg::MasterExternalReq::PerformExternalDDLQuery(msg::PerformExternalDDLQuery {
+          sender_eid: self.sender_eid.clone(),
+          request_id: request_id.clone(),
+          query: query.to_string(),
+        }),
+      )),
+      &self.sender_eid,
+      &self.master_eid,
+    );
+
+    assert!(self.simulate_until_response(sim, time_limit));
+    let response = self.next_response(sim);
+    match response {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalDDLQueryAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        if !abort_check(&payload.payload) {
+          panic!("Incorrect error payload: {:#?}", payload);
+        }
+      }
+      _ => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
+  /// Sends the sharding `op` using `sim` with a time limit of `time_limit`. Here,
+  /// we expect it to fail.
+  pub fn send_sharding_failure<PredT: Fn(&msg::ExternalShardingAbortData) -> bool>(
+    &mut self,
+    sim: &mut Simulation,
+    op: msg::ShardingOp,
+    time_limit: u32,
+    abort_check: PredT,
+  ) {
+    let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
+    self.next_request_idx += 1;
+    sim.add_msg(
+      msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+        msg::MasterExternalReq::PerformExternalSharding(msg::PerformExternalSharding {
+          sender_eid: self.sender_eid.clone(),
+          request_id: request_id.clone(),
+          op,
+        }),
+      )),
+      &self.sender_eid,
+      &self.master_eid,
+    );
+
+    assert!(self.simulate_until_response(sim, time_limit));
+    let response = self.next_response(sim);
+    match response {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalShardingAborted(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        if !abort_check(&payload.payload) {
+          panic!("Incorrect error payload: {:#?}", payload);
+        }
+      }
+      _ => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
   /// Enque `query` into `sim` and return the `RequestId` that was used for it.
   pub fn send_query(&mut self, sim: &mut Simulation, query: &str) -> RequestId {
+    self.send_query_with_params(sim, query, vec![])
+  }
+
+  /// Same as above, except `params` are bound to the `$n` parameters of `query`.
+  pub fn send_query_with_params(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    params: Vec<iast::Value>,
+  ) -> RequestId {
+    self.send_perform_query(sim, query, params, None, msg::TransactionPriority::Normal, None)
+  }
+
+  /// Same as `send_query`, except the query is aborted if it takes longer than `timeout_ms`.
+  pub fn send_query_with_timeout(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    timeout_ms: u128,
+  ) -> RequestId {
+    let priority = msg::TransactionPriority::Normal;
+    self.send_perform_query(sim, query, vec![], Some(timeout_ms), priority, None)
+  }
+
+  /// Same as `send_query`, except the query is run at the given `priority`.
+  pub fn send_query_with_priority(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    priority: msg::TransactionPriority,
+  ) -> RequestId {
+    self.send_perform_query(sim, query, vec![], None, priority, None)
+  }
+
+  /// Same as `send_query`, except the query is a bounded staleness read that may be
+  /// served by a Follower, reading data up to `max_staleness_ms` old.
+  pub fn send_query_with_staleness(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    max_staleness_ms: u128,
+  ) -> RequestId {
+    let priority = msg::TransactionPriority::Normal;
+    self.send_perform_query(sim, query, vec![], None, priority, Some(max_staleness_ms))
+  }
+
+  fn send_perform_query(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    params: Vec<iast::Value>,
+    opt_timeout_ms: Option<u128>,
+    priority: msg::TransactionPriority,
+    opt_max_staleness_ms: Option<u128>,
+  ) -> RequestId {
     let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
     self.next_request_idx += 1;
     sim.add_msg(
@@ -107,6 +235,11 @@ impl TestContext {
           sender_eid: self.sender_eid.clone(),
           request_id: request_id.clone(),
           query: query.to_string(),
+          params,
+          opt_timeout_ms,
+          opt_max_retries: None,
+          priority,
+          opt_max_staleness_ms,
         }),
       )),
       &self.sender_eid,
@@ -129,6 +262,75 @@ impl TestContext {
     );
   }
 
+  /// Send a `SHOW QUERIES` request and return the `RequestId` that was used for it.
+  pub fn send_show_queries(&mut self, sim: &mut Simulation) -> RequestId {
+    let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
+    self.next_request_idx += 1;
+    sim.add_msg(
+      msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+        msg::SlaveExternalReq::ExternalShowQueries(msg::ExternalShowQueries {
+          sender_eid: self.sender_eid.clone(),
+          request_id: request_id.clone(),
+        }),
+      )),
+      &self.sender_eid,
+      &self.slave_eid,
+    );
+    request_id
+  }
+
+  /// Send a `KILL QUERY` request for `target_request_id` and return the `RequestId` that
+  /// was used for it.
+  pub fn send_kill_query(
+    &mut self,
+    sim: &mut Simulation,
+    target_request_id: RequestId,
+  ) -> RequestId {
+    let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
+    self.next_request_idx += 1;
+    sim.add_msg(
+      msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+        msg::SlaveExternalReq::ExternalKillQuery(msg::ExternalKillQuery {
+          sender_eid: self.sender_eid.clone(),
+          request_id: request_id.clone(),
+          target_request_id,
+        }),
+      )),
+      &self.sender_eid,
+      &self.slave_eid,
+    );
+    request_id
+  }
+
+  /// Send a request to `to_eid` to transfer the Leadership of `gid` to `target_eid`, and
+  /// return the `RequestId` that was used for it.
+  pub fn send_transfer_leadership(
+    &mut self,
+    sim: &mut Simulation,
+    gid: PaxosGroupId,
+    target_eid: EndpointId,
+    to_eid: &EndpointId,
+  ) -> RequestId {
+    let request_id = RequestId(format!("rid{:?}", self.next_request_idx));
+    self.next_request_idx += 1;
+    let transfer = msg::ExternalTransferLeadership {
+      sender_eid: self.sender_eid.clone(),
+      request_id: request_id.clone(),
+      gid: gid.clone(),
+      target_eid,
+    };
+    let message = match gid {
+      PaxosGroupId::Master => msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+        msg::MasterExternalReq::ExternalTransferLeadership(transfer),
+      )),
+      PaxosGroupId::Slave(_) => msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+        msg::SlaveExternalReq::ExternalTransferLeadership(transfer),
+      )),
+    };
+    sim.add_msg(message, &self.sender_eid, to_eid);
+    request_id
+  }
+
   /// Executes the `query` using `sim` with a time limit of `time_limit`. If the query
   /// finishes, we check that it succeeded and that the resulting `ResultView` is the same
   /// as `expr_result`.
@@ -155,6 +357,31 @@ impl TestContext {
     }
   }
 
+  /// Same as above, except `params` are bound to the `$n` parameters of `query`.
+  pub fn execute_query_with_params(
+    &mut self,
+    sim: &mut Simulation,
+    query: &str,
+    params: Vec<iast::Value>,
+    time_limit: u32,
+    exp_result: QueryResult,
+  ) {
+    let request_id = self.send_query_with_params(sim, query, params);
+    assert!(self.simulate_until_response(sim, time_limit));
+    let response = self.next_response(sim);
+    match response {
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(payload)) => {
+        assert_eq!(payload.request_id, request_id);
+        if payload.result != exp_result {
+          println!("{:#?}", query);
+          println!("{:#?}", request_id);
+          assert_eq!(payload.result, exp_result);
+        }
+      }
+      _ => panic!("Incorrect Response: {:#?}", response),
+    }
+  }
+
   /// Same as above, except we do not check the returned resulting `ResultView`.
   pub fn execute_query_simple(&mut self, sim: &mut Simulation, query: &str, time_limit: u32) {
     let request_id = self.send_query(sim, query);
@@ -246,7 +473,7 @@ pub fn simulate_until_clean(sim: &mut Simulation, time_limit: u32) -> bool {
 
 /// Simple common setup with a PaxosGroup size of 1.
 pub fn setup(seed: [u8; 16]) -> (Simulation, TestContext) {
-  let sim = mk_general_sim(seed, 1, 5, 1, 1, 0);
+  let sim = mk_general_sim(seed, 1, 5, 1, 0);
   let context = TestContext::new(&sim);
   (sim, context)
 }
@@ -354,7 +581,10 @@ pub fn populate_product_stock_table_basic(sim: &mut Simulation, context: &mut Te
 // -----------------------------------------------------------------------------------------------
 
 /// Build the `NodeConfig` we should use for testing
-fn get_test_configs(num_paxos_groups: u32, timestamp_suffix_divisor: u64) -> NodeConfig {
+fn get_test_configs(num_paxos_groups: u32) -> NodeConfig {
+  // All nodes share the same clock in the simulation, so no skew should be detected.
+  let max_clock_offset_ms = 5;
+
   let paxos_config = PaxosConfig {
     heartbeat_threshold: 3,
     heartbeat_period_ms: mk_t(5),
@@ -363,6 +593,8 @@ fn get_test_configs(num_paxos_groups: u32, timestamp_suffix_divisor: u64) -> Nod
     proposal_increment: 1000,
     remote_next_index_thresh: 5,
     max_failable: 1,
+    max_in_flight: 4,
+    max_batch_size: 8,
   };
 
   let free_node_heartbeat_timer_ms = 5;
@@ -370,7 +602,7 @@ fn get_test_configs(num_paxos_groups: u32, timestamp_suffix_divisor: u64) -> Nod
   let failure_detector_period_ms = 5;
   let check_unconfirmed_eids_period_ms = 15;
   let master_config = MasterConfig {
-    timestamp_suffix_divisor,
+    max_clock_offset_ms,
     slave_group_size: num_paxos_groups,
     remote_leader_changed_period_ms,
     failure_detector_period_ms,
@@ -379,15 +611,28 @@ fn get_test_configs(num_paxos_groups: u32, timestamp_suffix_divisor: u64) -> Nod
     num_coords: 3,
     free_node_heartbeat_timer_ms,
   };
+  let closed_timestamp_lag_ms = 100;
+  let follower_read_wait_ms = 200;
   let slave_config = SlaveConfig {
-    timestamp_suffix_divisor,
+    max_clock_offset_ms,
+    closed_timestamp_lag_ms,
+    follower_read_wait_ms,
     remote_leader_changed_period_ms,
     failure_detector_period_ms,
     check_unconfirmed_eids_period_ms,
   };
 
-  let coord_config = CoordConfig { timestamp_suffix_divisor };
-  let tablet_config = TabletConfig { timestamp_suffix_divisor };
+  // Every request is reported as a `SlowQuery` so that tests can inspect the slow query log.
+  let coord_config = CoordConfig {
+    max_clock_offset_ms,
+    slow_query_threshold_ms: Some(0),
+    max_retries: 20,
+    retry_backoff_ms: 1,
+    max_retry_backoff_ms: 100,
+    follower_read_timeout_ms: 300,
+  };
+  let tablet_config =
+    TabletConfig { max_clock_offset_ms, closed_timestamp_lag_ms, follower_read_wait_ms };
 
   // Combine the above
   NodeConfig {
@@ -407,12 +652,11 @@ pub fn mk_general_sim(
   num_clients: u32,
   num_slave_groups: u32,
   num_paxos_nodes: u32,
-  timestamp_suffix_divisor: u64,
   num_reconfig_free_nodes: u32,
 ) -> Simulation {
   // Create the sim
   let num_count = (num_slave_groups + 1) * num_paxos_nodes + num_reconfig_free_nodes;
-  let node_config = get_test_configs(num_paxos_nodes, timestamp_suffix_divisor);
+  let node_config = get_test_configs(num_paxos_nodes);
   let mut sim = Simulation::new(seed, num_clients, num_count, node_config);
 
   // Construct the Master PaxosGroup to initiate the system.
@@ -7,11 +7,11 @@ use runiversal::common::{
   SlaveTraceMessage, Timestamp,
 };
 use runiversal::common::{
-  CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, QueryId, RequestId,
+  ClockId, CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, QueryId, RequestId,
   SlaveGroupId, TablePath, TabletGroupId, TabletKeyRange,
 };
 use runiversal::coord::coord_test::{assert_coord_consistency, check_coord_clean};
-use runiversal::coord::{CoordConfig, CoordContext, CoordForwardMsg, CoordState};
+use runiversal::coord::{CoordConfig, CoordContext, CoordForwardMsg, CoordState, SlowQuery};
 use runiversal::free_node_manager::FreeNodeType;
 use runiversal::master::master_test::check_master_clean;
 use runiversal::master::{
@@ -32,6 +32,7 @@ use runiversal::tablet::{
   TabletConfig, TabletContext, TabletForwardMsg, TabletSnapshot, TabletState,
 };
 use runiversal::test_utils::CheckCtx;
+use runiversal::trace::Span;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
@@ -98,12 +99,13 @@ impl<'a> FreeNodeIOCtx for TestIOCtx<'a> {
     gossip: Arc<GossipData>,
     snapshot: TabletSnapshot,
     this_eid: EndpointId,
+    clock_id: ClockId,
     tablet_config: TabletConfig,
   ) {
     let tid = snapshot.this_tid.clone();
     self
       .tablet_states
-      .insert(tid, TabletState::create_reconfig(gossip, snapshot, this_eid, tablet_config));
+      .insert(tid, TabletState::create_reconfig(gossip, snapshot, this_eid, clock_id, tablet_config));
   }
 
   fn create_coord_full(&mut self, ctx: CoordContext) {
@@ -313,6 +315,12 @@ struct Tracer {
   accounted_new_nodes: BTreeSet<Vec<EndpointId>>,
   master_reconfig_count: u64,
   slave_reconfig_count: u64,
+
+  // Distributed tracing
+  spans: Vec<Span>,
+
+  // Slow query log
+  slow_queries: Vec<SlowQuery>,
 }
 
 impl Tracer {
@@ -322,6 +330,8 @@ impl Tracer {
       accounted_new_nodes: Default::default(),
       master_reconfig_count: 0,
       slave_reconfig_count: 0,
+      spans: Vec::new(),
+      slow_queries: Vec::new(),
     }
   }
 
@@ -342,6 +352,12 @@ impl Tracer {
           }
         }
       }
+      GeneralTraceMessage::Span(span) => {
+        self.spans.push(span);
+      }
+      GeneralTraceMessage::SlowQuery(slow_query) => {
+        self.slow_queries.push(slow_query);
+      }
     }
   }
 }
@@ -548,6 +564,16 @@ impl Simulation {
     (self.tracer.master_reconfig_count, self.tracer.slave_reconfig_count)
   }
 
+  /// Returns all `Span`s of distributed traces that have finished so far.
+  pub fn get_spans(&self) -> &Vec<Span> {
+    &self.tracer.spans
+  }
+
+  /// Returns all `SlowQuery`s that have been reported so far.
+  pub fn get_slow_queries(&self) -> &Vec<SlowQuery> {
+    &self.tracer.slow_queries
+  }
+
   pub fn get_stats(&self) -> &Stats {
     &self.stats
   }
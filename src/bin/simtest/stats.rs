@@ -187,6 +187,9 @@ impl Stats {
         ExternalMessage::ExternalShardingSuccess(_) => K_EXTERNAL_SHARDING_SUCCESS,
         ExternalMessage::ExternalShardingAborted(_) => K_EXTERNAL_SHARDING_ABORTED,
         ExternalMessage::ExternalMetadataResponse(_) => K_UNNACCOUNTED,
+        ExternalMessage::ExternalShowQueriesResponse(_) => K_UNNACCOUNTED,
+        ExternalMessage::ExternalKillQueryResponse(_) => K_UNNACCOUNTED,
+        ExternalMessage::ExternalTransferLeadershipResponse(_) => K_UNNACCOUNTED,
       },
       NetworkMessage::Master(m) => match m {
         MasterMessage::MasterExternalReq(m) => match m {
@@ -195,6 +198,7 @@ impl Stats {
           MasterExternalReq::ExternalMetadataRequest(_) => K_UNNACCOUNTED,
           MasterExternalReq::PerformExternalSharding(_) => K_PERFORM_EXTERNAL_SHARDING,
           MasterExternalReq::CancelExternalSharding(_) => K_CANCEL_EXTERNAL_SHARDING,
+          MasterExternalReq::ExternalTransferLeadership(_) => K_UNNACCOUNTED,
         },
         MasterMessage::RemoteMessage(m) => match m {
           RemoteMessage { payload: m, .. } => match m {
@@ -214,6 +218,9 @@ impl Stats {
         MasterMessage::PaxosDriverMessage(m) => match m {
           PaxosDriverMessage::MultiPaxosMessage(_) => K_MASTER_MULTI_PAXOS_MESSAGE,
           PaxosDriverMessage::IsLeader(_) => K_MASTER_IS_LEADER,
+          PaxosDriverMessage::IsLeaderAck(_) => K_MASTER_IS_LEADER,
+          PaxosDriverMessage::PreVote(_) => K_MASTER_MULTI_PAXOS_MESSAGE,
+          PaxosDriverMessage::PreVoteGrant(_) => K_MASTER_MULTI_PAXOS_MESSAGE,
           PaxosDriverMessage::LogSyncRequest(_) => K_MASTER_LOG_SYNC_REQUEST,
           PaxosDriverMessage::LogSyncResponse(_) => K_MASTER_LOG_SYNC_RESPONSE,
           PaxosDriverMessage::NextIndexRequest(_) => K_MASTER_NEXT_INDEX_REQUEST,
@@ -232,6 +239,9 @@ impl Stats {
         SlaveMessage::SlaveExternalReq(m) => match m {
           SlaveExternalReq::PerformExternalQuery(_) => K_PERFORM_EXTERNAL_QUERY,
           SlaveExternalReq::CancelExternalQuery(_) => K_CANCEL_EXTERNAL_QUERY,
+          SlaveExternalReq::ExternalShowQueries(_) => K_UNNACCOUNTED,
+          SlaveExternalReq::ExternalKillQuery(_) => K_UNNACCOUNTED,
+          SlaveExternalReq::ExternalTransferLeadership(_) => K_UNNACCOUNTED,
         },
         SlaveMessage::RemoteMessage(m) => match m {
           RemoteMessage { payload: m, .. } => match m {
@@ -267,6 +277,9 @@ impl Stats {
         SlaveMessage::PaxosDriverMessage(m) => match m {
           PaxosDriverMessage::MultiPaxosMessage(_) => K_SLAVE_MULTI_PAXOS_MESSAGE,
           PaxosDriverMessage::IsLeader(_) => K_SLAVE_IS_LEADER,
+          PaxosDriverMessage::IsLeaderAck(_) => K_SLAVE_IS_LEADER,
+          PaxosDriverMessage::PreVote(_) => K_SLAVE_MULTI_PAXOS_MESSAGE,
+          PaxosDriverMessage::PreVoteGrant(_) => K_SLAVE_MULTI_PAXOS_MESSAGE,
           PaxosDriverMessage::LogSyncRequest(_) => K_SLAVE_LOG_SYNC_REQUEST,
           PaxosDriverMessage::LogSyncResponse(_) => K_SLAVE_LOG_SYNC_RESPONSE,
           PaxosDriverMessage::NextIndexRequest(_) => K_SLAVE_NEXT_INDEX_REQUEST,
@@ -275,6 +288,8 @@ impl Stats {
           PaxosDriverMessage::NewNodeStarted(_) => K_SLAVE_NEW_NODE_STARTED,
           PaxosDriverMessage::StartNewNode(_) => K_SLAVE_START_NEW_NODE,
         },
+        SlaveMessage::FollowerRead(_) => K_TABLET_PERFORM,
+        SlaveMessage::FollowerReadResponse(_) => K_COORD_PCSA,
       },
       NetworkMessage::FreeNode(m) => match m {
         FreeNodeMessage::StartMaster(_) => K_UNNACCOUNTED,
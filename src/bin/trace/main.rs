@@ -0,0 +1,62 @@
+use clap::{arg, App};
+use runiversal::common::RequestId;
+use runiversal::trace::{stitch_timeline, Span};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// Combines the trace logs written by the nodes (see the `--trace_log` option of `transact`)
+/// into a timeline of a single External request. If no request is specified, this lists the
+/// requests that were traced instead.
+fn main() {
+  // Setup CLI parsing
+  let matches = App::new("rUniversalDB Trace")
+    .version("1.0")
+    .author("Pasindu M. <pasindumuth@gmail.com>")
+    .arg(
+      arg!(-r --request_id <VALUE>)
+        .required(false)
+        .help("The RequestId of the External request to show the timeline of."),
+    )
+    .arg(arg!(<LOGS>).multiple_values(true).help("The trace logs of all nodes."))
+    .get_matches();
+
+  // Read in the Spans from all trace logs.
+  let mut spans = Vec::<Span>::new();
+  for path in matches.values_of("LOGS").unwrap() {
+    let file = File::open(path).unwrap_or_else(|err| panic!("Cannot open {}: {}", path, err));
+    for line in BufReader::new(file).lines() {
+      let line = line.unwrap();
+      match Span::parse_log_line(&line) {
+        Some(span) => spans.push(span),
+        None => eprintln!("Skipping malformed line in {}: {}", path, line),
+      }
+    }
+  }
+
+  match matches.value_of("request_id") {
+    Some(request_id) => {
+      let request_id = RequestId(request_id.to_string());
+      match stitch_timeline(&spans, &request_id) {
+        Some(lines) => {
+          for line in lines {
+            println!("{}", line);
+          }
+        }
+        None => eprintln!("No trace was found for Request {}.", request_id.0),
+      }
+    }
+    None => {
+      // List the root span of every traced request.
+      let mut roots: Vec<&Span> = spans.iter().filter(|span| span.request_id.is_some()).collect();
+      roots.sort_by_key(|span| span.start_ms);
+      for root in roots {
+        println!(
+          "Request {} at {}: {} ms",
+          root.request_id.as_ref().unwrap().0,
+          root.node,
+          root.end_ms.saturating_sub(root.start_ms)
+        );
+      }
+    }
+  }
+}
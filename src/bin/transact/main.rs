@@ -1,10 +1,14 @@
 #![feature(map_first_last)]
 
+mod metrics_http;
+mod pgwire;
 mod server;
 
 #[macro_use]
 extern crate runiversal;
 
+use crate::metrics_http::{mk_metrics_address, start_metrics_thread};
+use crate::pgwire::{mk_pg_address, start_pg_acceptor_thread, PgContext, PgRouter};
 use crate::server::{ProdCoreIOCtx, ProdIOCtx, TIMER_INCREMENT};
 use clap::{arg, App};
 use env_logger::Builder;
@@ -22,7 +26,7 @@ use runiversal::master::{
 };
 use runiversal::message as msg;
 use runiversal::message::FreeNodeMessage;
-use runiversal::net::{handle_self_conn, send_msg, start_acceptor_thread, SendAction};
+use runiversal::net::{handle_self_conn, mk_address, send_msg, start_acceptor_thread, SendAction};
 use runiversal::node::{get_prod_configs, GenericInput, NodeConfig, NodeState};
 use runiversal::paxos::PaxosConfig;
 use runiversal::slave::{
@@ -33,6 +37,7 @@ use runiversal::test_utils as tu;
 use runiversal::test_utils::mk_seed;
 use std::collections::{BTreeMap, LinkedList};
 use std::env;
+use std::fs::OpenOptions;
 use std::net::{TcpListener, TcpStream};
 use std::sync::mpsc::Sender;
 use std::sync::{mpsc, Arc, Mutex};
@@ -54,7 +59,10 @@ fn main() {
         .help("Indicates if this is an initial Master node ('masterbootup') or not ('freenode').'")
         .possible_values(["masterbootup", "freenode"]),
     )
-    .arg(arg!(-i --ip <VALUE>).required(true).help("The IP address of the current host."))
+    .arg(arg!(-i --ip <VALUE>).required(true).help(
+      "The address (IP, optionally followed by ':<port>') of the current host. \
+       The port defaults to 1610.",
+    ))
     .arg(
       arg!(-f --freenode_type <VALUE>)
         .required(false)
@@ -62,8 +70,29 @@ fn main() {
         .possible_values(["newslave", "reconfig"]),
     )
     .arg(arg!(-e --entry_ip <VALUE>).required(false).help(
-      "The IP address of the current Master \
-       Leader. (This is unused if the startup_type is 'masterbootup').",
+      "The address of the current Master Leader, in the same format as '--ip'. \
+       (This is unused if the startup_type is 'masterbootup').",
+    ))
+    .arg(arg!(-p --pg_address <VALUE>).required(false).help(
+      "If specified, accept PostgreSQL wire-protocol connections at this address. \
+       The port defaults to 5432.",
+    ))
+    .arg(arg!(-m --metrics_address <VALUE>).required(false).help(
+      "If specified, serve metrics in the Prometheus text format at 'http://<address>/metrics'. \
+       The port defaults to 9100.",
+    ))
+    .arg(arg!(--trace_log <PATH>).required(false).help(
+      "If specified, append the spans of the distributed traces of queries to this file. \
+       Use the 'trace' tool to combine the trace logs of all nodes into a timeline.",
+    ))
+    .arg(
+      arg!(--slow_query_log <PATH>).required(false).help(
+        "If specified, append the queries that take at least '--slow_query_ms' to this file.",
+      ),
+    )
+    .arg(arg!(--slow_query_ms <MS>).required(false).help(
+      "The duration (in milliseconds) at which a query is considered slow. \
+       This defaults to the value in the production config.",
     ))
     .get_matches();
 
@@ -72,7 +101,7 @@ fn main() {
 
   // Get required arguments
   let startup_type = matches.value_of("startup_type").unwrap().to_string();
-  let this_ip = matches.value_of("ip").unwrap().to_string();
+  let this_address = mk_address(matches.value_of("ip").unwrap());
 
   // The mpsc channel for passing data to the Server Thread from all FromNetwork Threads.
   let (to_server_sender, to_server_receiver) = mpsc::channel::<GenericInput>();
@@ -80,23 +109,22 @@ fn main() {
   let out_conn_map = Arc::new(Mutex::new(BTreeMap::<EndpointId, Sender<SendAction>>::new()));
 
   // Start the Accepting Thread
-  start_acceptor_thread(&to_server_sender, this_ip.clone());
+  start_acceptor_thread(&to_server_sender, this_address.clone());
 
   // Create the self-connection
   let this_internal_mode = InternalMode::Internal;
-  let this_eid = EndpointId::new(this_ip, this_internal_mode.clone());
+  let this_eid = EndpointId::new(this_address, this_internal_mode.clone());
   handle_self_conn(&this_eid, &out_conn_map, &to_server_sender);
 
-  // Run startup_type specific code.
-  match &startup_type[..] {
-    "masterbootup" => {}
+  // Run startup_type specific code. This returns a member of the Master Group.
+  let entry_master_eid = match &startup_type[..] {
+    "masterbootup" => this_eid.clone(),
     "freenode" => {
       // Parse entry_ip
-      let master_ip = matches
-        .value_of("entry_ip")
-        .expect("entry_ip is requred if startup_type is 'freenode'")
-        .to_string();
-      let master_eid = EndpointId::new(master_ip, InternalMode::Internal);
+      let master_address = mk_address(
+        matches.value_of("entry_ip").expect("entry_ip is requred if startup_type is 'freenode'"),
+      );
+      let master_eid = EndpointId::new(master_address, InternalMode::Internal);
 
       // Parse freenode_type
       let freenode_type = matches
@@ -122,29 +150,75 @@ fn main() {
           )),
           None,
         ),
-        &this_internal_mode,
+        &this_eid,
       );
+      master_eid
     }
     _ => unreachable!(),
+  };
+
+  // Start the PostgreSQL frontend, if requested.
+  let opt_pg_router = matches.value_of("pg_address").map(|pg_address| {
+    let router = PgRouter::new();
+    start_pg_acceptor_thread(
+      mk_pg_address(pg_address),
+      PgContext {
+        this_eid: this_eid.clone(),
+        out_conn_map: out_conn_map.clone(),
+        master_eid: entry_master_eid,
+        router: router.clone(),
+      },
+    );
+    router
+  });
+
+  // Start the Metrics endpoint, if requested.
+  if let Some(metrics_address) = matches.value_of("metrics_address") {
+    start_metrics_thread(mk_metrics_address(metrics_address));
   }
 
+  // Open the trace log and slow query log, if requested.
+  let open_log = |path: &str| {
+    let file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+    Arc::new(Mutex::new(file))
+  };
+  let trace_log = matches.value_of("trace_log").map(open_log);
+  let slow_query_log = matches.value_of("slow_query_log").map(open_log);
+
   let mut io_ctx = ProdIOCtx {
     rand: XorShiftRng::from_entropy(),
+    this_eid: this_eid.clone(),
     out_conn_map,
     exited: false,
     to_top: to_server_sender,
     tablet_map: Default::default(),
     coord_map: Default::default(),
     tasks: Arc::new(Mutex::new(Default::default())),
+    trace_log,
+    slow_query_log,
   };
   io_ctx.start();
 
-  let mut node = NodeState::new(this_eid, get_prod_configs());
+  let mut node_config = get_prod_configs();
+  if let Some(slow_query_ms) = matches.value_of("slow_query_ms") {
+    let slow_query_ms = slow_query_ms.parse().expect("slow_query_ms must be a number");
+    node_config.coord_config.slow_query_threshold_ms = Some(slow_query_ms);
+  }
+
+  let mut node = NodeState::new(this_eid, node_config);
   node.bootstrap(&mut io_ctx);
 
   // Enter the main loop forever.
   loop {
     let generic_input = to_server_receiver.recv().unwrap();
-    node.process_input(&mut io_ctx, generic_input);
+    match generic_input {
+      // These are responses to the queries sent by the PostgreSQL frontend.
+      GenericInput::Message(_, msg::NetworkMessage::External(external_msg)) => {
+        if let Some(pg_router) = &opt_pg_router {
+          pg_router.route(external_msg);
+        }
+      }
+      generic_input => node.process_input(&mut io_ctx, generic_input),
+    }
   }
 }
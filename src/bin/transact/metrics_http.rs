@@ -0,0 +1,85 @@
+use log::info;
+use runiversal::metrics::metrics;
+use runiversal::net::mk_address_with_port;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// The Metrics endpoint serves the global Metrics Registry over HTTP in the Prometheus text
+/// exposition format, so that it can be scraped at `GET /metrics`.
+///
+/// Scrapes are rare and cheap, so a single Metrics Thread serves all connections in turn.
+
+// -----------------------------------------------------------------------------------------------
+//  Constants
+// -----------------------------------------------------------------------------------------------
+
+/// The port used for a Metrics address that does not specify one.
+pub const METRICS_DEFAULT_PORT: u32 = 9100;
+
+/// The path that the metrics are served at.
+const METRICS_PATH: &str = "/metrics";
+
+// -----------------------------------------------------------------------------------------------
+//  Metrics Thread
+// -----------------------------------------------------------------------------------------------
+
+/// Returns `address` as a `host:port` address, appending `METRICS_DEFAULT_PORT` if `address`
+/// does not already specify a port.
+pub fn mk_metrics_address(address: &str) -> String {
+  mk_address_with_port(address, METRICS_DEFAULT_PORT)
+}
+
+/// Starts the Metrics Thread, which accepts HTTP connections at `address`.
+pub fn start_metrics_thread(address: String) {
+  thread::Builder::new()
+    .name(format!("Metrics"))
+    .spawn(move || {
+      let listener = TcpListener::bind(&address).unwrap();
+      for stream in listener.incoming() {
+        if let Ok(stream) = stream {
+          if let Err(err) = serve(stream) {
+            info!("Failed to serve metrics request: {:?}", err);
+          }
+        }
+      }
+    })
+    .unwrap();
+}
+
+/// Reads a single HTTP request from `stream` and responds to it, closing the connection after.
+fn serve(mut stream: TcpStream) -> io::Result<()> {
+  let mut reader = BufReader::new(stream.try_clone()?);
+
+  // Read the Request Line, and then skip the headers.
+  let mut request_line = String::new();
+  reader.read_line(&mut request_line)?;
+  loop {
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+      break;
+    }
+  }
+
+  let mut parts = request_line.split_whitespace();
+  let (status, body) = match (parts.next(), parts.next()) {
+    (Some("GET"), Some(METRICS_PATH)) => ("200 OK", metrics().render()),
+    (Some("GET"), Some(_)) => ("404 Not Found", "Not Found\n".to_string()),
+    _ => ("405 Method Not Allowed", "Method Not Allowed\n".to_string()),
+  };
+
+  write!(
+    stream,
+    "HTTP/1.1 {}\r\n\
+     Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+     Content-Length: {}\r\n\
+     Connection: close\r\n\
+     \r\n\
+     {}",
+    status,
+    body.len(),
+    body
+  )?;
+  stream.flush()
+}
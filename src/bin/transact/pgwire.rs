@@ -0,0 +1,1363 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use log::info;
+use rand::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use runiversal::client::running_queries_result;
+use runiversal::common::{
+  mk_rid, ColName, ColType, ColVal, EndpointId, GossipData, PaxosGroupId, QueryResult, RequestId,
+  TablePath,
+};
+use runiversal::explain::explain_schema;
+use runiversal::message as msg;
+use runiversal::net::{mk_address_with_port, send_msg, SendAction};
+use runiversal::sql_ast::iast;
+use runiversal::sql_parser::{
+  is_ddl, is_read_only, parse_admin_command, parse_dql, parse_prepared_stmt_command,
+  parse_sql_with_params, AdminCommand, PreparedStmtCommand, QueryMode,
+};
+use sqlparser::ast;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::Sender;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+#[path = "test/pgwire_test.rs"]
+mod pgwire_test;
+
+/// The PostgreSQL frontend lets standard drivers and `psql` talk to the system using the
+/// PostgreSQL v3 wire protocol (both the Simple Query and the Extended Query protocols).
+///
+/// Every connection is served by its own Session Thread. A Session acts as an External on
+/// behalf of this node: it sends `PerformExternalQuery` to a Slave Leader and
+/// `PerformExternalDDLQuery` to the Master Leader with `sender_eid` set to this node's
+/// `EndpointId`. The responses arrive at the Server Thread, which hands them to
+/// `PgRouter::route` so that they reach the Session that is waiting on them.
+
+// -----------------------------------------------------------------------------------------------
+//  Constants
+// -----------------------------------------------------------------------------------------------
+
+/// The port used for a PostgreSQL address that does not specify one.
+pub const PG_DEFAULT_PORT: u32 = 5432;
+
+/// The longest we wait for the system to respond to a request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The longest we wait for a node to answer an `ExternalMetadataRequest`.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many times we rediscover the Leaders and resend a request that failed because
+/// Leadership moved.
+const MAX_REROUTES: u32 = 3;
+
+/// The SQLSTATE of a request that was not executed because Leadership moved. This is
+/// `serialization_failure`, which drivers understand as safe to retry.
+const NOT_LEADER_CODE: &str = "40001";
+/// The SQLSTATE of a request that did not get a response.
+const TIMEOUT_CODE: &str = "08006";
+
+/// The protocol version in the StartupMessage (3.0).
+const PROTOCOL_VERSION: i32 = 196608;
+const SSL_REQUEST_CODE: i32 = 80877103;
+const GSSENC_REQUEST_CODE: i32 = 80877104;
+const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// The longest StartupMessage we accept (which is the same limit as PostgreSQL's).
+const MAX_STARTUP_LEN: i32 = 10000;
+/// The longest message we accept after startup (1 GiB).
+const MAX_MESSAGE_LEN: i32 = 1 << 30;
+
+// Type OIDs
+const BOOL_OID: i32 = 16;
+const INT8_OID: i32 = 20;
+const INT2_OID: i32 = 21;
+const INT4_OID: i32 = 23;
+const TEXT_OID: i32 = 25;
+const VARCHAR_OID: i32 = 1043;
+
+/// Returns `address` as a `host:port` address, appending `PG_DEFAULT_PORT` if `address`
+/// does not already specify a port.
+pub fn mk_pg_address(address: &str) -> String {
+  mk_address_with_port(address, PG_DEFAULT_PORT)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  PgRouter
+// -----------------------------------------------------------------------------------------------
+
+/// Routes the `ExternalMessage`s that arrive at this node to the Session awaiting them.
+#[derive(Clone)]
+pub struct PgRouter {
+  pending: Arc<Mutex<BTreeMap<RequestId, Sender<msg::ExternalMessage>>>>,
+}
+
+impl PgRouter {
+  pub fn new() -> PgRouter {
+    PgRouter { pending: Arc::new(Mutex::new(BTreeMap::new())) }
+  }
+
+  /// Deliver `message` to the Session that sent the corresponding request. If that Session
+  /// is no longer waiting (e.g. it timed out), the `message` is dropped.
+  pub fn route(&self, message: msg::ExternalMessage) {
+    if let Some(sender) = self.pending.lock().unwrap().remove(message.request_id()) {
+      let _ = sender.send(message);
+    }
+  }
+}
+
+/// The state shared by every Session.
+#[derive(Clone)]
+pub struct PgContext {
+  pub this_eid: EndpointId,
+  pub out_conn_map: Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
+  /// A member of the Master Group, which we ask for the current Leaderships if this
+  /// node does not answer.
+  pub master_eid: EndpointId,
+  pub router: PgRouter,
+}
+
+/// Starts the PostgreSQL Acceptor Thread, which accepts connections at `address` and
+/// creates a Session Thread for each new connection.
+pub fn start_pg_acceptor_thread(address: String, ctx: PgContext) {
+  thread::spawn(move || {
+    let listener = TcpListener::bind(&address).unwrap();
+    for stream in listener.incoming() {
+      let stream = stream.unwrap();
+      let peer_addr = stream.peer_addr().unwrap().to_string();
+      let ctx = ctx.clone();
+      thread::Builder::new()
+        .name(format!("PgSession {}", peer_addr))
+        .spawn(move || {
+          let result = stream.set_nodelay(true).and_then(|_| Session::new(ctx, stream).run());
+          info!("Thread 'PgSession {}' shutting down. Result: {:?}", peer_addr, result);
+        })
+        .unwrap();
+    }
+  });
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Errors and Outcomes
+// -----------------------------------------------------------------------------------------------
+
+/// An error to report to the client with an ErrorResponse.
+#[derive(Debug)]
+struct PgError {
+  /// The SQLSTATE code.
+  code: &'static str,
+  message: String,
+}
+
+impl PgError {
+  fn new(code: &'static str, message: String) -> PgError {
+    PgError { code, message }
+  }
+}
+
+fn planning_error(error: msg::QueryPlanningError) -> PgError {
+  let code = match &error {
+    msg::QueryPlanningError::TablesDNE(_) => "42P01",
+    msg::QueryPlanningError::NonExistentColumn(_)
+    | msg::QueryPlanningError::RequiredColumnDNE(_)
+    | msg::QueryPlanningError::InvalidColumnRef => "42703",
+    msg::QueryPlanningError::AmbiguousColumnRef => "42702",
+    msg::QueryPlanningError::UnsafeColumnTypeConversion { .. } => "42804",
+    _ => "42000",
+  };
+  PgError::new(code, format!("{:?}", error))
+}
+
+fn query_aborted_error(payload: msg::ExternalAbortedData) -> PgError {
+  match payload {
+    msg::ExternalAbortedData::NonUniqueRequestId => {
+      PgError::new("XX000", "non-unique request id".to_string())
+    }
+    msg::ExternalAbortedData::ParseError(message) => PgError::new("42601", message),
+    msg::ExternalAbortedData::QueryPlanningError(error) => planning_error(error),
+    msg::ExternalAbortedData::QueryExecutionError(error) => match error {
+      msg::ExternalQueryError::TypeError { msg } => PgError::new("42804", msg),
+      msg::ExternalQueryError::RuntimeError { msg } => PgError::new("22000", msg),
+    },
+    msg::ExternalAbortedData::CancelConfirmed => {
+      PgError::new("57014", "canceling statement due to user request".to_string())
+    }
+    msg::ExternalAbortedData::Killed => {
+      PgError::new("57014", "canceling statement due to KILL QUERY".to_string())
+    }
+    msg::ExternalAbortedData::NotLeader => not_leader_error(),
+    msg::ExternalAbortedData::Timeout => {
+      PgError::new("57014", "canceling statement due to statement timeout".to_string())
+    }
+    msg::ExternalAbortedData::TooManyRetries => {
+      PgError::new("40001", "could not serialize access due to repeated conflicts".to_string())
+    }
+  }
+}
+
+fn not_leader_error() -> PgError {
+  PgError::new(NOT_LEADER_CODE, "the request was sent to a node that is not a Leader".to_string())
+}
+
+fn ddl_aborted_error(payload: msg::ExternalDDLQueryAbortData) -> PgError {
+  match payload {
+    msg::ExternalDDLQueryAbortData::NonUniqueRequestId => {
+      PgError::new("XX000", "non-unique request id".to_string())
+    }
+    msg::ExternalDDLQueryAbortData::ParseError(message) => PgError::new("42601", message),
+    msg::ExternalDDLQueryAbortData::InvalidDDLQuery => {
+      PgError::new("42000", "invalid DDL query".to_string())
+    }
+    msg::ExternalDDLQueryAbortData::QueryPlanningError(error) => planning_error(error),
+    msg::ExternalDDLQueryAbortData::CancelConfirmed => {
+      PgError::new("57014", "canceling statement due to user request".to_string())
+    }
+    msg::ExternalDDLQueryAbortData::NotLeader => not_leader_error(),
+    msg::ExternalDDLQueryAbortData::Unknown => {
+      PgError::new("XX000", "the DDL query failed".to_string())
+    }
+  }
+}
+
+/// The result of successfully executing a query.
+struct Outcome {
+  /// The tag sent in the CommandComplete.
+  tag: String,
+  /// The rows to send back, if the query is one that returns rows (i.e. a SELECT).
+  opt_result: Option<QueryResult>,
+}
+
+/// Returns true iff `query` only sets session parameters (e.g. `SET search_path = public`),
+/// which drivers commonly do on connecting. Notice that `SET TRANSACTION` is not one of these,
+/// since it sets the isolation level of the query that follows it.
+fn is_session_set(query: &str) -> bool {
+  match parse_sql_with_params(query) {
+    Ok((stmts, _)) => {
+      !stmts.is_empty()
+        && stmts.iter().all(|stmt| matches!(stmt, ast::Statement::SetVariable { .. }))
+    }
+    Err(_) => false,
+  }
+}
+
+/// Computes the `Outcome` of a DQL `query` that returned `result`. Only a query whose
+/// final statement is a SELECT sends back rows. For the others, we only report the
+/// number of rows that were written.
+fn dql_outcome(query: &str, result: QueryResult) -> Outcome {
+  let row_count: u64 = result.data.rows.values().sum();
+  let opt_final_stmt = parse_sql_with_params(query).ok().and_then(|(mut s, _)| s.pop());
+  match opt_final_stmt {
+    Some(ast::Statement::Insert { .. }) => {
+      Outcome { tag: format!("INSERT 0 {}", row_count), opt_result: None }
+    }
+    Some(ast::Statement::Update { .. }) => {
+      Outcome { tag: format!("UPDATE {}", row_count), opt_result: None }
+    }
+    Some(ast::Statement::Delete { .. }) => {
+      Outcome { tag: format!("DELETE {}", row_count), opt_result: None }
+    }
+    _ => Outcome { tag: format!("SELECT {}", row_count), opt_result: Some(result) },
+  }
+}
+
+/// Computes the CommandComplete tag of a DDL `query` (e.g. `CREATE TABLE`).
+fn ddl_tag(query: &str) -> String {
+  let words: Vec<_> = query.split_whitespace().take(2).map(|word| word.to_uppercase()).collect();
+  words.join(" ")
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Encoding
+// -----------------------------------------------------------------------------------------------
+
+fn put_cstr(buf: &mut Vec<u8>, s: &str) {
+  buf.extend_from_slice(s.as_bytes());
+  buf.push(0);
+}
+
+fn read_cstr(buf: &mut &[u8]) -> io::Result<String> {
+  match buf.iter().position(|b| *b == 0) {
+    Some(pos) => {
+      let s = String::from_utf8_lossy(&buf[..pos]).to_string();
+      *buf = &buf[pos + 1..];
+      Ok(s)
+    }
+    None => Err(io::Error::new(io::ErrorKind::InvalidData, "unterminated string")),
+  }
+}
+
+/// Reads the body of a message whose length field is `len` (which counts the length field
+/// itself, but not the message type). A `len` that is too short to count the length field, or
+/// that exceeds `max_len`, is rejected. We read the body as it arrives rather than allocating
+/// it upfront, so a bogus `len` does not make us allocate memory for data that never comes.
+fn read_body<R: Read>(reader: &mut R, len: i32, max_len: i32) -> io::Result<Vec<u8>> {
+  if len < 4 || len > max_len {
+    let message = format!("invalid message length {}", len);
+    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+  }
+  let body_len = (len - 4) as usize;
+  let mut body = Vec::new();
+  reader.take(body_len as u64).read_to_end(&mut body)?;
+  if body.len() < body_len {
+    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "message truncated"));
+  }
+  Ok(body)
+}
+
+fn read_bytes(buf: &mut &[u8], len: usize) -> io::Result<Vec<u8>> {
+  if buf.len() < len {
+    return Err(io::Error::new(io::ErrorKind::InvalidData, "message too short"));
+  }
+  let bytes = buf[..len].to_vec();
+  *buf = &buf[len..];
+  Ok(bytes)
+}
+
+/// Returns the type OID and the type length of a column of type `opt_type`, where a column
+/// whose type is unknown is described as text.
+fn type_info(opt_type: &Option<ColType>) -> (i32, i16) {
+  match opt_type {
+    Some(ColType::Int) => (INT4_OID, 4),
+    Some(ColType::BigInt) => (INT8_OID, 8),
+    Some(ColType::Bool) => (BOOL_OID, 1),
+    Some(ColType::String) | None => (TEXT_OID, -1),
+  }
+}
+
+/// Encodes `val` in the given format code (0 for text, 1 for binary) as a value of the
+/// column type `opt_type` that was described to the client. A value is sent as text if it
+/// does not have a binary encoding for that type, which is also the binary format of text.
+fn encode_val(val: &ColVal, opt_type: &Option<ColType>, format: i16) -> Vec<u8> {
+  match (val, opt_type, format) {
+    (ColVal::Int(v), Some(ColType::Int), 1) => v.to_be_bytes().to_vec(),
+    (ColVal::Int(v), Some(ColType::BigInt), 1) => (*v as i64).to_be_bytes().to_vec(),
+    (ColVal::BigInt(v), Some(ColType::BigInt), 1) => v.to_be_bytes().to_vec(),
+    (ColVal::Bool(v), Some(ColType::Bool), 1) => vec![*v as u8],
+    (ColVal::Int(v), _, _) => v.to_string().into_bytes(),
+    (ColVal::BigInt(v), _, _) => v.to_string().into_bytes(),
+    (ColVal::Bool(v), _, _) => (if *v { "t" } else { "f" }).as_bytes().to_vec(),
+    (ColVal::String(v), _, _) => v.as_bytes().to_vec(),
+  }
+}
+
+/// Returns the format code of the `i`th column given the format codes of a Bind.
+fn col_format(formats: &[i16], i: usize) -> i16 {
+  match formats.len() {
+    0 => 0,
+    1 => formats[0],
+    _ => formats.get(i).cloned().unwrap_or(0),
+  }
+}
+
+/// Converts a parameter value from a Bind into an `iast::Value`. If the client did not specify
+/// the type (`oid` is 0), an integer-looking value is taken as a number, and everything
+/// else as a string.
+fn param_value(oid: i32, format: i16, opt_value: Option<Vec<u8>>) -> Result<iast::Value, PgError> {
+  let value = match opt_value {
+    None => return Ok(iast::Value::Null),
+    Some(value) => value,
+  };
+  let unsupported = || PgError::new("0A000", format!("unsupported parameter type {}", oid));
+  if format == 1 {
+    let int_value = |len: usize| -> Result<iast::Value, PgError> {
+      if value.len() != len {
+        return Err(PgError::new("08P01", "invalid binary parameter length".to_string()));
+      }
+      let mut buf = &value[..];
+      Ok(iast::Value::Number(buf.read_int::<BigEndian>(len).unwrap().to_string()))
+    };
+    match oid {
+      BOOL_OID => Ok(iast::Value::Boolean(value.get(0).map_or(false, |b| *b != 0))),
+      INT2_OID => int_value(2),
+      INT4_OID => int_value(4),
+      INT8_OID => int_value(8),
+      0 | TEXT_OID | VARCHAR_OID => {
+        Ok(iast::Value::QuotedString(String::from_utf8_lossy(&value).to_string()))
+      }
+      _ => Err(unsupported()),
+    }
+  } else {
+    let text = String::from_utf8_lossy(&value).to_string();
+    let invalid = || PgError::new("22P02", format!("invalid input syntax: {}", text));
+    match oid {
+      BOOL_OID => match text.to_lowercase().as_str() {
+        "t" | "true" | "y" | "yes" | "on" | "1" => Ok(iast::Value::Boolean(true)),
+        "f" | "false" | "n" | "no" | "off" | "0" => Ok(iast::Value::Boolean(false)),
+        _ => Err(invalid()),
+      },
+      INT2_OID | INT4_OID | INT8_OID => text
+        .trim()
+        .parse::<i64>()
+        .map(|v| iast::Value::Number(v.to_string()))
+        .map_err(|_| invalid()),
+      TEXT_OID | VARCHAR_OID => Ok(iast::Value::QuotedString(text)),
+      0 => Ok(match text.trim().parse::<i64>() {
+        Ok(v) => iast::Value::Number(v.to_string()),
+        Err(_) => iast::Value::QuotedString(text),
+      }),
+      _ => Err(unsupported()),
+    }
+  }
+}
+
+/// Returns the number of parameters `query` uses (i.e. the highest `$n`).
+fn num_params(query: &str) -> usize {
+  parse_sql_with_params(query).map_or(0, |(_, num_params)| num_params as usize)
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Describing
+// -----------------------------------------------------------------------------------------------
+
+/// A column of the rows that a query returns, as described in a RowDescription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ColumnDesc {
+  name: String,
+  /// The type of the column, or `None` if it cannot be determined before running the query
+  /// (e.g. for a `NULL` literal), in which case the column is described and sent as text.
+  opt_type: Option<ColType>,
+}
+
+impl ColumnDesc {
+  fn new(name: &str, opt_type: Option<ColType>) -> ColumnDesc {
+    ColumnDesc { name: name.to_string(), opt_type }
+  }
+}
+
+/// The columns of the JoinLeafs of a Select, keyed by their JoinLeaf Names (JLNs).
+type LeafCols = BTreeMap<String, Vec<ColumnDesc>>;
+
+/// The columns of the `QueryResult` of an `EXPLAIN` (see `explain_result`).
+fn explain_cols() -> Vec<ColumnDesc> {
+  let col_types = [ColType::Int, ColType::String];
+  explain_schema()
+    .into_iter()
+    .zip(col_types)
+    .map(|(col, col_type)| ColumnDesc::new(&col.unwrap().0, Some(col_type)))
+    .collect()
+}
+
+/// The columns of the `QueryResult` of `SHOW QUERIES` (see `running_queries_result`).
+fn running_queries_cols() -> Vec<ColumnDesc> {
+  let schema = running_queries_result(vec![]).schema;
+  let num_cols = schema.len();
+  let mut cols: Vec<_> =
+    schema.into_iter().map(|col| ColumnDesc::new(&col.unwrap().0, Some(ColType::String))).collect();
+  // The last column is the age of the query.
+  cols[num_cols - 1].opt_type = Some(ColType::BigInt);
+  cols
+}
+
+/// Returns the columns of the Table at `table_path` in their canonical order (i.e. the
+/// KeyCols followed by the ValCols), or `None` if the Table does not exist in `gossip`.
+fn table_cols(gossip: &GossipData, table_path: &TablePath) -> Option<Vec<ColumnDesc>> {
+  let view = gossip.get();
+  let timestamp = view.table_generation.get_latest_lat();
+  let (gen, _) = view.table_generation.static_read(table_path, &timestamp)?;
+  let schema = view.db_schema.get(&(table_path.clone(), gen.clone()))?;
+  let mut all_cols = schema.key_cols.clone();
+  all_cols.extend(schema.val_cols.static_snapshot_read(&schema.val_cols.get_latest_lat()));
+  Some(
+    all_cols
+      .into_iter()
+      .map(|(ColName(name), col_type)| ColumnDesc::new(&name, Some(col_type)))
+      .collect(),
+  )
+}
+
+/// Infers the columns that `query` returns from the Table schemas in `gossip`, so that it can
+/// be described without being run. This returns `None` if `query` does not return rows (i.e.
+/// it ends with an INSERT, UPDATE, or DELETE), and the `TablePath` of any Table that does not
+/// exist in `gossip` as an error.
+///
+/// Like the `query_converter`, `ctes` holds the columns of the TransTables in scope, and
+/// `outer` holds the columns of the JoinLeafs of the Selects that `query` is a subquery of.
+fn query_cols(
+  gossip: &GossipData,
+  ctes: &LeafCols,
+  outer: &[&LeafCols],
+  query: &iast::Query,
+) -> Result<Option<Vec<ColumnDesc>>, TablePath> {
+  let mut ctes = ctes.clone();
+  for (trans_table_name, child_query) in &query.ctes {
+    let cols = query_cols(gossip, &ctes, outer, child_query)?.unwrap_or_default();
+    ctes.insert(trans_table_name.clone(), cols);
+  }
+
+  match &query.body {
+    iast::QueryBody::Query(child_query) => query_cols(gossip, &ctes, outer, child_query),
+    iast::QueryBody::Select(select) => Ok(Some(select_cols(gossip, &ctes, outer, select)?)),
+    iast::QueryBody::Update(_) | iast::QueryBody::Insert(_) | iast::QueryBody::Delete(_) => {
+      Ok(None)
+    }
+  }
+}
+
+fn select_cols(
+  gossip: &GossipData,
+  ctes: &LeafCols,
+  outer: &[&LeafCols],
+  select: &iast::Select,
+) -> Result<Vec<ColumnDesc>, TablePath> {
+  let mut leaves = LeafCols::new();
+  add_leaf_cols(gossip, ctes, outer, &select.from, &mut leaves)?;
+  let mut scopes = outer.to_vec();
+  scopes.push(&leaves);
+
+  let mut cols = Vec::<ColumnDesc>::new();
+  for item in &select.projection {
+    match item {
+      iast::SelectItem::ExprWithAlias { item, alias } => {
+        let opt_type = match item {
+          iast::SelectExprItem::ValExpr(expr) => expr_type(gossip, ctes, &scopes, expr)?,
+          iast::SelectExprItem::UnaryAggregate(aggregate) => match &aggregate.op {
+            iast::UnaryAggregateOp::Count => Some(ColType::Int),
+            // The SUM and AVG of a column are computed in the type of the column.
+            iast::UnaryAggregateOp::Sum | iast::UnaryAggregateOp::Avg => {
+              expr_type(gossip, ctes, &scopes, &aggregate.expr)?
+            }
+          },
+        };
+        let name = match (alias, item) {
+          (Some(alias), _) => alias.as_str(),
+          (None, iast::SelectExprItem::ValExpr(iast::ValExpr::ColumnRef { col_name, .. })) => {
+            col_name.as_str()
+          }
+          _ => "?column?",
+        };
+        cols.push(ColumnDesc::new(name, opt_type));
+      }
+      iast::SelectItem::Wildcard { table_name: Some(table_name) } => {
+        cols.extend(leaves.get(table_name).cloned().unwrap_or_default());
+      }
+      iast::SelectItem::Wildcard { table_name: None } => {
+        // Like the `query_converter`, the JoinLeafs are taken in the order of their JLNs.
+        cols.extend(leaves.values().flatten().cloned());
+      }
+    }
+  }
+  Ok(cols)
+}
+
+/// Adds the columns of every JoinLeaf under `join_node` to `leaves`.
+fn add_leaf_cols(
+  gossip: &GossipData,
+  ctes: &LeafCols,
+  outer: &[&LeafCols],
+  join_node: &iast::JoinNode,
+  leaves: &mut LeafCols,
+) -> Result<(), TablePath> {
+  match join_node {
+    iast::JoinNode::JoinInnerNode(inner) => {
+      add_leaf_cols(gossip, ctes, outer, &inner.left, leaves)?;
+      add_leaf_cols(gossip, ctes, outer, &inner.right, leaves)
+    }
+    iast::JoinNode::JoinLeaf(leaf) => {
+      let cols = match &leaf.source {
+        iast::JoinNodeSource::Table(name) => match ctes.get(name) {
+          Some(cols) => cols.clone(),
+          None => {
+            let table_path = TablePath(name.clone());
+            table_cols(gossip, &table_path).ok_or(table_path)?
+          }
+        },
+        iast::JoinNodeSource::DerivedTable { query, .. } => {
+          query_cols(gossip, ctes, outer, query)?.unwrap_or_default()
+        }
+      };
+      if let Some(jln) = leaf.join_leaf_name() {
+        leaves.insert(jln.clone(), cols);
+      }
+      Ok(())
+    }
+  }
+}
+
+/// Infers the type of `expr` the same way `expression` evaluates it, where a `ColumnRef` is
+/// looked up in the innermost Select of `scopes` first. This returns `None` if the type
+/// cannot be determined.
+fn expr_type(
+  gossip: &GossipData,
+  ctes: &LeafCols,
+  scopes: &[&LeafCols],
+  expr: &iast::ValExpr,
+) -> Result<Option<ColType>, TablePath> {
+  Ok(match expr {
+    iast::ValExpr::ColumnRef { table_name, col_name } => scopes
+      .iter()
+      .rev()
+      .find_map(|leaves| {
+        leaves
+          .iter()
+          .filter(|(jln, _)| table_name.as_ref().map_or(true, |table_name| table_name == *jln))
+          .flat_map(|(_, cols)| cols)
+          .find(|col| &col.name == col_name)
+      })
+      .and_then(|col| col.opt_type.clone()),
+    iast::ValExpr::UnaryExpr { op, expr } => match op {
+      iast::UnaryOp::Plus | iast::UnaryOp::Minus => expr_type(gossip, ctes, scopes, expr)?,
+      iast::UnaryOp::Not | iast::UnaryOp::IsNull | iast::UnaryOp::IsNotNull => Some(ColType::Bool),
+    },
+    iast::ValExpr::BinaryExpr { op, left, right } => match op {
+      iast::BinaryOp::Plus
+      | iast::BinaryOp::Minus
+      | iast::BinaryOp::Multiply
+      | iast::BinaryOp::Divide
+      | iast::BinaryOp::Modulus => {
+        let left_type = expr_type(gossip, ctes, scopes, left)?;
+        let right_type = expr_type(gossip, ctes, scopes, right)?;
+        match (left_type, right_type) {
+          (Some(ColType::Int), Some(ColType::Int)) => Some(ColType::Int),
+          (Some(ColType::Int | ColType::BigInt), Some(ColType::Int | ColType::BigInt)) => {
+            Some(ColType::BigInt)
+          }
+          _ => None,
+        }
+      }
+      iast::BinaryOp::StringConcat => Some(ColType::String),
+      _ => Some(ColType::Bool),
+    },
+    iast::ValExpr::Value { val } => match val {
+      iast::Value::Number(num) => {
+        if num.parse::<i32>().is_ok() {
+          Some(ColType::Int)
+        } else if num.parse::<i64>().is_ok() {
+          Some(ColType::BigInt)
+        } else {
+          None
+        }
+      }
+      iast::Value::QuotedString(_) => Some(ColType::String),
+      iast::Value::Boolean(_) => Some(ColType::Bool),
+      iast::Value::Null | iast::Value::Param(_) => None,
+    },
+    iast::ValExpr::Subquery { query, .. } => query_cols(gossip, ctes, scopes, query)?
+      .and_then(|cols| cols.into_iter().next())
+      .and_then(|col| col.opt_type),
+  })
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Session
+// -----------------------------------------------------------------------------------------------
+
+/// A statement created by a Parse message.
+struct Statement {
+  query: String,
+  /// The parameter type OIDs specified by the client, where 0 means unspecified.
+  param_oids: Vec<i32>,
+}
+
+/// A portal created by a Bind message.
+struct Portal {
+  query: String,
+  /// The values bound to the parameters of `query`.
+  params: Vec<iast::Value>,
+  result_formats: Vec<i16>,
+  /// The columns sent in the RowDescription of a Describe, if there was one. The rows of a
+  /// following Execute are encoded accordingly.
+  opt_cols: Option<Vec<ColumnDesc>>,
+}
+
+struct Session<S: Read + Write> {
+  ctx: PgContext,
+  stream: S,
+  /// Messages to send, which are written out on `flush`.
+  out: Vec<u8>,
+  rand: XorShiftRng,
+  /// The Leaders we send queries to. These are populated from an `ExternalMetadataRequest`.
+  /// DQL is sent to `slave_leaders[slave_idx]`.
+  opt_master_leader: Option<EndpointId>,
+  slave_leaders: Vec<EndpointId>,
+  slave_idx: usize,
+  /// The `GossipData` from the last `ExternalMetadataRequest`, whose Table schemas we use
+  /// to describe queries.
+  opt_gossip: Option<GossipData>,
+  statements: BTreeMap<String, Statement>,
+  portals: BTreeMap<String, Portal>,
+  /// Set when an error occurs in the Extended Query protocol, in which case we discard
+  /// messages until the next Sync.
+  ignore_till_sync: bool,
+}
+
+impl<S: Read + Write> Session<S> {
+  fn new(ctx: PgContext, stream: S) -> Session<S> {
+    Session {
+      ctx,
+      stream,
+      out: vec![],
+      rand: XorShiftRng::from_entropy(),
+      opt_master_leader: None,
+      slave_leaders: vec![],
+      slave_idx: 0,
+      opt_gossip: None,
+      statements: BTreeMap::new(),
+      portals: BTreeMap::new(),
+      ignore_till_sync: false,
+    }
+  }
+
+  fn run(&mut self) -> io::Result<()> {
+    if !self.startup()? {
+      return Ok(());
+    }
+    loop {
+      let (tag, body) = self.read_message()?;
+      if self.ignore_till_sync && tag != b'S' && tag != b'X' {
+        continue;
+      }
+      let mut body = &body[..];
+      match tag {
+        b'Q' => {
+          let query = read_cstr(&mut body)?;
+          self.simple_query(&query);
+          self.ready_for_query();
+          self.flush()?;
+        }
+        b'P' => {
+          let name = read_cstr(&mut body)?;
+          let query = read_cstr(&mut body)?;
+          let num_oids = body.read_i16::<BigEndian>()?;
+          let mut param_oids = vec![];
+          for _ in 0..num_oids {
+            param_oids.push(body.read_i32::<BigEndian>()?);
+          }
+          self.statements.insert(name, Statement { query, param_oids });
+          self.write_message(b'1', &[]);
+        }
+        b'B' => {
+          let portal = read_cstr(&mut body)?;
+          let stmt_name = read_cstr(&mut body)?;
+          let mut param_formats = vec![];
+          for _ in 0..body.read_i16::<BigEndian>()? {
+            param_formats.push(body.read_i16::<BigEndian>()?);
+          }
+          let mut params = vec![];
+          for _ in 0..body.read_i16::<BigEndian>()? {
+            let len = body.read_i32::<BigEndian>()?;
+            params.push(if len < 0 { None } else { Some(read_bytes(&mut body, len as usize)?) });
+          }
+          let mut result_formats = vec![];
+          for _ in 0..body.read_i16::<BigEndian>()? {
+            result_formats.push(body.read_i16::<BigEndian>()?);
+          }
+          match self.bind(&stmt_name, &param_formats, params) {
+            Ok((query, params)) => {
+              let new_portal = Portal { query, params, result_formats, opt_cols: None };
+              self.portals.insert(portal, new_portal);
+              self.write_message(b'2', &[]);
+            }
+            Err(error) => self.extended_error(error),
+          }
+        }
+        b'D' => {
+          let kind = body.read_u8()?;
+          let name = read_cstr(&mut body)?;
+          let result =
+            if kind == b'S' { self.describe_statement(&name) } else { self.describe_portal(&name) };
+          if let Err(error) = result {
+            self.extended_error(error);
+          }
+        }
+        b'E' => {
+          let name = read_cstr(&mut body)?;
+          if let Err(error) = self.execute_portal(&name) {
+            self.extended_error(error);
+          }
+        }
+        b'C' => {
+          let kind = body.read_u8()?;
+          let name = read_cstr(&mut body)?;
+          if kind == b'S' {
+            self.statements.remove(&name);
+          } else {
+            self.portals.remove(&name);
+          }
+          self.write_message(b'3', &[]);
+        }
+        b'S' => {
+          self.ignore_till_sync = false;
+          self.portals.remove("");
+          self.ready_for_query();
+          self.flush()?;
+        }
+        b'H' => self.flush()?,
+        b'X' => return Ok(()),
+        _ => {
+          self.error_response(PgError::new(
+            "0A000",
+            format!("unsupported message type '{}'", tag as char),
+          ));
+          self.ready_for_query();
+          self.flush()?;
+        }
+      }
+    }
+  }
+
+  // Protocol I/O
+
+  /// Handles the startup phase. Returns false if the connection should be closed.
+  fn startup(&mut self) -> io::Result<bool> {
+    loop {
+      let len = self.stream.read_i32::<BigEndian>()?;
+      let body = read_body(&mut self.stream, len, MAX_STARTUP_LEN)?;
+      let mut body = &body[..];
+      match body.read_i32::<BigEndian>()? {
+        SSL_REQUEST_CODE | GSSENC_REQUEST_CODE => {
+          // We do not support encryption.
+          self.stream.write_all(b"N")?;
+        }
+        CANCEL_REQUEST_CODE => return Ok(false),
+        PROTOCOL_VERSION => break,
+        version => {
+          let error = PgError::new("08P01", format!("unsupported protocol version {}", version));
+          self.error_response(error);
+          self.flush()?;
+          return Ok(false);
+        }
+      }
+    }
+
+    // AuthenticationOk
+    self.write_message(b'R', &0i32.to_be_bytes());
+    for (name, value) in &[
+      ("server_version", "13.0"),
+      ("server_encoding", "UTF8"),
+      ("client_encoding", "UTF8"),
+      ("DateStyle", "ISO, MDY"),
+      ("integer_datetimes", "on"),
+      ("standard_conforming_strings", "on"),
+    ] {
+      let mut buf = vec![];
+      put_cstr(&mut buf, name);
+      put_cstr(&mut buf, value);
+      self.write_message(b'S', &buf);
+    }
+    // BackendKeyData
+    let mut buf = vec![];
+    buf.write_u32::<BigEndian>(self.rand.next_u32()).unwrap();
+    buf.write_u32::<BigEndian>(self.rand.next_u32()).unwrap();
+    self.write_message(b'K', &buf);
+    self.ready_for_query();
+    self.flush()?;
+    Ok(true)
+  }
+
+  fn read_message(&mut self) -> io::Result<(u8, Vec<u8>)> {
+    let tag = self.stream.read_u8()?;
+    let len = self.stream.read_i32::<BigEndian>()?;
+    Ok((tag, read_body(&mut self.stream, len, MAX_MESSAGE_LEN)?))
+  }
+
+  fn write_message(&mut self, tag: u8, body: &[u8]) {
+    self.out.push(tag);
+    self.out.write_i32::<BigEndian>(body.len() as i32 + 4).unwrap();
+    self.out.extend_from_slice(body);
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.stream.write_all(&self.out)?;
+    self.out.clear();
+    Ok(())
+  }
+
+  fn ready_for_query(&mut self) {
+    self.write_message(b'Z', b"I");
+  }
+
+  fn error_response(&mut self, error: PgError) {
+    let mut buf = vec![];
+    for (field, value) in
+      &[(b'S', "ERROR"), (b'V', "ERROR"), (b'C', error.code), (b'M', error.message.as_str())]
+    {
+      buf.push(*field);
+      put_cstr(&mut buf, value);
+    }
+    buf.push(0);
+    self.write_message(b'E', &buf);
+  }
+
+  /// Reports an error in the Extended Query protocol.
+  fn extended_error(&mut self, error: PgError) {
+    self.error_response(error);
+    self.ignore_till_sync = true;
+  }
+
+  fn command_complete(&mut self, tag: &str) {
+    let mut buf = vec![];
+    put_cstr(&mut buf, tag);
+    self.write_message(b'C', &buf);
+  }
+
+  fn row_description(&mut self, cols: &[ColumnDesc], formats: &[i16]) {
+    let mut buf = vec![];
+    buf.write_i16::<BigEndian>(cols.len() as i16).unwrap();
+    for (i, col) in cols.iter().enumerate() {
+      let (oid, len) = type_info(&col.opt_type);
+      put_cstr(&mut buf, &col.name);
+      buf.write_i32::<BigEndian>(0).unwrap();
+      buf.write_i16::<BigEndian>(0).unwrap();
+      buf.write_i32::<BigEndian>(oid).unwrap();
+      buf.write_i16::<BigEndian>(len).unwrap();
+      buf.write_i32::<BigEndian>(-1).unwrap();
+      buf.write_i16::<BigEndian>(col_format(formats, i)).unwrap();
+    }
+    self.write_message(b'T', &buf);
+  }
+
+  fn data_rows(&mut self, result: &QueryResult, cols: &[ColumnDesc], formats: &[i16]) {
+    for (row, count) in &result.data.rows {
+      let mut buf = vec![];
+      buf.write_i16::<BigEndian>(row.len() as i16).unwrap();
+      for (i, opt_val) in row.iter().enumerate() {
+        match opt_val {
+          None => buf.write_i32::<BigEndian>(-1).unwrap(),
+          Some(val) => {
+            let opt_type = cols.get(i).and_then(|col| col.opt_type.clone());
+            let data = encode_val(val, &opt_type, col_format(formats, i));
+            buf.write_i32::<BigEndian>(data.len() as i32).unwrap();
+            buf.extend_from_slice(&data);
+          }
+        }
+      }
+      for _ in 0..*count {
+        self.write_message(b'D', &buf);
+      }
+    }
+  }
+
+  // Simple Query protocol
+
+  fn simple_query(&mut self, query: &str) {
+    if query.trim().trim_end_matches(';').trim().is_empty() {
+      self.write_message(b'I', &[]);
+      return;
+    }
+    match self.execute(query, &[]) {
+      Ok(outcome) => {
+        if let Some(result) = &outcome.opt_result {
+          let cols = self.result_cols(query, result);
+          self.row_description(&cols, &[]);
+          self.data_rows(result, &cols, &[]);
+        }
+        self.command_complete(&outcome.tag);
+      }
+      Err(error) => self.error_response(error),
+    }
+  }
+
+  // Extended Query protocol
+
+  /// Converts the parameters of a Bind into the values bound to the query of the statement
+  /// `stmt_name`. These are sent to the system separately from the query.
+  fn bind(
+    &self,
+    stmt_name: &str,
+    param_formats: &[i16],
+    params: Vec<Option<Vec<u8>>>,
+  ) -> Result<(String, Vec<iast::Value>), PgError> {
+    let stmt = self.get_statement(stmt_name)?;
+    let mut values = vec![];
+    for (i, opt_value) in params.into_iter().enumerate() {
+      let oid = stmt.param_oids.get(i).cloned().unwrap_or(0);
+      values.push(param_value(oid, col_format(param_formats, i), opt_value)?);
+    }
+    let expected = num_params(&stmt.query);
+    if values.len() != expected {
+      return Err(PgError::new(
+        "08P01",
+        format!("bind message supplies {} parameters, but {} are used", values.len(), expected),
+      ));
+    }
+    Ok((stmt.query.clone(), values))
+  }
+
+  /// Sends a ParameterDescription, followed by a RowDescription if the statement returns
+  /// rows, or NoData otherwise.
+  fn describe_statement(&mut self, name: &str) -> Result<(), PgError> {
+    let stmt = self.get_statement(name)?;
+    let query = stmt.query.clone();
+    let param_oids: Vec<_> = (0..num_params(&query))
+      .map(|i| match stmt.param_oids.get(i).cloned().unwrap_or(0) {
+        0 => TEXT_OID,
+        oid => oid,
+      })
+      .collect();
+    let mut buf = vec![];
+    buf.write_i16::<BigEndian>(param_oids.len() as i16).unwrap();
+    for oid in param_oids {
+      buf.write_i32::<BigEndian>(oid).unwrap();
+    }
+    self.write_message(b't', &buf);
+
+    match self.describe_query(&query)? {
+      Some(cols) => self.row_description(&cols, &[]),
+      None => self.write_message(b'n', &[]),
+    }
+    Ok(())
+  }
+
+  /// Sends a RowDescription if the portal returns rows, or NoData otherwise. We hold onto
+  /// the columns so that the following Execute sends rows that match them.
+  fn describe_portal(&mut self, name: &str) -> Result<(), PgError> {
+    let portal = self.get_portal(name)?;
+    let (query, result_formats) = (portal.query.clone(), portal.result_formats.clone());
+    let opt_cols = self.describe_query(&query)?;
+    match &opt_cols {
+      Some(cols) => self.row_description(cols, &result_formats),
+      None => self.write_message(b'n', &[]),
+    }
+    self.portals.get_mut(name).unwrap().opt_cols = opt_cols;
+    Ok(())
+  }
+
+  fn execute_portal(&mut self, name: &str) -> Result<(), PgError> {
+    let portal = self.get_portal(name)?;
+    let (query, params) = (portal.query.clone(), portal.params.clone());
+    let result_formats = portal.result_formats.clone();
+    let opt_cols = portal.opt_cols.clone();
+    let outcome = self.execute(&query, &params)?;
+    if let Some(result) = &outcome.opt_result {
+      let cols = match opt_cols {
+        Some(cols) => cols,
+        None => self.result_cols(&query, result),
+      };
+      self.data_rows(result, &cols, &result_formats);
+    }
+    self.command_complete(&outcome.tag);
+    Ok(())
+  }
+
+  fn get_statement(&self, name: &str) -> Result<&Statement, PgError> {
+    self
+      .statements
+      .get(name)
+      .ok_or(PgError::new("26000", format!("prepared statement \"{}\" does not exist", name)))
+  }
+
+  fn get_portal(&self, name: &str) -> Result<&Portal, PgError> {
+    self
+      .portals
+      .get(name)
+      .ok_or(PgError::new("34000", format!("portal \"{}\" does not exist", name)))
+  }
+
+  // Describing
+
+  /// Returns the columns that `query` returns, or `None` if it does not return rows. This
+  /// does not run `query`; the columns of a DQL query are inferred from the Table schemas.
+  fn describe_query(&mut self, query: &str) -> Result<Option<Vec<ColumnDesc>>, PgError> {
+    // These mirror the statements that `execute` handles itself.
+    if is_session_set(query) {
+      return Ok(None);
+    }
+    if let Some(command) = parse_prepared_stmt_command(query) {
+      return match command.map_err(|message| PgError::new("42601", message))? {
+        PreparedStmtCommand::Execute { name, .. } => {
+          let query = self.get_statement(&name)?.query.clone();
+          self.describe_query(&query)
+        }
+        PreparedStmtCommand::Prepare { .. } | PreparedStmtCommand::Deallocate { .. } => Ok(None),
+      };
+    }
+    if let Some(command) = parse_admin_command(query) {
+      return match command.map_err(|message| PgError::new("42601", message))? {
+        AdminCommand::ShowQueries => Ok(Some(running_queries_cols())),
+        AdminCommand::KillQuery { .. } => Ok(None),
+      };
+    }
+    if is_ddl(query) {
+      return Ok(None);
+    }
+
+    let (query, _, mode, _) = parse_dql(query).map_err(|message| PgError::new("42601", message))?;
+    if mode != QueryMode::Execute {
+      return Ok(Some(explain_cols()));
+    }
+
+    // If a Table is not in the `GossipData` we have, it might have been created since we got
+    // it, so we get the latest `GossipData` and try again.
+    if self.opt_gossip.is_none() {
+      self.refresh_leaders()?;
+    }
+    let no_cols = LeafCols::new();
+    match query_cols(self.opt_gossip.as_ref().unwrap(), &no_cols, &[], &query) {
+      Ok(opt_cols) => Ok(opt_cols),
+      Err(_) => {
+        self.refresh_leaders()?;
+        query_cols(self.opt_gossip.as_ref().unwrap(), &no_cols, &[], &query).map_err(
+          |TablePath(table_name)| {
+            PgError::new("42P01", format!("relation \"{}\" does not exist", table_name))
+          },
+        )
+      }
+    }
+  }
+
+  /// Returns the columns to send `result` of `query` with. These have the names in `result`,
+  /// and the types of the columns that `describe_query` infers. If the latter does not match
+  /// `result` (e.g. because the `GossipData` is out of date), then we get the latest
+  /// `GossipData` and try again, falling back to sending every column as text.
+  fn result_cols(&mut self, query: &str, result: &QueryResult) -> Vec<ColumnDesc> {
+    let mut opt_types = None;
+    for _ in 0..2 {
+      if let Ok(Some(cols)) = self.describe_query(query) {
+        if cols.len() == result.schema.len() {
+          opt_types = Some(cols.into_iter().map(|col| col.opt_type).collect::<Vec<_>>());
+          break;
+        }
+      }
+      self.opt_gossip = None;
+    }
+    let opt_types = opt_types.unwrap_or(vec![None; result.schema.len()]);
+    result
+      .schema
+      .iter()
+      .zip(opt_types)
+      .map(|(opt_col, opt_type)| {
+        ColumnDesc::new(opt_col.as_ref().map_or("?column?", |col| col.0.as_str()), opt_type)
+      })
+      .collect()
+  }
+
+  // Execution
+
+  /// Executes `query` against the system, binding `params[n - 1]` to each `$n` in it.
+  fn execute(&mut self, query: &str, params: &[iast::Value]) -> Result<Outcome, PgError> {
+    // Drivers commonly set session parameters on connecting, which we accept and ignore.
+    if is_session_set(query) {
+      return Ok(Outcome { tag: "SET".to_string(), opt_result: None });
+    }
+
+    // SQL-level prepared statements share their names with those created by Parse messages.
+    if let Some(command) = parse_prepared_stmt_command(query) {
+      return match command.map_err(|message| PgError::new("42601", message))? {
+        PreparedStmtCommand::Prepare { name, query } => {
+          self.statements.insert(name, Statement { query, param_oids: vec![] });
+          Ok(Outcome { tag: "PREPARE".to_string(), opt_result: None })
+        }
+        PreparedStmtCommand::Execute { name, params } => {
+          let query = self.get_statement(&name)?.query.clone();
+          self.execute(&query, &params)
+        }
+        PreparedStmtCommand::Deallocate { name } => {
+          self.statements.remove(&name);
+          Ok(Outcome { tag: "DEALLOCATE".to_string(), opt_result: None })
+        }
+      };
+    }
+
+    if let Some(command) = parse_admin_command(query) {
+      let command = command.map_err(|message| PgError::new("42601", message))?;
+      return self.execute_admin(command);
+    }
+
+    // If Leadership moved, we resend the query to the new Leader. After a timeout, we
+    // only do this if running the query twice has no effect.
+    let resendable = !is_ddl(query) && is_read_only(query);
+    let mut num_reroutes = 0;
+    loop {
+      match self.execute_once(query, params) {
+        Err(error) if error.code == NOT_LEADER_CODE || error.code == TIMEOUT_CODE => {
+          // Move onto the next Slave Leader in case the one we used is gone, and look up
+          // the Leaders again.
+          self.opt_master_leader = None;
+          self.slave_leaders.clear();
+          self.slave_idx += 1;
+          let retryable = error.code == NOT_LEADER_CODE || resendable;
+          if retryable && num_reroutes < MAX_REROUTES {
+            num_reroutes += 1;
+          } else {
+            return Err(error);
+          }
+        }
+        result => return result,
+      }
+    }
+  }
+
+  fn execute_once(&mut self, query: &str, params: &[iast::Value]) -> Result<Outcome, PgError> {
+    let request_id = mk_rid(&mut self.rand);
+    if is_ddl(query) {
+      let master_leader = self.master_leader()?;
+      let network_msg = msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+        msg::MasterExternalReq::PerformExternalDDLQuery(msg::PerformExternalDDLQuery {
+          sender_eid: self.ctx.this_eid.clone(),
+          request_id: request_id.clone(),
+          query: query.to_string(),
+        }),
+      ));
+      match self.request(&master_leader, request_id, network_msg, RESPONSE_TIMEOUT)? {
+        msg::ExternalMessage::ExternalDDLQuerySuccess(_) => {
+          // The Table schemas have changed, so we get the latest `GossipData` when we need it.
+          self.opt_gossip = None;
+          Ok(Outcome { tag: ddl_tag(query), opt_result: None })
+        }
+        msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => {
+          Err(ddl_aborted_error(aborted.payload))
+        }
+        message => Err(PgError::new("XX000", format!("unexpected response {:?}", message))),
+      }
+    } else {
+      let slave_leader = self.slave_leader()?;
+      let network_msg = msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+        msg::SlaveExternalReq::PerformExternalQuery(msg::PerformExternalQuery {
+          sender_eid: self.ctx.this_eid.clone(),
+          request_id: request_id.clone(),
+          query: query.to_string(),
+          params: params.to_vec(),
+          opt_timeout_ms: Some(RESPONSE_TIMEOUT.as_millis()),
+          opt_max_retries: None,
+          priority: msg::TransactionPriority::Normal,
+          opt_max_staleness_ms: None,
+        }),
+      ));
+      match self.request(&slave_leader, request_id, network_msg, RESPONSE_TIMEOUT)? {
+        msg::ExternalMessage::ExternalQuerySuccess(success) => {
+          Ok(dql_outcome(query, success.result))
+        }
+        msg::ExternalMessage::ExternalQueryAborted(aborted) => {
+          Err(query_aborted_error(aborted.payload))
+        }
+        message => Err(PgError::new("XX000", format!("unexpected response {:?}", message))),
+      }
+    }
+  }
+
+  /// Executes `command` by sending it to every Slave Leader, since the requests it concerns
+  /// might be running at any of them.
+  fn execute_admin(&mut self, command: AdminCommand) -> Result<Outcome, PgError> {
+    self.slave_leader()?;
+    let sender_eid = self.ctx.this_eid.clone();
+    match command {
+      AdminCommand::ShowQueries => {
+        let mut queries = Vec::<msg::RunningQuery>::new();
+        for slave_leader in self.slave_leaders.clone() {
+          let request_id = mk_rid(&mut self.rand);
+          let network_msg = msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+            msg::SlaveExternalReq::ExternalShowQueries(msg::ExternalShowQueries {
+              sender_eid: sender_eid.clone(),
+              request_id: request_id.clone(),
+            }),
+          ));
+          match self.request(&slave_leader, request_id, network_msg, RESPONSE_TIMEOUT)? {
+            msg::ExternalMessage::ExternalShowQueriesResponse(resp) => queries.extend(resp.queries),
+            message => {
+              return Err(PgError::new("XX000", format!("unexpected response {:?}", message)))
+            }
+          }
+        }
+        let tag = format!("SELECT {}", queries.len());
+        Ok(Outcome { tag, opt_result: Some(running_queries_result(queries)) })
+      }
+      AdminCommand::KillQuery { request_id: target_request_id } => {
+        for slave_leader in self.slave_leaders.clone() {
+          let request_id = mk_rid(&mut self.rand);
+          let network_msg = msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+            msg::SlaveExternalReq::ExternalKillQuery(msg::ExternalKillQuery {
+              sender_eid: sender_eid.clone(),
+              request_id: request_id.clone(),
+              target_request_id: RequestId(target_request_id.clone()),
+            }),
+          ));
+          match self.request(&slave_leader, request_id, network_msg, RESPONSE_TIMEOUT)? {
+            msg::ExternalMessage::ExternalKillQueryResponse(resp) => {
+              if resp.result == msg::KillQueryResult::Killed {
+                return Ok(Outcome { tag: "KILL".to_string(), opt_result: None });
+              }
+            }
+            message => {
+              return Err(PgError::new("XX000", format!("unexpected response {:?}", message)))
+            }
+          }
+        }
+        Err(PgError::new("42704", format!("request {} is not running", target_request_id)))
+      }
+    }
+  }
+
+  /// Sends `network_msg` to `eid` and waits for the response to `request_id`.
+  fn request(
+    &mut self,
+    eid: &EndpointId,
+    request_id: RequestId,
+    network_msg: msg::NetworkMessage,
+    timeout: Duration,
+  ) -> Result<msg::ExternalMessage, PgError> {
+    let (sender, receiver) = mpsc::channel();
+    self.ctx.router.pending.lock().unwrap().insert(request_id.clone(), sender);
+    send_msg(&self.ctx.out_conn_map, eid, SendAction::new(network_msg, None), &self.ctx.this_eid);
+    match receiver.recv_timeout(timeout) {
+      Ok(message) => Ok(message),
+      Err(_) => {
+        self.ctx.router.pending.lock().unwrap().remove(&request_id);
+        Err(PgError::new(
+          TIMEOUT_CODE,
+          format!("timed out waiting for a response from {}", eid.address),
+        ))
+      }
+    }
+  }
+
+  /// Asks for the current Leaderships and picks which ones to send to. We first ask this
+  /// node (which forwards the request to the Master if needed), and then `ctx.master_eid`.
+  fn refresh_leaders(&mut self) -> Result<(), PgError> {
+    let mut candidates = vec![self.ctx.this_eid.clone()];
+    if !candidates.contains(&self.ctx.master_eid) {
+      candidates.push(self.ctx.master_eid.clone());
+    }
+
+    let mut last_error = None;
+    for eid in candidates {
+      let request_id = mk_rid(&mut self.rand);
+      let network_msg = msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+        msg::MasterExternalReq::ExternalMetadataRequest(msg::ExternalMetadataRequest {
+          sender_eid: self.ctx.this_eid.clone(),
+          request_id: request_id.clone(),
+        }),
+      ));
+      match self.request(&eid, request_id, network_msg, DISCOVERY_TIMEOUT) {
+        Ok(msg::ExternalMessage::ExternalMetadataResponse(resp)) => {
+          self.opt_gossip = Some(resp.gossip_data);
+          self.slave_leaders.clear();
+          for (gid, lid) in resp.leader_map {
+            match gid {
+              PaxosGroupId::Master => self.opt_master_leader = Some(lid.eid),
+              PaxosGroupId::Slave(_) => self.slave_leaders.push(lid.eid),
+            }
+          }
+          return Ok(());
+        }
+        Ok(_) => {}
+        Err(error) => last_error = Some(error),
+      }
+    }
+    Err(last_error.unwrap_or(PgError::new(TIMEOUT_CODE, "the Leaders are unknown".to_string())))
+  }
+
+  fn master_leader(&mut self) -> Result<EndpointId, PgError> {
+    if self.opt_master_leader.is_none() {
+      self.refresh_leaders()?;
+    }
+    self
+      .opt_master_leader
+      .clone()
+      .ok_or(PgError::new(TIMEOUT_CODE, "the Master Leader is unknown".to_string()))
+  }
+
+  fn slave_leader(&mut self) -> Result<EndpointId, PgError> {
+    if self.slave_leaders.is_empty() {
+      self.refresh_leaders()?;
+    }
+    if self.slave_leaders.is_empty() {
+      Err(PgError::new(TIMEOUT_CODE, "there are no Slaves".to_string()))
+    } else {
+      Ok(self.slave_leaders[self.slave_idx % self.slave_leaders.len()].clone())
+    }
+  }
+}
@@ -2,11 +2,11 @@ use rand::{RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use runiversal::common::{
   btree_multimap_insert, mk_cid, mk_sid, mk_t, BasicIOCtx, CoreIOCtx, FreeNodeIOCtx,
-  GeneralTraceMessage, GossipData, InternalMode, MasterIOCtx, MasterTraceMessage, NodeIOCtx,
-  SlaveIOCtx, SlaveTraceMessage, Timestamp,
+  GeneralTraceMessage, GossipData, MasterIOCtx, MasterTraceMessage, NodeIOCtx, SlaveIOCtx,
+  SlaveTraceMessage, Timestamp,
 };
 use runiversal::common::{
-  CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, SlaveGroupId,
+  ClockId, CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, SlaveGroupId,
   TabletGroupId,
 };
 use runiversal::coord::{CoordConfig, CoordContext, CoordForwardMsg, CoordState};
@@ -25,6 +25,8 @@ use runiversal::tablet::{
 use runiversal::test_utils::mk_seed;
 use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::Write;
 use std::net::TcpStream;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
@@ -39,9 +41,34 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// The granularity in which Timer events are executed, in microseconds
 pub const TIMER_INCREMENT: u64 = 250;
 
+/// A file that log lines are appended to (e.g. the `Span`s of distributed traces). This is
+/// shared by all threads of this node.
+pub type TraceLog = Arc<Mutex<File>>;
+
+/// Appends `trace_msg` to the `trace_log` if it is a `Span`, or to the `slow_query_log` if it
+/// is a `SlowQuery`. Other `GeneralTraceMessage`s are only used in tests, so we ignore them.
+fn write_trace_log(
+  trace_log: &Option<TraceLog>,
+  slow_query_log: &Option<TraceLog>,
+  trace_msg: GeneralTraceMessage,
+) {
+  match (trace_msg, trace_log, slow_query_log) {
+    (GeneralTraceMessage::Span(span), Some(trace_log), _) => {
+      let mut file = trace_log.lock().unwrap();
+      let _ = writeln!(file, "{}", span.to_log_line());
+    }
+    (GeneralTraceMessage::SlowQuery(slow_query), _, Some(slow_query_log)) => {
+      let mut file = slow_query_log.lock().unwrap();
+      let _ = writeln!(file, "{}", slow_query.to_log_line());
+    }
+    _ => {}
+  }
+}
+
 pub struct ProdIOCtx {
   // Basic
   pub rand: XorShiftRng,
+  pub this_eid: EndpointId,
   pub out_conn_map: Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
   pub exited: bool,
 
@@ -54,6 +81,10 @@ pub struct ProdIOCtx {
 
   // Timer Tasks
   pub tasks: Arc<Mutex<BTreeMap<Timestamp, Vec<GenericTimerInput>>>>,
+
+  // Tracing
+  pub trace_log: Option<TraceLog>,
+  pub slow_query_log: Option<TraceLog>,
 }
 
 impl ProdIOCtx {
@@ -102,10 +133,12 @@ impl BasicIOCtx for ProdIOCtx {
   }
 
   fn send(&mut self, eid: &EndpointId, msg: msg::NetworkMessage) {
-    send_msg(&self.out_conn_map, eid, SendAction::new(msg, None), &InternalMode::Internal);
+    send_msg(&self.out_conn_map, eid, SendAction::new(msg, None), &self.this_eid);
   }
 
-  fn general_trace(&mut self, _: GeneralTraceMessage) {}
+  fn general_trace(&mut self, trace_msg: GeneralTraceMessage) {
+    write_trace_log(&self.trace_log, &self.slow_query_log, trace_msg);
+  }
 }
 
 impl FreeNodeIOCtx for ProdIOCtx {
@@ -114,6 +147,7 @@ impl FreeNodeIOCtx for ProdIOCtx {
     gossip: Arc<GossipData>,
     snapshot: TabletSnapshot,
     this_eid: EndpointId,
+    clock_id: ClockId,
     tablet_config: TabletConfig,
   ) {
     // Create mpsc queue for Slave-Tablet communication.
@@ -122,14 +156,23 @@ impl FreeNodeIOCtx for ProdIOCtx {
 
     // Spawn a new thread and create the Tablet.
     let mut io_ctx = ProdCoreIOCtx {
+      this_eid: self.this_eid.clone(),
       out_conn_map: self.out_conn_map.clone(),
       rand: XorShiftRng::from_entropy(),
       to_top: self.to_top.clone(),
+      trace_log: self.trace_log.clone(),
+      slow_query_log: self.slow_query_log.clone(),
     };
     thread::Builder::new()
       .name(format!("TabletGroup {}", snapshot.this_tid.0))
       .spawn(move || {
-        let mut tablet = TabletState::create_reconfig(gossip, snapshot, this_eid, tablet_config);
+        let mut tablet = TabletState::create_reconfig(
+          gossip,
+          snapshot,
+          this_eid,
+          clock_id,
+          tablet_config,
+        );
         loop {
           let tablet_msg = to_tablet_receiver.recv().unwrap();
           tablet.handle_input(&mut io_ctx, tablet_msg);
@@ -145,9 +188,12 @@ impl FreeNodeIOCtx for ProdIOCtx {
 
     // Spawn a new thread and create the Coord.
     let mut io_ctx = ProdCoreIOCtx {
+      this_eid: self.this_eid.clone(),
       out_conn_map: self.out_conn_map.clone(),
       rand: XorShiftRng::from_entropy(),
       to_top: self.to_top.clone(),
+      trace_log: self.trace_log.clone(),
+      slow_query_log: self.slow_query_log.clone(),
     };
     thread::Builder::new()
       .name(format!("CoordGroup {}", ctx.this_cid.0))
@@ -188,9 +234,12 @@ impl SlaveIOCtx for ProdIOCtx {
 
     // Spawn a new thread and create the Tablet.
     let mut io_ctx = ProdCoreIOCtx {
+      this_eid: self.this_eid.clone(),
       out_conn_map: self.out_conn_map.clone(),
       rand: XorShiftRng::from_entropy(),
       to_top: self.to_top.clone(),
+      trace_log: self.trace_log.clone(),
+      slow_query_log: self.slow_query_log.clone(),
     };
     thread::Builder::new()
       .name(format!("TabletGroup {}", ctx.this_tid.0))
@@ -272,10 +321,15 @@ impl Debug for ProdIOCtx {
 pub struct ProdCoreIOCtx {
   // Basic
   pub rand: XorShiftRng,
+  pub this_eid: EndpointId,
   pub out_conn_map: Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
 
   // Slave
   pub to_top: Sender<GenericInput>,
+
+  // Tracing
+  pub trace_log: Option<TraceLog>,
+  pub slow_query_log: Option<TraceLog>,
 }
 
 impl BasicIOCtx for ProdCoreIOCtx {
@@ -290,10 +344,12 @@ impl BasicIOCtx for ProdCoreIOCtx {
   }
 
   fn send(&mut self, eid: &EndpointId, msg: msg::NetworkMessage) {
-    send_msg(&self.out_conn_map, eid, SendAction::new(msg, None), &InternalMode::Internal);
+    send_msg(&self.out_conn_map, eid, SendAction::new(msg, None), &self.this_eid);
   }
 
-  fn general_trace(&mut self, _: GeneralTraceMessage) {}
+  fn general_trace(&mut self, trace_msg: GeneralTraceMessage) {
+    write_trace_log(&self.trace_log, &self.slow_query_log, trace_msg);
+  }
 }
 
 impl CoreIOCtx for ProdCoreIOCtx {
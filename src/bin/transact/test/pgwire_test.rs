@@ -0,0 +1,419 @@
+use super::{
+  is_session_set, query_cols, read_body, ColumnDesc, LeafCols, PgContext, PgRouter, Session,
+  BOOL_OID, INT4_OID, MAX_MESSAGE_LEN, PROTOCOL_VERSION, TEXT_OID,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use runiversal::common::{mk_t, ColType, EndpointId, Gen, GossipData, InternalMode, TableSchema};
+use runiversal::sql_parser::parse_dql;
+use runiversal::test_utils::{cn, mk_tab};
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{Cursor, Read, Write};
+use std::sync::{Arc, Mutex};
+
+// -----------------------------------------------------------------------------------------------
+//  Utilities
+// -----------------------------------------------------------------------------------------------
+
+/// A stream that reads from a fixed input, and collects everything that is written to it.
+struct MockStream {
+  input: Cursor<Vec<u8>>,
+  output: Vec<u8>,
+}
+
+impl Read for MockStream {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    self.input.read(buf)
+  }
+}
+
+impl Write for MockStream {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.output.write(buf)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// Returns a `GossipData` with the Table `t (id INT PRIMARY KEY, name TEXT, active BOOL)`
+/// and the Table `u (uid INT PRIMARY KEY, score BIGINT)`.
+fn mk_gossip() -> GossipData {
+  let mut gossip = GossipData::new(BTreeMap::new(), vec![]);
+  gossip.update(|view| {
+    let t_schema = TableSchema::new(
+      vec![(cn("id"), ColType::Int)],
+      vec![(cn("name"), ColType::String), (cn("active"), ColType::Bool)],
+    );
+    let u_schema =
+      TableSchema::new(vec![(cn("uid"), ColType::Int)], vec![(cn("score"), ColType::BigInt)]);
+    view.db_schema.insert((mk_tab("t"), Gen(0)), t_schema);
+    view.db_schema.insert((mk_tab("u"), Gen(0)), u_schema);
+    view.table_generation.write(&mk_tab("t"), Some((Gen(0), Gen(0))), mk_t(1));
+    view.table_generation.write(&mk_tab("u"), Some((Gen(0), Gen(0))), mk_t(1));
+  });
+  gossip
+}
+
+/// Creates a `Session` whose client sends `input`. The `Session` already has the
+/// `GossipData` from `mk_gossip`, so describing a query does not use the network.
+fn mk_session(input: Vec<u8>) -> Session<MockStream> {
+  let eid = EndpointId::new("127.0.0.1:0".to_string(), InternalMode::Internal);
+  let ctx = PgContext {
+    this_eid: eid.clone(),
+    out_conn_map: Arc::new(Mutex::new(BTreeMap::new())),
+    master_eid: eid,
+    router: PgRouter::new(),
+  };
+  let mut session = Session::new(ctx, MockStream { input: Cursor::new(input), output: vec![] });
+  session.opt_gossip = Some(mk_gossip());
+  session
+}
+
+fn put_cstr(buf: &mut Vec<u8>, s: &str) {
+  buf.extend_from_slice(s.as_bytes());
+  buf.push(0);
+}
+
+fn put_message(buf: &mut Vec<u8>, tag: u8, body: &[u8]) {
+  buf.push(tag);
+  buf.write_i32::<BigEndian>(body.len() as i32 + 4).unwrap();
+  buf.extend_from_slice(body);
+}
+
+fn mk_startup() -> Vec<u8> {
+  let mut body = vec![];
+  body.write_i32::<BigEndian>(PROTOCOL_VERSION).unwrap();
+  put_cstr(&mut body, "user");
+  put_cstr(&mut body, "postgres");
+  body.push(0);
+  let mut buf = vec![];
+  buf.write_i32::<BigEndian>(body.len() as i32 + 4).unwrap();
+  buf.extend_from_slice(&body);
+  buf
+}
+
+fn parse(buf: &mut Vec<u8>, name: &str, query: &str) {
+  let mut body = vec![];
+  put_cstr(&mut body, name);
+  put_cstr(&mut body, query);
+  body.write_i16::<BigEndian>(0).unwrap();
+  put_message(buf, b'P', &body);
+}
+
+/// Binds the text `params` to the statement `stmt_name`, with the given result format codes.
+fn bind(buf: &mut Vec<u8>, portal: &str, stmt_name: &str, params: &[&str], formats: &[i16]) {
+  let mut body = vec![];
+  put_cstr(&mut body, portal);
+  put_cstr(&mut body, stmt_name);
+  body.write_i16::<BigEndian>(0).unwrap();
+  body.write_i16::<BigEndian>(params.len() as i16).unwrap();
+  for param in params {
+    body.write_i32::<BigEndian>(param.len() as i32).unwrap();
+    body.extend_from_slice(param.as_bytes());
+  }
+  body.write_i16::<BigEndian>(formats.len() as i16).unwrap();
+  for format in formats {
+    body.write_i16::<BigEndian>(*format).unwrap();
+  }
+  put_message(buf, b'B', &body);
+}
+
+/// Sends a Describe, Execute, or Close, which all consist of a kind and a name.
+fn named(buf: &mut Vec<u8>, tag: u8, kind: u8, name: &str) {
+  let mut body = vec![kind];
+  put_cstr(&mut body, name);
+  put_message(buf, tag, &body);
+}
+
+fn execute(buf: &mut Vec<u8>, portal: &str) {
+  let mut body = vec![];
+  put_cstr(&mut body, portal);
+  body.write_i32::<BigEndian>(0).unwrap();
+  put_message(buf, b'E', &body);
+}
+
+/// Runs a `Session` whose client sends the StartupMessage followed by `messages` and a
+/// Terminate. This returns the messages the `Session` sent after startup.
+fn run_session(messages: Vec<u8>) -> Vec<(u8, Vec<u8>)> {
+  let mut input = mk_startup();
+  input.extend(messages);
+  put_message(&mut input, b'X', &[]);
+  let mut session = mk_session(input);
+  session.run().unwrap();
+  let mut output = &session.stream.output[..];
+  let mut messages = vec![];
+  while !output.is_empty() {
+    let tag = output.read_u8().unwrap();
+    let len = output.read_i32::<BigEndian>().unwrap();
+    let body = output[..(len - 4) as usize].to_vec();
+    output = &output[(len - 4) as usize..];
+    messages.push((tag, body));
+  }
+  // Skip the messages sent during startup, which end with the first ReadyForQuery.
+  let startup_len = messages.iter().position(|(tag, _)| *tag == b'Z').unwrap() + 1;
+  messages.split_off(startup_len)
+}
+
+/// Returns the tags of `messages`.
+fn tags(messages: &[(u8, Vec<u8>)]) -> String {
+  messages.iter().map(|(tag, _)| *tag as char).collect()
+}
+
+/// Returns the (name, type OID, format code) of every column of a RowDescription.
+fn row_description_cols(body: &[u8]) -> Vec<(String, i32, i16)> {
+  let mut body = body;
+  let mut cols = vec![];
+  for _ in 0..body.read_i16::<BigEndian>().unwrap() {
+    let pos = body.iter().position(|b| *b == 0).unwrap();
+    let name = String::from_utf8(body[..pos].to_vec()).unwrap();
+    body = &body[pos + 1..];
+    body.read_i32::<BigEndian>().unwrap();
+    body.read_i16::<BigEndian>().unwrap();
+    let oid = body.read_i32::<BigEndian>().unwrap();
+    body.read_i16::<BigEndian>().unwrap();
+    body.read_i32::<BigEndian>().unwrap();
+    let format = body.read_i16::<BigEndian>().unwrap();
+    cols.push((name, oid, format));
+  }
+  cols
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Framing
+// -----------------------------------------------------------------------------------------------
+
+#[test]
+fn read_body_test() {
+  // The length counts itself, so the body is 4 bytes shorter.
+  let mut input: &[u8] = b"abcdef";
+  assert_eq!(read_body(&mut input, 7, MAX_MESSAGE_LEN).unwrap(), b"abc".to_vec());
+  assert_eq!(input, b"def");
+  assert_eq!(read_body(&mut input, 4, MAX_MESSAGE_LEN).unwrap(), Vec::<u8>::new());
+
+  // Lengths that cannot count the length field itself are rejected.
+  for len in [-1, 0, 3, i32::MIN] {
+    let error = read_body(&mut input, len, MAX_MESSAGE_LEN).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  }
+
+  // Lengths above the maximum are rejected without reading anything.
+  let error = read_body(&mut input, 11, 10).unwrap_err();
+  assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  let error = read_body(&mut input, i32::MAX, MAX_MESSAGE_LEN).unwrap_err();
+  assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  assert_eq!(input, b"def");
+
+  // A body that ends early is an error.
+  let error = read_body(&mut input, 8, MAX_MESSAGE_LEN).unwrap_err();
+  assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn invalid_length_test() {
+  // A StartupMessage with a length that is too short.
+  let mut session = mk_session(vec![0, 0, 0, 2]);
+  assert_eq!(session.run().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+  // A StartupMessage with a length that is too long.
+  let mut input = vec![];
+  input.write_i32::<BigEndian>(1 << 20).unwrap();
+  input.write_i32::<BigEndian>(PROTOCOL_VERSION).unwrap();
+  let mut session = mk_session(input);
+  assert_eq!(session.run().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+  // A message after startup with a negative length.
+  let mut input = mk_startup();
+  input.push(b'Q');
+  input.write_i32::<BigEndian>(-8).unwrap();
+  let mut session = mk_session(input);
+  assert_eq!(session.run().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+  // A message after startup with a length that is too long.
+  let mut input = mk_startup();
+  input.push(b'Q');
+  input.write_i32::<BigEndian>(MAX_MESSAGE_LEN + 1).unwrap();
+  let mut session = mk_session(input);
+  assert_eq!(session.run().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Extended Query Protocol
+// -----------------------------------------------------------------------------------------------
+
+#[test]
+fn describe_statement_test() {
+  let mut buf = vec![];
+  parse(&mut buf, "s", "SELECT id, name AS n, active, id + 1 FROM t WHERE id = $1");
+  named(&mut buf, b'D', b'S', "s");
+  parse(&mut buf, "w", "INSERT INTO t (id, name) VALUES ($1, $2)");
+  named(&mut buf, b'D', b'S', "w");
+  put_message(&mut buf, b'S', &[]);
+  let messages = run_session(buf);
+  assert_eq!(tags(&messages), "1tT1tnZ");
+
+  // The parameters of unspecified type are described as text.
+  let mut param_desc = &messages[1].1[..];
+  assert_eq!(param_desc.read_i16::<BigEndian>().unwrap(), 1);
+  assert_eq!(param_desc.read_i32::<BigEndian>().unwrap(), TEXT_OID);
+
+  // The column types come from the Table schema.
+  assert_eq!(
+    row_description_cols(&messages[2].1),
+    vec![
+      ("id".to_string(), INT4_OID, 0),
+      ("n".to_string(), TEXT_OID, 0),
+      ("active".to_string(), BOOL_OID, 0),
+      ("?column?".to_string(), INT4_OID, 0),
+    ]
+  );
+
+  let mut param_desc = &messages[4].1[..];
+  assert_eq!(param_desc.read_i16::<BigEndian>().unwrap(), 2);
+}
+
+#[test]
+fn describe_portal_test() {
+  let mut buf = vec![];
+  parse(&mut buf, "", "SELECT * FROM t WHERE id = $1");
+  bind(&mut buf, "", "", &["1"], &[1]);
+  named(&mut buf, b'D', b'P', "");
+  put_message(&mut buf, b'S', &[]);
+  let messages = run_session(buf);
+  assert_eq!(tags(&messages), "12TZ");
+
+  // The result format codes of the Bind are used.
+  assert_eq!(
+    row_description_cols(&messages[2].1),
+    vec![
+      ("id".to_string(), INT4_OID, 1),
+      ("active".to_string(), BOOL_OID, 1),
+      ("name".to_string(), TEXT_OID, 1),
+    ]
+  );
+}
+
+#[test]
+fn extended_flow_test() {
+  // Statements that do not return rows are described with NoData.
+  let mut buf = vec![];
+  parse(&mut buf, "s", "SET search_path = public");
+  bind(&mut buf, "p", "s", &[], &[]);
+  named(&mut buf, b'D', b'P', "p");
+  execute(&mut buf, "p");
+  named(&mut buf, b'C', b'P', "p");
+  named(&mut buf, b'C', b'S', "s");
+  put_message(&mut buf, b'S', &[]);
+  let messages = run_session(buf);
+  assert_eq!(tags(&messages), "12nC33Z");
+  assert_eq!(messages[3].1, b"SET\0".to_vec());
+}
+
+#[test]
+fn extended_error_test() {
+  // After an error, every message is ignored until the next Sync.
+  let mut buf = vec![];
+  bind(&mut buf, "", "missing", &[], &[]);
+  parse(&mut buf, "s", "SET search_path = public");
+  execute(&mut buf, "");
+  put_message(&mut buf, b'S', &[]);
+  // The statement `s` was never created.
+  named(&mut buf, b'D', b'S', "s");
+  put_message(&mut buf, b'S', &[]);
+  // Normal processing resumes after the Sync.
+  parse(&mut buf, "s", "SET search_path = public");
+  named(&mut buf, b'D', b'S', "s");
+  put_message(&mut buf, b'S', &[]);
+  let messages = run_session(buf);
+  assert_eq!(tags(&messages), "EZEZ1tnZ");
+}
+
+#[test]
+fn session_set_test() {
+  assert!(is_session_set("SET search_path = public"));
+  assert!(is_session_set("SET extra_float_digits TO 3; SET application_name = 'psql'"));
+  // These are run by the system rather than ignored.
+  assert!(!is_session_set("SET TRANSACTION ISOLATION LEVEL SNAPSHOT"));
+  assert!(!is_session_set("SET TRANSACTION ISOLATION LEVEL SNAPSHOT; SELECT * FROM t"));
+  assert!(!is_session_set("SELECT * FROM t"));
+}
+
+#[test]
+fn bind_param_count_test() {
+  let mut buf = vec![];
+  parse(&mut buf, "s", "SELECT * FROM t WHERE id = $1");
+  bind(&mut buf, "", "s", &[], &[]);
+  put_message(&mut buf, b'S', &[]);
+  let messages = run_session(buf);
+  assert_eq!(tags(&messages), "1EZ");
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Describing
+// -----------------------------------------------------------------------------------------------
+
+fn describe(query: &str) -> Option<Vec<ColumnDesc>> {
+  let (query, _, _, _) = parse_dql(query).unwrap();
+  query_cols(&mk_gossip(), &LeafCols::new(), &[], &query).unwrap()
+}
+
+fn col(name: &str, opt_type: Option<ColType>) -> ColumnDesc {
+  ColumnDesc::new(name, opt_type)
+}
+
+#[test]
+fn query_cols_test() {
+  // Wildcards take the KeyCols first, and the JoinLeafs in the order of their names.
+  assert_eq!(
+    describe("SELECT * FROM u AS a JOIN t AS b ON a.uid = b.id"),
+    Some(vec![
+      col("uid", Some(ColType::Int)),
+      col("score", Some(ColType::BigInt)),
+      col("id", Some(ColType::Int)),
+      col("active", Some(ColType::Bool)),
+      col("name", Some(ColType::String)),
+    ])
+  );
+  assert_eq!(
+    describe("SELECT b.* FROM u AS a JOIN t AS b ON a.uid = b.id"),
+    describe("SELECT * FROM t"),
+  );
+
+  // Expressions, aggregates, and literals.
+  assert_eq!(
+    describe("SELECT score + id, id * 2, 'x', NULL, id > 1 FROM t JOIN u ON id = uid"),
+    Some(vec![
+      col("?column?", Some(ColType::BigInt)),
+      col("?column?", Some(ColType::Int)),
+      col("?column?", Some(ColType::String)),
+      col("?column?", None),
+      col("?column?", Some(ColType::Bool)),
+    ])
+  );
+  assert_eq!(
+    describe("SELECT COUNT(name), SUM(score) AS total, AVG(score) FROM u JOIN t ON id = uid"),
+    Some(vec![
+      col("?column?", Some(ColType::Int)),
+      col("total", Some(ColType::BigInt)),
+      col("?column?", Some(ColType::BigInt)),
+    ])
+  );
+
+  // CTEs, Derived Tables, and Subqueries.
+  assert_eq!(
+    describe(
+      "WITH c AS (SELECT score AS s FROM u) \
+       SELECT d.s, (SELECT name FROM t WHERE id = d.s) AS n FROM (SELECT s FROM c) AS d"
+    ),
+    Some(vec![col("s", Some(ColType::BigInt)), col("n", Some(ColType::String))])
+  );
+
+  // Writes do not return rows.
+  assert_eq!(describe("UPDATE t SET name = 'a' WHERE id = 1"), None);
+  assert_eq!(describe("DELETE FROM t WHERE id = 1"), None);
+
+  // Tables that do not exist are returned as an error.
+  let (query, _, _, _) = parse_dql("SELECT * FROM v").unwrap();
+  assert_eq!(query_cols(&mk_gossip(), &LeafCols::new(), &[], &query), Err(mk_tab("v")));
+}
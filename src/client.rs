@@ -0,0 +1,614 @@
+use crate::common::{
+  mk_rid, rand_string, ColName, ColVal, EndpointId, InternalMode, PaxosGroupId, QueryResult,
+  RequestId, Timestamp,
+};
+use crate::message as msg;
+use crate::net::{mk_address, send_msg, start_acceptor_thread, GenericInputTrait, SendAction};
+use crate::sql_ast::iast;
+use crate::sql_parser::{
+  is_ddl, is_read_only, parse_admin_command, parse_prepared_stmt_command, AdminCommand,
+  PreparedStmtCommand,
+};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// -----------------------------------------------------------------------------------------------
+//  Client
+// -----------------------------------------------------------------------------------------------
+// A `Client` lets a program talk to the system over the network the same way that the client
+// CLI does. DQL is sent to a Slave Leader, and DDL and Sharding operations are sent to the
+// Master Leader. Every call blocks until the response arrives.
+//
+// The Leaders are discovered by sending an `ExternalMetadataRequest` to any node in the system
+// (which is forwarded to the Master if needed). If a request fails because Leadership moved,
+// the `Client` rediscovers the Leaders and resends it, as long as that cannot cause the request
+// to take effect twice.
+
+/// Config for a `Client`.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+  /// How long to wait for a response before cancelling the request.
+  pub timeout: Duration,
+  /// How many times to retry a request that was aborted for a transient reason.
+  pub max_retries: u32,
+  /// How long to wait before retrying a request.
+  pub retry_delay: Duration,
+  /// The priority of the queries sent by this `Client`.
+  pub priority: msg::TransactionPriority,
+  /// How long to wait for a node to answer an `ExternalMetadataRequest` before trying
+  /// the next one.
+  pub discovery_timeout: Duration,
+}
+
+impl Default for ClientConfig {
+  fn default() -> ClientConfig {
+    ClientConfig {
+      timeout: Duration::from_secs(30),
+      max_retries: 3,
+      retry_delay: Duration::from_millis(500),
+      priority: msg::TransactionPriority::Normal,
+      discovery_timeout: Duration::from_secs(2),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+  /// No response arrived within `ClientConfig::timeout`, or no node answered while
+  /// discovering the Leaders. The request was cancelled, but it might have still taken effect.
+  Timeout,
+  /// There is no Slave Leader to send DQL to (e.g. if no Slave Groups exist yet).
+  NoSlaveLeader,
+  QueryAborted(msg::ExternalAbortedData),
+  DDLAborted(msg::ExternalDDLQueryAbortData),
+  ShardingAborted(msg::ExternalShardingAbortData),
+}
+
+impl ClientError {
+  /// Returns true iff trying the request again might succeed.
+  pub fn is_transient(&self) -> bool {
+    match self {
+      ClientError::QueryAborted(msg::ExternalAbortedData::NonUniqueRequestId)
+      | ClientError::QueryAborted(msg::ExternalAbortedData::TooManyRetries)
+      | ClientError::DDLAborted(msg::ExternalDDLQueryAbortData::NonUniqueRequestId)
+      | ClientError::DDLAborted(msg::ExternalDDLQueryAbortData::Unknown)
+      | ClientError::ShardingAborted(msg::ExternalShardingAbortData::NonUniqueRequestId)
+      | ClientError::ShardingAborted(msg::ExternalShardingAbortData::Unknown) => true,
+      _ => self.is_leadership_change(),
+    }
+  }
+
+  /// Returns true iff the request was not executed because it was sent to a node that
+  /// is not (or is no longer) a Leader.
+  fn is_leadership_change(&self) -> bool {
+    match self {
+      ClientError::NoSlaveLeader
+      | ClientError::QueryAborted(msg::ExternalAbortedData::NotLeader)
+      | ClientError::DDLAborted(msg::ExternalDDLQueryAbortData::NotLeader)
+      | ClientError::ShardingAborted(msg::ExternalShardingAbortData::NotLeader) => true,
+      _ => false,
+    }
+  }
+}
+
+/// An input containing incoming network data.
+struct ClientInput {
+  message: msg::NetworkMessage,
+}
+
+impl GenericInputTrait for ClientInput {
+  fn from_network(_: EndpointId, message: msg::NetworkMessage) -> ClientInput {
+    ClientInput { message }
+  }
+}
+
+/// The kinds of requests, which determine how they are cancelled.
+#[derive(Debug, Clone)]
+enum RequestKind {
+  Query,
+  DDL,
+  Sharding,
+  Metadata,
+  Admin,
+}
+
+/// A request that is awaiting its response.
+#[derive(Debug, Clone)]
+struct InFlight {
+  kind: RequestKind,
+  eid: EndpointId,
+  request_id: RequestId,
+}
+
+/// Used to cancel the request that a `Client` is currently waiting on from another thread.
+#[derive(Clone)]
+pub struct CancelHandle {
+  this_eid: EndpointId,
+  out_conn_map: Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
+  in_flight: Arc<Mutex<Option<InFlight>>>,
+}
+
+impl CancelHandle {
+  /// Cancels the current request, if there is one. If the system cancels it in time, the
+  /// request fails with a `CancelConfirmed` abort.
+  pub fn cancel(&self) {
+    if let Some(in_flight) = self.in_flight.lock().unwrap().clone() {
+      let sender_eid = self.this_eid.clone();
+      let request_id = in_flight.request_id;
+      let network_msg = match in_flight.kind {
+        RequestKind::Query => msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::CancelExternalQuery(msg::CancelExternalQuery {
+            sender_eid,
+            request_id,
+          }),
+        )),
+        RequestKind::DDL => msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::CancelExternalDDLQuery(msg::CancelExternalDDLQuery {
+            sender_eid,
+            request_id,
+          }),
+        )),
+        RequestKind::Sharding => msg::NetworkMessage::Master(
+          msg::MasterMessage::MasterExternalReq(msg::MasterExternalReq::CancelExternalSharding(
+            msg::CancelExternalSharding { sender_eid, request_id },
+          )),
+        ),
+        // Metadata and Admin requests are not cancellable.
+        RequestKind::Metadata | RequestKind::Admin => return,
+      };
+      send_msg(
+        &self.out_conn_map,
+        &in_flight.eid,
+        SendAction::new(network_msg, None),
+        &self.this_eid,
+      );
+    }
+  }
+}
+
+pub struct Client {
+  this_eid: EndpointId,
+  out_conn_map: Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
+  receiver: Receiver<ClientInput>,
+  rand: XorShiftRng,
+  config: ClientConfig,
+  in_flight: Arc<Mutex<Option<InFlight>>>,
+  /// The nodes that the `Client` was started with, which are used to discover the Leaders
+  /// if none of the Master nodes that are known respond.
+  seed_eids: Vec<EndpointId>,
+  /// The Master nodes, as of the last `ExternalMetadataResponse`.
+  master_eids: Vec<EndpointId>,
+  /// The Leaders that requests are sent to. DQL is sent to `slave_eids[slave_idx]`.
+  master_eid: EndpointId,
+  slave_eids: Vec<EndpointId>,
+  slave_idx: usize,
+  /// The queries of the statements created with `PREPARE`, keyed by their name.
+  prepared_stmts: BTreeMap<String, String>,
+}
+
+impl Client {
+  /// Starts accepting connections at `this_address` (which the system sends responses to),
+  /// and discovers the current Leaderships from the first of `seed_addresses` that answers.
+  /// These can be the addresses of any nodes in the system. Note that `this_address` stays
+  /// bound for the lifetime of the process.
+  pub fn connect(
+    this_address: &str,
+    seed_addresses: &[&str],
+    config: ClientConfig,
+  ) -> Result<Client, ClientError> {
+    let (to_client_sender, receiver) = mpsc::channel::<ClientInput>();
+    let this_address = mk_address(this_address);
+    start_acceptor_thread(&to_client_sender, this_address.clone());
+
+    let mut rand = XorShiftRng::from_entropy();
+    let this_eid =
+      EndpointId::new(this_address, InternalMode::External { salt: rand_string(&mut rand) });
+    let seed_eids: Vec<EndpointId> = seed_addresses
+      .iter()
+      .map(|address| EndpointId::new(mk_address(address), InternalMode::Internal))
+      .collect();
+    let mut client = Client {
+      this_eid,
+      out_conn_map: Arc::new(Mutex::new(BTreeMap::new())),
+      receiver,
+      rand,
+      config,
+      in_flight: Arc::new(Mutex::new(None)),
+      master_eid: seed_eids.first().cloned().ok_or(ClientError::Timeout)?,
+      seed_eids,
+      master_eids: vec![],
+      slave_eids: vec![],
+      slave_idx: 0,
+      prepared_stmts: BTreeMap::new(),
+    };
+    client.refresh_leaders()?;
+    Ok(client)
+  }
+
+  /// Returns a `CancelHandle` for cancelling requests from another thread.
+  pub fn cancel_handle(&self) -> CancelHandle {
+    CancelHandle {
+      this_eid: self.this_eid.clone(),
+      out_conn_map: self.out_conn_map.clone(),
+      in_flight: self.in_flight.clone(),
+    }
+  }
+
+  /// Sets the Master that DDL and Sharding operations are sent to. This is only used
+  /// until the Leaders are next rediscovered.
+  pub fn set_master_target(&mut self, address: &str) {
+    self.master_eid = EndpointId::new(mk_address(address), InternalMode::Internal);
+  }
+
+  /// Sets the Slave that DQL is sent to. This is only used until the Leaders are
+  /// next rediscovered.
+  pub fn set_slave_target(&mut self, address: &str) {
+    self.slave_eids = vec![EndpointId::new(mk_address(address), InternalMode::Internal)];
+    self.slave_idx = 0;
+  }
+
+  /// Rediscovers the current Leaderships, and targets those.
+  pub fn refresh_leaders(&mut self) -> Result<(), ClientError> {
+    self.metadata()?;
+    Ok(())
+  }
+
+  /// Fetches the metadata of the system, and targets the Leaderships in it. The Master
+  /// Leader is asked first, followed by the other Master nodes and then the seed nodes.
+  pub fn metadata(&mut self) -> Result<msg::ExternalMetadataResponse, ClientError> {
+    let mut candidates = vec![self.master_eid.clone()];
+    for eid in self.master_eids.iter().chain(self.seed_eids.iter()) {
+      if !candidates.contains(eid) {
+        candidates.push(eid.clone());
+      }
+    }
+
+    for eid in candidates {
+      let sender_eid = self.this_eid.clone();
+      let timeout = self.config.discovery_timeout;
+      match self.request(RequestKind::Metadata, &eid, timeout, |request_id| {
+        msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::ExternalMetadataRequest(msg::ExternalMetadataRequest {
+            sender_eid,
+            request_id,
+          }),
+        ))
+      }) {
+        Ok(message) => {
+          let resp = cast!(msg::ExternalMessage::ExternalMetadataResponse, message).unwrap();
+          self.update_leaders(&resp);
+          return Ok(resp);
+        }
+        Err(ClientError::Timeout) => continue,
+        Err(error) => return Err(error),
+      }
+    }
+    Err(ClientError::Timeout)
+  }
+
+  /// Targets the Leaderships in `resp`.
+  fn update_leaders(&mut self, resp: &msg::ExternalMetadataResponse) {
+    self.master_eids = resp.gossip_data.get().master_address_config.clone();
+    self.slave_eids.clear();
+    for (gid, lid) in &resp.leader_map {
+      match gid {
+        PaxosGroupId::Master => self.master_eid = lid.eid.clone(),
+        PaxosGroupId::Slave(_) => self.slave_eids.push(lid.eid.clone()),
+      }
+    }
+    if !self.slave_eids.is_empty() {
+      self.slave_idx %= self.slave_eids.len();
+    }
+  }
+
+  /// Executes `query`, sending it as DDL if it parses as such, and as DQL otherwise.
+  /// The result of DQL is returned. `PREPARE`, `EXECUTE`, and `DEALLOCATE` manage the
+  /// prepared statements of this `Client`, and `SHOW QUERIES` and `KILL QUERY` are sent
+  /// as Admin requests.
+  pub fn execute(&mut self, query: &str) -> Result<Option<QueryResult>, ClientError> {
+    if let Some(command) = parse_admin_command(query) {
+      match command.map_err(mk_parse_error)? {
+        AdminCommand::ShowQueries => Ok(Some(running_queries_result(self.show_queries()?))),
+        AdminCommand::KillQuery { request_id } => {
+          let killed = self.kill_query(RequestId(request_id))?;
+          let mut result = QueryResult::new(vec![Some(ColName("killed".to_string()))]);
+          result.add_row(vec![Some(ColVal::Bool(killed))]);
+          Ok(Some(result))
+        }
+      }
+    } else if let Some(command) = parse_prepared_stmt_command(query) {
+      match command.map_err(mk_parse_error)? {
+        PreparedStmtCommand::Prepare { name, query } => {
+          self.prepared_stmts.insert(name, query);
+          Ok(None)
+        }
+        PreparedStmtCommand::Execute { name, params } => {
+          let query = self.prepared_stmts.get(&name).cloned().ok_or_else(|| {
+            mk_parse_error(format!("Prepared statement {} does not exist.", name))
+          })?;
+          Ok(Some(self.query_with_params(&query, &params)?))
+        }
+        PreparedStmtCommand::Deallocate { name } => {
+          self.prepared_stmts.remove(&name);
+          Ok(None)
+        }
+      }
+    } else if is_ddl(query) {
+      self.ddl(query)?;
+      Ok(None)
+    } else {
+      Ok(Some(self.query(query)?))
+    }
+  }
+
+  /// Executes the DQL `query`.
+  pub fn query(&mut self, query: &str) -> Result<QueryResult, ClientError> {
+    self.query_with_params(query, &[])
+  }
+
+  /// Executes the DQL `query`, binding `params[n - 1]` to each `$n` parameter in it. The
+  /// values are sent separately from `query`, so they are never interpreted as SQL.
+  pub fn query_with_params(
+    &mut self,
+    query: &str,
+    params: &[iast::Value],
+  ) -> Result<QueryResult, ClientError> {
+    self.with_retries(is_read_only(query), |client| {
+      let slave_eid = client.slave_eids.get(client.slave_idx).cloned();
+      let slave_eid = slave_eid.ok_or(ClientError::NoSlaveLeader)?;
+      let sender_eid = client.this_eid.clone();
+      let timeout = client.config.timeout;
+      let priority = client.config.priority;
+      let message = client.request(RequestKind::Query, &slave_eid, timeout, |request_id| {
+        msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::PerformExternalQuery(msg::PerformExternalQuery {
+            sender_eid,
+            request_id,
+            query: query.to_string(),
+            params: params.to_vec(),
+            opt_timeout_ms: Some(timeout.as_millis()),
+            opt_max_retries: None,
+            priority,
+            opt_max_staleness_ms: None,
+          }),
+        ))
+      })?;
+      match message {
+        msg::ExternalMessage::ExternalQuerySuccess(success) => Ok(success.result),
+        msg::ExternalMessage::ExternalQueryAborted(aborted) => {
+          Err(ClientError::QueryAborted(aborted.payload))
+        }
+        _ => unreachable!(),
+      }
+    })
+  }
+
+  /// Executes the DDL `query`, returning the Timestamp it committed at.
+  pub fn ddl(&mut self, query: &str) -> Result<Timestamp, ClientError> {
+    self.with_retries(false, |client| {
+      let master_eid = client.master_eid.clone();
+      let sender_eid = client.this_eid.clone();
+      let timeout = client.config.timeout;
+      let message = client.request(RequestKind::DDL, &master_eid, timeout, |request_id| {
+        msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::PerformExternalDDLQuery(msg::PerformExternalDDLQuery {
+            sender_eid,
+            request_id,
+            query: query.to_string(),
+          }),
+        ))
+      })?;
+      match message {
+        msg::ExternalMessage::ExternalDDLQuerySuccess(success) => Ok(success.timestamp),
+        msg::ExternalMessage::ExternalDDLQueryAborted(aborted) => {
+          Err(ClientError::DDLAborted(aborted.payload))
+        }
+        _ => unreachable!(),
+      }
+    })
+  }
+
+  /// Performs the Sharding operation `split`, returning the Timestamp it committed at.
+  pub fn split(&mut self, split: msg::SplitShardingOp) -> Result<Timestamp, ClientError> {
+    self.with_retries(false, |client| {
+      let master_eid = client.master_eid.clone();
+      let sender_eid = client.this_eid.clone();
+      let timeout = client.config.timeout;
+      let message = client.request(RequestKind::Sharding, &master_eid, timeout, |request_id| {
+        msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::PerformExternalSharding(msg::PerformExternalSharding {
+            sender_eid,
+            request_id,
+            op: msg::ShardingOp::Split(split.clone()),
+          }),
+        ))
+      })?;
+      match message {
+        msg::ExternalMessage::ExternalShardingSuccess(success) => Ok(success.timestamp),
+        msg::ExternalMessage::ExternalShardingAborted(aborted) => {
+          Err(ClientError::ShardingAborted(aborted.payload))
+        }
+        _ => unreachable!(),
+      }
+    })
+  }
+
+  /// Lists the ESs running at every Slave Leader (for `SHOW QUERIES`).
+  pub fn show_queries(&mut self) -> Result<Vec<msg::RunningQuery>, ClientError> {
+    let mut queries = Vec::<msg::RunningQuery>::new();
+    for slave_eid in self.slave_eids.clone() {
+      let sender_eid = self.this_eid.clone();
+      let timeout = self.config.timeout;
+      let message = self.request(RequestKind::Admin, &slave_eid, timeout, |request_id| {
+        msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::ExternalShowQueries(msg::ExternalShowQueries {
+            sender_eid,
+            request_id,
+          }),
+        ))
+      })?;
+      let resp = cast!(msg::ExternalMessage::ExternalShowQueriesResponse, message).unwrap();
+      queries.extend(resp.queries);
+    }
+    Ok(queries)
+  }
+
+  /// Kills the DQL request `target_request_id` (for `KILL QUERY`), returning true iff it was
+  /// killed. Since we do not know which Slave the request was sent to, we ask every Slave
+  /// Leader until one of them kills it.
+  pub fn kill_query(&mut self, target_request_id: RequestId) -> Result<bool, ClientError> {
+    for slave_eid in self.slave_eids.clone() {
+      let sender_eid = self.this_eid.clone();
+      let target_request_id = target_request_id.clone();
+      let timeout = self.config.timeout;
+      let message = self.request(RequestKind::Admin, &slave_eid, timeout, |request_id| {
+        msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::ExternalKillQuery(msg::ExternalKillQuery {
+            sender_eid,
+            request_id,
+            target_request_id,
+          }),
+        ))
+      })?;
+      let resp = cast!(msg::ExternalMessage::ExternalKillQueryResponse, message).unwrap();
+      if resp.result == msg::KillQueryResult::Killed {
+        return Ok(true);
+      }
+    }
+    Ok(false)
+  }
+
+  /// Moves the Leadership of `gid` to `target_eid` (e.g. before taking the current Leader
+  /// down for maintenance). The target first catches up on the PaxosLog, so no requests
+  /// are lost, although the ones that are in-flight at the old Leader are aborted.
+  pub fn transfer_leadership(
+    &mut self,
+    gid: PaxosGroupId,
+    target_eid: EndpointId,
+  ) -> Result<msg::TransferLeadershipResult, ClientError> {
+    let resp = self.metadata()?;
+    let leader_eid = match resp.leader_map.get(&gid) {
+      Some(lid) => lid.eid.clone(),
+      None => return Ok(msg::TransferLeadershipResult::InvalidTarget),
+    };
+    let sender_eid = self.this_eid.clone();
+    let timeout = self.config.timeout;
+    let message = self.request(RequestKind::Admin, &leader_eid, timeout, |request_id| {
+      let req = msg::ExternalTransferLeadership { sender_eid, request_id, gid, target_eid };
+      match &req.gid {
+        PaxosGroupId::Master => msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(
+          msg::MasterExternalReq::ExternalTransferLeadership(req),
+        )),
+        PaxosGroupId::Slave(_) => msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(
+          msg::SlaveExternalReq::ExternalTransferLeadership(req),
+        )),
+      }
+    })?;
+    let resp = cast!(msg::ExternalMessage::ExternalTransferLeadershipResponse, message).unwrap();
+    if let msg::TransferLeadershipResult::Transferred(_) = &resp.result {
+      // Target the new Leaders.
+      let _ = self.refresh_leaders();
+    }
+    Ok(resp.result)
+  }
+
+  /// Runs `attempt` until it succeeds, fails with an error that is not transient, or
+  /// `ClientConfig::max_retries` is exhausted. If `attempt` fails because Leadership might
+  /// have moved, the Leaders are rediscovered first. Timeouts are only retried if
+  /// `resendable` (i.e. if running the request twice has no effect).
+  fn with_retries<T, F: FnMut(&mut Client) -> Result<T, ClientError>>(
+    &mut self,
+    resendable: bool,
+    mut attempt: F,
+  ) -> Result<T, ClientError> {
+    let mut num_retries = 0;
+    loop {
+      match attempt(self) {
+        Err(error) => {
+          let is_timeout = error == ClientError::Timeout;
+          if error.is_leadership_change() || is_timeout {
+            // Move onto the next Slave Leader in case the one we used is gone, and
+            // rediscover the Leaders so that the next request is routed properly.
+            self.slave_idx += 1;
+            let _ = self.refresh_leaders();
+          }
+
+          let retryable = error.is_transient() || (is_timeout && resendable);
+          if retryable && num_retries < self.config.max_retries {
+            num_retries += 1;
+            thread::sleep(self.config.retry_delay);
+          } else {
+            return Err(error);
+          }
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Sends the message constructed by `mk_msg` (using a new `RequestId`) to `eid` and waits
+  /// for the response. If it does not arrive within `timeout`, the request is cancelled.
+  fn request<F: FnOnce(RequestId) -> msg::NetworkMessage>(
+    &mut self,
+    kind: RequestKind,
+    eid: &EndpointId,
+    timeout: Duration,
+    mk_msg: F,
+  ) -> Result<msg::ExternalMessage, ClientError> {
+    let request_id = mk_rid(&mut self.rand);
+    *self.in_flight.lock().unwrap() =
+      Some(InFlight { kind, eid: eid.clone(), request_id: request_id.clone() });
+    let network_msg = mk_msg(request_id.clone());
+    send_msg(&self.out_conn_map, eid, SendAction::new(network_msg, None), &self.this_eid);
+
+    // Wait for the response, skipping the responses of prior requests that timed out.
+    let deadline = Instant::now() + timeout;
+    let result = loop {
+      let timeout = deadline.saturating_duration_since(Instant::now());
+      match self.receiver.recv_timeout(timeout) {
+        Ok(ClientInput { message: msg::NetworkMessage::External(external_msg) }) => {
+          if external_msg.request_id() == &request_id {
+            break Ok(external_msg);
+          }
+        }
+        Ok(_) => {}
+        Err(RecvTimeoutError::Timeout) => {
+          self.cancel_handle().cancel();
+          break Err(ClientError::Timeout);
+        }
+        Err(RecvTimeoutError::Disconnected) => unreachable!(),
+      }
+    };
+    *self.in_flight.lock().unwrap() = None;
+    result
+  }
+}
+
+/// Renders the result of `SHOW QUERIES` as a table.
+pub fn running_queries_result(queries: Vec<msg::RunningQuery>) -> QueryResult {
+  let cols = ["server", "es_type", "query_id", "root_query_id", "request_id", "state", "age_ms"];
+  let mut result =
+    QueryResult::new(cols.iter().map(|col| Some(ColName(col.to_string()))).collect());
+  for query in queries {
+    result.add_row(vec![
+      Some(ColVal::String(query.server)),
+      Some(ColVal::String(query.es_type)),
+      Some(ColVal::String(query.query_id.0)),
+      Some(ColVal::String(query.root_query_id.0)),
+      query.request_id.map(|request_id| ColVal::String(request_id.0)),
+      Some(ColVal::String(query.state)),
+      query.age_ms.map(|age_ms| ColVal::BigInt(age_ms.min(i64::MAX as u128) as i64)),
+    ]);
+  }
+  result
+}
+
+/// Constructs the error for a statement that the `Client` could not make sense of.
+fn mk_parse_error(err_msg: String) -> ClientError {
+  ClientError::QueryAborted(msg::ExternalAbortedData::ParseError(err_msg))
+}
@@ -1,21 +1,22 @@
-use crate::coord::{CoordContext, CoordForwardMsg, CoordState};
+use crate::coord::{CoordContext, CoordForwardMsg, CoordState, SlowQuery};
 use crate::expression::does_types_match;
 use crate::master::MasterTimerInput;
 use crate::master_query_planning_es::ColPresenceReq;
 use crate::message as msg;
+use crate::metrics::{metrics, M_CLOCK_SKEW_DETECTED};
 use crate::multiversion_map::MVM;
 use crate::node::{GenericInput, GenericTimerInput};
 use crate::server::{CTServerContext, CommonQuery};
 use crate::slave::{SlaveBackMessage, SlaveTimerInput};
 use crate::sql_ast::proc;
 use crate::tablet::{TabletConfig, TabletContext, TabletForwardMsg, TabletSnapshot, TabletState};
+use crate::trace::Span;
 use rand::distributions::Alphanumeric;
 use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
-use std::hash::Hash;
 use std::sync::Arc;
 
 #[path = "test/common_test.rs"]
@@ -25,7 +26,8 @@ pub mod common_test;
 //  Basic
 // -----------------------------------------------------------------------------------------------
 
-/// These messages are primarily for testing purposes.
+/// These messages are primarily for testing purposes. The exceptions are `Span` and `SlowQuery`,
+/// which production nodes write to their trace log and slow query log, respectively.
 pub enum GeneralTraceMessage {
   /// This should be called every time a `external_request_id_map` is updated.
   RequestIdQueryId(RequestId, QueryId),
@@ -35,6 +37,10 @@ pub enum GeneralTraceMessage {
   /// This indicates a reconfiguration happened at `PaxosGroupId`. Recall that such events can
   /// be identified uniquely globally by the new nodes they introduced.
   Reconfig(PaxosGroupId, Vec<EndpointId>),
+  /// This is emitted every time a `Span` of a distributed trace finishes.
+  Span(Span),
+  /// This is emitted every time an External request takes too long to finish.
+  SlowQuery(SlowQuery),
 }
 
 pub trait BasicIOCtx<NetworkMessageT = msg::NetworkMessage> {
@@ -60,6 +66,7 @@ pub trait FreeNodeIOCtx: BasicIOCtx {
     gossip: Arc<GossipData>,
     snapshot: TabletSnapshot,
     this_eid: EndpointId,
+    clock_id: ClockId,
     tablet_config: TabletConfig,
   );
 
@@ -314,6 +321,7 @@ pub enum ColType {
   Int,
   Bool,
   String,
+  BigInt,
 }
 
 /// The values that the columns of a Relational Tablet can take on.
@@ -322,6 +330,7 @@ pub enum ColVal {
   Int(i32),
   Bool(bool),
   String(String),
+  BigInt(i64),
 }
 
 /// This is a nullable `ColVal`. We use this alias for self-documentation
@@ -357,10 +366,14 @@ impl PrimaryKey {
 /// prefixes (instead of the whole `PrimaryKey`). (Notice that this would not make
 /// `row_region` slicing any more expensive if we continue to use the first KeyCol
 /// most of the time).
+///
+/// If `hash` is present, the Table is hash-partitioned, and this range only contains the
+/// keys that fall into that `HashBucket`. In this case, `start` and `end` are `None`.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TabletKeyRange {
   pub start: Option<ColVal>,
   pub end: Option<ColVal>,
+  pub hash: Option<HashBucket>,
 }
 
 impl TabletKeyRange {
@@ -384,6 +397,13 @@ impl TabletKeyRange {
   /// This function returns `false` when `pkey` falls outside of `Self`. Importantly,
   /// the `ColType` of the first key in `pkey` (if it exists) must match that of `Self`.
   pub fn contains_pkey(&self, pkey: &PrimaryKey) -> bool {
+    if let Some(hash) = &self.hash {
+      if let Some(hashed_key) = pkey.cols.get(hash.key_idx) {
+        if !hash.contains(hashed_key) {
+          return false;
+        }
+      }
+    }
     if let Some(first_key) = pkey.cols.first() {
       self.contains(first_key)
     } else {
@@ -411,6 +431,55 @@ impl TabletKeyRange {
   }
 }
 
+/// One bucket of a hash-partitioned Table. A `PrimaryKey` falls in this bucket if the KeyCol
+/// at `key_idx` hashes to `bucket` (out of `num_buckets`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HashBucket {
+  pub key_idx: usize,
+  pub bucket: u32,
+  pub num_buckets: u32,
+}
+
+impl HashBucket {
+  /// Returns `true` if the `key` (which should be a value of the KeyCol at `key_idx`)
+  /// hashes into this bucket.
+  pub fn contains(&self, key: &ColVal) -> bool {
+    hash_bucket(key, self.num_buckets) == self.bucket
+  }
+}
+
+/// Computes which of the `num_buckets` the `col_val` hashes to. Every node must agree on
+/// this, so we use FNV-1a over a fixed encoding rather than a `std` hasher (whose output
+/// may differ across builds).
+pub fn hash_bucket(col_val: &ColVal, num_buckets: u32) -> u32 {
+  let mut bytes = Vec::<u8>::new();
+  match col_val {
+    ColVal::Int(val) => {
+      bytes.push(0);
+      bytes.extend_from_slice(&val.to_le_bytes());
+    }
+    ColVal::Bool(val) => {
+      bytes.push(1);
+      bytes.push(*val as u8);
+    }
+    ColVal::String(val) => {
+      bytes.push(2);
+      bytes.extend_from_slice(val.as_bytes());
+    }
+    ColVal::BigInt(val) => {
+      bytes.push(3);
+      bytes.extend_from_slice(&val.to_le_bytes());
+    }
+  }
+
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in bytes {
+    hash ^= byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  (hash % num_buckets as u64) as u32
+}
+
 /// A Type used to represent a generation.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
 pub struct Gen(pub u64);
@@ -444,7 +513,8 @@ pub enum InternalMode {
 /// clients, and other nodes in the network.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct EndpointId {
-  pub ip: String,
+  /// The `host:port` address that this endpoint accepts connections at.
+  pub address: String,
   /// Internal `EndpointId` are network endpoints that belong to processes within the
   /// system. In particular, these are the Slave Nodes, Master Nodes, Free Nodes, etc.
   /// Processes outside of the system includes the user. In practice, `mode` is
@@ -454,8 +524,8 @@ pub struct EndpointId {
 }
 
 impl EndpointId {
-  pub fn new(ip: String, is_internal: InternalMode) -> EndpointId {
-    EndpointId { ip, mode: is_internal }
+  pub fn new(address: String, is_internal: InternalMode) -> EndpointId {
+    EndpointId { address, mode: is_internal }
   }
 }
 
@@ -742,6 +812,25 @@ impl QueryResult {
 pub struct TableSchema {
   pub key_cols: Vec<(ColName, ColType)>,
   pub val_cols: MVM<ColName, ColType>,
+  /// The DEFAULT values of the ValCols, keyed by the `ColName` and the `Timestamp` of the
+  /// version in `val_cols` it belongs to. A present row with no value written to the column
+  /// since that `Timestamp` resolves to this value, rather than `NULL`.
+  pub val_col_defaults: BTreeMap<(ColName, Timestamp), ColVal>,
+  /// The versions of the ValCols (keyed like `val_col_defaults`) that take over the data of
+  /// another version, due to a RENAME COLUMN or an ALTER COLUMN TYPE. A present row with no
+  /// value written to the column since that `Timestamp` resolves to its value in the
+  /// `ColSource` at that `Timestamp` (converted to the type of the version), rather than to
+  /// the DEFAULT. This way, the data does not need to be rewritten for a RENAME COLUMN. For
+  /// an ALTER COLUMN TYPE, this is only used until the data is rewritten (see `ColConversion`).
+  pub val_col_sources: BTreeMap<(ColName, Timestamp), ColSource>,
+}
+
+/// The version of a ValCol whose data another version of a ValCol takes over.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ColSource {
+  pub col: ColName,
+  /// The `Timestamp` of the version in `TableSchema::val_cols`.
+  pub col_timestamp: Timestamp,
 }
 
 impl TableSchema {
@@ -754,7 +843,40 @@ impl TableSchema {
       // existance maps to None.
       mvm.write(&col_name, Some(col_type), mk_t(1));
     }
-    TableSchema { key_cols, val_cols: mvm }
+    TableSchema {
+      key_cols,
+      val_cols: mvm,
+      val_col_defaults: BTreeMap::new(),
+      val_col_sources: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the DEFAULT value of the version of `col` that was written at `col_timestamp`.
+  pub fn get_default(&self, col: &ColName, col_timestamp: &Timestamp) -> ColValN {
+    self.val_col_defaults.get(&(col.clone(), col_timestamp.clone())).cloned()
+  }
+
+  /// Returns the DEFAULT value of the version of `col` that is present at `timestamp`.
+  pub fn get_default_static(&self, col: &ColName, timestamp: &Timestamp) -> ColValN {
+    let (col_timestamp, _) = self.val_cols.static_read_version(col, timestamp)?;
+    self.get_default(col, col_timestamp)
+  }
+
+  /// Sets the DEFAULT value of the version of `col` that was written at `col_timestamp`.
+  pub fn set_default(&mut self, col: &ColName, col_timestamp: &Timestamp, default: ColValN) {
+    if let Some(default) = default {
+      self.val_col_defaults.insert((col.clone(), col_timestamp.clone()), default);
+    }
+  }
+
+  /// Returns the `ColSource` of the version of `col` that was written at `col_timestamp`.
+  pub fn get_source(&self, col: &ColName, col_timestamp: &Timestamp) -> Option<&ColSource> {
+    self.val_col_sources.get(&(col.clone(), col_timestamp.clone()))
+  }
+
+  /// Sets the `ColSource` of the version of `col` that was written at `col_timestamp`.
+  pub fn set_source(&mut self, col: &ColName, col_timestamp: &Timestamp, source: ColSource) {
+    self.val_col_sources.insert((col.clone(), col_timestamp.clone()), source);
   }
 
   pub fn get_key_col_refs(&self, table_name: &String) -> Vec<proc::ColumnRef> {
@@ -1121,9 +1243,9 @@ impl<T> VersionedValue<T> {
 // -----------------------------------------------------------------------------------------------
 
 /// We use this type whenever we need to represent time. The `time_ms` is a time in
-/// milliseconds. The `suffix` is often randomly generated to reduce the probability
-/// of collisions. We can think of it as a decimal value, for arithemetic as well as
-/// the ordering relation.
+/// milliseconds. The `suffix` is used to order and distinguish `Timestamp`s that have the
+/// same `time_ms` (see `HybridClock`). We can think of it as a decimal value, for arithemetic
+/// as well as the ordering relation.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Timestamp {
   pub time_ms: u128,
@@ -1148,16 +1270,162 @@ pub fn mk_t(time_ms: u128) -> Timestamp {
   Timestamp { time_ms, suffix: 0 }
 }
 
-/// Add some noise to the `Timestamp` returned by the clock to help avoid collisions
-/// between transactions. Importantly, a server should not expect that consecutive calls
-/// to `cur_timestamp` would result in non-decreasing `Timestamp`; only the `time_ms`
-/// will be non-decreasing
-/// TODO: make this a method of the `BasicIOCtx`, where structs that implement
-///  `BasicIOCTx` will contain the `timestmap_suffix_divisor` underneath.
-pub fn cur_timestamp<IO: BasicIOCtx>(io_ctx: &mut IO, timestamp_suffix_divisor: u64) -> Timestamp {
-  let mut now_timestamp = io_ctx.now();
-  now_timestamp.suffix = io_ctx.rand().next_u64() % timestamp_suffix_divisor;
-  now_timestamp
+// -----------------------------------------------------------------------------------------------
+//  HybridClock
+// -----------------------------------------------------------------------------------------------
+
+/// The number of bits of a `ClockId` that hold the `sub_node_index`.
+const SUB_NODE_INDEX_BITS: u32 = 20;
+/// The number of bits of a `ClockId` that hold the `node_index`.
+const NODE_INDEX_BITS: u32 = 20;
+/// The number of low bits of a `Timestamp`'s `suffix` that hold the `ClockId`.
+const CLOCK_ID_BITS: u32 = NODE_INDEX_BITS + SUB_NODE_INDEX_BITS;
+/// The top bit of a `Timestamp`'s `suffix`, which is set for `Timestamp`s generated by
+/// `HybridClock::now_stale`. The bits between this and the `ClockId` hold the logical counter.
+const STALE_BIT: u64 = 1 << 63;
+
+/// Identifies a `HybridClock` uniquely across the system. The Master hands out a distinct
+/// `node_index` to every node when it registers as a FreeNode (the initial Master nodes take
+/// the first ones, in the order of `StartMaster::master_eids`). Every node then hands out a
+/// distinct `sub_node_index` to each server it runs with a `ClockIdAllocator`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClockId {
+  pub node_index: u32,
+  pub sub_node_index: u32,
+}
+
+impl ClockId {
+  /// Encodes this `ClockId` in the low `CLOCK_ID_BITS` bits of a `u64`.
+  fn encode(&self) -> u64 {
+    assert!(self.node_index < 1 << NODE_INDEX_BITS);
+    assert!(self.sub_node_index < 1 << SUB_NODE_INDEX_BITS);
+    ((self.node_index as u64) << SUB_NODE_INDEX_BITS) | self.sub_node_index as u64
+  }
+}
+
+/// Hands out the `ClockId`s of the servers (the Slave, Tablets, and Coords) of a node.
+#[derive(Debug, Clone)]
+pub struct ClockIdAllocator {
+  node_index: u32,
+  next_sub_node_index: u32,
+}
+
+impl ClockIdAllocator {
+  pub fn new(node_index: u32) -> ClockIdAllocator {
+    ClockIdAllocator { node_index, next_sub_node_index: 0 }
+  }
+
+  /// Returns a `ClockId` that has not been returned before.
+  pub fn next(&mut self) -> ClockId {
+    let clock_id =
+      ClockId { node_index: self.node_index, sub_node_index: self.next_sub_node_index };
+    self.next_sub_node_index += 1;
+    clock_id
+  }
+}
+
+/// A Hybrid Logical Clock, which every server (the Master, Slave, Tablets, and Coords) uses
+/// to generate all of its `Timestamp`s. The `time_ms` of a generated `Timestamp` tracks the
+/// physical time returned by `io_ctx.now()`, and the `suffix` holds a logical counter (which
+/// breaks ties between `Timestamp`s with the same `time_ms`) in its high bits and the
+/// encoded `ClockId` in its low `CLOCK_ID_BITS` bits.
+///
+/// Consecutive calls to `now` are strictly increasing. `now_stale` generates `Timestamp`s in
+/// the past for stale reads; these are kept apart from the ones generated by `now` with the
+/// `STALE_BIT`, and are strictly increasing among themselves. In addition, the `Timestamp`s carried
+/// by messages (e.g. `PerformQuery` and the Paxos2PC messages) are passed to `update` when
+/// they are received, so a `Timestamp` generated after a message is received is greater
+/// than the one generated before the message was sent. Since the `ClockId` differs between
+/// servers, `Timestamp`s generated by different servers also differ.
+#[derive(Debug, Clone)]
+pub struct HybridClock {
+  /// The greatest `Timestamp` that has been generated or accepted by `update`.
+  latest: Timestamp,
+  /// The greatest `Timestamp` that has been generated by `now_stale`.
+  latest_stale: Timestamp,
+  clock_id: u64,
+  /// If a `Timestamp` passed to `update` is ahead of our physical time by more than this,
+  /// the sender's clock is considered skewed and the `Timestamp` is not accepted. This
+  /// bounds how far ahead of physical time this clock can get.
+  max_offset_ms: u128,
+}
+
+impl HybridClock {
+  /// Creates a clock for the server that was handed `clock_id`.
+  pub fn new(clock_id: ClockId, max_offset_ms: u128) -> HybridClock {
+    let clock_id = clock_id.encode();
+    HybridClock {
+      latest: Timestamp::new(0, clock_id),
+      latest_stale: Timestamp::new(0, STALE_BIT | clock_id),
+      clock_id,
+      max_offset_ms,
+    }
+  }
+
+  /// Returns a `Timestamp` that is greater than all `Timestamp`s that were previously
+  /// generated or accepted by this clock.
+  pub fn now<NetworkMessageT, IO: BasicIOCtx<NetworkMessageT>>(
+    &mut self,
+    io_ctx: &mut IO,
+  ) -> Timestamp {
+    let physical_ms = io_ctx.now().time_ms;
+    advance(&mut self.latest, physical_ms, self.clock_id)
+  }
+
+  /// Returns a `Timestamp` that is `max_staleness_ms` behind the physical time, for reads
+  /// that may be stale. This is greater than all `Timestamp`s previously generated by
+  /// `now_stale`, and differs from all `Timestamp`s generated by `now`.
+  pub fn now_stale<NetworkMessageT, IO: BasicIOCtx<NetworkMessageT>>(
+    &mut self,
+    io_ctx: &mut IO,
+    max_staleness_ms: u128,
+  ) -> Timestamp {
+    let stale_ms = io_ctx.now().time_ms.saturating_sub(max_staleness_ms);
+    advance(&mut self.latest_stale, stale_ms, STALE_BIT | self.clock_id)
+  }
+
+  /// Returns a `Timestamp` that is greater than `lower_bound`, as well as all `Timestamp`s
+  /// that were previously generated or accepted by this clock. Unlike `update`, this does not
+  /// check `lower_bound` for skew, since it is meant for retrying with a `Timestamp` that this
+  /// server has already used.
+  pub fn now_after<NetworkMessageT, IO: BasicIOCtx<NetworkMessageT>>(
+    &mut self,
+    io_ctx: &mut IO,
+    lower_bound: &Timestamp,
+  ) -> Timestamp {
+    if lower_bound > &self.latest {
+      self.latest = lower_bound.clone();
+    }
+    self.now(io_ctx)
+  }
+
+  /// Advances this clock past `remote`, which was generated by the clock of another server.
+  /// If `remote` is too far ahead of our physical time, we do not advance, and record the
+  /// skew in `M_CLOCK_SKEW_DETECTED`.
+  pub fn update<NetworkMessageT, IO: BasicIOCtx<NetworkMessageT>>(
+    &mut self,
+    io_ctx: &mut IO,
+    remote: &Timestamp,
+  ) {
+    let physical_ms = io_ctx.now().time_ms;
+    if remote.time_ms > physical_ms + self.max_offset_ms {
+      metrics().inc(M_CLOCK_SKEW_DETECTED, vec![]);
+    } else if remote > &self.latest {
+      self.latest = remote.clone();
+    }
+  }
+}
+
+/// Sets `latest` to a `Timestamp` greater than it, with a `time_ms` of at least `time_ms` and
+/// a `suffix` that ends with `tag`, and returns it.
+fn advance(latest: &mut Timestamp, time_ms: u128, tag: u64) -> Timestamp {
+  let logical = if time_ms > latest.time_ms {
+    0
+  } else {
+    ((latest.suffix & !STALE_BIT) >> CLOCK_ID_BITS) + 1
+  };
+  *latest = Timestamp::new(max(time_ms, latest.time_ms), (logical << CLOCK_ID_BITS) | tag);
+  latest.clone()
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1175,6 +1443,54 @@ pub struct QueryPlan {
   ///
   /// Note: not all `TablePaths` used in the MSQuery needs to be here.
   pub col_presence_req: BTreeMap<TablePath, ColPresenceReq>,
+  /// The isolation level of the MSQuery. Subqueries use the same one as their parents.
+  pub isolation_level: IsolationLevel,
+  /// The priority of the MSQuery. Subqueries use the same one as their parents.
+  pub priority: Priority,
+}
+
+/// The isolation level that an MSQuery runs under, set with `SET TRANSACTION ISOLATION LEVEL`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+  /// Every ReadRegion of the MSQuery is protected, so that no write with a smaller `Timestamp`
+  /// can change what was read. This is the default.
+  Serializable,
+  /// The ReadRegions of SELECT stages are not protected, so they do not cause writes with
+  /// smaller `Timestamp`s to abort. Reads still wait for prior writes to finish, and the
+  /// MSQuery aborts if it writes to a region that a write with a larger `Timestamp` writes to.
+  Snapshot,
+}
+
+impl Default for IsolationLevel {
+  fn default() -> Self {
+    IsolationLevel::Serializable
+  }
+}
+
+/// The number of retries it takes for an MSQuery to be boosted to the next
+/// `TransactionPriority`.
+const RETRIES_PER_PRIORITY_LEVEL: u32 = 10;
+
+/// The priority of an MSQuery. When MSQueries conflict in a Tablet, the one with the lower
+/// priority is aborted. This starts at the `TransactionPriority` set by the External, and is
+/// boosted every time the MSQuery is retried so that it cannot be starved.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Priority(pub u32);
+
+impl Priority {
+  pub fn new(priority: msg::TransactionPriority) -> Priority {
+    let level = match priority {
+      msg::TransactionPriority::Low => 0,
+      msg::TransactionPriority::Normal => 1,
+      msg::TransactionPriority::High => 2,
+    };
+    Priority(level * RETRIES_PER_PRIORITY_LEVEL)
+  }
+
+  /// Returns the priority to use when retrying an MSQuery with this priority.
+  pub fn boost(self) -> Priority {
+    Priority(self.0.saturating_add(1))
+  }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -1202,12 +1518,37 @@ impl<T> ColBound<T> {
   }
 }
 
+impl<T: Clone + PartialEq> ColBound<T> {
+  /// Returns the only value in this `ColBound`, if it bounds exactly one value.
+  pub fn point(&self) -> Option<T> {
+    match (&self.start, &self.end) {
+      (SingleBound::Included(start), SingleBound::Included(end)) if start == end => {
+        Some(start.clone())
+      }
+      _ => None,
+    }
+  }
+}
+
 // There is a Variant here for every ColType.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PolyColBound {
   Int(ColBound<i32>),
   String(ColBound<String>),
   Bool(ColBound<bool>),
+  BigInt(ColBound<i64>),
+}
+
+impl PolyColBound {
+  /// Returns the only value in this `PolyColBound`, if it bounds exactly one value.
+  pub fn point(&self) -> Option<ColVal> {
+    match self {
+      PolyColBound::Int(bound) => bound.point().map(ColVal::Int),
+      PolyColBound::String(bound) => bound.point().map(ColVal::String),
+      PolyColBound::Bool(bound) => bound.point().map(ColVal::Bool),
+      PolyColBound::BigInt(bound) => bound.point().map(ColVal::BigInt),
+    }
+  }
 }
 
 /// A full Boundary for a `PrimaryKey`.
@@ -1246,6 +1587,38 @@ pub struct WriteRegion {
 pub struct QueryESResult {
   pub result: Vec<TableView>,
   pub new_rms: Vec<TQueryPath>,
+  pub stats: ExecutionStats,
+}
+
+/// Statistics about the execution of a query. These are accumulated up the ES tree in the
+/// same way as `new_rms`, ultimately arriving at the `MSCoordES` (for `EXPLAIN ANALYZE`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExecutionStats {
+  /// The number of rows read from the storage of each Tablet.
+  pub rows_read: BTreeMap<TabletGroupId, u64>,
+  /// The number of child queries sent out by `GRQueryES`s and `JoinReadES`s.
+  pub subqueries: u64,
+  /// The number of `PerformQuery` and `QuerySuccess` messages sent.
+  pub messages_sent: u64,
+  /// The total time (in ms) spent waiting for read protection from Region Isolation.
+  pub read_protection_wait_ms: u128,
+}
+
+impl ExecutionStats {
+  /// Accumulates `rows` into the number of rows read from `tid`.
+  pub fn add_rows_read(&mut self, tid: &TabletGroupId, rows: u64) {
+    *self.rows_read.entry(tid.clone()).or_insert(0) += rows;
+  }
+
+  /// Accumulates the statistics in `other` into this one.
+  pub fn merge(&mut self, other: ExecutionStats) {
+    for (tid, rows) in other.rows_read {
+      self.add_rows_read(&tid, rows);
+    }
+    self.subqueries += other.subqueries;
+    self.messages_sent += other.messages_sent;
+    self.read_protection_wait_ms += other.read_protection_wait_ms;
+  }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -1302,6 +1675,38 @@ impl BoundType for i32 {
   }
 }
 
+/// `BoundType` for `i64`. Note that `Int` values are widened, since they may be compared
+/// against a `BigInt` column.
+impl BoundType for i64 {
+  fn col_val_cast(col_val: ColVal) -> Option<Self> {
+    match col_val {
+      ColVal::BigInt(val) => Some(val),
+      ColVal::Int(val) => Some(val as i64),
+      _ => None,
+    }
+  }
+
+  fn col_val_cast_ref(col_val: &ColVal) -> Option<&Self> {
+    if let ColVal::BigInt(val) = col_val {
+      Some(val)
+    } else {
+      None
+    }
+  }
+
+  fn from_poly(poly_col_bound: &PolyColBound) -> Option<&ColBound<Self>> {
+    if let PolyColBound::BigInt(col_bound) = poly_col_bound {
+      Some(col_bound)
+    } else {
+      None
+    }
+  }
+
+  fn to_poly(col_bound: ColBound<Self>) -> PolyColBound {
+    PolyColBound::BigInt(col_bound)
+  }
+}
+
 /// `BoundType` for `bool`
 impl BoundType for bool {
   fn col_val_cast(col_val: ColVal) -> Option<Self> {
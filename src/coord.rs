@@ -1,13 +1,14 @@
 use crate::common::{
-  cur_timestamp, map_insert, mk_qid, mk_t, remove_item, update_leader_map,
-  update_leader_map_unversioned, BasicIOCtx, GeneralTraceMessage, GossipData, LeaderMap, OrigP,
-  Timestamp, VersionedValue,
+  map_insert, mk_qid, mk_t, remove_item, update_leader_map, update_leader_map_unversioned,
+  BasicIOCtx, GeneralTraceMessage, GossipData, LeaderMap, OrigP, Timestamp, VersionedValue,
 };
 use crate::common::{
   CNodePath, CQueryPath, CSubNodePath, CTSubNodePath, ColName, CoordGroupId, Gen, LeadershipId,
   PaxosGroupId, PaxosGroupIdTrait, SlaveGroupId, TNodePath, TQueryPath, TableView,
 };
-use crate::common::{CoreIOCtx, RemoteLeaderChangedPLm};
+use crate::common::{
+  ClockId, CoreIOCtx, ExecutionStats, HybridClock, IsolationLevel, Priority, RemoteLeaderChangedPLm,
+};
 use crate::common::{EndpointId, QueryId, RequestId};
 use crate::finish_query_tm_es::{
   FinishQueryPayloadTypes, FinishQueryPrepare, FinishQueryTMES, FinishQueryTMInner, ResponseData,
@@ -15,24 +16,24 @@ use crate::finish_query_tm_es::{
 use crate::gr_query_es::{GRQueryAction, GRQueryES};
 use crate::master_query_planning_es::StaticDBSchemaView;
 use crate::message as msg;
+use crate::metrics::{metrics, M_ACTIVE_ESS, M_TRANSACTIONS_COMMITTED};
 use crate::ms_query_coord_es::{
   FullMSCoordES, MSQueryCoordAction, QueryPlanningES, QueryPlanningS,
 };
 use crate::paxos2pc_tm as paxos2pc;
 use crate::paxos2pc_tm::{Paxos2PCTMAction, TMMessage};
 use crate::server::{CTServerContext, CommonQuery, ServerContextBase};
+use crate::slave::{CoordDefer, RunningQueries, SlaveBackMessage};
 use crate::sql_ast::iast;
 use crate::sql_ast::proc;
-use crate::sql_parser::convert_ast;
+use crate::sql_parser::{parse_dql, QueryMode};
 use crate::tablet::{GRQueryESWrapper, TransTableReadESWrapper};
 use crate::tablet::{JoinReadESWrapper, TPESAction};
 use crate::tm_status::TMStatus;
+use crate::trace::{mk_trace_id, OpenSpans, TraceContext};
 use crate::trans_table_read_es::{TransExecutionS, TransTableReadES};
 use rand::RngCore;
-use sqlparser::dialect::GenericDialect;
-use sqlparser::parser::Parser;
-use sqlparser::parser::ParserError::{ParserError, TokenizerError};
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
 use std::rc::Rc;
@@ -51,12 +52,46 @@ pub enum CoordForwardMsg {
   GossipData(Arc<GossipData>, LeaderMap),
   RemoteLeaderChanged(RemoteLeaderChangedPLm),
   LeaderChanged(msg::LeaderChanged),
+  /// Asks for the ESs running at this Coord. The `RequestId` is that of the
+  /// `ExternalShowQueries` that the Slave is gathering them for.
+  ShowQueries(RequestId),
+  CoordTimerInput(CoordTimerInput),
+}
+
+/// Events that the Coord defers to be run on itself (via a `CoordDefer`).
+#[derive(Debug)]
+pub enum CoordTimerInput {
+  /// Starts the `MSCoordES` at the `QueryId` once it has backed off from a retry. The `bool`
+  /// indicates whether it should start with a `MasterQueryPlanning`.
+  RetryMSCoordES(QueryId, bool),
+  /// Aborts the External request with `Timeout` if it is past its deadline.
+  Deadline(RequestId),
+  /// Sends the `FollowerRead`s of the `TMStatus` at the `QueryId` that have not been
+  /// responded to yet to their Leaders.
+  FollowerReadTimeout(QueryId),
 }
 
 // -----------------------------------------------------------------------------------------------
 //  Coord Statuses
 // -----------------------------------------------------------------------------------------------
 
+/// An External request that is being serviced by this Coord.
+#[derive(Debug)]
+pub struct ExternalRequest {
+  /// The `QueryId` of the `MSCoordES` or `FinishQueryES` servicing the request.
+  pub query_id: QueryId,
+  /// The SQL text of the request.
+  pub query: String,
+  /// The time that the request arrived at this Coord.
+  pub start_time: Timestamp,
+  /// The time (in ms) after which the request is aborted with `Timeout` if it is still an
+  /// `MSCoordES`.
+  pub opt_deadline_ms: Option<u128>,
+  /// The number of times the request has been retried, and how many times it may be.
+  pub num_retries: u32,
+  pub max_retries: u32,
+}
+
 /// A wrapper around MSCoordES that keeps track of the child queries it created. We
 /// we use this for resource management
 #[derive(Debug)]
@@ -103,6 +138,10 @@ impl paxos2pc::TMServerContext<FinishQueryPayloadTypes> for CoordContext {
   fn is_leader(&self) -> bool {
     CoordContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -111,10 +150,51 @@ impl paxos2pc::TMServerContext<FinishQueryPayloadTypes> for CoordContext {
 
 #[derive(Debug, Clone)]
 pub struct CoordConfig {
-  /// This is used for generate the `suffix` of a Timestamp, where we just generate
-  /// a random `u64` and take the remainder after dividing by `timestamp_suffix_divisor`.
-  /// This cannot be 0; the default value is 1, making the suffix always be 0.
-  pub timestamp_suffix_divisor: u64,
+  /// The maximum amount (in ms) that the clock of another node can be ahead of ours before
+  /// we consider it to be skewed. See `HybridClock`.
+  pub max_clock_offset_ms: u128,
+  /// External requests that take at least this long (in ms) to finish are reported as a
+  /// `SlowQuery`. If this is `None`, no requests are reported.
+  pub slow_query_threshold_ms: Option<u128>,
+  /// The maximum number of retries for requests that do not specify one.
+  pub max_retries: u32,
+  /// The time (in ms) to back off for before the first retry of a request. This doubles
+  /// on every subsequent retry, up to `max_retry_backoff_ms`.
+  pub retry_backoff_ms: u128,
+  pub max_retry_backoff_ms: u128,
+  /// The time (in ms) to wait for a `FollowerRead` to be responded to before it is sent to
+  /// the Leader instead. The node it was sent to is then avoided for a while (see
+  /// `UNRESPONSIVE_FOLLOWER_TIMEOUTS`).
+  pub follower_read_timeout_ms: u128,
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Slow Query Log
+// -----------------------------------------------------------------------------------------------
+
+/// An External request that took at least `CoordConfig::slow_query_threshold_ms` to finish.
+/// In production, these are appended to the slow query log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery {
+  pub request_id: RequestId,
+  /// The SQL text of the request.
+  pub query: String,
+  pub duration_ms: u128,
+  /// The reason the request was aborted, or `None` if it succeeded.
+  pub aborted: Option<msg::ExternalAbortedData>,
+}
+
+impl SlowQuery {
+  /// Renders this `SlowQuery` as a line of the slow query log. The fields are separated by
+  /// tabs, where the SQL text comes last with its whitespace collapsed.
+  pub fn to_log_line(&self) -> String {
+    let aborted = match &self.aborted {
+      Some(aborted) => format!("{:?}", aborted),
+      None => "-".to_string(),
+    };
+    let query = self.query.split_whitespace().collect::<Vec<&str>>().join(" ");
+    format!("{}\t{}\t{}\t{}", self.request_id.0, self.duration_ms, aborted, query)
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -147,6 +227,32 @@ impl CTServerContext for CoordContext {
   fn gossip(&self) -> &Arc<GossipData> {
     &self.gossip
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Prepared Statements
+// -----------------------------------------------------------------------------------------------
+
+/// The maximum number of entries in `CoordContext::prepared_stmts`.
+const MAX_PREPARED_STMTS: usize = 1000;
+
+/// The number of `CoordConfig::follower_read_timeout_ms` that a node which did not respond
+/// to a `FollowerRead` in time is avoided for.
+const UNRESPONSIVE_FOLLOWER_TIMEOUTS: u128 = 10;
+
+#[derive(Debug)]
+pub struct PreparedStmt {
+  /// The parsed query, which still contains its `iast::Value::Param`s.
+  pub iast_query: iast::Query,
+  pub num_params: u32,
+  pub isolation_level: IsolationLevel,
+  /// The last plan computed for `iast_query` (before the parameters were bound). This is
+  /// only used if the `Gen` of every table it uses is still the current one.
+  pub opt_master_query_plan: Option<msg::MasterQueryPlan>,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -168,6 +274,8 @@ pub struct CoordContext {
   pub this_cid: CoordGroupId,
   pub sub_node_path: CTSubNodePath, // Wraps `this_cid` for expedience
   pub this_eid: EndpointId,
+  /// The clock used to generate all `Timestamp`s at this server.
+  pub clock: HybridClock,
 
   /// Gossip
   pub gossip: Arc<GossipData>,
@@ -176,10 +284,23 @@ pub struct CoordContext {
   pub leader_map: LeaderMap,
 
   /// This is used to allow the user to cancel requests. There is an element (`RequestId`,
-  /// `QueryId`) here exactly when there is an `MSCoordES` or `FinishQueryES` stored at that
-  /// `QueryId` in `statuses` (recall that the former becomes the latter). In addition, ES
-  /// will hold the `RequestId` in its `request_id` field.
-  pub external_request_id_map: BTreeMap<RequestId, QueryId>,
+  /// `ExternalRequest`) here exactly when there is an `MSCoordES` or `FinishQueryES` stored at
+  /// the `ExternalRequest`'s `query_id` in `statuses` (recall that the former becomes the
+  /// latter). In addition, ES will hold the `RequestId` in its `request_id` field.
+  pub external_request_id_map: BTreeMap<RequestId, ExternalRequest>,
+
+  /// The queries with `$n` bind parameters that have come in, keyed by their SQL text. This
+  /// lets us skip parsing them, and also skip query planning while the `table_generation`
+  /// of the tables they use stays the same. This holds at most `MAX_PREPARED_STMTS` entries.
+  pub prepared_stmts: BTreeMap<String, PreparedStmt>,
+
+  /// The nodes that did not respond to a `FollowerRead` in time, mapping to the time (in ms)
+  /// at which that was detected. See `unresponsive_followers`.
+  pub unresponsive_followers: BTreeMap<EndpointId, u128>,
+
+  /// The spans of the ESs at this Coord that are being done for a trace, including the root
+  /// spans of the External requests.
+  pub spans: OpenSpans,
 }
 
 impl CoordState {
@@ -189,6 +310,25 @@ impl CoordState {
 
   pub fn handle_input<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO, coord_input: CoordForwardMsg) {
     self.ctx.handle_input(io_ctx, &mut self.statuses, coord_input);
+    self.record_metrics();
+  }
+
+  /// Updates the metrics of this Coord.
+  fn record_metrics(&self) {
+    let cid = &self.ctx.this_cid.0;
+    let statuses = &self.statuses;
+    let active_ess = [
+      ("finish_query_tm", statuses.finish_query_tm_ess.len()),
+      ("ms_coord", statuses.ms_coord_ess.len()),
+      ("gr_query", statuses.gr_query_ess.len()),
+      ("join_read", statuses.join_query_ess.len()),
+      ("trans_table_read", statuses.trans_table_read_ess.len()),
+      ("tm_status", statuses.tm_statuss.len()),
+    ];
+    for (kind, count) in active_ess {
+      let labels = vec![("group", cid.clone()), ("kind", kind.to_string())];
+      metrics().set(M_ACTIVE_ESS, labels, count as i64);
+    }
   }
 }
 
@@ -203,6 +343,7 @@ impl CoordContext {
     mut leader_map: LeaderMap,
     paxos_nodes: Vec<EndpointId>,
     this_eid: EndpointId,
+    clock_id: ClockId,
     coord_config: CoordConfig,
   ) -> CoordContext {
     // Amend the LeaderMap to include this new Slave.
@@ -217,6 +358,7 @@ impl CoordContext {
     all_eids.extend(gossip.get().master_address_config.clone());
     all_eids.extend(paxos_nodes.clone());
 
+    let clock = HybridClock::new(clock_id, coord_config.max_clock_offset_ms);
     CoordContext {
       coord_config,
       this_sid: this_sid.clone(),
@@ -224,9 +366,13 @@ impl CoordContext {
       this_cid: this_cid.clone(),
       sub_node_path: CTSubNodePath::Coord(this_cid),
       this_eid,
+      clock,
       gossip,
       leader_map,
       external_request_id_map: Default::default(),
+      prepared_stmts: Default::default(),
+      unresponsive_followers: Default::default(),
+      spans: Default::default(),
     }
   }
 
@@ -241,17 +387,53 @@ impl CoordContext {
         match message {
           msg::SlaveExternalReq::PerformExternalQuery(external_query) => {
             match self.init_request(&external_query) {
-              Ok(query) => {
+              Ok((query, opt_prepared_key, mode, isolation_level)) => {
                 let query_id = mk_qid(io_ctx.rand());
                 let request_id = &external_query.request_id;
 
+                // Schedule the deadline of the request, if it has one.
+                let start_time = io_ctx.now();
+                let opt_deadline_ms = external_query.opt_timeout_ms.map(|timeout_ms| {
+                  io_ctx.slave_forward(SlaveBackMessage::CoordDefer(CoordDefer {
+                    cid: self.this_cid.clone(),
+                    defer_time: mk_t(timeout_ms),
+                    timer_input: CoordTimerInput::Deadline(request_id.clone()),
+                  }));
+                  start_time.time_ms + timeout_ms
+                });
+
                 // Update the `external_request_id_map` and trace it.
-                self.external_request_id_map.insert(request_id.clone(), query_id.clone());
+                let max_retries =
+                  external_query.opt_max_retries.unwrap_or(self.coord_config.max_retries);
+                self.external_request_id_map.insert(
+                  request_id.clone(),
+                  ExternalRequest {
+                    query_id: query_id.clone(),
+                    query: external_query.query.clone(),
+                    start_time,
+                    opt_deadline_ms,
+                    num_retries: 0,
+                    max_retries,
+                  },
+                );
                 io_ctx.general_trace(GeneralTraceMessage::RequestIdQueryId(
                   request_id.clone(),
                   query_id.clone(),
                 ));
 
+                // Start a trace for the request.
+                let trace = TraceContext {
+                  trace_id: mk_trace_id(io_ctx.rand()),
+                  span_id: mk_qid(io_ctx.rand()),
+                };
+                self.spans.start_root(
+                  io_ctx,
+                  &trace.span_id,
+                  &trace.trace_id,
+                  request_id,
+                  "MSCoordES",
+                );
+
                 let ms_coord = map_insert(
                   &mut statuses.ms_coord_ess,
                   &query_id,
@@ -260,9 +442,20 @@ impl CoordContext {
                     sender_eid: external_query.sender_eid,
                     child_queries: vec![],
                     es: FullMSCoordES::QueryPlanning(QueryPlanningES {
-                      timestamp: cur_timestamp(io_ctx, self.coord_config.timestamp_suffix_divisor),
+                      timestamp: read_timestamp(
+                        &mut self.clock,
+                        io_ctx,
+                        external_query.opt_max_staleness_ms,
+                      ),
                       iast_query: query,
+                      params: external_query.params,
+                      opt_prepared_key,
+                      mode,
+                      isolation_level,
+                      priority: Priority::new(external_query.priority),
+                      opt_max_staleness_ms: external_query.opt_max_staleness_ms,
                       query_id: query_id.clone(),
+                      trace,
                       state: QueryPlanningS::Start,
                     }),
                   },
@@ -281,29 +474,71 @@ impl CoordContext {
           msg::SlaveExternalReq::CancelExternalQuery(cancel) => {
             // Recall that Cancellation is merely a passive hint on how the execution of the
             // original query should go. The Coord have no obligation to respond to it.
-            if let Some(query_id) = self.external_request_id_map.get(&cancel.request_id) {
-              if statuses.ms_coord_ess.contains_key(query_id) {
+            if let Some(request) = self.external_request_id_map.get(&cancel.request_id) {
+              let query_id = request.query_id.clone();
+              if statuses.ms_coord_ess.contains_key(&query_id) {
                 // Recall that a request is only cancellable while it is an MSCoordES.
-                self.exit_and_clean_up(io_ctx, statuses, query_id.clone());
+                let payload = msg::ExternalAbortedData::CancelConfirmed;
+                self.record_request_end(io_ctx, &cancel.request_id, Some(&payload));
+                self.exit_and_clean_up(io_ctx, statuses, query_id);
 
                 // Send the payload back to the client.
                 io_ctx.send(
                   &cancel.sender_eid,
+                  msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
+                    msg::ExternalQueryAborted { request_id: cancel.request_id, payload },
+                  )),
+                );
+              }
+            }
+          }
+          msg::SlaveExternalReq::ExternalKillQuery(kill) => {
+            // Like cancellation, a request can only be killed while it is an MSCoordES.
+            let mut result = msg::KillQueryResult::NotFound;
+            if let Some(request) = self.external_request_id_map.get(&kill.target_request_id) {
+              let query_id = request.query_id.clone();
+              if let Some(ms_coord) = statuses.ms_coord_ess.get(&query_id) {
+                // Tell the External of the killed request that it was aborted.
+                let payload = msg::ExternalAbortedData::Killed;
+                io_ctx.send(
+                  &ms_coord.sender_eid,
                   msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
                     msg::ExternalQueryAborted {
-                      request_id: cancel.request_id,
-                      payload: msg::ExternalAbortedData::CancelConfirmed,
+                      request_id: kill.target_request_id.clone(),
+                      payload: payload.clone(),
                     },
                   )),
                 );
+                self.record_request_end(io_ctx, &kill.target_request_id, Some(&payload));
+                self.exit_and_clean_up(io_ctx, statuses, query_id);
+                result = msg::KillQueryResult::Killed;
               }
             }
+
+            io_ctx.send(
+              &kill.sender_eid,
+              msg::NetworkMessage::External(msg::ExternalMessage::ExternalKillQueryResponse(
+                msg::ExternalKillQueryResponse { request_id: kill.request_id, result },
+              )),
+            );
+          }
+          msg::SlaveExternalReq::ExternalShowQueries(_) => {
+            // The Slave handles these by sending `CoordForwardMsg::ShowQueries` instead.
+            debug_assert!(false);
+          }
+          msg::SlaveExternalReq::ExternalTransferLeadership(_) => {
+            // The Slave handles these itself.
+            debug_assert!(false);
           }
         }
       }
       CoordForwardMsg::CoordMessage(message) => {
         match message {
           msg::CoordMessage::PerformQuery(perform_query) => {
+            self.clock.update(io_ctx, &perform_query.sender_time);
+            let query_id = &perform_query.query_id;
+            self.spans.start(io_ctx, query_id, &perform_query.trace, "TransTableReadES");
+            let trace = perform_query.trace.child(query_id);
             match perform_query.query {
               msg::GeneralQuery::TransTableSelectQuery(query) => {
                 // First, we check if the MSCoordES or GRCoordES still exists in the Statuses,
@@ -321,6 +556,7 @@ impl CoordContext {
                       child_queries: vec![],
                       es: TransTableReadES {
                         root_query_path: perform_query.root_query_path,
+                        trace,
                         location_prefix: query.location_prefix,
                         context: Rc::new(query.context),
                         sender_path: perform_query.sender_path,
@@ -328,6 +564,7 @@ impl CoordContext {
                         sql_query: query.sql_query,
                         query_plan: query.query_plan,
                         new_rms: Default::default(),
+                        stats: Default::default(),
                         state: TransExecutionS::Start,
                         child_queries: vec![],
                         timestamp: es.timestamp.clone(),
@@ -354,6 +591,7 @@ impl CoordContext {
                       child_queries: vec![],
                       es: TransTableReadES {
                         root_query_path: perform_query.root_query_path,
+                        trace,
                         location_prefix: query.location_prefix,
                         context: Rc::new(query.context),
                         sender_path: perform_query.sender_path,
@@ -361,6 +599,7 @@ impl CoordContext {
                         sql_query: query.sql_query,
                         query_plan: query.query_plan,
                         new_rms: Default::default(),
+                        stats: Default::default(),
                         state: TransExecutionS::Start,
                         child_queries: vec![],
                         timestamp: gr_query.es.timestamp.clone(),
@@ -378,6 +617,7 @@ impl CoordContext {
                 } else {
                   // This means that the target GRQueryES was deleted. We can send back an
                   // Abort with LateralError. Exit and Clean Up will be done later.
+                  self.spans.finish(io_ctx, &self.this_eid, &perform_query.query_id);
                   self.send_query_error(
                     io_ctx,
                     perform_query.sender_path,
@@ -532,16 +772,85 @@ impl CoordContext {
           }
         }
       }
+      CoordForwardMsg::ShowQueries(request_id) => {
+        let queries = self.running_queries(io_ctx, statuses);
+        io_ctx
+          .slave_forward(SlaveBackMessage::RunningQueries(RunningQueries { request_id, queries }));
+      }
+      CoordForwardMsg::CoordTimerInput(timer_input) => match timer_input {
+        CoordTimerInput::RetryMSCoordES(query_id, start_with_master_query_planning) => {
+          // Recall that the MSCoordES might have been cancelled while backing off.
+          if let Some(ms_coord) = statuses.ms_coord_ess.get_mut(&query_id) {
+            if let FullMSCoordES::QueryPlanning(plan_es) = &mut ms_coord.es {
+              plan_es.timestamp = max(self.clock.now(io_ctx), plan_es.timestamp.clone());
+            }
+            let action = ms_coord.es.start(self, io_ctx, start_with_master_query_planning);
+            self.handle_ms_coord_es_action(io_ctx, statuses, query_id, action);
+          }
+        }
+        CoordTimerInput::Deadline(request_id) => {
+          // Like cancellation, a request can only time out while it is an MSCoordES. We
+          // check the deadline in case the `RequestId` was reused by a later request.
+          if let Some(request) = self.external_request_id_map.get(&request_id) {
+            let query_id = request.query_id.clone();
+            let is_past_deadline = match request.opt_deadline_ms {
+              Some(deadline_ms) => io_ctx.now().time_ms >= deadline_ms,
+              None => false,
+            };
+            if let Some(ms_coord) = statuses.ms_coord_ess.get(&query_id) {
+              if is_past_deadline {
+                let payload = msg::ExternalAbortedData::Timeout;
+                io_ctx.send(
+                  &ms_coord.sender_eid,
+                  msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
+                    msg::ExternalQueryAborted {
+                      request_id: request_id.clone(),
+                      payload: payload.clone(),
+                    },
+                  )),
+                );
+                self.record_request_end(io_ctx, &request_id, Some(&payload));
+                self.exit_and_clean_up(io_ctx, statuses, query_id);
+              }
+            }
+          }
+        }
+        CoordTimerInput::FollowerReadTimeout(tm_query_id) => {
+          // Recall that the TMStatus might have finished or been aborted in the meantime.
+          if let Some(tm_status) = statuses.tm_statuss.get_mut(&tm_query_id) {
+            let now_ms = io_ctx.now().time_ms;
+            for eid in tm_status.redirect_follower_reads(self, io_ctx) {
+              self.unresponsive_followers.insert(eid, now_ms);
+            }
+          }
+        }
+      },
       CoordForwardMsg::LeaderChanged(leader_changed) => {
         let this_gid = self.this_sid.to_gid();
         self.leader_map.insert(this_gid, leader_changed.lid);
 
         // Check if this node just lost Leadership
         if !self.is_leader() {
+          // Requests still in an MSCoordES have not started committing, so we tell their
+          // Externals to resend to the new leader. Those in a FinishQueryTMES might still
+          // commit, so we let them time out instead.
+          for (_, ms_coord) in &statuses.ms_coord_ess {
+            let payload = msg::ExternalAbortedData::NotLeader;
+            self.record_request_end(io_ctx, &ms_coord.request_id, Some(&payload));
+            self.spans.finish(io_ctx, &self.this_eid, &ms_coord.es.trace().span_id);
+            io_ctx.send(
+              &ms_coord.sender_eid,
+              msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
+                msg::ExternalQueryAborted { request_id: ms_coord.request_id.clone(), payload },
+              )),
+            );
+          }
+
           // Wink away all RequestIds
           self.external_request_id_map.clear();
 
-          // Wink away all TM ESs.
+          // Wink away all TM ESs, as well as their spans.
+          self.spans = OpenSpans::default();
           statuses.ms_coord_ess.clear();
           statuses.finish_query_tm_ess.clear();
 
@@ -556,32 +865,55 @@ impl CoordContext {
   }
 
   /// Does some initial validations and MSQuery processing before we start
-  /// servicing the request.
+  /// servicing the request. If the query has bind parameters, this also returns
+  /// its key in `prepared_stmts`.
   fn init_request(
-    &self,
+    &mut self,
     external_query: &msg::PerformExternalQuery,
-  ) -> Result<iast::Query, msg::ExternalAbortedData> {
+  ) -> Result<(iast::Query, Option<String>, QueryMode, IsolationLevel), msg::ExternalAbortedData>
+  {
     if self.external_request_id_map.contains_key(&external_query.request_id) {
       // Duplicate RequestId; respond with an abort.
-      Err(msg::ExternalAbortedData::NonUniqueRequestId)
+      return Err(msg::ExternalAbortedData::NonUniqueRequestId);
+    }
+
+    let num_given = external_query.params.len();
+    if let Some(prepared_stmt) = self.prepared_stmts.get(&external_query.query) {
+      // This is a known prepared statement, so we can skip parsing.
+      return if prepared_stmt.num_params as usize == num_given {
+        let prepared_key = Some(external_query.query.clone());
+        let isolation_level = prepared_stmt.isolation_level;
+        Ok((prepared_stmt.iast_query.clone(), prepared_key, QueryMode::Execute, isolation_level))
+      } else {
+        Err(mk_param_count_error(prepared_stmt.num_params, num_given))
+      };
+    }
+
+    // Parse the SQL and convert it to an `iast::Query`.
+    let (iast_query, num_params, mode, isolation_level) =
+      parse_dql(&external_query.query).map_err(msg::ExternalAbortedData::ParseError)?;
+    if num_params as usize != num_given {
+      return Err(mk_param_count_error(num_params, num_given));
+    }
+
+    if num_params == 0 || mode != QueryMode::Execute {
+      Ok((iast_query, None, mode, isolation_level))
     } else {
-      // Parse the SQL
-      match Parser::parse_sql(&GenericDialect {}, &external_query.query) {
-        Ok(parsed_ast) => {
-          // Convert to MSQuery
-          match convert_ast(parsed_ast) {
-            Ok(internal_ast) => Ok(internal_ast),
-            Err(parse_error) => Err(msg::ExternalAbortedData::ParseError(parse_error)),
-          }
-        }
-        Err(parse_error) => {
-          // Extract error string
-          Err(msg::ExternalAbortedData::ParseError(match parse_error {
-            TokenizerError(err_msg) => err_msg,
-            ParserError(err_msg) => err_msg,
-          }))
-        }
+      // Remember the prepared statement, evicting an arbitrary one if there are too many.
+      if self.prepared_stmts.len() >= MAX_PREPARED_STMTS {
+        self.prepared_stmts.pop_first();
       }
+      let prepared_key = external_query.query.clone();
+      self.prepared_stmts.insert(
+        prepared_key.clone(),
+        PreparedStmt {
+          iast_query: iast_query.clone(),
+          num_params,
+          isolation_level,
+          opt_master_query_plan: None,
+        },
+      );
+      Ok((iast_query, Some(prepared_key), mode, isolation_level))
     }
   }
 
@@ -599,8 +931,8 @@ impl CoordContext {
       if tm_status.is_complete() {
         // Remove the TMStatus and take ownership before forwarding the results back.
         let tm_status = statuses.tm_statuss.remove(&tm_query_id).unwrap();
-        let (orig_p, results, new_rms) = tm_status.get_results();
-        self.handle_tm_done(io_ctx, statuses, orig_p, tm_query_id, new_rms, results);
+        let (orig_p, results, new_rms, stats) = tm_status.get_results();
+        self.handle_tm_done(io_ctx, statuses, orig_p, tm_query_id, new_rms, stats, results);
       }
     }
   }
@@ -613,19 +945,20 @@ impl CoordContext {
     orig_p: OrigP,
     tm_qid: QueryId,
     new_rms: BTreeSet<TQueryPath>,
+    stats: ExecutionStats,
     results: Vec<TableView>,
   ) {
     let query_id = orig_p.query_id;
     // Route TM results to MSCoordES
     if let Some(ms_coord) = statuses.ms_coord_ess.get_mut(&query_id) {
       remove_item(&mut ms_coord.child_queries, &tm_qid);
-      let action = ms_coord.es.handle_tm_success(self, io_ctx, tm_qid, new_rms, results);
+      let action = ms_coord.es.handle_tm_success(self, io_ctx, tm_qid, new_rms, stats, results);
       self.handle_ms_coord_es_action(io_ctx, statuses, query_id, action);
     }
     // Route TM results to GRQueryES
     else if let Some(gr_query) = statuses.gr_query_ess.get_mut(&query_id) {
       remove_item(&mut gr_query.child_queries, &tm_qid);
-      let action = gr_query.es.handle_tm_success(self, io_ctx, tm_qid, new_rms, results);
+      let action = gr_query.es.handle_tm_success(self, io_ctx, tm_qid, new_rms, stats, results);
       self.handle_gr_query_es_action(io_ctx, statuses, query_id, action);
     }
   }
@@ -637,6 +970,18 @@ impl CoordContext {
     query_aborted: msg::QueryAborted,
   ) {
     let tm_query_id = &query_aborted.return_qid;
+    // A Follower that cannot serve a `FollowerRead` in time does not abort the TMStatus.
+    // Instead, we send the read to the Leader.
+    if let msg::AbortedData::QueryError(msg::QueryError::FollowerReadUnavailable) =
+      &query_aborted.payload
+    {
+      if let Some(tm_status) = statuses.tm_statuss.get_mut(tm_query_id) {
+        let node_path = &query_aborted.responder_path.node_path;
+        tm_status.redirect_follower_read(self, io_ctx, node_path);
+      }
+      return;
+    }
+
     if let Some(tm_status) = statuses.tm_statuss.get(tm_query_id) {
       // We ECU this TMStatus by sending CancelQuery to all remaining participants.
       // Then, we propagate the QueryAborted back to the orig_p.
@@ -679,14 +1024,21 @@ impl CoordContext {
     orig_p: OrigP,
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     result: Vec<TableView>,
   ) {
     let query_id = orig_p.query_id;
     // JoinReadES
     if let Some(join_select) = statuses.join_query_ess.get_mut(&query_id) {
       remove_item(&mut join_select.child_queries, &subquery_id);
-      let action =
-        join_select.es.handle_subquery_done(self, io_ctx, subquery_id, subquery_new_rms, result);
+      let action = join_select.es.handle_subquery_done(
+        self,
+        io_ctx,
+        subquery_id,
+        subquery_new_rms,
+        subquery_stats,
+        result,
+      );
       self.handle_join_read_action(io_ctx, statuses, query_id, action);
     }
     // TransTableReadES
@@ -700,6 +1052,7 @@ impl CoordContext {
           &gr_query.es,
           subquery_id,
           subquery_new_rms,
+          subquery_stats,
           result,
         )
       } else if let Some(ms_coord) = statuses.ms_coord_ess.get(&prefix.source.query_id) {
@@ -709,6 +1062,7 @@ impl CoordContext {
           ms_coord.es.to_exec(),
           subquery_id,
           subquery_new_rms,
+          subquery_stats,
           result,
         )
       } else {
@@ -799,6 +1153,7 @@ impl CoordContext {
               io_ctx,
               query_id,
               success.new_rms,
+              success.stats,
               success.result,
             );
             self.handle_gr_query_es_action(io_ctx, statuses, orig_qid, action);
@@ -808,6 +1163,7 @@ impl CoordContext {
               io_ctx,
               query_id,
               success.new_rms,
+              success.stats,
               success.result,
             );
             self.handle_ms_coord_es_action(io_ctx, statuses, orig_qid, action);
@@ -854,6 +1210,14 @@ impl CoordContext {
       Some(MSQueryCoordAction::ExecuteTMStatus(tm_status)) => {
         let ms_coord = statuses.ms_coord_ess.get_mut(&query_id).unwrap();
         ms_coord.child_queries.push(tm_status.query_id.clone());
+        // Bound how long we wait for `FollowerRead`s before sending them to the Leader.
+        if tm_status.has_pending_follower_reads() {
+          io_ctx.slave_forward(SlaveBackMessage::CoordDefer(CoordDefer {
+            cid: self.this_cid.clone(),
+            defer_time: mk_t(self.coord_config.follower_read_timeout_ms),
+            timer_input: CoordTimerInput::FollowerReadTimeout(tm_status.query_id.clone()),
+          }));
+        }
         statuses.tm_statuss.insert(tm_status.query_id.clone(), tm_status);
       }
       Some(MSQueryCoordAction::ExecuteJoinReadES(join_es)) => {
@@ -868,12 +1232,25 @@ impl CoordContext {
         let action = wrapper.es.start(self, io_ctx);
         self.handle_join_read_action(io_ctx, statuses, join_qid, action);
       }
-      Some(MSQueryCoordAction::Success(all_rms, sql_query, result, timestamp)) => {
+      Some(MSQueryCoordAction::Success(
+        all_rms,
+        sql_query,
+        params,
+        opt_prepared_key,
+        mode,
+        isolation_level,
+        priority,
+        result,
+        timestamp,
+      )) => {
         let ms_coord = statuses.ms_coord_ess.remove(&query_id).unwrap();
 
         if all_rms.is_empty() {
           // If there are no RMs, respond immediately.
+          metrics().inc(M_TRANSACTIONS_COMMITTED, vec![]);
+          self.record_request_end(io_ctx, &ms_coord.request_id, None);
           self.external_request_id_map.remove(&ms_coord.request_id);
+          self.spans.finish(io_ctx, &self.this_eid, &ms_coord.es.trace().span_id);
           io_ctx.send(
             &ms_coord.sender_eid,
             msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(
@@ -888,15 +1265,24 @@ impl CoordContext {
           }
 
           // Add in the FinishQueryES and start it
+          let trace = ms_coord.es.trace().clone();
+          self.spans.start(io_ctx, &query_id, &trace, "FinishQueryTMES");
           let outer = FinishQueryTMES::start_orig(
             self,
             io_ctx,
             query_id.clone(),
+            Some(trace.child(&query_id)),
             FinishQueryTMInner {
               response_data: Some(ResponseData {
                 request_id: ms_coord.request_id,
                 sender_eid: ms_coord.sender_eid,
                 sql_query,
+                params,
+                opt_prepared_key,
+                mode,
+                isolation_level,
+                priority,
+                trace,
                 result,
                 timestamp,
               }),
@@ -907,8 +1293,24 @@ impl CoordContext {
           statuses.finish_query_tm_ess.insert(query_id, outer);
         }
       }
+      Some(MSQueryCoordAction::Explained(result, timestamp)) => {
+        let ms_coord = statuses.ms_coord_ess.get(&query_id).unwrap();
+        self.record_request_end(io_ctx, &ms_coord.request_id, None);
+        io_ctx.send(
+          &ms_coord.sender_eid,
+          msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(
+            msg::ExternalQuerySuccess {
+              request_id: ms_coord.request_id.clone(),
+              timestamp,
+              result,
+            },
+          )),
+        );
+        self.exit_and_clean_up(io_ctx, statuses, query_id);
+      }
       Some(MSQueryCoordAction::FatalFailure(payload)) => {
         let ms_coord = statuses.ms_coord_ess.get(&query_id).unwrap();
+        self.record_request_end(io_ctx, &ms_coord.request_id, Some(&payload));
         io_ctx.send(
           &ms_coord.sender_eid,
           msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
@@ -924,30 +1326,31 @@ impl CoordContext {
         self.exit_all(io_ctx, statuses, child_queries);
 
         // Construct a new MSCoordES using a Timestamp that is strictly greater than before.
+        // Since `exec.timestamp` came from `clock`, which only generates increasing Timestamps,
+        // a new one from `clock` suffices.
         let mut ms_coord = statuses.ms_coord_ess.remove(&query_id).unwrap();
         let exec = ms_coord.es.to_exec();
         let query_id = mk_qid(io_ctx.rand());
         ms_coord.es = FullMSCoordES::QueryPlanning(QueryPlanningES {
-          timestamp: max(
-            cur_timestamp(io_ctx, self.coord_config.timestamp_suffix_divisor),
-            exec.timestamp.add(mk_t(1)),
-          ),
+          timestamp: read_timestamp(&mut self.clock, io_ctx, exec.opt_max_staleness_ms),
           iast_query: exec.iast_query.clone(),
+          params: exec.params.clone(),
+          opt_prepared_key: exec.opt_prepared_key.clone(),
+          mode: exec.mode,
+          isolation_level: exec.isolation_level,
+          priority: exec.priority.boost(),
+          opt_max_staleness_ms: exec.opt_max_staleness_ms,
           query_id: query_id.clone(),
+          trace: exec.trace.clone(),
           state: QueryPlanningS::Start,
         });
-        let ms_coord = map_insert(&mut statuses.ms_coord_ess, &query_id, ms_coord);
-
-        // Update the QueryId that's stored in the `external_request_id_map` and trace it.
-        *self.external_request_id_map.get_mut(&ms_coord.request_id).unwrap() = query_id.clone();
-        io_ctx.general_trace(GeneralTraceMessage::RequestIdQueryId(
-          ms_coord.request_id.clone(),
-          query_id.clone(),
-        ));
-
-        // Start executing the new MSCoordES.
-        let action = ms_coord.es.start(self, io_ctx, start_with_master_query_planning);
-        self.handle_ms_coord_es_action(io_ctx, statuses, query_id, action);
+        self.retry_ms_coord_es(
+          io_ctx,
+          statuses,
+          query_id,
+          ms_coord,
+          start_with_master_query_planning,
+        );
       }
     }
   }
@@ -964,10 +1367,14 @@ impl CoordContext {
       Paxos2PCTMAction::Wait => {}
       Paxos2PCTMAction::Exit => {
         let es = statuses.finish_query_tm_ess.remove(&query_id).unwrap();
+        self.spans.finish(io_ctx, &self.this_eid, &query_id);
         if es.inner.committed {
           // If the ES was successful, send back a success to the External.
+          metrics().inc(M_TRANSACTIONS_COMMITTED, vec![]);
           if let Some(response_data) = es.inner.response_data {
+            self.record_request_end(io_ctx, &response_data.request_id, None);
             self.external_request_id_map.remove(&response_data.request_id);
+            self.spans.finish(io_ctx, &self.this_eid, &response_data.trace.span_id);
             io_ctx.send(
               &response_data.sender_eid,
               msg::NetworkMessage::External(msg::ExternalMessage::ExternalQuerySuccess(
@@ -983,35 +1390,26 @@ impl CoordContext {
           if let Some(response_data) = es.inner.response_data {
             // Otherwise, we should retry the request. We reconstruct a MSCoordESWrapper.
             let query_id = mk_qid(io_ctx.rand());
-            let ms_coord = map_insert(
-              &mut statuses.ms_coord_ess,
-              &query_id,
-              MSCoordESWrapper {
-                request_id: response_data.request_id,
-                sender_eid: response_data.sender_eid,
-                child_queries: vec![],
-                es: FullMSCoordES::QueryPlanning(QueryPlanningES {
-                  timestamp: max(
-                    cur_timestamp(io_ctx, self.coord_config.timestamp_suffix_divisor),
-                    response_data.timestamp.add(mk_t(1)),
-                  ),
-                  iast_query: response_data.sql_query,
-                  query_id: query_id.clone(),
-                  state: QueryPlanningS::Start,
-                }),
-              },
-            );
-
-            // Update the QueryId that's stored in the `external_request_id_map` and trace it.
-            *self.external_request_id_map.get_mut(&ms_coord.request_id).unwrap() = query_id.clone();
-            io_ctx.general_trace(GeneralTraceMessage::RequestIdQueryId(
-              ms_coord.request_id.clone(),
-              query_id.clone(),
-            ));
-
-            // Start executing the new MSCoordES.
-            let action = ms_coord.es.start(self, io_ctx, false);
-            self.handle_ms_coord_es_action(io_ctx, statuses, query_id, action);
+            let ms_coord = MSCoordESWrapper {
+              request_id: response_data.request_id,
+              sender_eid: response_data.sender_eid,
+              child_queries: vec![],
+              es: FullMSCoordES::QueryPlanning(QueryPlanningES {
+                timestamp: self.clock.now_after(io_ctx, &response_data.timestamp),
+                iast_query: response_data.sql_query,
+                params: response_data.params,
+                opt_prepared_key: response_data.opt_prepared_key,
+                mode: response_data.mode,
+                isolation_level: response_data.isolation_level,
+                priority: response_data.priority.boost(),
+                // Bounded staleness reads never have RMs, so they never get here.
+                opt_max_staleness_ms: None,
+                query_id: query_id.clone(),
+                trace: response_data.trace,
+                state: QueryPlanningS::Start,
+              }),
+            };
+            self.retry_ms_coord_es(io_ctx, statuses, query_id, ms_coord, false);
           }
         }
       }
@@ -1034,6 +1432,7 @@ impl CoordContext {
       Some(TPESAction::Success(success)) => {
         // Remove the TableReadESWrapper and respond.
         let trans_read = statuses.trans_table_read_ess.remove(&query_id).unwrap();
+        self.spans.finish(io_ctx, &self.this_eid, &query_id);
         let sender_path = trans_read.sender_path;
         let responder_path = self.mk_query_path(query_id).into_ct();
         // This is the originating Leadership (see Scenario 4,"SenderPath LeaderMap Consistency").
@@ -1045,12 +1444,14 @@ impl CoordContext {
             responder_path,
             result: success.result,
             new_rms: success.new_rms,
+            stats: success.stats,
           }),
         )
       }
       Some(TPESAction::QueryError(query_error)) => {
         // Remove the TableReadESWrapper, abort subqueries, and respond.
         let trans_read = statuses.trans_table_read_ess.remove(&query_id).unwrap();
+        self.spans.finish(io_ctx, &self.this_eid, &query_id);
         let sender_path = trans_read.sender_path;
         let responder_path = self.mk_query_path(query_id).into_ct();
         // This is the originating Leadership (see Scenario 4,"SenderPath LeaderMap Consistency").
@@ -1103,6 +1504,7 @@ impl CoordContext {
           gr_query.es.orig_p,
           gr_query.es.query_id,
           res.new_rms,
+          res.stats,
           res.result,
         );
       }
@@ -1156,6 +1558,71 @@ impl CoordContext {
     }
   }
 
+  /// Retries the External request that `ms_coord` is servicing, where `ms_coord` holds the
+  /// `QueryPlanningES` of the new attempt (with QueryId `query_id`). The new attempt starts
+  /// after an exponential backoff, unless the request has run out of retries or would pass
+  /// its deadline, in which case it is aborted.
+  fn retry_ms_coord_es<IO: CoreIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    query_id: QueryId,
+    ms_coord: MSCoordESWrapper,
+    start_with_master_query_planning: bool,
+  ) {
+    let request = self.external_request_id_map.get_mut(&ms_coord.request_id).unwrap();
+    request.num_retries += 1;
+
+    // Compute the backoff, where we randomly choose between half and all of it so that
+    // conflicting requests do not retry in lockstep.
+    let exponent = min(request.num_retries - 1, 32);
+    let backoff_ms = min(
+      self.coord_config.retry_backoff_ms.saturating_mul(1 << exponent),
+      self.coord_config.max_retry_backoff_ms,
+    );
+    let backoff_ms = backoff_ms / 2 + io_ctx.rand().next_u64() as u128 % (backoff_ms / 2 + 1);
+
+    let opt_payload = if request.num_retries > request.max_retries {
+      Some(msg::ExternalAbortedData::TooManyRetries)
+    } else if let Some(deadline_ms) = request.opt_deadline_ms {
+      if io_ctx.now().time_ms + backoff_ms >= deadline_ms {
+        Some(msg::ExternalAbortedData::Timeout)
+      } else {
+        None
+      }
+    } else {
+      None
+    };
+
+    if let Some(payload) = opt_payload {
+      // Abort the request.
+      self.record_request_end(io_ctx, &ms_coord.request_id, Some(&payload));
+      self.external_request_id_map.remove(&ms_coord.request_id);
+      self.spans.finish(io_ctx, &self.this_eid, &ms_coord.es.trace().span_id);
+      io_ctx.send(
+        &ms_coord.sender_eid,
+        msg::NetworkMessage::External(msg::ExternalMessage::ExternalQueryAborted(
+          msg::ExternalQueryAborted { request_id: ms_coord.request_id, payload },
+        )),
+      );
+    } else {
+      // Update the QueryId that's stored in the `external_request_id_map` and trace it.
+      request.query_id = query_id.clone();
+      io_ctx.general_trace(GeneralTraceMessage::RequestIdQueryId(
+        ms_coord.request_id.clone(),
+        query_id.clone(),
+      ));
+
+      // Start executing the new MSCoordES after the backoff.
+      statuses.ms_coord_ess.insert(query_id.clone(), ms_coord);
+      io_ctx.slave_forward(SlaveBackMessage::CoordDefer(CoordDefer {
+        cid: self.this_cid.clone(),
+        defer_time: mk_t(backoff_ms),
+        timer_input: CoordTimerInput::RetryMSCoordES(query_id, start_with_master_query_planning),
+      }));
+    }
+  }
+
   /// This function is used to initiate an Exit and Clean Up of ESs. This is needed to handle
   /// CancelQuery's, as well as when one ES wants to Exit and Clean Up another ES. Note that
   /// we allow the ES at `query_id` to be in any state, and to not even exist.
@@ -1168,6 +1635,7 @@ impl CoordContext {
     // MSCoordES
     if let Some(mut ms_coord) = statuses.ms_coord_ess.remove(&query_id) {
       self.external_request_id_map.remove(&ms_coord.request_id);
+      self.spans.finish(io_ctx, &self.this_eid, &ms_coord.es.trace().span_id);
       ms_coord.es.exit_and_clean_up(self, io_ctx);
       self.exit_all(io_ctx, statuses, ms_coord.child_queries);
     }
@@ -1183,6 +1651,7 @@ impl CoordContext {
     }
     // TransTableReadES
     else if let Some(mut trans_read) = statuses.trans_table_read_ess.remove(&query_id) {
+      self.spans.finish(io_ctx, &self.this_eid, &query_id);
       trans_read.es.exit_and_clean_up(self, io_ctx);
       self.exit_all(io_ctx, statuses, trans_read.child_queries);
     }
@@ -1192,6 +1661,82 @@ impl CoordContext {
     }
   }
 
+  /// Returns the nodes that recently did not respond to a `FollowerRead` in time, so that
+  /// new `FollowerRead`s can avoid them. Nodes that have been avoided for long enough are
+  /// forgotten here.
+  pub fn unresponsive_followers<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+  ) -> BTreeSet<EndpointId> {
+    let now_ms = io_ctx.now().time_ms;
+    let avoid_ms = UNRESPONSIVE_FOLLOWER_TIMEOUTS * self.coord_config.follower_read_timeout_ms;
+    self.unresponsive_followers.retain(|_, detected_ms| now_ms < *detected_ms + avoid_ms);
+    self.unresponsive_followers.keys().cloned().collect()
+  }
+
+  /// Reports the External request `request_id` as a `SlowQuery` if it took too long. This
+  /// should be called when the request finishes (while it is still in the
+  /// `external_request_id_map`), where `aborted` holds the reason if it failed.
+  fn record_request_end<IO: BasicIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    request_id: &RequestId,
+    aborted: Option<&msg::ExternalAbortedData>,
+  ) {
+    if let Some(threshold_ms) = self.coord_config.slow_query_threshold_ms {
+      let request = self.external_request_id_map.get(request_id).unwrap();
+      let duration_ms = io_ctx.now().time_ms.saturating_sub(request.start_time.time_ms);
+      if duration_ms >= threshold_ms {
+        io_ctx.general_trace(GeneralTraceMessage::SlowQuery(SlowQuery {
+          request_id: request_id.clone(),
+          query: request.query.clone(),
+          duration_ms,
+          aborted: aborted.cloned(),
+        }));
+      }
+    }
+  }
+
+  /// Describes the `MSCoordES`s and `FinishQueryTMES`s at this Coord (for `SHOW QUERIES`).
+  fn running_queries<IO: BasicIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    statuses: &Statuses,
+  ) -> Vec<msg::RunningQuery> {
+    let now = io_ctx.now();
+    let server = format!("Coord {}", self.this_cid.0);
+    let age_ms = |request_id: &RequestId| {
+      let request = self.external_request_id_map.get(request_id)?;
+      Some(now.time_ms.saturating_sub(request.start_time.time_ms))
+    };
+
+    let mut queries = Vec::<msg::RunningQuery>::new();
+    for (query_id, ms_coord) in &statuses.ms_coord_ess {
+      queries.push(msg::RunningQuery {
+        server: server.clone(),
+        es_type: "MSCoordES".to_string(),
+        query_id: query_id.clone(),
+        root_query_id: query_id.clone(),
+        request_id: Some(ms_coord.request_id.clone()),
+        state: ms_coord.es.state_name().to_string(),
+        age_ms: age_ms(&ms_coord.request_id),
+      });
+    }
+    for (query_id, es) in &statuses.finish_query_tm_ess {
+      let request_id = es.inner.response_data.as_ref().map(|data| data.request_id.clone());
+      queries.push(msg::RunningQuery {
+        server: server.clone(),
+        es_type: "FinishQueryTMES".to_string(),
+        query_id: query_id.clone(),
+        root_query_id: query_id.clone(),
+        age_ms: request_id.as_ref().and_then(age_ms),
+        request_id,
+        state: es.state_name().to_string(),
+      });
+    }
+    queries
+  }
+
   /// Construct QueryPath for a given `query_id` that belongs to this Coord.
   pub fn mk_query_path(&self, query_id: QueryId) -> CQueryPath {
     CQueryPath { node_path: self.mk_this_node_path(), query_id }
@@ -1207,3 +1752,25 @@ impl CoordContext {
     lid.eid == self.this_eid
   }
 }
+
+/// Constructs the error for when the number of bind parameters given does not match
+/// the number of parameters in the query.
+fn mk_param_count_error(num_params: u32, num_given: usize) -> msg::ExternalAbortedData {
+  msg::ExternalAbortedData::ParseError(format!(
+    "The query has {} bind parameters, but {} values were given.",
+    num_params, num_given
+  ))
+}
+
+/// Returns a new `Timestamp` from `clock` that a query should read at, which is
+/// `opt_max_staleness_ms` behind the physical time for a bounded staleness read.
+fn read_timestamp<IO: CoreIOCtx>(
+  clock: &mut HybridClock,
+  io_ctx: &mut IO,
+  opt_max_staleness_ms: Option<u128>,
+) -> Timestamp {
+  match opt_max_staleness_ms {
+    Some(max_staleness_ms) => clock.now_stale(io_ctx, max_staleness_ms),
+    None => clock.now(io_ctx),
+  }
+}
@@ -1,4 +1,4 @@
-use crate::common::{mk_t, BasicIOCtx, CTSubNodePath, PaxosGroupIdTrait, TableSchema};
+use crate::common::{mk_t, BasicIOCtx, CTSubNodePath, HybridClock, PaxosGroupIdTrait, TableSchema};
 use crate::common::{
   ColName, ColType, Gen, SlaveGroupId, TablePath, TabletGroupId, TabletKeyRange,
 };
@@ -32,7 +32,7 @@ impl RMPayloadTypes for CreateTableRMPayloadTypes {
   type RMContext = SlaveContext;
 
   // Actions
-  type RMCommitActionData = TabletContext;
+  type RMCommitActionData = Vec<TabletContext>;
 
   // RM PLm
   type RMPreparedPLm = CreateTableRMPrepared;
@@ -44,11 +44,10 @@ impl RMPayloadTypes for CreateTableRMPayloadTypes {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CreateTableRMPrepared {
-  pub tablet_group_id: TabletGroupId,
   pub table_path: TablePath,
   pub gen: Gen,
 
-  pub key_range: TabletKeyRange,
+  pub shards: Vec<(TabletKeyRange, TabletGroupId)>,
   pub key_cols: Vec<(ColName, ColType)>,
   pub val_cols: Vec<(ColName, ColType)>,
 }
@@ -84,6 +83,10 @@ impl RMServerContext<CreateTableRMPayloadTypes> for SlaveContext {
   fn is_leader(&self) -> bool {
     SlaveContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -92,11 +95,10 @@ impl RMServerContext<CreateTableRMPayloadTypes> for SlaveContext {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CreateTableRMInner {
-  pub tablet_group_id: TabletGroupId,
   pub table_path: TablePath,
   pub gen: Gen,
 
-  pub key_range: TabletKeyRange,
+  pub shards: Vec<(TabletKeyRange, TabletGroupId)>,
   pub key_cols: Vec<(ColName, ColType)>,
   pub val_cols: Vec<(ColName, ColType)>,
 }
@@ -111,10 +113,9 @@ impl STMPaxos2PCRMInner<CreateTableRMPayloadTypes> for CreateTableRMInner {
     payload: CreateTablePrepare,
   ) -> CreateTableRMInner {
     CreateTableRMInner {
-      tablet_group_id: payload.tablet_group_id,
       table_path: payload.table_path,
       gen: payload.gen,
-      key_range: payload.key_range,
+      shards: payload.shards,
       key_cols: payload.key_cols,
       val_cols: payload.val_cols,
     }
@@ -126,10 +127,9 @@ impl STMPaxos2PCRMInner<CreateTableRMPayloadTypes> for CreateTableRMInner {
     payload: CreateTableRMPrepared,
   ) -> CreateTableRMInner {
     CreateTableRMInner {
-      tablet_group_id: payload.tablet_group_id,
       table_path: payload.table_path,
       gen: payload.gen,
-      key_range: payload.key_range,
+      shards: payload.shards,
       key_cols: payload.key_cols,
       val_cols: payload.val_cols,
     }
@@ -145,10 +145,9 @@ impl STMPaxos2PCRMInner<CreateTableRMPayloadTypes> for CreateTableRMInner {
     _: &mut IO,
   ) -> Option<CreateTableRMPrepared> {
     Some(CreateTableRMPrepared {
-      tablet_group_id: self.tablet_group_id.clone(),
       table_path: self.table_path.clone(),
       gen: self.gen.clone(),
-      key_range: self.key_range.clone(),
+      shards: self.shards.clone(),
       key_cols: self.key_cols.clone(),
       val_cols: self.val_cols.clone(),
     })
@@ -171,50 +170,67 @@ impl STMPaxos2PCRMInner<CreateTableRMPayloadTypes> for CreateTableRMInner {
     CreateTableRMCommitted {}
   }
 
-  /// Construct `TabletContext` so a Tablet be constructed. We return the `TabletContext`
-  /// in the `RMCommitActionData` rather than construct the Tablet here, since we do not have
-  /// access to the `SlaveIOCtx`.
+  /// Construct a `TabletContext` for every shard so the Tablets can be constructed. We return
+  /// these in the `RMCommitActionData` rather than construct the Tablets here, since we do not
+  /// have access to the `SlaveIOCtx`.
   fn committed_plm_inserted<IO: BasicIOCtx>(
     &mut self,
     ctx: &mut SlaveContext,
     io_ctx: &mut IO,
     _: &RMCommittedPLm<CreateTableRMPayloadTypes>,
-  ) -> TabletContext {
-    let mut rand_seed = [0; 16];
-    io_ctx.rand().fill_bytes(&mut rand_seed);
-    TabletContext {
-      tablet_config: TabletConfig {
-        timestamp_suffix_divisor: ctx.slave_config.timestamp_suffix_divisor,
-      },
-      this_sid: ctx.this_sid.clone(),
-      this_gid: ctx.this_sid.to_gid(),
-      this_tid: self.tablet_group_id.clone(),
-      sub_node_path: CTSubNodePath::Tablet(self.tablet_group_id.clone()),
-      this_eid: ctx.this_eid.clone(),
-      gossip: ctx.gossip.clone(),
-      leader_map: ctx.leader_map.value().clone(),
-      storage: GenericMVTable::new(),
-      this_table_path: self.table_path.clone(),
-      this_sharding_gen: Gen(0),
-      this_tablet_key_range: self.key_range.clone(),
-      sharding_done: true,
-      table_schema: TableSchema {
-        key_cols: self.key_cols.clone(),
-        val_cols: MVM::init(self.val_cols.clone().into_iter().collect()),
-      },
-      presence_timestamp: mk_t(0),
-      verifying_writes: Default::default(),
-      inserting_prepared_writes: Default::default(),
-      prepared_writes: Default::default(),
-      committed_writes: Default::default(),
-      waiting_read_protected: Default::default(),
-      inserting_read_protected: Default::default(),
-      read_protected: Default::default(),
-      waiting_locked_cols: Default::default(),
-      inserting_locked_cols: Default::default(),
-      ms_root_query_map: Default::default(),
-      tablet_bundle: vec![],
+  ) -> Vec<TabletContext> {
+    let mut tablet_ctxs = Vec::<TabletContext>::new();
+    for (key_range, tablet_group_id) in &self.shards {
+      let mut rand_seed = [0; 16];
+      io_ctx.rand().fill_bytes(&mut rand_seed);
+      tablet_ctxs.push(TabletContext {
+        tablet_config: TabletConfig {
+          max_clock_offset_ms: ctx.slave_config.max_clock_offset_ms,
+          closed_timestamp_lag_ms: ctx.slave_config.closed_timestamp_lag_ms,
+          follower_read_wait_ms: ctx.slave_config.follower_read_wait_ms,
+        },
+        this_sid: ctx.this_sid.clone(),
+        this_gid: ctx.this_sid.to_gid(),
+        this_tid: tablet_group_id.clone(),
+        sub_node_path: CTSubNodePath::Tablet(tablet_group_id.clone()),
+        this_eid: ctx.this_eid.clone(),
+        clock: HybridClock::new(ctx.clock_ids.next(), ctx.slave_config.max_clock_offset_ms),
+        gossip: ctx.gossip.clone(),
+        leader_map: ctx.leader_map.value().clone(),
+        storage: GenericMVTable::new(),
+        this_table_path: self.table_path.clone(),
+        prior_table_paths: Default::default(),
+        this_sharding_gen: Gen(0),
+        this_tablet_key_range: key_range.clone(),
+        sharding_done: true,
+        table_schema: TableSchema {
+          key_cols: self.key_cols.clone(),
+          val_cols: MVM::init(self.val_cols.clone().into_iter().collect()),
+          val_col_defaults: Default::default(),
+          val_col_sources: Default::default(),
+        },
+        presence_timestamp: mk_t(0),
+        col_conversions: vec![],
+        inserting_col_conversion: false,
+        verifying_writes: Default::default(),
+        inserting_prepared_writes: Default::default(),
+        prepared_writes: Default::default(),
+        committed_writes: Default::default(),
+        waiting_read_protected: Default::default(),
+        inserting_read_protected: Default::default(),
+        read_protected: Default::default(),
+        preempted_ms_queries: Default::default(),
+        lease_expiry: None,
+        lease_floor: mk_t(0),
+        closed_timestamp: mk_t(0),
+        waiting_locked_cols: Default::default(),
+        inserting_locked_cols: Default::default(),
+        ms_root_query_map: Default::default(),
+        tablet_bundle: vec![],
+        spans: Default::default(),
+      });
     }
+    tablet_ctxs
   }
 
   fn mk_aborted_plm<IO: BasicIOCtx>(
@@ -1,6 +1,6 @@
 use crate::alter_table_tm_es::ResponseData;
 use crate::common::{
-  cur_timestamp, mk_t, BasicIOCtx, FullGen, GeneralTraceMessage, GossipDataMutView, TableSchema,
+  mk_t, BasicIOCtx, FullGen, GeneralTraceMessage, GossipDataMutView, HybridClock, TableSchema,
   Timestamp,
 };
 use crate::common::{
@@ -75,14 +75,13 @@ pub struct CreateTableTMClosed {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CreateTablePrepare {
-  /// Randomly generated by the Master for use by the new Tablet.
-  pub tablet_group_id: TabletGroupId,
-  /// The `TablePath` of the new Tablet
+  /// The `TablePath` of the new Tablets
   pub table_path: TablePath,
   /// The `Gen` of the new Table.
   pub gen: Gen,
-  /// The KeyRange that the Tablet created by this should be servicing.
-  pub key_range: TabletKeyRange,
+  /// The Tablets that should be created in the Slave. Each `TabletGroupId` is randomly
+  /// generated by the Master, and is paired with the KeyRange the Tablet should be servicing.
+  pub shards: Vec<(TabletKeyRange, TabletGroupId)>,
 
   /// The initial schema of the Table.
   pub key_cols: Vec<(ColName, ColType)>,
@@ -131,6 +130,10 @@ impl TMServerContext<CreateTableTMPayloadTypes> for MasterContext {
   fn is_leader(&self) -> bool {
     MasterContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -184,7 +187,12 @@ impl CreateTableTMInner {
       for (col_name, col_type) in &self.val_cols {
         val_cols.write(col_name, Some(col_type.clone()), commit_timestamp.clone());
       }
-      let table_schema = TableSchema { key_cols: self.key_cols.clone(), val_cols };
+      let table_schema = TableSchema {
+        key_cols: self.key_cols.clone(),
+        val_cols,
+        val_col_defaults: BTreeMap::new(),
+        val_col_sources: BTreeMap::new(),
+      };
       gossip.db_schema.insert(table_path_gen.clone(), table_schema);
 
       // Update `sharding_config`.
@@ -239,24 +247,20 @@ impl STMPaxos2PCTMInner<CreateTableTMPayloadTypes> for CreateTableTMInner {
     ctx: &mut MasterContext,
     _: &mut IO,
   ) -> BTreeMap<SlaveGroupId, CreateTablePrepare> {
-    // The RMs are just the shards. Each shard should be in its own Slave.
+    // The RMs are the Slaves that the shards are in. A Slave might hold several shards.
     let mut prepares = BTreeMap::<SlaveGroupId, CreateTablePrepare>::new();
     let gossip_view = ctx.gossip.get();
     let gen = next_gen(gossip_view.table_generation.get_last_present_version(&self.table_path));
     for (key_range, tid, sid) in &self.shards {
-      prepares.insert(
-        sid.clone(),
-        CreateTablePrepare {
-          tablet_group_id: tid.clone(),
-          table_path: self.table_path.clone(),
-          gen: gen.clone(),
-          key_range: key_range.clone(),
-          key_cols: self.key_cols.clone(),
-          val_cols: self.val_cols.clone(),
-        },
-      );
+      let prepare = prepares.entry(sid.clone()).or_insert_with(|| CreateTablePrepare {
+        table_path: self.table_path.clone(),
+        gen: gen.clone(),
+        shards: vec![],
+        key_cols: self.key_cols.clone(),
+        val_cols: self.val_cols.clone(),
+      });
+      prepare.shards.push((key_range.clone(), tid.clone()));
     }
-    debug_assert_eq!(prepares.len(), self.shards.len());
     prepares
   }
 
@@ -277,12 +281,11 @@ impl STMPaxos2PCTMInner<CreateTableTMPayloadTypes> for CreateTableTMInner {
   ) -> BTreeMap<SlaveGroupId, CreateTableCommit> {
     self.did_commit = true;
 
-    // The RMs are just the shards. Each shard should be in its own Slave.
+    // The RMs are the Slaves that the shards are in.
     let mut commits = BTreeMap::<SlaveGroupId, CreateTableCommit>::new();
     for (_, _, sid) in &self.shards {
       commits.insert(sid.clone(), CreateTableCommit {});
     }
-    debug_assert_eq!(commits.len(), self.shards.len());
     commits
   }
 
@@ -330,11 +333,7 @@ impl STMPaxos2PCTMInner<CreateTableTMPayloadTypes> for CreateTableTMInner {
     ctx: &mut MasterContext,
     io_ctx: &mut IO,
   ) -> CreateTableTMClosed {
-    let timestamp_hint = if self.did_commit {
-      Some(cur_timestamp(io_ctx, ctx.master_config.timestamp_suffix_divisor))
-    } else {
-      None
-    };
+    let timestamp_hint = if self.did_commit { Some(ctx.clock.now(io_ctx)) } else { None };
     CreateTableTMClosed { timestamp_hint }
   }
 
@@ -395,7 +394,7 @@ impl STMPaxos2PCTMInner<CreateTableTMPayloadTypes> for CreateTableTMInner {
 }
 
 /// Compute the next generating, taking it as 0 if it does not exist yet.
-fn next_gen(m_cur_full_gen: Option<&FullGen>) -> Gen {
+pub fn next_gen(m_cur_full_gen: Option<&FullGen>) -> Gen {
   if let Some((gen, _)) = m_cur_full_gen {
     gen.next()
   } else {
@@ -1,6 +1,6 @@
 use crate::common::TNodePath;
-use crate::common::{cur_timestamp, Timestamp};
-use crate::common::{mk_t, BasicIOCtx};
+use crate::common::Timestamp;
+use crate::common::{mk_t, BasicIOCtx, HybridClock};
 use crate::drop_table_tm_es::{
   DropTableClosed, DropTableCommit, DropTablePrepare, DropTablePrepared, DropTableTMPayloadTypes,
 };
@@ -75,6 +75,10 @@ impl RMServerContext<DropTableRMPayloadTypes> for TabletContext {
   fn is_leader(&self) -> bool {
     TabletContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -96,7 +100,7 @@ impl STMPaxos2PCRMInner<DropTableRMPayloadTypes> for DropTableRMInner {
     _: DropTablePrepare,
   ) -> DropTableRMInner {
     // Construct the `preparing_timestamp`
-    let mut timestamp = cur_timestamp(io_ctx, ctx.tablet_config.timestamp_suffix_divisor);
+    let mut timestamp = ctx.clock.now(io_ctx);
     timestamp = max(timestamp, ctx.table_schema.val_cols.get_latest_lat());
     timestamp = max(timestamp, ctx.presence_timestamp.clone());
     for (_, req) in ctx.waiting_locked_cols.iter().chain(ctx.inserting_locked_cols.iter()) {
@@ -1,5 +1,5 @@
 use crate::alter_table_tm_es::{get_rms, ResponseData};
-use crate::common::{cur_timestamp, mk_t, BasicIOCtx, GeneralTraceMessage, Timestamp};
+use crate::common::{mk_t, BasicIOCtx, GeneralTraceMessage, HybridClock, Timestamp};
 use crate::common::{TNodePath, TablePath};
 use crate::master::{MasterContext, MasterPLm};
 use crate::message as msg;
@@ -113,6 +113,10 @@ impl TMServerContext<DropTableTMPayloadTypes> for MasterContext {
   fn is_leader(&self) -> bool {
     MasterContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -165,7 +169,7 @@ impl STMPaxos2PCTMInner<DropTableTMPayloadTypes> for DropTableTMInner {
     io_ctx: &mut IO,
     prepared: &BTreeMap<TNodePath, DropTablePrepared>,
   ) -> DropTableTMCommitted {
-    let mut timestamp_hint = cur_timestamp(io_ctx, ctx.master_config.timestamp_suffix_divisor);
+    let mut timestamp_hint = ctx.clock.now(io_ctx);
     for (_, prepared) in prepared {
       timestamp_hint = max(timestamp_hint, prepared.timestamp.clone());
     }
@@ -0,0 +1,457 @@
+use crate::common::{
+  ColBound, ColName, ColType, ColVal, ExecutionStats, FullGen, KeyBound, PolyColBound, QueryResult,
+  SingleBound, TablePath, TabletGroupId, TierMap, TransTableName,
+};
+use crate::expression::compute_key_region;
+use crate::server::CTServerContext;
+use crate::sql_ast::{iast, proc};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// -----------------------------------------------------------------------------------------------
+//  EXPLAIN
+// -----------------------------------------------------------------------------------------------
+// The result of an `EXPLAIN` is a tree of lines that describe the `MSQuery` the planner
+// produced. Every line is a row of the `QueryResult`, where the first column is the line
+// number (so that the lines come out in order) and the second is the indented text.
+
+/// The schema of the `QueryResult` of an `EXPLAIN`.
+pub fn explain_schema() -> Vec<Option<ColName>> {
+  vec![Some(ColName("line".to_string())), Some(ColName("QUERY PLAN".to_string()))]
+}
+
+/// Converts `lines` into the `QueryResult` of an `EXPLAIN`.
+pub fn explain_result(lines: Vec<String>) -> QueryResult {
+  let mut result = QueryResult::new(explain_schema());
+  for (i, line) in lines.into_iter().enumerate() {
+    result.add_row(vec![Some(ColVal::Int(i as i32)), Some(ColVal::String(line))]);
+  }
+  result
+}
+
+/// Describes how `ms_query` will be executed. This covers every stage, the Join Trees of
+/// the `JoinSelect`s (with their dependencies and pushed down conjunctions), and, for every
+/// Table that is read or written, the Key Region and the Tablets (and their SlaveGroups)
+/// that will be contacted.
+pub fn explain_ms_query<CtxT: CTServerContext>(
+  ctx: &CtxT,
+  ms_query: &proc::MSQuery,
+  table_location_map: &BTreeMap<TablePath, FullGen>,
+  all_tier_maps: &BTreeMap<TransTableName, TierMap>,
+) -> Vec<String> {
+  let mut explainer = Explainer { ctx, table_location_map, lines: vec![] };
+  explainer.push(0, format!("MSQuery (returning {})", ms_query.returning.0));
+  for (trans_table_name, stage) in &ms_query.trans_tables {
+    explainer.explain_ms_stage(1, trans_table_name, stage);
+    if let Some(tier_map) = all_tier_maps.get(trans_table_name).filter(|t| !t.map.is_empty()) {
+      let tiers: Vec<String> =
+        tier_map.map.iter().map(|(path, tier)| format!("{}: {}", path.0, tier)).collect();
+      explainer.push(2, format!("Tier Map: {{{}}}", tiers.join(", ")));
+    }
+  }
+  explainer.lines
+}
+
+struct Explainer<'a, CtxT: CTServerContext> {
+  ctx: &'a CtxT,
+  table_location_map: &'a BTreeMap<TablePath, FullGen>,
+  lines: Vec<String>,
+}
+
+impl<'a, CtxT: CTServerContext> Explainer<'a, CtxT> {
+  fn push(&mut self, depth: usize, line: String) {
+    self.lines.push(format!("{}{}", "  ".repeat(depth), line));
+  }
+
+  fn explain_ms_stage(
+    &mut self,
+    depth: usize,
+    trans_table_name: &TransTableName,
+    stage: &proc::MSQueryStage,
+  ) {
+    let name = &trans_table_name.0;
+    let mut subqueries = Vec::<&proc::GRQuery>::new();
+    match stage {
+      proc::MSQueryStage::TableSelect(select) => {
+        self.explain_table_select(depth, name, select, &mut subqueries)
+      }
+      proc::MSQueryStage::TransTableSelect(select) => {
+        self.explain_trans_table_select(depth, name, select, &mut subqueries)
+      }
+      proc::MSQueryStage::JoinSelect(select) => {
+        self.explain_join_select(depth, name, select, &mut subqueries)
+      }
+      proc::MSQueryStage::Update(update) => {
+        let source = &update.table;
+        self.push(depth, format!("{}: Update {}", name, fmt_table_source(source)));
+        let assignments: Vec<String> = update
+          .assignment
+          .iter()
+          .map(|(col, expr)| format!("{} = {}", col.0, fmt_expr(expr, &mut subqueries)))
+          .collect();
+        self.push(depth + 1, format!("Set: {}", assignments.join(", ")));
+        self.explain_filter(depth + 1, &update.selection, &mut subqueries);
+        self.explain_table_access(depth + 1, source, Some(&update.selection));
+      }
+      proc::MSQueryStage::Insert(insert) => {
+        let source = &insert.table;
+        let cols: Vec<&str> = insert.columns.iter().map(|col| col.0.as_str()).collect();
+        self.push(
+          depth,
+          format!("{}: Insert into {} ({})", name, fmt_table_source(source), cols.join(", ")),
+        );
+        for row in &insert.values {
+          let vals: Vec<String> = row.iter().map(|val| fmt_expr(val, &mut subqueries)).collect();
+          self.push(depth + 1, format!("Values: ({})", vals.join(", ")));
+        }
+        self.explain_table_access(depth + 1, source, None);
+      }
+      proc::MSQueryStage::Delete(delete) => {
+        let source = &delete.table;
+        self.push(depth, format!("{}: Delete from {}", name, fmt_table_source(source)));
+        self.explain_filter(depth + 1, &delete.selection, &mut subqueries);
+        self.explain_table_access(depth + 1, source, Some(&delete.selection));
+      }
+    }
+    self.explain_subqueries(depth + 1, subqueries);
+  }
+
+  fn explain_gr_query(&mut self, depth: usize, gr_query: &proc::GRQuery) {
+    self.push(depth, format!("GRQuery (returning {})", gr_query.returning.0));
+    for (trans_table_name, stage) in &gr_query.trans_tables {
+      let name = &trans_table_name.0;
+      let mut subqueries = Vec::<&proc::GRQuery>::new();
+      match stage {
+        proc::GRQueryStage::TableSelect(select) => {
+          self.explain_table_select(depth + 1, name, select, &mut subqueries)
+        }
+        proc::GRQueryStage::TransTableSelect(select) => {
+          self.explain_trans_table_select(depth + 1, name, select, &mut subqueries)
+        }
+        proc::GRQueryStage::JoinSelect(select) => {
+          self.explain_join_select(depth + 1, name, select, &mut subqueries)
+        }
+      }
+      self.explain_subqueries(depth + 2, subqueries);
+    }
+  }
+
+  fn explain_subqueries(&mut self, depth: usize, subqueries: Vec<&proc::GRQuery>) {
+    for (i, subquery) in subqueries.into_iter().enumerate() {
+      self.push(depth, format!("Subquery #{}:", i));
+      self.explain_gr_query(depth + 1, subquery);
+    }
+  }
+
+  fn explain_table_select<'b>(
+    &mut self,
+    depth: usize,
+    name: &str,
+    select: &'b proc::TableSelect,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    let distinct = if select.distinct { " Distinct" } else { "" };
+    let source = fmt_table_source(&select.from);
+    self.push(depth, format!("{}: TableSelect{} from {}", name, distinct, source));
+    self.explain_projection(depth + 1, &select.projection, subqueries);
+    self.explain_filter(depth + 1, &select.selection, subqueries);
+    if let Some(lock) = select.lock {
+      self.push(depth + 1, format!("Lock: {:?}", lock));
+    }
+    self.explain_table_access(depth + 1, &select.from, Some(&select.selection));
+  }
+
+  fn explain_trans_table_select<'b>(
+    &mut self,
+    depth: usize,
+    name: &str,
+    select: &'b proc::TransTableSelect,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    let distinct = if select.distinct { " Distinct" } else { "" };
+    let source = &select.from;
+    self.push(
+      depth,
+      format!(
+        "{}: TransTableSelect{} from {} AS {}",
+        name, distinct, source.trans_table_name.0, source.alias
+      ),
+    );
+    self.explain_projection(depth + 1, &select.projection, subqueries);
+    self.explain_filter(depth + 1, &select.selection, subqueries);
+  }
+
+  fn explain_join_select<'b>(
+    &mut self,
+    depth: usize,
+    name: &str,
+    select: &'b proc::JoinSelect,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    let distinct = if select.distinct { " Distinct" } else { "" };
+    self.push(depth, format!("{}: JoinSelect{}", name, distinct));
+    self.explain_projection(depth + 1, &select.projection, subqueries);
+    for (dependent, dependency) in &select.dependency_graph {
+      self.push(depth + 1, format!("Dependency: {} waits on {}", dependent, dependency));
+    }
+    self.explain_join_node(depth + 1, "root".to_string(), &select.from, subqueries);
+  }
+
+  /// Explains the `JoinNode` that has the identifier `id` in the Join Tree (which are
+  /// used by the `dependency_graph`).
+  fn explain_join_node<'b>(
+    &mut self,
+    depth: usize,
+    id: String,
+    join_node: &'b proc::JoinNode,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    let mk_child_id = |side: &str| if id == "root" { side.to_string() } else { id.clone() + side };
+    match join_node {
+      proc::JoinNode::JoinInnerNode(inner) => {
+        self.push(depth, format!("[{}] {:?} Join", id, inner.join_type));
+        for expr in &inner.strong_conjunctions {
+          self.push(depth + 1, format!("Strong Conjunction: {}", fmt_expr(expr, subqueries)));
+        }
+        for expr in &inner.weak_conjunctions {
+          self.push(depth + 1, format!("Weak Conjunction: {}", fmt_expr(expr, subqueries)));
+        }
+        self.explain_join_node(depth + 1, mk_child_id("L"), &inner.left, subqueries);
+        self.explain_join_node(depth + 1, mk_child_id("R"), &inner.right, subqueries);
+      }
+      proc::JoinNode::JoinLeaf(leaf) => {
+        let lateral = if leaf.lateral { " (lateral)" } else { "" };
+        self.push(depth, format!("[{}] Leaf {}{}", id, leaf.alias, lateral));
+        self.explain_gr_query(depth + 1, &leaf.query);
+      }
+    }
+  }
+
+  fn explain_projection<'b>(
+    &mut self,
+    depth: usize,
+    projection: &'b Vec<proc::SelectItem>,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    let mut items = Vec::<String>::new();
+    for item in projection {
+      items.push(match item {
+        proc::SelectItem::ExprWithAlias { item, alias } => {
+          let item_str = match item {
+            proc::SelectExprItem::ValExpr(expr) => fmt_expr(expr, subqueries),
+            proc::SelectExprItem::UnaryAggregate(agg) => {
+              let distinct = if agg.distinct { "DISTINCT " } else { "" };
+              let op = format!("{:?}", agg.op).to_uppercase();
+              format!("{}({}{})", op, distinct, fmt_expr(&agg.expr, subqueries))
+            }
+          };
+          match alias {
+            Some(alias) => format!("{} AS {}", item_str, alias.0),
+            None => item_str,
+          }
+        }
+        proc::SelectItem::Wildcard { table_name: Some(table_name) } => format!("{}.*", table_name),
+        proc::SelectItem::Wildcard { table_name: None } => "*".to_string(),
+      });
+    }
+    self.push(depth, format!("Projection: {}", items.join(", ")));
+  }
+
+  fn explain_filter<'b>(
+    &mut self,
+    depth: usize,
+    selection: &'b proc::ValExpr,
+    subqueries: &mut Vec<&'b proc::GRQuery>,
+  ) {
+    if selection != &(proc::ValExpr::Value { val: iast::Value::Boolean(true) }) {
+      self.push(depth, format!("Filter: {}", fmt_expr(selection, subqueries)));
+    }
+  }
+
+  /// Explains the Key Region that is read from `source` (if there is a `selection`), and
+  /// the Tablets that will be contacted.
+  fn explain_table_access(
+    &mut self,
+    depth: usize,
+    source: &proc::TableSource,
+    opt_selection: Option<&proc::ValExpr>,
+  ) {
+    let full_gen = match self.table_location_map.get(&source.table_path) {
+      Some(full_gen) => full_gen,
+      None => return,
+    };
+    let tids = if let Some(selection) = opt_selection {
+      let (gen, _) = full_gen;
+      let table_path_gen = (source.table_path.clone(), gen.clone());
+      let gossip = self.ctx.gossip().get();
+      let key_cols = &gossip.db_schema.get(&table_path_gen).unwrap().key_cols;
+      let row_region = compute_key_region(selection, BTreeMap::new(), &source.alias, key_cols);
+      for key_bound in &row_region {
+        self.push(depth, format!("Key Region: {}", fmt_key_bound(key_bound, key_cols)));
+      }
+      self.ctx.get_min_tablets(source, full_gen, selection)
+    } else {
+      self.ctx.get_all_tablets(source, full_gen)
+    };
+    self.push(depth, format!("Tablets: {}", self.fmt_tablets(&tids)));
+  }
+
+  /// Lists `tids` along with the SlaveGroups that they are in.
+  fn fmt_tablets(&self, tids: &Vec<TabletGroupId>) -> String {
+    let gossip = self.ctx.gossip().get();
+    let mut tablets = Vec::<String>::new();
+    for tid in tids {
+      match gossip.tablet_address_config.get(tid) {
+        Some(sid) => tablets.push(format!("{} (SlaveGroup {})", tid.0, sid.0)),
+        None => tablets.push(tid.0.clone()),
+      }
+    }
+    tablets.join(", ")
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  EXPLAIN ANALYZE
+// -----------------------------------------------------------------------------------------------
+
+/// The `ExecutionStats` of a single stage of an `MSQuery`, as collected by the `MSCoordES`.
+#[derive(Debug, Clone)]
+pub struct StageStats {
+  pub trans_table_name: TransTableName,
+  /// The time (in ms) from when the stage was sent out to when its results came back.
+  pub duration_ms: u128,
+  pub stats: ExecutionStats,
+}
+
+/// Describes how each stage of an `MSQuery` executed. This is appended to the lines
+/// from `explain_ms_query` for an `EXPLAIN ANALYZE`.
+pub fn explain_analyze(stage_stats: &Vec<StageStats>) -> Vec<String> {
+  let mut lines = vec!["Execution".to_string()];
+  let mut total = ExecutionStats::default();
+  let mut total_duration_ms = 0;
+  for stage in stage_stats {
+    lines.push(format!("  {}: {} ms", stage.trans_table_name.0, stage.duration_ms));
+    push_stats(&mut lines, &stage.stats);
+    total.merge(stage.stats.clone());
+    total_duration_ms += stage.duration_ms;
+  }
+  lines.push(format!("  Total: {} ms", total_duration_ms));
+  push_stats(&mut lines, &total);
+  lines
+}
+
+fn push_stats(lines: &mut Vec<String>, stats: &ExecutionStats) {
+  let rows_read: Vec<String> =
+    stats.rows_read.iter().map(|(tid, rows)| format!("{}: {}", tid.0, rows)).collect();
+  let total_rows_read: u64 = stats.rows_read.values().sum();
+  lines.push(format!("    Rows Read: {} {{{}}}", total_rows_read, rows_read.join(", ")));
+  lines.push(format!("    Subqueries: {}", stats.subqueries));
+  lines.push(format!("    Messages Sent: {}", stats.messages_sent));
+  lines.push(format!("    Read Protection Wait: {} ms", stats.read_protection_wait_ms));
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Formatting
+// -----------------------------------------------------------------------------------------------
+
+fn fmt_table_source(source: &proc::TableSource) -> String {
+  format!("{} AS {}", source.table_path.0, source.alias)
+}
+
+/// Formats `expr` as SQL. Subqueries are only referred to by their index in `subqueries`,
+/// which they are pushed onto so that they can be explained separately.
+fn fmt_expr<'b>(expr: &'b proc::ValExpr, subqueries: &mut Vec<&'b proc::GRQuery>) -> String {
+  match expr {
+    proc::ValExpr::ColumnRef(col_ref) => format!("{}.{}", col_ref.table_name, col_ref.col_name.0),
+    proc::ValExpr::UnaryExpr { op, expr } => {
+      let inner = fmt_expr(expr, subqueries);
+      match op {
+        iast::UnaryOp::Plus => format!("+{}", inner),
+        iast::UnaryOp::Minus => format!("-{}", inner),
+        iast::UnaryOp::Not => format!("NOT {}", inner),
+        iast::UnaryOp::IsNull => format!("{} IS NULL", inner),
+        iast::UnaryOp::IsNotNull => format!("{} IS NOT NULL", inner),
+      }
+    }
+    proc::ValExpr::BinaryExpr { op, left, right } => {
+      let op_str = match op {
+        iast::BinaryOp::Plus => "+",
+        iast::BinaryOp::Minus => "-",
+        iast::BinaryOp::Multiply => "*",
+        iast::BinaryOp::Divide => "/",
+        iast::BinaryOp::Modulus => "%",
+        iast::BinaryOp::StringConcat => "||",
+        iast::BinaryOp::Gt => ">",
+        iast::BinaryOp::Lt => "<",
+        iast::BinaryOp::GtEq => ">=",
+        iast::BinaryOp::LtEq => "<=",
+        iast::BinaryOp::Spaceship => "<=>",
+        iast::BinaryOp::Eq => "=",
+        iast::BinaryOp::NotEq => "<>",
+        iast::BinaryOp::And => "AND",
+        iast::BinaryOp::Or => "OR",
+      };
+      let left = fmt_expr(left, subqueries);
+      let right = fmt_expr(right, subqueries);
+      format!("({} {} {})", left, op_str, right)
+    }
+    proc::ValExpr::Value { val } => match val {
+      iast::Value::Number(num) => num.clone(),
+      iast::Value::QuotedString(string) => format!("'{}'", string.replace('\'', "''")),
+      iast::Value::Boolean(true) => "TRUE".to_string(),
+      iast::Value::Boolean(false) => "FALSE".to_string(),
+      iast::Value::Null => "NULL".to_string(),
+      iast::Value::Param(n) => format!("${}", n),
+    },
+    proc::ValExpr::Subquery { query } => {
+      subqueries.push(query);
+      format!("(Subquery #{})", subqueries.len() - 1)
+    }
+  }
+}
+
+/// Formats `key_bound` as a conjunction of constraints on the `key_cols`, omitting
+/// the ones that are unconstrained.
+fn fmt_key_bound(key_bound: &KeyBound, key_cols: &Vec<(ColName, ColType)>) -> String {
+  let mut constraints = Vec::<String>::new();
+  for ((col_name, _), poly_col_bound) in key_cols.iter().zip(key_bound.col_bounds.iter()) {
+    let opt_constraint = match poly_col_bound {
+      PolyColBound::Int(bound) => fmt_col_bound(bound, |v| v.to_string()),
+      PolyColBound::String(bound) => fmt_col_bound(bound, |v| format!("'{}'", v)),
+      PolyColBound::Bool(bound) => fmt_col_bound(bound, |v| v.to_string()),
+      PolyColBound::BigInt(bound) => fmt_col_bound(bound, |v| v.to_string()),
+    };
+    if let Some(constraint) = opt_constraint {
+      constraints.push(format!("{} {}", col_name.0, constraint));
+    }
+  }
+  if constraints.is_empty() {
+    "all keys".to_string()
+  } else {
+    constraints.join(" AND ")
+  }
+}
+
+/// Formats `bound` as either `= v` or an interval, returning `None` if it is unbounded.
+fn fmt_col_bound<T: Display + Clone + PartialEq, F: Fn(&T) -> String>(
+  bound: &ColBound<T>,
+  fmt_val: F,
+) -> Option<String> {
+  if let Some(point) = bound.point() {
+    return Some(format!("= {}", fmt_val(&point)));
+  }
+  let start = match &bound.start {
+    SingleBound::Included(v) => format!("[{}", fmt_val(v)),
+    SingleBound::Excluded(v) => format!("({}", fmt_val(v)),
+    SingleBound::Unbounded => "(-inf".to_string(),
+  };
+  let end = match &bound.end {
+    SingleBound::Included(v) => format!("{}]", fmt_val(v)),
+    SingleBound::Excluded(v) => format!("{})", fmt_val(v)),
+    SingleBound::Unbounded => "+inf)".to_string(),
+  };
+  match (&bound.start, &bound.end) {
+    (SingleBound::Unbounded, SingleBound::Unbounded) => None,
+    _ => Some(format!("in {}, {}", start, end)),
+  }
+}
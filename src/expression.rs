@@ -44,6 +44,8 @@ pub enum EvalError {
   TypeError,
   /// The `ValExpr` is not a Simple ValExpr.
   InvalidSimpleExpr,
+  /// An arithmetic operation overflowed its type.
+  Overflow,
 }
 
 /// This is the expression type we use to Compute a value (hence why it is called CExpr).
@@ -68,13 +70,16 @@ pub enum KBExpr {
   UnknownValue,
 }
 
-/// This parses an `iast::Value` into a valid `ColValN`. Issues might arise if the string that's
-/// representing an integer is too big or has non-digit characters.
+/// This parses an `iast::Value` into a valid `ColValN`. Integers that do not fit into an `Int`
+/// become a `BigInt`. Issues might arise if the string that's representing an integer is too
+/// big even for that or has non-digit characters.
 pub fn construct_colvaln(val: iast::Value) -> Result<ColValN, EvalError> {
   let col_val = match val {
     iast::Value::Number(num_string) => {
       if let Ok(parsed_num) = num_string.parse::<i32>() {
         Some(ColVal::Int(parsed_num))
+      } else if let Ok(parsed_num) = num_string.parse::<i64>() {
+        Some(ColVal::BigInt(parsed_num))
       } else {
         return Err(EvalError::GenericError);
       }
@@ -82,6 +87,8 @@ pub fn construct_colvaln(val: iast::Value) -> Result<ColValN, EvalError> {
     iast::Value::QuotedString(string_val) => Some(ColVal::String(string_val)),
     iast::Value::Boolean(bool_val) => Some(ColVal::Bool(bool_val)),
     iast::Value::Null => None,
+    // Bind parameters should have been replaced before evaluation.
+    iast::Value::Param(_) => return Err(EvalError::GenericError),
   };
   Ok(col_val)
 }
@@ -144,9 +151,13 @@ fn evaluate_unary_op(op: &iast::UnaryOp, expr: ColValN) -> Result<ColValN, EvalE
   match (op, expr) {
     // Plus
     (iast::UnaryOp::Plus, Some(ColVal::Int(val))) => Ok(Some(ColVal::Int(val))),
+    (iast::UnaryOp::Plus, Some(ColVal::BigInt(val))) => Ok(Some(ColVal::BigInt(val))),
     (iast::UnaryOp::Plus, None) => Ok(None),
     // Minus
     (iast::UnaryOp::Minus, Some(ColVal::Int(val))) => Ok(Some(ColVal::Int(-val))),
+    (iast::UnaryOp::Minus, Some(ColVal::BigInt(val))) => {
+      Ok(Some(ColVal::BigInt(val.checked_neg().ok_or(EvalError::Overflow)?)))
+    }
     (iast::UnaryOp::Minus, None) => Ok(None),
     // Not
     (iast::UnaryOp::Not, Some(ColVal::Bool(val))) => Ok(Some(ColVal::Bool(!val))),
@@ -166,33 +177,51 @@ fn evaluate_unary_op(op: &iast::UnaryOp, expr: ColValN) -> Result<ColValN, EvalE
 ///
 /// The `NULL` handling was observed with Postgres, where we applied the unary operations
 /// to a column name (not the NULL keyword directly). Observe that for most operators, if either
-/// side is `NULL`, we bubble that up.
+/// side is `NULL`, we bubble that up. If an `Int` meets a `BigInt`, it is widened first.
 fn evaluate_binary_op(
   op: &iast::BinaryOp,
   left: ColValN,
   right: ColValN,
 ) -> Result<ColValN, EvalError> {
+  let (left, right) = match (left, right) {
+    (Some(ColVal::Int(left_val)), Some(ColVal::BigInt(right_val))) => {
+      (Some(ColVal::BigInt(left_val as i64)), Some(ColVal::BigInt(right_val)))
+    }
+    (Some(ColVal::BigInt(left_val)), Some(ColVal::Int(right_val))) => {
+      (Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val as i64)))
+    }
+    (left, right) => (left, right),
+  };
   match (op, left, right) {
     // Plus
     (iast::BinaryOp::Plus, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Int(left_val + right_val)))
     }
-    (iast::BinaryOp::Plus, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Plus, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Plus, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::BigInt(left_val.checked_add(right_val).ok_or(EvalError::Overflow)?)))
+    }
+    (iast::BinaryOp::Plus, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Plus, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Plus, None, None) => Ok(None),
     // Minus
     (iast::BinaryOp::Minus, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Int(left_val - right_val)))
     }
-    (iast::BinaryOp::Minus, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Minus, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Minus, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::BigInt(left_val.checked_sub(right_val).ok_or(EvalError::Overflow)?)))
+    }
+    (iast::BinaryOp::Minus, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Minus, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Minus, None, None) => Ok(None),
     // Multiply
     (iast::BinaryOp::Multiply, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Int(left_val * right_val)))
     }
-    (iast::BinaryOp::Multiply, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Multiply, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Multiply, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::BigInt(left_val.checked_mul(right_val).ok_or(EvalError::Overflow)?)))
+    }
+    (iast::BinaryOp::Multiply, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Multiply, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Multiply, None, None) => Ok(None),
     // Divide
     (iast::BinaryOp::Divide, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
@@ -202,8 +231,15 @@ fn evaluate_binary_op(
         Err(EvalError::InvalidBinaryOp)
       }
     }
-    (iast::BinaryOp::Divide, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Divide, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Divide, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      if right_val != 0 {
+        Ok(Some(ColVal::BigInt(left_val.checked_div(right_val).ok_or(EvalError::Overflow)?)))
+      } else {
+        Err(EvalError::InvalidBinaryOp)
+      }
+    }
+    (iast::BinaryOp::Divide, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Divide, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Divide, None, None) => Ok(None),
     // Modulus
     (iast::BinaryOp::Modulus, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
@@ -213,8 +249,15 @@ fn evaluate_binary_op(
         Err(EvalError::InvalidBinaryOp)
       }
     }
-    (iast::BinaryOp::Modulus, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Modulus, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Modulus, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      if right_val != 0 {
+        Ok(Some(ColVal::BigInt(left_val.checked_rem(right_val).ok_or(EvalError::Overflow)?)))
+      } else {
+        Err(EvalError::InvalidBinaryOp)
+      }
+    }
+    (iast::BinaryOp::Modulus, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Modulus, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Modulus, None, None) => Ok(None),
     // StringConcat
     (
@@ -233,30 +276,42 @@ fn evaluate_binary_op(
     (iast::BinaryOp::Gt, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val > right_val)))
     }
-    (iast::BinaryOp::Gt, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Gt, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Gt, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val > right_val)))
+    }
+    (iast::BinaryOp::Gt, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Gt, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Gt, None, None) => Ok(None),
     // Lt
     (iast::BinaryOp::Lt, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val < right_val)))
     }
-    (iast::BinaryOp::Lt, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::Lt, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::Lt, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val < right_val)))
+    }
+    (iast::BinaryOp::Lt, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::Lt, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::Lt, None, None) => Ok(None),
     // GtEq
     (iast::BinaryOp::GtEq, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val >= right_val)))
     }
-    (iast::BinaryOp::GtEq, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::GtEq, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::GtEq, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val >= right_val)))
+    }
+    (iast::BinaryOp::GtEq, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::GtEq, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::GtEq, None, None) => Ok(None),
     // LtEq
     (iast::BinaryOp::LtEq, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val <= right_val)))
     }
+    (iast::BinaryOp::LtEq, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val <= right_val)))
+    }
     // TODO: define this for strings and bool.
-    (iast::BinaryOp::LtEq, Some(ColVal::Int(_)), None) => Ok(None),
-    (iast::BinaryOp::LtEq, None, Some(ColVal::Int(_))) => Ok(None),
+    (iast::BinaryOp::LtEq, Some(ColVal::Int(_) | ColVal::BigInt(_)), None) => Ok(None),
+    (iast::BinaryOp::LtEq, None, Some(ColVal::Int(_) | ColVal::BigInt(_))) => Ok(None),
     (iast::BinaryOp::LtEq, None, None) => Ok(None),
     // Spaceship
     (iast::BinaryOp::Spaceship, left_val, right_val) => {
@@ -267,6 +322,9 @@ fn evaluate_binary_op(
     (iast::BinaryOp::Eq, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val == right_val)))
     }
+    (iast::BinaryOp::Eq, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val == right_val)))
+    }
     (iast::BinaryOp::Eq, Some(ColVal::Bool(left_val)), Some(ColVal::Bool(right_val))) => {
       Ok(Some(ColVal::Bool(left_val == right_val)))
     }
@@ -280,6 +338,9 @@ fn evaluate_binary_op(
     (iast::BinaryOp::NotEq, Some(ColVal::Int(left_val)), Some(ColVal::Int(right_val))) => {
       Ok(Some(ColVal::Bool(left_val != right_val)))
     }
+    (iast::BinaryOp::NotEq, Some(ColVal::BigInt(left_val)), Some(ColVal::BigInt(right_val))) => {
+      Ok(Some(ColVal::Bool(left_val != right_val)))
+    }
     (iast::BinaryOp::NotEq, Some(ColVal::Bool(left_val)), Some(ColVal::Bool(right_val))) => {
       Ok(Some(ColVal::Bool(left_val != right_val)))
     }
@@ -605,6 +666,10 @@ fn compute_poly_col_bounds(
       .into_iter()
       .map(|bound| PolyColBound::String(bound))
       .collect(),
+    ColType::BigInt => compute_col_bounds::<i64>(&kb_expr, col_name)
+      .into_iter()
+      .map(|bound| PolyColBound::BigInt(bound))
+      .collect(),
   }
 }
 
@@ -623,9 +688,22 @@ fn full_poly_col_bounds(col_type: &ColType) -> Vec<PolyColBound> {
       let bound = ColBound { start: SingleBound::Unbounded, end: SingleBound::Unbounded };
       vec![(PolyColBound::String(bound))]
     }
+    ColType::BigInt => {
+      let bound = ColBound { start: SingleBound::Unbounded, end: SingleBound::Unbounded };
+      vec![(PolyColBound::BigInt(bound))]
+    }
   }
 }
 
+/// Computes the `KeyBound` that contains every `PrimaryKey` with the given `key_cols`.
+pub fn full_key_bound(key_cols: &Vec<(ColName, ColType)>) -> KeyBound {
+  let mut col_bounds = Vec::<PolyColBound>::new();
+  for (_, col_type) in key_cols {
+    col_bounds.extend(full_poly_col_bounds(col_type));
+  }
+  KeyBound { col_bounds }
+}
+
 /// Eliminates intersection between the `ColBound`s
 fn merged_col_bounds<T: Ord + BoundType + Clone>(col_bounds: Vec<ColBound<T>>) -> Vec<ColBound<T>> {
   // TODO: do properly. This is a non-critical optimization.
@@ -882,10 +960,45 @@ pub fn does_types_match(col_type: &ColType, col_valn: Option<&ColVal>) -> bool {
     (ColType::Bool, None) => true,
     (ColType::String, Some(ColVal::String(_))) => true,
     (ColType::String, None) => true,
+    (ColType::BigInt, Some(ColVal::BigInt(_))) => true,
+    (ColType::BigInt, None) => true,
+    _ => false,
+  }
+}
+
+/// Widens an `Int` into a `BigInt` if `col_type` is `BigInt`, so that values (e.g. literals)
+/// can be written into a `BigInt` column. Other values are returned unchanged.
+pub fn coerce_col_valn(col_type: &ColType, col_valn: ColValN) -> ColValN {
+  match (col_type, col_valn) {
+    (ColType::BigInt, Some(ColVal::Int(val))) => Some(ColVal::BigInt(val as i64)),
+    (_, col_valn) => col_valn,
+  }
+}
+
+/// Returns true if every value of type `from` can be converted to a value of type `to`
+/// without loss, which is what `ALTER COLUMN ... TYPE` requires.
+pub fn is_safe_type_conversion(from: &ColType, to: &ColType) -> bool {
+  match (from, to) {
+    (ColType::Int, ColType::BigInt) => true,
+    (ColType::Int, ColType::String) => true,
+    (ColType::BigInt, ColType::String) => true,
+    (ColType::Bool, ColType::String) => true,
     _ => false,
   }
 }
 
+/// Converts `col_valn` into a value of type `to`. This should only be called if the
+/// conversion `is_safe_type_conversion`.
+pub fn convert_col_valn(to: &ColType, col_valn: ColValN) -> ColValN {
+  match (to, col_valn?) {
+    (ColType::BigInt, ColVal::Int(val)) => Some(ColVal::BigInt(val as i64)),
+    (ColType::String, ColVal::Int(val)) => Some(ColVal::String(val.to_string())),
+    (ColType::String, ColVal::BigInt(val)) => Some(ColVal::String(val.to_string())),
+    (ColType::String, ColVal::Bool(val)) => Some(ColVal::String(val.to_string())),
+    (_, col_val) => Some(col_val),
+  }
+}
+
 // -----------------------------------------------------------------------------------------------
 //  Region Isolation Property Utilities
 // -----------------------------------------------------------------------------------------------
@@ -914,6 +1027,7 @@ fn might_key_bound_intersect(key_bound1: &KeyBound, key_bound2: &KeyBound) -> bo
       (PolyColBound::Int(c1), PolyColBound::Int(c2)) => might_col_intersect(c1, c2),
       (PolyColBound::Bool(c1), PolyColBound::Bool(c2)) => might_col_intersect(c1, c2),
       (PolyColBound::String(c1), PolyColBound::String(c2)) => might_col_intersect(c1, c2),
+      (PolyColBound::BigInt(c1), PolyColBound::BigInt(c2)) => might_col_intersect(c1, c2),
       _ => panic!(),
     };
     if !might_col_bound_intersect {
@@ -979,6 +1093,28 @@ pub fn is_surely_isolated_multiwrite(
   true
 }
 
+/// Returns true if these `WriteRegion`s surely do not write to any of the same cells. Here, a
+/// change in presence is considered to write to every cell of the rows. Returning false might
+/// be a false negative under rare circumstances.
+pub fn is_surely_disjoint_multiwrite(
+  write_regions: &BTreeSet<WriteRegion>,
+  write_region: &WriteRegion,
+) -> bool {
+  for other_region in write_regions {
+    if might_row_region_intersect(&other_region.row_region, &write_region.row_region) {
+      if other_region.presence || write_region.presence {
+        return false;
+      } else if does_col_regions_intersect(
+        &other_region.val_col_region,
+        &write_region.val_col_region,
+      ) {
+        return false;
+      }
+    }
+  }
+  true
+}
+
 // -----------------------------------------------------------------------------------------------
 //  Sharding Keybound Utils
 // -----------------------------------------------------------------------------------------------
@@ -1015,6 +1151,7 @@ pub fn range_might_intersect_row_region(
         ColType::Int => range_might_intersects_key_bound::<i32>(range, &col_bound),
         ColType::Bool => range_might_intersects_key_bound::<bool>(range, &col_bound),
         ColType::String => range_might_intersects_key_bound::<String>(range, &col_bound),
+        ColType::BigInt => range_might_intersects_key_bound::<i64>(range, &col_bound),
       }
       .unwrap();
       if might_intersect {
@@ -1060,6 +1197,7 @@ pub fn range_row_region_intersection(
         ColType::Int => range_col_bound_intersection::<i32>(range, &col_bound),
         ColType::Bool => range_col_bound_intersection::<bool>(range, &col_bound),
         ColType::String => range_col_bound_intersection::<String>(range, &col_bound),
+        ColType::BigInt => range_col_bound_intersection::<i64>(range, &col_bound),
       }
       .unwrap();
       *key_bound.col_bounds.first_mut().unwrap() = new_col_bound;
@@ -1,4 +1,5 @@
-use crate::common::{BasicIOCtx, ColName, QueryResult, ShardingGen, Timestamp};
+use crate::common::Timestamp;
+use crate::common::{BasicIOCtx, ColName, IsolationLevel, Priority, QueryResult, ShardingGen};
 use crate::common::{CNodePath, EndpointId, QueryId, RequestId, TNodePath, TQueryPath, TableView};
 use crate::coord::CoordContext;
 use crate::message as msg;
@@ -6,8 +7,10 @@ use crate::paxos2pc_tm::{
   Paxos2PCTMInner, Paxos2PCTMOuter, PayloadTypes, RMMessage, RMPLm, TMMessage,
 };
 use crate::sql_ast::{iast, proc};
+use crate::sql_parser::QueryMode;
 use crate::storage::GenericTable;
 use crate::tablet::{MSQueryES, ReadWriteRegion, TabletContext, TabletPLm};
+use crate::trace::TraceContext;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -87,6 +90,13 @@ pub struct ResponseData {
   pub sender_eid: EndpointId,
   /// We hold onto the original `Query` in case of an Abort so that we can restart.
   pub sql_query: iast::Query,
+  pub params: Vec<iast::Value>,
+  pub opt_prepared_key: Option<String>,
+  pub mode: QueryMode,
+  pub isolation_level: IsolationLevel,
+  pub priority: Priority,
+  /// The trace of the request, where the span is its root span.
+  pub trace: TraceContext,
 
   // Result values (values computed by the MSCoordES)
   pub result: QueryResult,
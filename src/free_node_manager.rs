@@ -47,20 +47,27 @@ pub struct FreeNodeManager {
   pending_new_free_nodes: BTreeSet<(EndpointId, FreeNodeType)>,
   free_node_heartbeat: BTreeMap<EndpointId, u32>,
   requested_reconfig_eids: BTreeMap<PaxosGroupId, usize>,
+  /// The `node_index` (see `ClockId`) to hand out to the next FreeNode that registers.
+  next_node_index: u32,
 }
 
 impl FreeNodeManager {
-  pub fn new() -> FreeNodeManager {
+  /// Here, `next_node_index` is the number of initial Master nodes, which take the first ones.
+  pub fn new(next_node_index: u32) -> FreeNodeManager {
     FreeNodeManager {
       free_nodes: Default::default(),
       pending_new_free_nodes: Default::default(),
       free_node_heartbeat: Default::default(),
       requested_reconfig_eids: Default::default(),
+      next_node_index,
     }
   }
 
   /// Handles a `free_nodes` to initiate a `FreeNodeManager` for a reconfigured node properly.
-  pub fn create_reconfig(free_nodes: BTreeMap<EndpointId, FreeNodeType>) -> FreeNodeManager {
+  pub fn create_reconfig(
+    free_nodes: BTreeMap<EndpointId, FreeNodeType>,
+    next_node_index: u32,
+  ) -> FreeNodeManager {
     let mut free_node_heartbeat = BTreeMap::<EndpointId, u32>::new();
     for (eid, _) in &free_nodes {
       free_node_heartbeat.insert(eid.clone(), 0);
@@ -70,6 +77,7 @@ impl FreeNodeManager {
       pending_new_free_nodes: Default::default(),
       free_node_heartbeat,
       requested_reconfig_eids: Default::default(),
+      next_node_index,
     }
   }
 
@@ -128,13 +136,17 @@ impl FreeNodeManager {
     for (new_eid, node_type) in plm.new_nodes {
       self.free_nodes.insert(new_eid.clone(), node_type);
       self.free_node_heartbeat.insert(new_eid.clone(), 0);
+      // Hand out a `node_index`. Since this is done while handling the PLm, every Master
+      // node hands out the same one.
+      let node_index = self.next_node_index;
+      self.next_node_index += 1;
       // Send back a FreeNodeRegistered message
       if ctx.is_leader() {
         let cur_lid = ctx.leader_map.value().get(&PaxosGroupId::Master).unwrap().clone();
         io_ctx.send(
           &new_eid,
           msg::NetworkMessage::FreeNode(msg::FreeNodeMessage::FreeNodeRegistered(
-            msg::FreeNodeRegistered { cur_lid },
+            msg::FreeNodeRegistered { cur_lid, node_index },
           )),
         )
       }
@@ -283,6 +295,10 @@ impl FreeNodeManager {
     &self.free_nodes
   }
 
+  pub fn next_node_index(&self) -> u32 {
+    self.next_node_index
+  }
+
   pub fn free_node_eids(&self) -> Vec<EndpointId> {
     self.free_nodes.keys().cloned().collect()
   }
@@ -4,7 +4,7 @@ use crate::col_usage::{
 };
 use crate::common::{
   lookup, lookup_pos, merge_table_views, mk_qid, rand_string, unexpected_branch, CTQueryPath,
-  ColVal, ColValN, CoreIOCtx, FullGen, OrigP, QueryPlan, ReadOnlySet, Timestamp,
+  ColVal, ColValN, CoreIOCtx, ExecutionStats, FullGen, OrigP, QueryPlan, ReadOnlySet, Timestamp,
 };
 use crate::common::{
   CQueryPath, ColName, Context, ContextRow, ContextSchema, Gen, LeadershipId, PaxosGroupIdTrait,
@@ -25,6 +25,7 @@ use crate::sql_ast::{iast, proc};
 use crate::table_read_es::perform_aggregation;
 use crate::tablet::Executing;
 use crate::tm_status::{SendHelper, TMStatus};
+use crate::trace::TraceContext;
 use crate::trans_table_read_es::TransTableSource;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
@@ -40,6 +41,7 @@ pub enum InternalError {
 
 pub struct GRQueryResult {
   pub new_rms: BTreeSet<TQueryPath>,
+  pub stats: ExecutionStats,
   pub result: Vec<TableView>,
 }
 
@@ -81,6 +83,8 @@ pub struct GRQueryES {
   /// This is only here so it can be forwarded to child queries.
   pub root_query_path: CQueryPath,
   /// This is only here so it can be forwarded to child queries.
+  pub trace: TraceContext,
+  /// This is only here so it can be forwarded to child queries.
   pub timestamp: Timestamp,
   pub context: Rc<Context>,
 
@@ -99,6 +103,7 @@ pub struct GRQueryES {
 
   // The dynamically evolving fields.
   pub new_rms: BTreeSet<TQueryPath>,
+  pub stats: ExecutionStats,
   pub trans_table_views: Vec<(TransTableName, Vec<TableView>)>,
   pub state: GRExecutionS,
 
@@ -168,6 +173,8 @@ impl SubqueryComputableSql for proc::Delete {
 
 pub struct GRQueryConstructorView<'a, SqlQueryT: SubqueryComputableSql> {
   pub root_query_path: &'a CQueryPath,
+  /// The trace that child queries should be performed for.
+  pub trace: &'a TraceContext,
   pub timestamp: &'a Timestamp,
   /// SQL query containing by the parent ES.
   pub sql_query: &'a SqlQueryT,
@@ -191,6 +198,7 @@ impl<'a, SqlQueryT: SubqueryComputableSql> GRQueryConstructorView<'a, SqlQueryT>
     // Finally, construct the GRQueryES.
     GRQueryES {
       root_query_path: self.root_query_path.clone(),
+      trace: self.trace.clone(),
       timestamp: self.timestamp.clone(),
       context,
       new_trans_table_context,
@@ -198,6 +206,7 @@ impl<'a, SqlQueryT: SubqueryComputableSql> GRQueryConstructorView<'a, SqlQueryT>
       sql_query: self.sql_query.collect_subqueries().remove(subquery_idx),
       query_plan: self.query_plan.clone(),
       new_rms: Default::default(),
+      stats: Default::default(),
       trans_table_views: vec![],
       state: GRExecutionS::Start,
       orig_p: OrigP::new(self.query_id.clone()),
@@ -231,6 +240,7 @@ impl GRQueryES {
     io_ctx: &mut IO,
     tm_qid: QueryId,
     new_rms: BTreeSet<TQueryPath>,
+    stats: ExecutionStats,
     pre_agg_table_views: Vec<TableView>,
   ) -> Option<GRQueryAction> {
     let read_stage = cast!(GRExecutionS::ReadStage, &mut self.state).unwrap();
@@ -261,8 +271,9 @@ impl GRQueryES {
       self.new_trans_table_context.get_mut(i).unwrap().push(*idx);
     }
 
-    // Accumulate in the `new_rms`.
+    // Accumulate in the `new_rms` and `stats`.
     self.new_rms.extend(new_rms);
+    self.stats.merge(stats);
 
     // Add the `table_views` to the GRQueryES and advance it.
     self.trans_table_views.push((trans_table_name.clone(), table_views));
@@ -292,6 +303,7 @@ impl GRQueryES {
     io_ctx: &mut IO,
     child_qid: QueryId,
     new_rms: Vec<TQueryPath>,
+    stats: ExecutionStats,
     table_views: Vec<TableView>,
   ) -> Option<GRQueryAction> {
     let read_stage = cast!(GRExecutionS::ReadStage, &self.state)?;
@@ -304,8 +316,9 @@ impl GRQueryES {
       self.new_trans_table_context.get_mut(i).unwrap().push(*idx);
     }
 
-    // Accumulate in the `new_rms`.
+    // Accumulate in the `new_rms` and `stats`.
     self.new_rms.extend(new_rms);
+    self.stats.merge(stats);
 
     // Add the `table_views` to the GRQueryES and advance it.
     self.trans_table_views.push((trans_table_name.clone(), table_views));
@@ -371,7 +384,11 @@ impl GRQueryES {
 
       // Finally, we signal that the GRQueryES is done and send back the results.
       self.state = GRExecutionS::Done;
-      Some(GRQueryAction::Success(GRQueryResult { new_rms: self.new_rms.clone(), result }))
+      Some(GRQueryAction::Success(GRQueryResult {
+        new_rms: self.new_rms.clone(),
+        stats: self.stats.clone(),
+        result,
+      }))
     }
   }
 
@@ -407,11 +424,17 @@ impl GRQueryES {
       query_leader_map: query_leader_map.clone(),
       table_location_map: self.query_plan.table_location_map.clone(),
       col_presence_req: self.query_plan.col_presence_req.clone(),
+      isolation_level: self.query_plan.isolation_level,
+      priority: self.query_plan.priority,
     };
 
     // Construct the TMStatus
-    let mut tm_status =
-      TMStatus::new(io_ctx, self.root_query_path.clone(), OrigP::new(self.query_id.clone()));
+    let mut tm_status = TMStatus::new(
+      io_ctx,
+      self.root_query_path.clone(),
+      self.trace.clone(),
+      OrigP::new(self.query_id.clone()),
+    );
 
     // Send out the PerformQuery and populate TMStatus accordingly.
     match stage {
@@ -432,6 +455,7 @@ impl GRQueryES {
           Some(GRQueryAction::QueryError(msg::QueryError::InvalidLeadershipId))
         } else {
           // Move the GRQueryES to the next Stage.
+          self.stats.subqueries += 1;
           self.state = GRExecutionS::ReadStage(ReadStage {
             stage_idx,
             parent_to_child_context_map,
@@ -464,6 +488,7 @@ impl GRQueryES {
           Some(GRQueryAction::QueryError(msg::QueryError::InvalidLeadershipId))
         } else {
           // Move the GRQueryES to the next Stage.
+          self.stats.subqueries += 1;
           self.state = GRExecutionS::ReadStage(ReadStage {
             stage_idx,
             parent_to_child_context_map,
@@ -479,6 +504,7 @@ impl GRQueryES {
         let child_es = JoinReadES::create(
           io_ctx,
           self.root_query_path.clone(),
+          self.trace.clone(),
           self.timestamp.clone(),
           context,
           select.clone(),
@@ -487,6 +513,7 @@ impl GRQueryES {
         );
 
         // Move the GRQueryES to the next Stage.
+        self.stats.subqueries += 1;
         self.state = GRExecutionS::ReadStage(ReadStage {
           stage_idx,
           parent_to_child_context_map,
@@ -1,8 +1,8 @@
 use crate::col_usage::{gr_query_collecting_cb, QueryElement, QueryIterator};
 use crate::common::{
   mk_qid, rand_string, unexpected_branch, CQueryPath, CTQueryPath, ColValN, Context, ContextRow,
-  ContextSchema, CoreIOCtx, OrigP, QueryESResult, QueryId, QueryPlan, ReadOnlySet, TQueryPath,
-  TableView, Timestamp,
+  ContextSchema, CoreIOCtx, ExecutionStats, OrigP, QueryESResult, QueryId, QueryPlan, ReadOnlySet,
+  TQueryPath, TableView, Timestamp,
 };
 use crate::expression::{construct_cexpr, evaluate_c_expr, is_true, EvalError};
 use crate::gr_query_es::{GRExecutionS, GRQueryES};
@@ -18,6 +18,7 @@ use crate::server::{
 use crate::sql_ast::{iast, proc};
 use crate::table_read_es::perform_aggregation;
 use crate::tablet::{Executing, TPESAction};
+use crate::trace::TraceContext;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
 use std::rc::Rc;
@@ -119,6 +120,7 @@ enum ExecutionS {
 #[derive(Debug)]
 pub struct JoinReadES {
   root_query_path: CQueryPath,
+  trace: TraceContext,
   timestamp: Timestamp,
   context: Rc<Context>,
 
@@ -131,6 +133,7 @@ pub struct JoinReadES {
 
   // Dynamically evolving fields.
   new_rms: BTreeSet<TQueryPath>,
+  stats: ExecutionStats,
   state: ExecutionS,
   pub orig_p: OrigP,
 }
@@ -143,6 +146,7 @@ impl JoinReadES {
   pub fn create<IO: CoreIOCtx>(
     io_ctx: &mut IO,
     root_query_path: CQueryPath,
+    trace: TraceContext,
     timestamp: Timestamp,
     context: Rc<Context>,
     sql_query: proc::JoinSelect,
@@ -151,12 +155,14 @@ impl JoinReadES {
   ) -> JoinReadES {
     JoinReadES {
       root_query_path,
+      trace,
       timestamp,
       context,
       query_id: mk_qid(io_ctx.rand()),
       sql_query,
       query_plan,
       new_rms: BTreeSet::new(),
+      stats: ExecutionStats::default(),
       state: ExecutionS::Start,
       orig_p,
     }
@@ -222,7 +228,7 @@ impl JoinReadES {
 
   /// Construct subqueries using `child_query_data`.
   fn mk_gr_queries(
-    &self,
+    &mut self,
     child_query_data: Vec<(QueryId, Rc<Context>, proc::GRQuery)>,
   ) -> Vec<GRQueryES> {
     let mut gr_query_ess = Vec::<GRQueryES>::new();
@@ -233,6 +239,7 @@ impl JoinReadES {
       // Append GRQueryES
       gr_query_ess.push(GRQueryES {
         root_query_path: self.root_query_path.clone(),
+        trace: self.trace.clone(),
         timestamp: self.timestamp.clone(),
         context,
         new_trans_table_context,
@@ -240,12 +247,14 @@ impl JoinReadES {
         sql_query,
         query_plan: self.query_plan.clone(),
         new_rms: Default::default(),
+        stats: Default::default(),
         trans_table_views: vec![],
         state: GRExecutionS::Start,
         orig_p: OrigP::new(self.query_id.clone()),
       });
     }
 
+    self.stats.subqueries += gr_query_ess.len() as u64;
     gr_query_ess
   }
 
@@ -814,9 +823,11 @@ impl JoinReadES {
     io_ctx: &mut IO,
     qid: QueryId,
     rms: BTreeSet<TQueryPath>,
+    stats: ExecutionStats,
     result: Vec<TableView>,
   ) -> Option<TPESAction> {
     self.new_rms.extend(rms);
+    self.stats.merge(stats);
 
     let QueryId(qid_str) = &qid;
     let mut it = Option::<usize>::None;
@@ -1008,6 +1019,7 @@ impl JoinReadES {
       Ok(table_views) => Some(TPESAction::Success(QueryESResult {
         result: table_views,
         new_rms: self.new_rms.iter().cloned().collect(),
+        stats: self.stats.clone(),
       })),
       Err(eval_error) => return Some(TPESAction::QueryError(mk_eval_error(eval_error))),
     }
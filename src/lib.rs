@@ -6,6 +6,7 @@ extern crate core;
 pub mod lang;
 pub mod alter_table_rm_es;
 pub mod alter_table_tm_es;
+pub mod client;
 pub mod col_usage;
 pub mod common;
 pub mod coord;
@@ -14,6 +15,7 @@ pub mod create_table_tm_es;
 pub mod drop_table_rm_es;
 pub mod drop_table_tm_es;
 pub mod experimental;
+pub mod explain;
 pub mod expression;
 pub mod finish_query_rm_es;
 pub mod finish_query_tm_es;
@@ -24,6 +26,7 @@ pub mod join_util;
 pub mod master;
 pub mod master_query_planning_es;
 pub mod message;
+pub mod metrics;
 pub mod ms_query_coord_es;
 pub mod ms_table_delete_es;
 pub mod ms_table_es;
@@ -58,4 +61,5 @@ pub mod table_read_es;
 pub mod tablet;
 pub mod test_utils;
 pub mod tm_status;
+pub mod trace;
 pub mod trans_table_read_es;
@@ -6,15 +6,15 @@ use crate::common::{
   GossipData, LeaderMap, MasterIOCtx, MasterTraceMessage, TableSchema, Timestamp, VersionedValue,
   ALPHABET,
 };
-use crate::common::{BasicIOCtx, RemoteLeaderChangedPLm};
+use crate::common::{BasicIOCtx, ClockId, HybridClock, RemoteLeaderChangedPLm};
 use crate::common::{
-  ColName, ColType, ColVal, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait,
-  PrimaryKey, QueryId, RequestId, SlaveGroupId, TNodePath, TablePath, TabletGroupId,
-  TabletKeyRange,
+  ColName, ColType, ColVal, EndpointId, Gen, HashBucket, LeadershipId, PaxosGroupId,
+  PaxosGroupIdTrait, PrimaryKey, QueryId, RequestId, SlaveGroupId, TNodePath, TablePath,
+  TabletGroupId, TabletKeyRange,
 };
 use crate::create_table_tm_es::{CreateTableTMES, CreateTableTMInner, CreateTableTMPayloadTypes};
 use crate::drop_table_tm_es::{DropTableTMES, DropTableTMInner, DropTableTMPayloadTypes};
-use crate::expression::does_types_match;
+use crate::expression::{does_types_match, is_safe_type_conversion};
 use crate::free_node_manager::{FreeNodeManager, FreeNodeManagerPLm, FreeNodeType};
 use crate::master_query_planning_es::{MasterQueryPlanning, MasterQueryPlanningESS};
 use crate::message as msg;
@@ -26,13 +26,10 @@ use crate::shard_split_tm_es::{ShardSplitTMES, ShardSplitTMInner, ShardSplitTMPa
 use crate::slave_group_create_es::{ConfirmCreateGroup, SlaveGroupCreateESS};
 use crate::slave_reconfig_es::{SlaveReconfigESS, SlaveReconfigPLm};
 use crate::sql_ast::proc;
-use crate::sql_parser::{convert_ddl_ast, DDLQuery};
+use crate::sql_parser::{parse_ddl, DDLQuery};
 use crate::stmpaxos2pc_tm as paxos2pc;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use sqlparser::dialect::GenericDialect;
-use sqlparser::parser::Parser;
-use sqlparser::parser::ParserError::{ParserError, TokenizerError};
 use std::cmp::min;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
@@ -51,6 +48,13 @@ pub struct MasterBundle {
   pub plms: Vec<MasterPLm>,
 }
 
+impl MasterBundle {
+  /// Returns true iff this holds nothing to persist.
+  fn is_empty(&self) -> bool {
+    self.remote_leader_changes.is_empty() && self.plms.is_empty()
+  }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum MasterPLm {
   MasterQueryPlanning(MasterQueryPlanning),
@@ -90,6 +94,10 @@ impl<'a, IO: MasterIOCtx> PaxosContextBase<MasterBundle> for MasterPaxosContext<
     self.this_eid
   }
 
+  fn now(&mut self) -> Timestamp {
+    self.io_ctx.now()
+  }
+
   fn send(&mut self, eid: &EndpointId, message: msg::PaxosDriverMessage<MasterBundle>) {
     self
       .io_ctx
@@ -152,10 +160,9 @@ pub enum FullMasterInput {
 
 #[derive(Debug, Clone)]
 pub struct MasterConfig {
-  /// This is used for generate the `suffix` of a Timestamp, where we just generate
-  /// a random `u64` and take the remainder after dividing by `timestamp_suffix_divisor`.
-  /// This cannot be 0; the default value is 1, making the suffix always be 0.
-  pub timestamp_suffix_divisor: u64,
+  /// The maximum amount (in ms) that the clock of another node can be ahead of ours before
+  /// we consider it to be skewed. See `HybridClock`.
+  pub max_clock_offset_ms: u128,
 
   /// The size that PaxosGroups should be.
   pub slave_group_size: u32,
@@ -180,6 +187,7 @@ pub struct MasterSnapshot {
   pub gossip: GossipData,
   pub leader_map: LeaderMap,
   pub free_nodes: BTreeMap<EndpointId, FreeNodeType>,
+  pub next_node_index: u32,
   pub paxos_driver_start: msg::StartNewNode<MasterBundle>,
 
   // Statuses
@@ -242,6 +250,9 @@ pub struct Statuses {
   /// `PaxosGroupFailureDetector` might re-create `do_reconfig` and
   /// request us to remove a node that already becomes removed.
   pub do_reconfig: Option<Vec<EndpointId>>,
+
+  /// The `ExternalTransferLeadership` that this Leader is carrying out, if any.
+  pub leadership_transfer: Option<msg::ExternalTransferLeadership>,
 }
 
 #[derive(Debug)]
@@ -254,6 +265,8 @@ pub struct MasterContext {
   /// Metadata
   pub master_config: MasterConfig,
   pub this_eid: EndpointId,
+  /// The clock used to generate all `Timestamp`s at this server.
+  pub clock: HybridClock,
 
   /// Between calls to `handle_input`, we should maintain the following properties:
   /// 1. `leader_map` should contain an entry for every `PaxosGroupId` in the
@@ -297,6 +310,8 @@ impl Debug for MasterContext {
 
 impl MasterState {
   pub fn new(ctx: MasterContext) -> MasterState {
+    // The initial Master nodes hold the first node indices.
+    let next_node_index = ctx.gossip.get().master_address_config.len() as u32;
     MasterState {
       ctx,
       statuses: Statuses {
@@ -307,8 +322,9 @@ impl MasterState {
         planning_ess: MasterQueryPlanningESS::new(),
         slave_group_create_ess: SlaveGroupCreateESS::new(),
         slave_reconfig_ess: SlaveReconfigESS::new(),
-        free_node_manager: FreeNodeManager::new(),
+        free_node_manager: FreeNodeManager::new(next_node_index),
         do_reconfig: None,
+        leadership_transfer: None,
       },
     }
   }
@@ -320,6 +336,7 @@ impl MasterState {
     master_config: MasterConfig,
     paxos_config: PaxosConfig,
     this_eid: EndpointId,
+    clock_id: ClockId,
   ) -> MasterState {
     // Create Statuses
     let statuses = Statuses {
@@ -330,8 +347,12 @@ impl MasterState {
       planning_ess: MasterQueryPlanningESS::new(),
       slave_group_create_ess: snapshot.slave_group_create_ess,
       slave_reconfig_ess: snapshot.slave_reconfig_ess,
-      free_node_manager: FreeNodeManager::create_reconfig(snapshot.free_nodes),
+      free_node_manager: FreeNodeManager::create_reconfig(
+        snapshot.free_nodes,
+        snapshot.next_node_index,
+      ),
       do_reconfig: None,
+      leadership_transfer: None,
     };
 
     // Create the MasterCtx
@@ -345,9 +366,11 @@ impl MasterState {
     all_eids.extend(snapshot.gossip.get().master_address_config.clone());
 
     let network_driver = NetworkDriver::new(&leader_map);
+    let clock = HybridClock::new(clock_id, master_config.max_clock_offset_ms);
     let ctx = MasterContext {
       master_config,
       this_eid: this_eid.clone(),
+      clock,
       gossip: snapshot.gossip,
       leader_map,
       all_eids: VersionedValue::new(all_eids),
@@ -404,6 +427,9 @@ impl MasterState {
         self.ctx.handle_input(io_ctx, &mut self.statuses, forward_msg);
       }
     }
+
+    // Insert anything that was added to the `master_bundle` early, if possible.
+    self.ctx.maybe_insert_early(io_ctx);
   }
 
   pub fn get_eids(&self) -> &VersionedValue<BTreeSet<EndpointId>> {
@@ -417,6 +443,7 @@ impl MasterContext {
     master_address_config: Vec<EndpointId>,
     leader_map: LeaderMap,
     this_eid: EndpointId,
+    clock_id: ClockId,
     master_config: MasterConfig,
     paxos_config: PaxosConfig,
   ) -> MasterContext {
@@ -427,9 +454,11 @@ impl MasterContext {
     all_eids.extend(master_address_config.clone());
 
     let network_driver = NetworkDriver::new(&leader_map);
+    let clock = HybridClock::new(clock_id, master_config.max_clock_offset_ms);
     MasterContext {
       master_config,
       this_eid,
+      clock,
       gossip: GossipData::new(BTreeMap::default(), master_address_config.clone()),
       leader_map,
       all_eids: VersionedValue::new(all_eids),
@@ -450,6 +479,13 @@ impl MasterContext {
       msg::MasterMessage::MasterExternalReq(request) => {
         if self.is_leader() {
           self.handle_input(io_ctx, statuses, MasterForwardMsg::MasterExternalReq(request))
+        } else if let msg::MasterExternalReq::ExternalMetadataRequest(request) = request {
+          // Followers also answer metadata requests so that Externals can bootstrap from
+          // any Master node. The `LeaderMap` sent back points the External to the leader.
+          self.send_metadata(io_ctx, statuses, request);
+        } else if let Some((eid, response)) = request.not_leader_response() {
+          // Tell the External to find the current leader and resend.
+          io_ctx.send(&eid, response);
         }
       }
       msg::MasterMessage::RemoteMessage(remote_message) => {
@@ -514,6 +550,9 @@ impl MasterContext {
         for pl_entry in pl_entries {
           match pl_entry {
             msg::PLEntry::LeaderChanged(leader_changed) => {
+              // Respond to the `ExternalTransferLeadership` that was being carried out.
+              self.finish_leadership_transfer(io_ctx, statuses, &leader_changed.lid);
+
               // Forward to Master Backend
               self.handle_input(
                 io_ctx,
@@ -573,6 +612,10 @@ impl MasterContext {
                 self.deliver_blocked_messages(io_ctx, statuses, remote_leader_changed);
               }
             }
+            msg::PLEntry::Batch(_) => {
+              // Recall that the PaxosDriver delivers the entries in a `Batch` individually.
+              debug_assert!(false);
+            }
           }
         }
       }
@@ -632,6 +675,14 @@ impl MasterContext {
           self
             .paxos_driver
             .timer_event(&mut MasterPaxosContext { io_ctx, this_eid: &self.this_eid }, timer_event);
+
+          // Check if the PaxosDriver abandoned the `ExternalTransferLeadership`.
+          if self.paxos_driver.transfer_target().is_none() {
+            if let Some(transfer) = statuses.leadership_transfer.take() {
+              let (eid, response) = transfer.response(msg::TransferLeadershipResult::Aborted);
+              io_ctx.send(&eid, response);
+            }
+          }
         }
         MasterTimerInput::RemoteLeaderChanged => {
           if self.is_leader() {
@@ -761,36 +812,42 @@ impl MasterContext {
           // MasterQueryPlanningES
           statuses.planning_ess.handle_bundle_processed(self);
 
-          // Construct PLms related to FreeNode management.
-          let granted_reconfig_eids = statuses.free_node_manager.process(self, io_ctx);
-
-          // Forwarded the granted `EndpointId`s to the `SlaveGroupReconfig`s that requested it.
-          // We might also be doing a Master Reconfig, record the new_eids for that.
-          let mut do_reconfig: Option<(Vec<EndpointId>, Vec<EndpointId>)> = None;
-          for (gid, new_eids) in granted_reconfig_eids {
-            match gid {
-              PaxosGroupId::Master => {
-                let rem_eids = statuses.do_reconfig.clone().unwrap();
-                do_reconfig = Some((rem_eids, new_eids));
-              }
-              PaxosGroupId::Slave(sid) => {
-                statuses.slave_reconfig_ess.handle_eids_granted(self, &sid, new_eids);
+          // Continue the insert cycle, unless there is nothing to persist and the bundles in
+          // flight already keep it going. Note that we only construct the FreeNode PLms here,
+          // since otherwise, every learned bundle would have something to persist, and the
+          // insert cycle would fork whenever a bundle is inserted early.
+          if !self.master_bundle.is_empty() || !self.paxos_driver.is_inserting() {
+            // Construct PLms related to FreeNode management.
+            let granted_reconfig_eids = statuses.free_node_manager.process(self, io_ctx);
+
+            // Forwarded the granted `EndpointId`s to the `SlaveGroupReconfig`s that requested
+            // it. We might also be doing a Master Reconfig, record the new_eids for that.
+            let mut do_reconfig: Option<(Vec<EndpointId>, Vec<EndpointId>)> = None;
+            for (gid, new_eids) in granted_reconfig_eids {
+              match gid {
+                PaxosGroupId::Master => {
+                  let rem_eids = statuses.do_reconfig.clone().unwrap();
+                  do_reconfig = Some((rem_eids, new_eids));
+                }
+                PaxosGroupId::Slave(sid) => {
+                  statuses.slave_reconfig_ess.handle_eids_granted(self, &sid, new_eids);
+                }
               }
             }
-          }
 
-          // Continue the insert cycle. If the Master needs to reconfigure, we choose
-          // `Reconfig`. Otherwise, we choose `Bundle`.
-          let bundle = std::mem::replace(&mut self.master_bundle, MasterBundle::default());
-          let user_entry = if let Some((rem_eids, new_eids)) = do_reconfig {
-            UserPLEntry::ReconfigBundle(msg::ReconfigBundle { rem_eids, new_eids, bundle })
-          } else {
-            UserPLEntry::Bundle(bundle)
-          };
-          self.paxos_driver.insert_bundle(
-            &mut MasterPaxosContext { io_ctx, this_eid: &self.this_eid },
-            user_entry,
-          );
+            // If the Master needs to reconfigure, we choose `Reconfig`. Otherwise, we
+            // choose `Bundle`.
+            let bundle = std::mem::replace(&mut self.master_bundle, MasterBundle::default());
+            let user_entry = if let Some((rem_eids, new_eids)) = do_reconfig {
+              UserPLEntry::ReconfigBundle(msg::ReconfigBundle { rem_eids, new_eids, bundle })
+            } else {
+              UserPLEntry::Bundle(bundle)
+            };
+            self.paxos_driver.insert_bundle(
+              &mut MasterPaxosContext { io_ctx, this_eid: &self.this_eid },
+              user_entry,
+            );
+          }
         }
       }
       MasterForwardMsg::MasterExternalReq(message) => {
@@ -811,8 +868,15 @@ impl MasterContext {
 
                 match ddl_query {
                   DDLQuery::Create(create_table) => {
-                    // Generate random shards
-                    let shards = self.simple_partition(io_ctx, &create_table);
+                    // Generate the shards from the hash partitioning or split keys if
+                    // present, and randomly otherwise.
+                    let shards = if let Some(hash_partition) = &create_table.hash_partition {
+                      self.hash_partition(io_ctx, &create_table, hash_partition)
+                    } else if !create_table.split_keys.is_empty() {
+                      self.split_partition(io_ctx, &create_table.split_keys)
+                    } else {
+                      self.simple_partition(io_ctx, &create_table)
+                    };
 
                     // Construct ES
                     map_insert(
@@ -939,20 +1003,37 @@ impl MasterContext {
             // ES. The Main Loop will validate them, abort them, and start them.
             match external_query.op {
               msg::ShardingOp::Split(split) => {
-                map_insert(
-                  &mut statuses.shard_split_tm_ess,
-                  &query_id,
-                  ShardSplitTMES::new(
-                    query_id.clone(),
-                    ShardSplitTMInner {
-                      response_data: Some(ResponseData { request_id, sender_eid }),
-                      table_path: split.table_path,
-                      target_old: split.target_old,
-                      target_new: split.target_new,
-                      did_commit: false,
-                    },
-                  ),
-                );
+                // Hash-partitioned Tables cannot be split, so we reject these immediately.
+                if self.is_hash_partitioned(&split.table_path)
+                  || split.target_old.range.hash.is_some()
+                  || split.target_new.range.hash.is_some()
+                {
+                  self.external_request_id_map.remove(&request_id);
+                  io_ctx.send(
+                    &sender_eid,
+                    msg::NetworkMessage::External(msg::ExternalMessage::ExternalShardingAborted(
+                      msg::ExternalShardingAborted {
+                        request_id,
+                        payload: msg::ExternalShardingAbortData::HashPartitionedTable,
+                      },
+                    )),
+                  );
+                } else {
+                  map_insert(
+                    &mut statuses.shard_split_tm_ess,
+                    &query_id,
+                    ShardSplitTMES::new(
+                      query_id.clone(),
+                      ShardSplitTMInner {
+                        response_data: Some(ResponseData { request_id, sender_eid }),
+                        table_path: split.table_path,
+                        target_old: split.target_old,
+                        target_new: split.target_new,
+                        did_commit: false,
+                      },
+                    ),
+                  );
+                }
               }
             }
           }
@@ -987,19 +1068,10 @@ impl MasterContext {
             }
           }
           msg::MasterExternalReq::ExternalMetadataRequest(request) => {
-            // Send back metadata.
-            io_ctx.send(
-              &request.sender_eid.clone(),
-              msg::NetworkMessage::External(msg::ExternalMessage::ExternalMetadataResponse(
-                msg::ExternalMetadataResponse {
-                  sender_eid: request.sender_eid,
-                  request_id: request.request_id,
-                  gossip_data: self.gossip.clone(),
-                  leader_map: self.leader_map.value().clone(),
-                  free_node_eids: statuses.free_node_manager.free_node_eids(),
-                },
-              )),
-            );
+            self.send_metadata(io_ctx, statuses, request);
+          }
+          msg::MasterExternalReq::ExternalTransferLeadership(transfer) => {
+            self.start_leadership_transfer(io_ctx, statuses, transfer);
           }
         }
 
@@ -1132,6 +1204,16 @@ impl MasterContext {
     }
   }
 
+  /// Inserts the `master_bundle` if it is non-empty and the PaxosDriver can propose it right
+  /// away, rather than waiting for the bundles in flight to be learned first.
+  fn maybe_insert_early<IO: MasterIOCtx>(&mut self, io_ctx: &mut IO) {
+    let mut paxos_ctx = MasterPaxosContext { io_ctx, this_eid: &self.this_eid };
+    if !self.master_bundle.is_empty() && self.paxos_driver.can_propose_now(&paxos_ctx) {
+      let bundle = std::mem::replace(&mut self.master_bundle, MasterBundle::default());
+      self.paxos_driver.insert_bundle(&mut paxos_ctx, UserPLEntry::Bundle(bundle));
+    }
+  }
+
   /// Validate the uniqueness of `RequestId` and return the parsed SQL, or an
   /// appropriate error if this fails.
   fn validate_ddl_query(
@@ -1143,20 +1225,91 @@ impl MasterContext {
       Err(msg::ExternalDDLQueryAbortData::NonUniqueRequestId)
     } else {
       // Parse the SQL
-      match Parser::parse_sql(&GenericDialect {}, &external_query.query) {
-        Ok(parsed_ast) => match convert_ddl_ast(parsed_ast) {
-          Ok(ddl_ast) => Ok(ddl_ast),
-          Err(parse_error) => Err(msg::ExternalDDLQueryAbortData::ParseError(parse_error)),
-        },
-        Err(parse_error) => {
-          // Extract error string
-          Err(msg::ExternalDDLQueryAbortData::ParseError(match parse_error {
-            TokenizerError(err_msg) => err_msg,
-            ParserError(err_msg) => err_msg,
-          }))
-        }
+      match parse_ddl(&external_query.query) {
+        Ok(ddl_ast) => Ok(ddl_ast),
+        Err(parse_error) => Err(msg::ExternalDDLQueryAbortData::ParseError(parse_error)),
+      }
+    }
+  }
+
+  /// Returns true iff the latest version of `table_path` is partitioned by hash.
+  fn is_hash_partitioned(&self, table_path: &TablePath) -> bool {
+    let gossip = self.gossip.get();
+    if let Some(full_gen) = gossip.table_generation.get_last_version(table_path) {
+      if let Some(shards) = gossip.sharding_config.get(&(table_path.clone(), full_gen.clone())) {
+        return shards.iter().any(|(range, _)| range.hash.is_some());
       }
     }
+    false
+  }
+
+  /// Places every `TabletKeyRange` in `ranges` into a new Tablet. The Tablets are spread
+  /// across the SlaveGroups evenly, starting from a random permutation of them. If there
+  /// are more `ranges` than SlaveGroups, some SlaveGroups will hold several Tablets.
+  fn spread_shards<IO: MasterIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    ranges: Vec<TabletKeyRange>,
+  ) -> Vec<(TabletKeyRange, TabletGroupId, SlaveGroupId)> {
+    let mut sids = Vec::from_iter(self.gossip.get().slave_address_config.keys().into_iter());
+    debug_assert!(!sids.is_empty());
+    let mut shuffled_sids = Vec::<SlaveGroupId>::new();
+    while !sids.is_empty() {
+      let idx = io_ctx.rand().next_u32() as usize % sids.len();
+      shuffled_sids.push(sids.remove(idx).clone());
+    }
+
+    let mut shards = Vec::<(TabletKeyRange, TabletGroupId, SlaveGroupId)>::new();
+    for (i, range) in ranges.into_iter().enumerate() {
+      let sid = shuffled_sids.get(i % shuffled_sids.len()).unwrap().clone();
+      shards.push((range, mk_tid(io_ctx.rand()), sid));
+    }
+    shards
+  }
+
+  /// Creates a shard for every hash bucket in `hash_partition`. The buckets are placed
+  /// into SlaveGroups in a random order, so that consecutive buckets land in distinct
+  /// SlaveGroups. If there are more buckets than SlaveGroups, the placement wraps around.
+  fn hash_partition<IO: MasterIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    create_table: &proc::CreateTable,
+    hash_partition: &proc::HashPartition,
+  ) -> Vec<(TabletKeyRange, TabletGroupId, SlaveGroupId)> {
+    let mut sids = Vec::from_iter(self.gossip.get().slave_address_config.keys().into_iter());
+    let mut shuffled_sids = Vec::<SlaveGroupId>::new();
+    while !sids.is_empty() {
+      let idx = io_ctx.rand().next_u32() as usize % sids.len();
+      shuffled_sids.push(sids.remove(idx).clone());
+    }
+
+    let key_idx = lookup_pos(&create_table.key_cols, &hash_partition.col_name).unwrap();
+    let num_buckets = hash_partition.num_partitions;
+    let mut shards = Vec::<(TabletKeyRange, TabletGroupId, SlaveGroupId)>::new();
+    for bucket in 0..num_buckets {
+      let sid = shuffled_sids.get(bucket as usize % shuffled_sids.len()).unwrap().clone();
+      let hash = Some(HashBucket { key_idx, bucket, num_buckets });
+      shards.push((TabletKeyRange { start: None, end: None, hash }, mk_tid(io_ctx.rand()), sid));
+    }
+    shards
+  }
+
+  /// Creates a shard for every range between consecutive `split_keys` (which must be
+  /// in ascending order), as well as for the ranges before the first and after the last.
+  fn split_partition<IO: MasterIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    split_keys: &Vec<ColVal>,
+  ) -> Vec<(TabletKeyRange, TabletGroupId, SlaveGroupId)> {
+    let mut ranges = Vec::<TabletKeyRange>::new();
+    let mut start = None;
+    for split_key in split_keys {
+      let end = Some(split_key.clone());
+      ranges.push(TabletKeyRange { start, end: end.clone(), hash: None });
+      start = end;
+    }
+    ranges.push(TabletKeyRange { start, end: None, hash: None });
+    self.spread_shards(io_ctx, ranges)
   }
 
   /// Uses a simple scheme to create an initial partition of the Table.
@@ -1180,7 +1333,7 @@ impl MasterContext {
     ) -> (TabletKeyRange, TabletGroupId, SlaveGroupId) {
       let idx = io_ctx.rand().next_u32() as usize % sids.len();
       let sid = sids.remove(idx);
-      (TabletKeyRange { start, end }, mk_tid(io_ctx.rand()), sid.clone())
+      (TabletKeyRange { start, end, hash: None }, mk_tid(io_ctx.rand()), sid.clone())
     }
 
     // If the Table has no KeyCols (which means the Tablet can have only at-most one row),
@@ -1192,7 +1345,7 @@ impl MasterContext {
       // Get the first KeyCol
       let (_, col_type) = create_table.key_cols.iter().next().unwrap();
       match col_type {
-        ColType::Int => {
+        ColType::Int | ColType::BigInt => {
           // Decide if we want 1 shard or 2 shards. Make sure we do not choose more shards
           // than there are Slaves.
           let num_shards = min(io_ctx.rand().next_u32() % 2 + 1, sids.len() as u32);
@@ -1201,7 +1354,8 @@ impl MasterContext {
             shards.push(mk_shard(io_ctx, &mut sids, None, None));
           } else {
             // 2 shards
-            let split_key = ColVal::Int(0);
+            let split_key =
+              if col_type == &ColType::Int { ColVal::Int(0) } else { ColVal::BigInt(0) };
             shards.push(mk_shard(io_ctx, &mut sids, None, Some(split_key.clone())));
             shards.push(mk_shard(io_ctx, &mut sids, Some(split_key.clone()), None));
           }
@@ -1330,6 +1484,9 @@ impl MasterContext {
       if let paxos2pc::State::Start = &es.state {
       } else {
         tables_being_modified.insert(es.inner.table_path.clone());
+        if let proc::AlterOp::RenameTable(op) = &es.inner.alter_op {
+          tables_being_modified.insert(op.new_table_path.clone());
+        }
       }
     }
 
@@ -1376,7 +1533,7 @@ impl MasterContext {
 
     // Move AlterTableESs forward for TablePaths not in `tables_being_modified`
     {
-      let mut ess_to_remove = Vec::<QueryId>::new();
+      let mut ess_to_remove = Vec::<(QueryId, msg::ExternalDDLQueryAbortData)>::new();
       for (_, es) in &mut statuses.alter_table_tm_ess {
         if let paxos2pc::State::Start = &es.state {
           if !tables_being_modified.contains(&es.inner.table_path) {
@@ -1387,28 +1544,78 @@ impl MasterContext {
               // The Table Exists.
               let schema =
                 gossip.db_schema.get(&(es.inner.table_path.clone(), gen.clone())).unwrap();
-              if lookup_pos(&schema.key_cols, &es.inner.alter_op.col_name).is_none() {
-                // The `col_name` is not a KeyCol.
-                let contains_col = contains_col_latest(schema, &es.inner.alter_op.col_name);
-                let is_add_col = es.inner.alter_op.maybe_col_type.is_some();
-                if contains_col && !is_add_col || !contains_col && is_add_col {
-                  // We have Column Validity, so we move it to WaitingInsertTMPrepared.
-                  es.state = paxos2pc::State::WaitingInsertTMPrepared;
-                  tables_being_modified.insert(es.inner.table_path.clone());
-                  continue;
+              let mut abort_data = msg::ExternalDDLQueryAbortData::InvalidDDLQuery;
+              let is_valid = match &es.inner.alter_op {
+                proc::AlterOp::AlterColumn(op) => {
+                  // The `col_name` must not be a KeyCol.
+                  if lookup_pos(&schema.key_cols, &op.col_name).is_none() {
+                    let contains_col = contains_col_latest(schema, &op.col_name);
+                    let is_add_col = op.maybe_col_type.is_some();
+                    contains_col && !is_add_col || !contains_col && is_add_col
+                  } else {
+                    false
+                  }
+                }
+                proc::AlterOp::RenameColumn(op) => {
+                  // The `old_col_name` must be a ValCol, and the `new_col_name` must not
+                  // be any column at all.
+                  lookup_pos(&schema.key_cols, &op.old_col_name).is_none()
+                    && contains_col_latest(schema, &op.old_col_name)
+                    && !contains_col_latest(schema, &op.new_col_name)
+                }
+                proc::AlterOp::RenameTable(op) => {
+                  // The `new_table_path` must not exist, nor be having a DDL Query run for it.
+                  !tables_being_modified.contains(&op.new_table_path)
+                    && gossip.table_generation.get_last_version(&op.new_table_path).is_none()
+                }
+                proc::AlterOp::ChangeColumnType(op) => {
+                  // The `col_name` must be a ValCol, and its current type must be safely
+                  // convertible to the new `col_type`.
+                  match schema.val_cols.get_last_version(&op.col_name) {
+                    Some(cur_col_type) if cur_col_type != &op.col_type => {
+                      if is_safe_type_conversion(cur_col_type, &op.col_type) {
+                        true
+                      } else {
+                        abort_data = msg::ExternalDDLQueryAbortData::QueryPlanningError(
+                          msg::QueryPlanningError::UnsafeColumnTypeConversion {
+                            col_name: op.col_name.clone(),
+                            from: cur_col_type.clone(),
+                            to: op.col_type.clone(),
+                          },
+                        );
+                        false
+                      }
+                    }
+                    _ => false,
+                  }
+                }
+              };
+
+              if is_valid {
+                // We have Column Validity, so we move it to WaitingInsertTMPrepared.
+                es.state = paxos2pc::State::WaitingInsertTMPrepared;
+                tables_being_modified.insert(es.inner.table_path.clone());
+                if let proc::AlterOp::RenameTable(op) = &es.inner.alter_op {
+                  tables_being_modified.insert(op.new_table_path.clone());
                 }
+                continue;
               }
+
+              // We do not have Column Validity, so we abort.
+              ess_to_remove.push((es.query_id.clone(), abort_data));
+              continue;
             }
 
-            // We do not have Column Validity, so we abort.
-            ess_to_remove.push(es.query_id.clone())
+            // The Table does not exist, so we abort.
+            ess_to_remove
+              .push((es.query_id.clone(), msg::ExternalDDLQueryAbortData::InvalidDDLQuery))
           }
         }
       }
-      for query_id in ess_to_remove {
+      for (query_id, abort_data) in ess_to_remove {
         let es = statuses.alter_table_tm_ess.remove(&query_id).unwrap();
         if let Some(response_data) = &es.inner.response_data {
-          self.respond_invalid_ddl(io_ctx, response_data);
+          self.respond_ddl_aborted(io_ctx, response_data, abort_data);
         }
       }
     }
@@ -1461,8 +1668,12 @@ impl MasterContext {
                 if gossip.slave_address_config.contains_key(&es.inner.target_new.sid)
                   && !gossip.tablet_address_config.contains_key(&es.inner.target_new.tid)
                 {
-                  // Check that the `orig_range` is split perfectly.
-                  if orig_range.start == es.inner.target_old.range.start
+                  // Check that the `orig_range` is split perfectly. Note that hash-partitioned
+                  // Tables cannot be split.
+                  if orig_range.hash.is_none()
+                    && es.inner.target_old.range.hash.is_none()
+                    && es.inner.target_new.range.hash.is_none()
+                    && orig_range.start == es.inner.target_old.range.start
                     && es.inner.target_old.range.end == es.inner.target_new.range.start
                     && es.inner.target_new.range.end == orig_range.end
                   {
@@ -1511,6 +1722,7 @@ impl MasterContext {
       gossip: self.gossip.clone(),
       leader_map: self.leader_map.value().clone(),
       free_nodes: statuses.free_node_manager.free_nodes().clone(),
+      next_node_index: statuses.free_node_manager.next_node_index(),
       paxos_driver_start,
       create_table_tm_ess: paxos2pc::handle_reconfig_snapshot(&statuses.create_table_tm_ess),
       alter_table_tm_ess: paxos2pc::handle_reconfig_snapshot(&statuses.alter_table_tm_ess),
@@ -1529,12 +1741,84 @@ impl MasterContext {
     }
   }
 
+  /// Starts handing Leadership of the Master PaxosGroup over to the `target_eid` of
+  /// `transfer` (see `PaxosDriver::transfer_leadership`). The response is sent once it finishes.
+  fn start_leadership_transfer<IO: MasterIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    transfer: msg::ExternalTransferLeadership,
+  ) {
+    let result = if transfer.gid != PaxosGroupId::Master
+      || !self.paxos_driver.paxos_nodes().contains(&transfer.target_eid)
+    {
+      msg::TransferLeadershipResult::InvalidTarget
+    } else if transfer.target_eid == self.this_eid {
+      // Here, the target is already the Leader.
+      let lid = self.leader_map.value().get(&PaxosGroupId::Master).unwrap();
+      msg::TransferLeadershipResult::Transferred(lid.clone())
+    } else if statuses.leadership_transfer.is_none()
+      && self.paxos_driver.transfer_leadership(
+        &mut MasterPaxosContext { io_ctx, this_eid: &self.this_eid },
+        transfer.target_eid.clone(),
+      )
+    {
+      statuses.leadership_transfer = Some(transfer);
+      return;
+    } else {
+      msg::TransferLeadershipResult::Aborted
+    };
+
+    let (eid, response) = transfer.response(result);
+    io_ctx.send(&eid, response);
+  }
+
+  /// Responds to the `ExternalTransferLeadership` that this node was carrying out (if any)
+  /// now that the Leadership has changed to `lid`.
+  fn finish_leadership_transfer<IO: MasterIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    lid: &LeadershipId,
+  ) {
+    if let Some(transfer) = statuses.leadership_transfer.take() {
+      let result = if lid.eid == transfer.target_eid {
+        msg::TransferLeadershipResult::Transferred(lid.clone())
+      } else {
+        msg::TransferLeadershipResult::Aborted
+      };
+      let (eid, response) = transfer.response(result);
+      io_ctx.send(&eid, response);
+    }
+  }
+
   /// Returns true iff this is the Leader.
   pub fn is_leader(&self) -> bool {
     let lid = self.leader_map.value().get(&PaxosGroupId::Master).unwrap();
     lid.eid == self.this_eid
   }
 
+  /// Send back the metadata of the system as known by this node.
+  fn send_metadata<IO: BasicIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    statuses: &Statuses,
+    request: msg::ExternalMetadataRequest,
+  ) {
+    io_ctx.send(
+      &request.sender_eid.clone(),
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalMetadataResponse(
+        msg::ExternalMetadataResponse {
+          sender_eid: request.sender_eid,
+          request_id: request.request_id,
+          gossip_data: self.gossip.clone(),
+          leader_map: self.leader_map.value().clone(),
+          free_node_eids: statuses.free_node_manager.free_node_eids(),
+        },
+      )),
+    );
+  }
+
   /// Broadcast GossipData
   pub fn broadcast_gossip<IO: BasicIOCtx>(&mut self, io_ctx: &mut IO) {
     let sids: Vec<SlaveGroupId> = self.gossip.get().slave_address_config.keys().cloned().collect();
@@ -1544,8 +1828,9 @@ impl MasterContext {
   }
 
   /// Broadcast out `MasterGossip` to *all* Slave Nodes.
-  pub fn general_broadcast_gossip<IO: BasicIOCtx<msg::NetworkMessage>>(&self, io_ctx: &mut IO) {
+  pub fn general_broadcast_gossip<IO: BasicIOCtx<msg::NetworkMessage>>(&mut self, io_ctx: &mut IO) {
     // Send to SlaveGroups
+    let sender_time = self.clock.now(io_ctx);
     for (_, eids) in self.gossip.get().slave_address_config {
       for eid in eids {
         io_ctx.send(
@@ -1553,6 +1838,7 @@ impl MasterContext {
           msg::NetworkMessage::Slave(msg::SlaveMessage::MasterGossip(msg::MasterGossip {
             gossip_data: self.gossip.clone(),
             leader_map: self.leader_map.value().clone(),
+            sender_time: sender_time.clone(),
           })),
         )
       }
@@ -1561,12 +1847,14 @@ impl MasterContext {
 
   /// Send GossipData
   pub fn send_gossip<IO: BasicIOCtx>(&mut self, io_ctx: &mut IO, sid: SlaveGroupId) {
+    let sender_time = self.clock.now(io_ctx);
     self.send_to_slave_common(
       io_ctx,
       sid,
       msg::SlaveRemotePayload::MasterGossip(msg::MasterGossip {
         gossip_data: self.gossip.clone(),
         leader_map: self.leader_map.value().clone(),
+        sender_time,
       }),
     );
   }
@@ -1576,15 +1864,22 @@ impl MasterContext {
     &mut self,
     io_ctx: &mut IO,
     response_data: &ResponseData,
+  ) {
+    self.respond_ddl_aborted(io_ctx, response_data, msg::ExternalDDLQueryAbortData::InvalidDDLQuery)
+  }
+
+  /// Send an `ExternalDDLQueryAborted` with the `payload` to the given `ResponseData`
+  fn respond_ddl_aborted<IO: MasterIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    response_data: &ResponseData,
+    payload: msg::ExternalDDLQueryAbortData,
   ) {
     self.external_request_id_map.remove(&response_data.request_id);
     io_ctx.send(
       &response_data.sender_eid,
       msg::NetworkMessage::External(msg::ExternalMessage::ExternalDDLQueryAborted(
-        msg::ExternalDDLQueryAborted {
-          request_id: response_data.request_id.clone(),
-          payload: msg::ExternalDDLQueryAbortData::InvalidDDLQuery,
-        },
+        msg::ExternalDDLQueryAborted { request_id: response_data.request_id.clone(), payload },
       )),
     )
   }
@@ -18,6 +18,7 @@ use crate::query_planning::{
 use crate::server::ServerContextBase;
 use crate::sql_ast::iast;
 use crate::sql_ast::proc;
+use crate::trace::OpenSpans;
 use serde::{Deserialize, Serialize};
 use sqlparser::test_utils::table;
 use std::collections::BTreeMap;
@@ -588,11 +589,13 @@ fn master_query_planning_post(
 #[derive(Debug)]
 pub struct MasterQueryPlanningESS {
   ess: BTreeMap<QueryId, MasterQueryPlanningES>,
+  /// The spans of the `MasterQueryPlanningES`s, including those that respond immediately.
+  spans: OpenSpans,
 }
 
 impl MasterQueryPlanningESS {
   pub fn new() -> MasterQueryPlanningESS {
-    MasterQueryPlanningESS { ess: Default::default() }
+    MasterQueryPlanningESS { ess: Default::default(), spans: Default::default() }
   }
 
   // Leader-only
@@ -605,6 +608,7 @@ impl MasterQueryPlanningESS {
   ) {
     match request {
       msg::MasterQueryPlanningRequest::Perform(perform) => {
+        self.spans.start(io_ctx, &perform.query_id, &perform.trace, "MasterQueryPlanningES");
         let action = master_query_planning_pre(ctx, perform.clone());
         match action {
           MasterQueryPlanningAction::Wait => {
@@ -619,6 +623,7 @@ impl MasterQueryPlanningESS {
             );
           }
           MasterQueryPlanningAction::Respond(result) => {
+            self.spans.finish(io_ctx, &ctx.this_eid, &perform.query_id);
             ctx.send_to_c(
               io_ctx,
               perform.sender_path.node_path,
@@ -633,6 +638,7 @@ impl MasterQueryPlanningESS {
       }
       msg::MasterQueryPlanningRequest::Cancel(cancel) => {
         self.ess.remove(&cancel.query_id);
+        self.spans.abandon(&cancel.query_id);
       }
     }
   }
@@ -661,6 +667,7 @@ impl MasterQueryPlanningESS {
     if ctx.is_leader() {
       if let Some(es) = self.ess.remove(&query_id) {
         // If the ES still exists, we respond.
+        self.spans.finish(io_ctx, &ctx.this_eid, &query_id);
         ctx.send_to_c(
           io_ctx,
           es.sender_path.node_path,
@@ -682,6 +689,7 @@ impl MasterQueryPlanningESS {
       let es = self.ess.get_mut(&query_id).unwrap();
       if es.sender_path.node_path.sid.to_gid() == remote_leader_changed.gid {
         self.ess.remove(&query_id);
+        self.spans.abandon(&query_id);
       }
     }
   }
@@ -690,6 +698,7 @@ impl MasterQueryPlanningESS {
   pub fn handle_lc(&mut self, ctx: &mut MasterContext) {
     if !ctx.is_leader() {
       self.ess.clear();
+      self.spans = OpenSpans::default();
     }
   }
 
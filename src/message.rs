@@ -1,9 +1,9 @@
 use crate::alter_table_tm_es::AlterTableTMPayloadTypes;
 use crate::common::{
-  CQueryPath, CTQueryPath, ColName, Context, CoordGroupId, EndpointId, Gen, InternalMode,
-  LeadershipId, PaxosGroupId, QueryId, QueryResult, RequestId, SlaveGroupId, TNodePath, TQueryPath,
-  TablePath, TableView, TabletGroupId, TabletKeyRange, TierMap, TransTableLocationPrefix,
-  TransTableName,
+  CQueryPath, CTQueryPath, ColName, ColType, Context, CoordGroupId, EndpointId, ExecutionStats,
+  Gen, InternalMode, LeadershipId, PaxosGroupId, QueryId, QueryResult, RequestId, SlaveGroupId,
+  TNodePath, TQueryPath, TablePath, TableView, TabletGroupId, TabletKeyRange, TierMap,
+  TransTableLocationPrefix, TransTableName,
 };
 use crate::common::{FullGen, GossipData, LeaderMap, QueryPlan, RemoteLeaderChangedPLm, Timestamp};
 use crate::create_table_tm_es::CreateTableTMPayloadTypes;
@@ -19,6 +19,7 @@ use crate::slave::{SharedPaxosBundle, SlaveSnapshot};
 use crate::sql_ast::{iast, proc};
 use crate::stmpaxos2pc_tm;
 use crate::tablet::ShardingSnapshot;
+use crate::trace::TraceContext;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -28,6 +29,8 @@ use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct InitMessage {
+  /// The address that the sender accepts connections at. See `EndpointId::address`.
+  pub address: String,
   /// See `EndpointId::is_internal`.
   pub is_internal: InternalMode,
 }
@@ -58,6 +61,39 @@ pub enum MasterExternalReq {
   CancelExternalSharding(CancelExternalSharding),
   /// This is used to query Metadata about the database.
   ExternalMetadataRequest(ExternalMetadataRequest),
+  /// Admin
+  ExternalTransferLeadership(ExternalTransferLeadership),
+}
+
+impl MasterExternalReq {
+  /// Returns the response to send back if this request arrives at a node that is not the
+  /// Master leader. `ExternalMetadataRequest`s and cancellations have no such response.
+  pub fn not_leader_response(&self) -> Option<(EndpointId, NetworkMessage)> {
+    match self {
+      MasterExternalReq::PerformExternalDDLQuery(req) => Some((
+        req.sender_eid.clone(),
+        NetworkMessage::External(ExternalMessage::ExternalDDLQueryAborted(
+          ExternalDDLQueryAborted {
+            request_id: req.request_id.clone(),
+            payload: ExternalDDLQueryAbortData::NotLeader,
+          },
+        )),
+      )),
+      MasterExternalReq::PerformExternalSharding(req) => Some((
+        req.sender_eid.clone(),
+        NetworkMessage::External(ExternalMessage::ExternalShardingAborted(
+          ExternalShardingAborted {
+            request_id: req.request_id.clone(),
+            payload: ExternalShardingAbortData::NotLeader,
+          },
+        )),
+      )),
+      MasterExternalReq::ExternalTransferLeadership(req) => {
+        Some(req.response(TransferLeadershipResult::NotLeader))
+      }
+      _ => None,
+    }
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -137,6 +173,8 @@ pub struct StartMaster {
 pub struct FreeNodeRegistered {
   /// The `LeadershipId` of the sending Master node
   pub cur_lid: LeadershipId,
+  /// The `node_index` of the `ClockId`s of the registered node.
+  pub node_index: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -160,6 +198,41 @@ pub struct CreateSlaveGroup {
 pub enum SlaveExternalReq {
   PerformExternalQuery(PerformExternalQuery),
   CancelExternalQuery(CancelExternalQuery),
+  /// Admin
+  ExternalShowQueries(ExternalShowQueries),
+  ExternalKillQuery(ExternalKillQuery),
+  ExternalTransferLeadership(ExternalTransferLeadership),
+}
+
+impl SlaveExternalReq {
+  /// Returns the response to send back if this request arrives at a node that is not the
+  /// leader of a SlaveGroup. Cancellations have no such response, and neither do
+  /// `ExternalShowQueries`, which every node of a SlaveGroup answers.
+  pub fn not_leader_response(&self) -> Option<(EndpointId, NetworkMessage)> {
+    match self {
+      SlaveExternalReq::PerformExternalQuery(req) => Some((
+        req.sender_eid.clone(),
+        NetworkMessage::External(ExternalMessage::ExternalQueryAborted(ExternalQueryAborted {
+          request_id: req.request_id.clone(),
+          payload: ExternalAbortedData::NotLeader,
+        })),
+      )),
+      SlaveExternalReq::CancelExternalQuery(_) => None,
+      SlaveExternalReq::ExternalShowQueries(_) => None,
+      SlaveExternalReq::ExternalKillQuery(req) => Some((
+        req.sender_eid.clone(),
+        NetworkMessage::External(ExternalMessage::ExternalKillQueryResponse(
+          ExternalKillQueryResponse {
+            request_id: req.request_id.clone(),
+            result: KillQueryResult::NotLeader,
+          },
+        )),
+      )),
+      SlaveExternalReq::ExternalTransferLeadership(req) => {
+        Some(req.response(TransferLeadershipResult::NotLeader))
+      }
+    }
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -169,6 +242,9 @@ pub enum SlaveMessage {
   MasterGossip(MasterGossip),
   RemoteLeaderChangedGossip(RemoteLeaderChangedGossip),
   PaxosDriverMessage(PaxosDriverMessage<SharedPaxosBundle>),
+  /// Follower Reads
+  FollowerRead(FollowerRead),
+  FollowerReadResponse(FollowerReadResponse),
 }
 
 impl SlaveMessage {
@@ -210,6 +286,28 @@ pub enum ExternalMessage {
   ExternalShardingAborted(ExternalShardingAborted),
   /// Debug. This is used for debugging purposes during development.
   ExternalMetadataResponse(ExternalMetadataResponse),
+  /// Admin
+  ExternalShowQueriesResponse(ExternalShowQueriesResponse),
+  ExternalKillQueryResponse(ExternalKillQueryResponse),
+  ExternalTransferLeadershipResponse(ExternalTransferLeadershipResponse),
+}
+
+impl ExternalMessage {
+  /// Returns the `RequestId` of the request that this is a response to.
+  pub fn request_id(&self) -> &RequestId {
+    match self {
+      ExternalMessage::ExternalQuerySuccess(res) => &res.request_id,
+      ExternalMessage::ExternalQueryAborted(res) => &res.request_id,
+      ExternalMessage::ExternalDDLQuerySuccess(res) => &res.request_id,
+      ExternalMessage::ExternalDDLQueryAborted(res) => &res.request_id,
+      ExternalMessage::ExternalShardingSuccess(res) => &res.request_id,
+      ExternalMessage::ExternalShardingAborted(res) => &res.request_id,
+      ExternalMessage::ExternalMetadataResponse(res) => &res.request_id,
+      ExternalMessage::ExternalShowQueriesResponse(res) => &res.request_id,
+      ExternalMessage::ExternalKillQueryResponse(res) => &res.request_id,
+      ExternalMessage::ExternalTransferLeadershipResponse(res) => &res.request_id,
+    }
+  }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -374,11 +472,21 @@ pub struct LeaderChanged {
   pub lid: LeadershipId,
 }
 
+/// The `PLEntry`s that a Leader inserted with a single proposal. If `lid` is no longer the
+/// Leadership by the time this is learned, the `entries` are dropped. Otherwise, they are
+/// delivered one by one, so users of the `PaxosDriver` never see a `Batch`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PLBatch<BundleT> {
+  pub lid: LeadershipId,
+  pub entries: Vec<PLEntry<BundleT>>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum PLEntry<BundleT> {
   Bundle(BundleT),
   ReconfigBundle(ReconfigBundle<BundleT>),
   LeaderChanged(LeaderChanged),
+  Batch(PLBatch<BundleT>),
 }
 
 pub type PLIndex = u128;
@@ -394,6 +502,35 @@ pub struct MultiPaxosMessage<BundleT> {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct IsLeader {
   pub lid: LeadershipId,
+  /// The time at the Leader when this was sent, which is echoed back in `IsLeaderAck`.
+  pub sent_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IsLeaderAck {
+  pub sender_eid: EndpointId,
+  pub lid: LeadershipId,
+  pub sent_time: Timestamp,
+}
+
+/// Sent by a PaxosNode that has stopped hearing from the Leader `lid` before it tries to
+/// become the Leader itself.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PreVote {
+  pub sender_eid: EndpointId,
+  pub lid: LeadershipId,
+  pub next_index: PLIndex,
+  /// Distinguishes the `PreVote`s that the sender sends on successive heartbeats.
+  pub round: u64,
+}
+
+/// Sent in response to a `PreVote` if the responder has also stopped hearing from the Leader.
+/// The `round` is echoed from the `PreVote`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PreVoteGrant {
+  pub sender_eid: EndpointId,
+  pub lid: LeadershipId,
+  pub round: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -447,6 +584,9 @@ pub struct NewNodeStarted {
 pub enum PaxosDriverMessage<BundleT> {
   MultiPaxosMessage(MultiPaxosMessage<BundleT>),
   IsLeader(IsLeader),
+  IsLeaderAck(IsLeaderAck),
+  PreVote(PreVote),
+  PreVoteGrant(PreVoteGrant),
   InformLearned(InformLearned),
   LogSyncRequest(LogSyncRequest),
   LogSyncResponse(LogSyncResponse<BundleT>),
@@ -465,6 +605,9 @@ pub struct PerformQuery {
   pub sender_path: CTQueryPath,
   pub query_id: QueryId,
   pub query: GeneralQuery,
+  pub trace: TraceContext,
+  /// The time of the sender's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 impl PerformQuery {
@@ -492,6 +635,7 @@ pub struct QuerySuccess {
   pub responder_path: CTQueryPath,
   pub result: Vec<TableView>,
   pub new_rms: Vec<TQueryPath>,
+  pub stats: ExecutionStats,
 }
 
 /// These are Errors that are simply recursively propagated up to the Slave,
@@ -499,13 +643,23 @@ pub struct QuerySuccess {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum QueryError {
   // Fatal Query Errors to be propagated to the user.
-  TypeError { msg: String },
-  RuntimeError { msg: String },
+  TypeError {
+    msg: String,
+  },
+  RuntimeError {
+    msg: String,
+  },
 
   // Transient Errors that can be solved by retrying.
   WriteRegionConflictWithSubsequentRead,
+  /// Used by `IsolationLevel::Snapshot` when a subsequent write wrote to the same cells.
+  WriteWriteConflict,
+  /// Used when an MSQuery with a higher `Priority` wrote to a region that was read.
+  Preempted,
+  /// Used by a Follower that could not serve a `FollowerRead` in time. The Coord sends the
+  /// read to the Leader instead (see `TMStatus::redirect_follower_read`).
+  FollowerReadUnavailable,
   DeadlockSafetyAbortion,
-  TimestampConflict,
 
   // Lateral error, used for recursive aborting but never to be sent back to the External
   LateralError,
@@ -528,6 +682,26 @@ pub struct QueryAborted {
   pub payload: AbortedData,
 }
 
+/// Sent by a Coord to any node of a SlaveGroup (not just the Leader) to perform a
+/// `TableSelectQuery` at one of its Tablets with bounded staleness (see
+/// `TabletContext::closed_timestamp`). Unlike the `PerformQuery`s in `RemoteMessage`s, these
+/// do not pass through the `NetworkDriver`, so that Followers can receive them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FollowerRead {
+  /// The node of the Coord that sent this, which the `FollowerReadResponse` is sent to.
+  pub sender_eid: EndpointId,
+  pub tid: TabletGroupId,
+  pub perform_query: PerformQuery,
+}
+
+/// Holds the `QuerySuccess` or `QueryAborted` that answers a `FollowerRead`, addressed to
+/// the server that sent it. The `payload` is only delivered if the receiving node is still
+/// the Leader.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FollowerReadResponse {
+  pub payload: SlaveRemotePayload,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum GeneralQuery {
   TransTableSelectQuery(TransTableSelectQuery),
@@ -600,6 +774,35 @@ pub struct PerformExternalQuery {
   pub sender_eid: EndpointId,
   pub request_id: RequestId,
   pub query: String,
+  /// The values bound to the `$n` parameters in `query` (where `params[n - 1]` is bound
+  /// to `$n`). This is empty if `query` has no parameters.
+  pub params: Vec<iast::Value>,
+  /// If set, the query is aborted with `Timeout` if it has not started committing within
+  /// this many ms of arriving.
+  pub opt_timeout_ms: Option<u128>,
+  /// The maximum number of times the query is retried after transient failures. If this is
+  /// not set, `CoordConfig::max_retries` is used.
+  pub opt_max_retries: Option<u32>,
+  /// The priority that the query starts at. This is boosted every time it is retried.
+  pub priority: TransactionPriority,
+  /// If set, the query is a bounded staleness read: it reads the database as of up to this
+  /// many ms ago, which allows its Table reads to be served by Followers. Such a query
+  /// cannot write.
+  pub opt_max_staleness_ms: Option<u128>,
+}
+
+/// Decides which query is aborted when two queries conflict (see `Priority`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionPriority {
+  Low,
+  Normal,
+  High,
+}
+
+impl Default for TransactionPriority {
+  fn default() -> Self {
+    TransactionPriority::Normal
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -648,8 +851,16 @@ pub enum QueryPlanningError {
   InvalidColumnRef,
   /// Returned if we detect that the Select clause is not right.
   InvalidSelectClause,
+  /// Occurs if a `FOR UPDATE` or `FOR SHARE` is used on a Select that does not read
+  /// directly from a single Table.
+  InvalidLock,
+  /// Occurs if a query with a `PerformExternalQuery::opt_max_staleness_ms` writes or locks.
+  StaleWrite,
   /// Occurs when `ColName`s are not present in the database schema.
   RequiredColumnDNE(ColName),
+  /// Occurs when an ALTER COLUMN would change the type of `col_name` from `from` to `to`
+  /// where not every existing value can be converted without loss.
+  UnsafeColumnTypeConversion { col_name: ColName, from: ColType, to: ColType },
 }
 
 /// Data to send back to the External in case of a fatal Error.
@@ -673,6 +884,18 @@ pub enum ExternalAbortedData {
   /// This is sent back as a response when a CancelExternalQuery comes in. If the
   /// transaction still exists, we make sure to abort it.
   CancelConfirmed,
+  /// Happens if the query was aborted by an `ExternalKillQuery`.
+  Killed,
+  /// Happens if the request arrived at a node that is not the leader of its PaxosGroup,
+  /// or if the node lost leadership before the query could commit. The query was not
+  /// executed, so it is safe to resend it to the current leader.
+  NotLeader,
+  /// Happens if the query did not start committing before its deadline (see
+  /// `PerformExternalQuery::opt_timeout_ms`). The query was not executed.
+  Timeout,
+  /// Happens if the query failed for transient reasons more than the maximum number of
+  /// retries. The query was not executed, so it is safe to resend it.
+  TooManyRetries,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -698,6 +921,7 @@ pub struct PerformMasterQueryPlanning {
   pub query_id: QueryId,
   pub timestamp: Timestamp,
   pub sql_query: iast::Query,
+  pub trace: TraceContext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -777,6 +1001,8 @@ pub struct MasterGossip {
   // This is use to distribute a valid Leadership of a Slave so that Slaves that do not yet
   // know about a it can populate their LeaderMap properly.
   pub leader_map: LeaderMap,
+  /// The time of the sender's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -808,7 +1034,10 @@ pub enum ExternalDDLQueryAbortData {
   NonUniqueRequestId,
   ParseError(String),
   InvalidDDLQuery,
+  QueryPlanningError(QueryPlanningError),
   CancelConfirmed,
+  /// See `ExternalAbortedData::NotLeader`.
+  NotLeader,
   Unknown,
 }
 
@@ -859,7 +1088,11 @@ pub struct CancelExternalSharding {
 pub enum ExternalShardingAbortData {
   NonUniqueRequestId,
   InvalidShardingOp,
+  /// The Table is partitioned by hash, and so its Tablets cannot be split.
+  HashPartitionedTable,
   CancelConfirmed,
+  /// See `ExternalAbortedData::NotLeader`.
+  NotLeader,
   Unknown,
 }
 
@@ -894,3 +1127,112 @@ pub struct ExternalMetadataResponse {
   pub leader_map: LeaderMap,
   pub free_node_eids: Vec<EndpointId>,
 }
+
+// -------------------------------------------------------------------------------------------------
+//  Admin Messages
+// -------------------------------------------------------------------------------------------------
+
+/// Asks a node in a SlaveGroup for the queries that are currently running at it, i.e. the
+/// ESs at the Slave, its Coords, and its Tablets.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalShowQueries {
+  pub sender_eid: EndpointId,
+  pub request_id: RequestId,
+}
+
+/// Describes an ES that is running at a node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RunningQuery {
+  /// The server at the node that the ES is running at (the Slave, a Coord, or a Tablet).
+  pub server: String,
+  pub es_type: String,
+  pub query_id: QueryId,
+  /// The `QueryId` of the root of the query. For TP, this is the `MSCoordES`, and for
+  /// an RM of a Paxos2PC, this is the TM.
+  pub root_query_id: QueryId,
+  /// The External request that the ES is servicing, if it is known at this server.
+  pub request_id: Option<RequestId>,
+  pub state: String,
+  /// How long ago the query started, if it is known at this server.
+  pub age_ms: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalShowQueriesResponse {
+  pub request_id: RequestId,
+  pub queries: Vec<RunningQuery>,
+}
+
+/// Asks the Coord servicing the External request `target_request_id` to abort it. This
+/// has to be sent to the same SlaveGroup that the request was sent to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalKillQuery {
+  pub sender_eid: EndpointId,
+  pub request_id: RequestId,
+  pub target_request_id: RequestId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum KillQueryResult {
+  /// The request was aborted, and its External was sent a `Killed` abort.
+  Killed,
+  /// The request does not exist, or it has already started to commit (in which case it
+  /// can no longer be aborted).
+  NotFound,
+  /// Happens if the `ExternalKillQuery` arrived at a node that is not the leader of
+  /// its SlaveGroup.
+  NotLeader,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalKillQueryResponse {
+  pub request_id: RequestId,
+  pub result: KillQueryResult,
+}
+
+/// Asks the Leader of the PaxosGroup `gid` to hand Leadership over to `target_eid`, e.g.
+/// before the Leader is taken down for maintenance. The Leader waits for `target_eid` to
+/// catch up on the PaxosLog, and then inserts a `LeaderChanged` for it. This has to be sent
+/// to the Leader of `gid`, and is answered once the transfer finishes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalTransferLeadership {
+  pub sender_eid: EndpointId,
+  pub request_id: RequestId,
+  pub gid: PaxosGroupId,
+  pub target_eid: EndpointId,
+}
+
+impl ExternalTransferLeadership {
+  /// Returns the response to send back for this request.
+  pub fn response(&self, result: TransferLeadershipResult) -> (EndpointId, NetworkMessage) {
+    (
+      self.sender_eid.clone(),
+      NetworkMessage::External(ExternalMessage::ExternalTransferLeadershipResponse(
+        ExternalTransferLeadershipResponse { request_id: self.request_id.clone(), result },
+      )),
+    )
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum TransferLeadershipResult {
+  /// The `target_eid` became the Leader with the given `LeadershipId`.
+  Transferred(LeadershipId),
+  /// The `target_eid` is not a node in the PaxosGroup, or the request was sent to the
+  /// wrong PaxosGroup.
+  InvalidTarget,
+  /// The transfer was not carried out, either because another one was already in
+  /// progress, the `target_eid` did not catch up in time, or the Leadership changed to a
+  /// different node in the meantime. The prior Leader remains the Leader in the first two
+  /// cases, so it is safe to resend the request.
+  Aborted,
+  /// Happens if the `ExternalTransferLeadership` arrived at a node that is not the leader
+  /// of its PaxosGroup.
+  NotLeader,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalTransferLeadershipResponse {
+  pub request_id: RequestId,
+  pub result: TransferLeadershipResult,
+}
@@ -0,0 +1,250 @@
+use crate::message as msg;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// The Metrics Registry holds all counters and gauges that describe the operation of this
+/// process. There is one global registry (see `metrics()`), which every Node component
+/// (the Master, the Slave, Tablets, Coords, and the PaxosDriver) updates as it runs. In
+/// production, a process corresponds to a single node, so the registry describes that node.
+///
+/// The registry can be rendered in the Prometheus text exposition format with `render`.
+
+// -----------------------------------------------------------------------------------------------
+//  Metric Names
+// -----------------------------------------------------------------------------------------------
+
+/// Counter of `NetworkMessage`s received by the node, labeled by `type` (see `message_type`).
+pub const M_MESSAGES_RECEIVED: &str = "runiversal_messages_received_total";
+/// Counter of proposals started by the PaxosDriver.
+pub const M_PAXOS_PROPOSALS: &str = "runiversal_paxos_proposals_total";
+/// Gauge of the number of entries in the Paxos Log that have been learned.
+pub const M_PAXOS_LOG_LENGTH: &str = "runiversal_paxos_log_length";
+/// Gauge of the number of active ESs, labeled by `group` (the TabletGroupId or CoordGroupId)
+/// and `kind` (the kind of ES).
+pub const M_ACTIVE_ESS: &str = "runiversal_active_ess";
+/// Counter of transactions that have committed.
+pub const M_TRANSACTIONS_COMMITTED: &str = "runiversal_transactions_committed_total";
+/// Counter of transaction aborts, labeled by `reason` (the `QueryError` variant).
+pub const M_TRANSACTIONS_ABORTED: &str = "runiversal_transactions_aborted_total";
+/// Gauge of the number of present rows in a Tablet, labeled by `tablet` (the TabletGroupId).
+pub const M_TABLET_ROWS: &str = "runiversal_tablet_rows";
+/// Counter of `Timestamp`s received from another server that were too far ahead of this node's
+/// clock (see `HybridClock`).
+pub const M_CLOCK_SKEW_DETECTED: &str = "runiversal_clock_skew_detected_total";
+/// Counter of reads that a Tablet Leader served with its leader lease, without persisting
+/// the ReadProtection.
+pub const M_LEASE_READS: &str = "runiversal_lease_reads_total";
+/// Counter of `FollowerRead`s that a Tablet served below its `closed_timestamp`.
+pub const M_FOLLOWER_READS: &str = "runiversal_follower_reads_total";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetricType {
+  Counter,
+  Gauge,
+}
+
+impl MetricType {
+  fn name(&self) -> &'static str {
+    match self {
+      MetricType::Counter => "counter",
+      MetricType::Gauge => "gauge",
+    }
+  }
+}
+
+/// This defines the order that the metrics should be rendered, as well as their help text.
+const NUM_METRICS: usize = 10;
+const METRIC_DESCS: [(&str, MetricType, &str); NUM_METRICS] = [
+  (M_MESSAGES_RECEIVED, MetricType::Counter, "Network messages received, by type."),
+  (M_PAXOS_PROPOSALS, MetricType::Counter, "Paxos proposals started by this node."),
+  (M_PAXOS_LOG_LENGTH, MetricType::Gauge, "Number of learned entries in the Paxos Log."),
+  (M_ACTIVE_ESS, MetricType::Gauge, "Number of active ESs, by group and kind."),
+  (M_TRANSACTIONS_COMMITTED, MetricType::Counter, "Transactions that committed."),
+  (M_TRANSACTIONS_ABORTED, MetricType::Counter, "Transaction aborts, by QueryError."),
+  (M_TABLET_ROWS, MetricType::Gauge, "Number of present rows in a Tablet."),
+  (M_CLOCK_SKEW_DETECTED, MetricType::Counter, "Timestamps received from a skewed clock."),
+  (M_LEASE_READS, MetricType::Counter, "Reads served by a Tablet with its leader lease."),
+  (M_FOLLOWER_READS, MetricType::Counter, "Bounded staleness reads served by a Tablet."),
+];
+
+// -----------------------------------------------------------------------------------------------
+//  Metrics
+// -----------------------------------------------------------------------------------------------
+
+/// The labels of a metric series, as `(label name, label value)` pairs.
+pub type Labels = Vec<(&'static str, String)>;
+
+type SeriesKey = (&'static str, Labels);
+
+#[derive(Debug)]
+pub struct Metrics {
+  counters: Mutex<BTreeMap<SeriesKey, u64>>,
+  gauges: Mutex<BTreeMap<SeriesKey, i64>>,
+}
+
+static METRICS: Metrics = Metrics::new();
+
+/// Returns the global Metrics Registry.
+pub fn metrics() -> &'static Metrics {
+  &METRICS
+}
+
+impl Metrics {
+  pub const fn new() -> Metrics {
+    Metrics { counters: Mutex::new(BTreeMap::new()), gauges: Mutex::new(BTreeMap::new()) }
+  }
+
+  /// Increments the counter `name` with `labels` by `amount`.
+  pub fn inc_by(&self, name: &'static str, labels: Labels, amount: u64) {
+    let mut counters = self.counters.lock().unwrap();
+    *counters.entry((name, labels)).or_insert(0) += amount;
+  }
+
+  /// Increments the counter `name` with `labels` by one.
+  pub fn inc(&self, name: &'static str, labels: Labels) {
+    self.inc_by(name, labels, 1);
+  }
+
+  /// Sets the gauge `name` with `labels` to `value`.
+  pub fn set(&self, name: &'static str, labels: Labels, value: i64) {
+    let mut gauges = self.gauges.lock().unwrap();
+    gauges.insert((name, labels), value);
+  }
+
+  /// Returns the current value of the counter `name` with `labels`.
+  pub fn counter(&self, name: &'static str, labels: Labels) -> u64 {
+    let counters = self.counters.lock().unwrap();
+    counters.get(&(name, labels)).cloned().unwrap_or(0)
+  }
+
+  /// Returns the current value of the gauge `name` with `labels`.
+  pub fn gauge(&self, name: &'static str, labels: Labels) -> i64 {
+    let gauges = self.gauges.lock().unwrap();
+    gauges.get(&(name, labels)).cloned().unwrap_or(0)
+  }
+
+  /// Renders all metrics in the Prometheus text exposition format.
+  pub fn render(&self) -> String {
+    let counters = self.counters.lock().unwrap().clone();
+    let gauges = self.gauges.lock().unwrap().clone();
+
+    let mut out = String::new();
+    for (name, metric_type, help) in METRIC_DESCS.iter() {
+      writeln!(out, "# HELP {} {}", name, help).unwrap();
+      writeln!(out, "# TYPE {} {}", name, metric_type.name()).unwrap();
+      match metric_type {
+        MetricType::Counter => {
+          for ((series_name, labels), value) in &counters {
+            if series_name == name {
+              writeln!(out, "{}{} {}", name, render_labels(labels), value).unwrap();
+            }
+          }
+        }
+        MetricType::Gauge => {
+          for ((series_name, labels), value) in &gauges {
+            if series_name == name {
+              writeln!(out, "{}{} {}", name, render_labels(labels), value).unwrap();
+            }
+          }
+        }
+      }
+    }
+    out
+  }
+}
+
+/// Renders `labels` as `{name="value",...}`, or the empty string if there are no `labels`.
+fn render_labels(labels: &Labels) -> String {
+  if labels.is_empty() {
+    return String::new();
+  }
+
+  let mut pairs = Vec::<String>::new();
+  for (name, value) in labels {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    pairs.push(format!("{}=\"{}\"", name, value));
+  }
+  format!("{{{}}}", pairs.join(","))
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Metric Utils
+// -----------------------------------------------------------------------------------------------
+
+/// Returns the `type` label that `M_MESSAGES_RECEIVED` uses for `m`.
+pub fn message_type(m: &msg::NetworkMessage) -> &'static str {
+  match m {
+    msg::NetworkMessage::External(_) => "external",
+    msg::NetworkMessage::Master(m) => match m {
+      msg::MasterMessage::MasterExternalReq(_) => "master_external_req",
+      msg::MasterMessage::FreeNodeAssoc(_) => "master_free_node_assoc",
+      msg::MasterMessage::RemoteMessage(m) => match &m.payload {
+        msg::MasterRemotePayload::MasterQueryPlanning(_) => "master_query_planning",
+        msg::MasterRemotePayload::CreateTable(_)
+        | msg::MasterRemotePayload::AlterTable(_)
+        | msg::MasterRemotePayload::DropTable(_) => "master_ddl",
+        msg::MasterRemotePayload::ShardSplit(_) => "master_sharding",
+        msg::MasterRemotePayload::SlaveReconfig(_) => "master_slave_reconfig",
+        msg::MasterRemotePayload::MasterGossipRequest(_) => "master_gossip_request",
+      },
+      msg::MasterMessage::RemoteLeaderChangedGossip(_) => "master_remote_leader_changed",
+      msg::MasterMessage::PaxosDriverMessage(_) => "master_paxos",
+    },
+    msg::NetworkMessage::Slave(m) => match m {
+      msg::SlaveMessage::SlaveExternalReq(_) => "slave_external_req",
+      msg::SlaveMessage::RemoteMessage(m) => match &m.payload {
+        msg::SlaveRemotePayload::CreateTable(_) => "slave_create_table",
+        msg::SlaveRemotePayload::ShardSplit(_) | msg::SlaveRemotePayload::ShardingMessage(_) => {
+          "slave_sharding"
+        }
+        msg::SlaveRemotePayload::ReconfigSlaveGroup(_) => "slave_reconfig",
+        msg::SlaveRemotePayload::MasterGossip(_) => "slave_master_gossip",
+        msg::SlaveRemotePayload::TabletMessage(_, m) => match m {
+          msg::TabletMessage::PerformQuery(_) => "tablet_perform_query",
+          msg::TabletMessage::CancelQuery(_) => "tablet_cancel_query",
+          msg::TabletMessage::QueryAborted(_) => "tablet_query_aborted",
+          msg::TabletMessage::QuerySuccess(_) => "tablet_query_success",
+          msg::TabletMessage::FinishQuery(_) => "tablet_finish_query",
+          msg::TabletMessage::AlterTable(_) | msg::TabletMessage::DropTable(_) => "tablet_ddl",
+          msg::TabletMessage::ShardSplit(_) | msg::TabletMessage::ShardingConfirmed(_) => {
+            "tablet_sharding"
+          }
+        },
+        msg::SlaveRemotePayload::CoordMessage(_, m) => match m {
+          msg::CoordMessage::MasterQueryPlanningSuccess(_) => "coord_query_planning",
+          msg::CoordMessage::PerformQuery(_) => "coord_perform_query",
+          msg::CoordMessage::CancelQuery(_) => "coord_cancel_query",
+          msg::CoordMessage::QueryAborted(_) => "coord_query_aborted",
+          msg::CoordMessage::QuerySuccess(_) => "coord_query_success",
+          msg::CoordMessage::FinishQuery(_) => "coord_finish_query",
+          msg::CoordMessage::RegisterQuery(_) => "coord_register_query",
+        },
+      },
+      msg::SlaveMessage::MasterGossip(_) => "slave_master_gossip",
+      msg::SlaveMessage::RemoteLeaderChangedGossip(_) => "slave_remote_leader_changed",
+      msg::SlaveMessage::PaxosDriverMessage(_) => "slave_paxos",
+      msg::SlaveMessage::FollowerRead(_) => "slave_follower_read",
+      msg::SlaveMessage::FollowerReadResponse(_) => "slave_follower_read_response",
+    },
+    msg::NetworkMessage::FreeNode(_) => "free_node",
+  }
+}
+
+/// Returns the `reason` label that `M_TRANSACTIONS_ABORTED` uses for `query_error`.
+pub fn query_error_type(query_error: &msg::QueryError) -> &'static str {
+  match query_error {
+    msg::QueryError::TypeError { .. } => "TypeError",
+    msg::QueryError::RuntimeError { .. } => "RuntimeError",
+    msg::QueryError::WriteRegionConflictWithSubsequentRead => {
+      "WriteRegionConflictWithSubsequentRead"
+    }
+    msg::QueryError::WriteWriteConflict => "WriteWriteConflict",
+    msg::QueryError::Preempted => "Preempted",
+    msg::QueryError::FollowerReadUnavailable => "FollowerReadUnavailable",
+    msg::QueryError::DeadlockSafetyAbortion => "DeadlockSafetyAbortion",
+    msg::QueryError::LateralError => "LateralError",
+    msg::QueryError::InvalidLeadershipId => "InvalidLeadershipId",
+    msg::QueryError::InvalidQueryPlan => "InvalidQueryPlan",
+  }
+}
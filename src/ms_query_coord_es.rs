@@ -2,25 +2,31 @@ use crate::col_usage::{
   external_trans_table_collecting_cb, trans_table_collecting_cb, QueryElement, QueryIterator,
 };
 use crate::common::{
-  lookup, merge_table_views, mk_qid, unexpected_branch, FullGen, OrigP, QueryPlan, QueryResult,
-  Timestamp,
+  lookup, merge_table_views, mk_qid, unexpected_branch, ExecutionStats, FullGen, OrigP, QueryPlan,
+  QueryResult, Timestamp,
 };
 use crate::common::{
   ColName, Context, ContextRow, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, QueryId,
   SlaveGroupId, TQueryPath, TablePath, TableView, TabletGroupId, TierMap, TransTableLocationPrefix,
   TransTableName,
 };
-use crate::common::{CoreIOCtx, RemoteLeaderChangedPLm};
+use crate::common::{CoreIOCtx, IsolationLevel, Priority, RemoteLeaderChangedPLm};
 use crate::coord::CoordContext;
+use crate::explain::{explain_analyze, explain_ms_query, explain_result, StageStats};
 use crate::expression::EvalError;
+use crate::gr_query_es::SubqueryComputableSql;
 use crate::join_read_es::JoinReadES;
 use crate::master_query_planning_es::{master_query_planning, ColPresenceReq, StaticDBSchemaView};
 use crate::message as msg;
+use crate::metrics::{metrics, query_error_type, M_TRANSACTIONS_ABORTED};
+use crate::query_converter::bind_params;
 use crate::server::{CTServerContext, CommonQuery, ServerContextBase};
 use crate::sql_ast::iast;
 use crate::sql_ast::proc;
+use crate::sql_parser::QueryMode;
 use crate::table_read_es::perform_aggregation;
 use crate::tm_status::{SendHelper, TMStatus};
+use crate::trace::TraceContext;
 use crate::trans_table_read_es::TransTableSource;
 use sqlparser::test_utils::table;
 use std::collections::{BTreeMap, BTreeSet};
@@ -43,6 +49,8 @@ pub struct Stage {
   stage_idx: usize,
   /// Here, `stage_query_id` is the QueryId of the TMStatus
   stage_query_id: QueryId,
+  /// The time at which the stage was sent out (for `EXPLAIN ANALYZE`).
+  start_time_ms: u128,
 }
 
 #[derive(Debug)]
@@ -58,7 +66,18 @@ pub struct MSCoordES {
   pub timestamp: Timestamp,
 
   pub query_id: QueryId,
+  /// The trace of the External request, where the span is its root span.
+  pub trace: TraceContext,
   pub iast_query: iast::Query,
+  /// The values bound to the `$n` parameters of `iast_query`, and the key of its entry in
+  /// `CoordContext::prepared_stmts` (if the query has parameters).
+  pub params: Vec<iast::Value>,
+  pub opt_prepared_key: Option<String>,
+  pub mode: QueryMode,
+  pub isolation_level: IsolationLevel,
+  pub priority: Priority,
+  /// If set, this is a read-only query whose `TableSelect`s are served by Followers.
+  pub opt_max_staleness_ms: Option<u128>,
   pub sql_query: proc::MSQuery,
 
   // Results of the query planning.
@@ -67,6 +86,7 @@ pub struct MSCoordES {
   // The dynamically evolving fields.
   pub all_rms: BTreeSet<TQueryPath>,
   pub trans_table_views: Vec<(TransTableName, TableView)>,
+  pub stage_stats: Vec<StageStats>,
   pub state: CoordState,
 
   /// Recall that since we remove a `TQueryPath` when its Leadership changes, that means that
@@ -97,8 +117,23 @@ pub enum MSQueryCoordAction {
   ExecuteTMStatus(TMStatus),
   /// This tells the parent Server to execute the given JoinReadES.
   ExecuteJoinReadES(JoinReadES),
-  /// Indicates that a valid MSCoordES was successful, and was ECU.
-  Success(Vec<TQueryPath>, iast::Query, QueryResult, Timestamp),
+  /// Indicates that a valid MSCoordES was successful, and was ECU. This holds the query,
+  /// its bound parameters, its prepared statement key, its `QueryMode`, its `IsolationLevel`,
+  /// and its `Priority` so that it can be retried.
+  Success(
+    Vec<TQueryPath>,
+    iast::Query,
+    Vec<iast::Value>,
+    Option<String>,
+    QueryMode,
+    IsolationLevel,
+    Priority,
+    QueryResult,
+    Timestamp,
+  ),
+  /// Indicates that the query was planned and only had to be explained. This holds the
+  /// description of the plan. The MSCoordES will be in `Done`.
+  Explained(QueryResult, Timestamp),
   /// Indicates that a valid MSCoordES was unsuccessful and there is no
   /// chance of success, and was ECU.
   FatalFailure(msg::ExternalAbortedData),
@@ -114,6 +149,22 @@ pub enum MSQueryCoordAction {
 // -----------------------------------------------------------------------------------------------
 
 impl FullMSCoordES {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match self {
+      FullMSCoordES::QueryPlanning(es) => match &es.state {
+        QueryPlanningS::Start => "QueryPlanning",
+        QueryPlanningS::MasterQueryPlanning(_) => "MasterQueryPlanning",
+        QueryPlanningS::GossipDataWaiting(_) => "GossipDataWaiting",
+        QueryPlanningS::Done => "Done",
+      },
+      FullMSCoordES::Executing(es) => match &es.state {
+        CoordState::Start | CoordState::Stage(_) => "Executing",
+        CoordState::Done => "Done",
+      },
+    }
+  }
+
   /// Start the FullMSCoordES
   pub fn start<IO: CoreIOCtx>(
     &mut self,
@@ -157,15 +208,44 @@ impl FullMSCoordES {
         *self = FullMSCoordES::Executing(MSCoordES {
           timestamp: plan_es.timestamp.clone(),
           query_id: plan_es.query_id.clone(),
+          trace: plan_es.trace.clone(),
           iast_query: plan_es.iast_query.clone(),
+          params: plan_es.params.clone(),
+          opt_prepared_key: plan_es.opt_prepared_key.clone(),
+          mode: plan_es.mode,
+          isolation_level: plan_es.isolation_level,
+          priority: plan_es.priority,
+          opt_max_staleness_ms: plan_es.opt_max_staleness_ms,
           sql_query: ms_query,
           query_plan: query_plan.clone(),
           all_rms: Default::default(),
           trans_table_views: vec![],
+          stage_stats: vec![],
           state: CoordState::Start,
           registered_queries: Default::default(),
         });
 
+        // A bounded staleness read might be served by Followers, which cannot write or lock.
+        let es = cast!(FullMSCoordES::Executing, self)?;
+        if es.opt_max_staleness_ms.is_some() && !is_read_only(&es.sql_query) {
+          es.state = CoordState::Done;
+          return Some(MSQueryCoordAction::FatalFailure(
+            msg::ExternalAbortedData::QueryPlanningError(msg::QueryPlanningError::StaleWrite),
+          ));
+        }
+
+        // If we only need to explain the query, we are done.
+        if es.mode == QueryMode::Explain {
+          es.state = CoordState::Done;
+          let lines = explain_ms_query(
+            ctx,
+            &es.sql_query,
+            &es.query_plan.table_location_map,
+            &es.query_plan.all_tier_maps,
+          );
+          return Some(MSQueryCoordAction::Explained(explain_result(lines), es.timestamp.clone()));
+        }
+
         // Move the ES onto the next stage.
         self.advance(ctx, io_ctx)
       }
@@ -184,6 +264,7 @@ impl FullMSCoordES {
     io_ctx: &mut IO,
     tm_qid: QueryId,
     new_rms: BTreeSet<TQueryPath>,
+    stats: ExecutionStats,
     pre_agg_table_views: Vec<TableView>,
   ) -> Option<MSQueryCoordAction> {
     let es = cast!(FullMSCoordES::Executing, self)?;
@@ -225,6 +306,11 @@ impl FullMSCoordES {
     let table_view = table_views.into_iter().next().unwrap();
     es.trans_table_views.push((trans_table_name.clone(), table_view));
     es.all_rms.extend(new_rms);
+    es.stage_stats.push(StageStats {
+      trans_table_name: trans_table_name.clone(),
+      duration_ms: io_ctx.now().time_ms.saturating_sub(coord_stage.start_time_ms),
+      stats,
+    });
     self.advance(ctx, io_ctx)
   }
 
@@ -235,6 +321,10 @@ impl FullMSCoordES {
     io_ctx: &mut IO,
     aborted_data: msg::AbortedData,
   ) -> Option<MSQueryCoordAction> {
+    let msg::AbortedData::QueryError(query_error) = &aborted_data;
+    metrics()
+      .inc(M_TRANSACTIONS_ABORTED, vec![("reason", query_error_type(query_error).to_string())]);
+
     // Interpret the `aborted_data`.
     match aborted_data {
       // `TypeError` and `RuntimeError` both imply an unrecoverable error, since trying again at
@@ -255,8 +345,10 @@ impl FullMSCoordES {
         )))
       }
       msg::AbortedData::QueryError(msg::QueryError::WriteRegionConflictWithSubsequentRead)
+      | msg::AbortedData::QueryError(msg::QueryError::WriteWriteConflict)
+      | msg::AbortedData::QueryError(msg::QueryError::Preempted)
+      | msg::AbortedData::QueryError(msg::QueryError::FollowerReadUnavailable)
       | msg::AbortedData::QueryError(msg::QueryError::DeadlockSafetyAbortion)
-      | msg::AbortedData::QueryError(msg::QueryError::TimestampConflict)
       // TODO: Verify this code in the below case.
       | msg::AbortedData::QueryError(msg::QueryError::InvalidLeadershipId)=> {
         // This implies a recoverable failure, so we ECU and return accordingly.
@@ -264,7 +356,15 @@ impl FullMSCoordES {
         Some(MSQueryCoordAction::NonFatalFailure(false))
       }
       | msg::AbortedData::QueryError(msg::QueryError::InvalidQueryPlan) => {
-        // Unlike the above, we want to forcefully do a MasterQueryPlanning
+        // Unlike the above, we want to forcefully do a MasterQueryPlanning. We also drop
+        // the cached plan of the prepared statement, since it is evidently stale.
+        if let FullMSCoordES::Executing(es) = self {
+          if let Some(prepared_key) = &es.opt_prepared_key {
+            if let Some(prepared_stmt) = ctx.prepared_stmts.get_mut(prepared_key) {
+              prepared_stmt.opt_master_query_plan = None;
+            }
+          }
+        }
         self.exit_and_clean_up(ctx, io_ctx);
         Some(MSQueryCoordAction::NonFatalFailure(true))
       }
@@ -280,6 +380,7 @@ impl FullMSCoordES {
     io_ctx: &mut IO,
     child_qid: QueryId,
     new_rms: Vec<TQueryPath>,
+    stats: ExecutionStats,
     table_views: Vec<TableView>,
   ) -> Option<MSQueryCoordAction> {
     let es = cast_safe!(FullMSCoordES::Executing, self)?;
@@ -294,6 +395,11 @@ impl FullMSCoordES {
     let table_view = table_views.into_iter().next().unwrap();
     es.trans_table_views.push((trans_table_name.clone(), table_view));
     es.all_rms.extend(new_rms);
+    es.stage_stats.push(StageStats {
+      trans_table_name: trans_table_name.clone(),
+      duration_ms: io_ctx.now().time_ms.saturating_sub(stage.start_time_ms),
+      stats,
+    });
     self.advance(ctx, io_ctx)
   }
 
@@ -420,19 +526,37 @@ impl FullMSCoordES {
         }
       }
 
-      // Finally, we go to Done and return the appropriate TableView.
-      let schema = es.get_schema(&es.sql_query.returning).clone();
-      let (_, data) = es
-        .trans_table_views
-        .iter()
-        .find(|(trans_table_name, _)| trans_table_name == &es.sql_query.returning)
-        .unwrap()
-        .clone();
+      // Finally, we go to Done and return the appropriate TableView. For an `EXPLAIN ANALYZE`,
+      // we instead return the plan along with how each stage executed.
+      let result = if es.mode == QueryMode::ExplainAnalyze {
+        let mut lines = explain_ms_query(
+          ctx,
+          &es.sql_query,
+          &es.query_plan.table_location_map,
+          &es.query_plan.all_tier_maps,
+        );
+        lines.extend(explain_analyze(&es.stage_stats));
+        explain_result(lines)
+      } else {
+        let schema = es.get_schema(&es.sql_query.returning).clone();
+        let (_, data) = es
+          .trans_table_views
+          .iter()
+          .find(|(trans_table_name, _)| trans_table_name == &es.sql_query.returning)
+          .unwrap()
+          .clone();
+        QueryResult { schema, data }
+      };
       es.state = CoordState::Done;
       Some(MSQueryCoordAction::Success(
         es.all_rms.iter().cloned().collect(),
         es.iast_query.clone(),
-        QueryResult { schema, data },
+        es.params.clone(),
+        es.opt_prepared_key.clone(),
+        es.mode,
+        es.isolation_level,
+        es.priority,
+        result,
         es.timestamp.clone(),
       ))
     }
@@ -447,6 +571,7 @@ impl FullMSCoordES {
     stage_idx: usize,
   ) -> Option<MSQueryCoordAction> {
     let es = cast!(FullMSCoordES::Executing, self)?;
+    let start_time_ms = io_ctx.now().time_ms;
 
     // Get the corresponding MSQueryStage and ColUsageNode.
     let (trans_table_name, stage) = es.sql_query.trans_tables.get(stage_idx).unwrap();
@@ -485,12 +610,18 @@ impl FullMSCoordES {
       query_leader_map: query_leader_map.clone(),
       table_location_map: es.query_plan.table_location_map.clone(),
       col_presence_req: es.query_plan.col_presence_req.clone(),
+      isolation_level: es.isolation_level,
+      priority: es.priority,
     };
 
     // Construct the TMStatus that is going to be used to coordinate this stage
     let root_query_path = ctx.mk_query_path(es.query_id.clone());
-    let mut tm_status =
-      TMStatus::new(io_ctx, root_query_path.clone(), OrigP::new(es.query_id.clone()));
+    let mut tm_status = TMStatus::new(
+      io_ctx,
+      root_query_path.clone(),
+      es.trace.clone(),
+      OrigP::new(es.query_id.clone()),
+    );
 
     // Send out the PerformQuery.
     match stage {
@@ -504,14 +635,23 @@ impl FullMSCoordES {
         });
         let full_gen = es.query_plan.table_location_map.get(&select.from.table_path).unwrap();
         let tids = ctx.get_min_tablets(&select.from, full_gen, &select.selection);
-        let helper = SendHelper::TableQuery(general_query, tids);
+        // Followers cannot send out subqueries, so only the Leaders can serve those.
+        let helper = if es.opt_max_staleness_ms.is_some() && select.collect_subqueries().is_empty()
+        {
+          SendHelper::FollowerRead(general_query, tids, ctx.unresponsive_followers(io_ctx))
+        } else {
+          SendHelper::TableQuery(general_query, tids)
+        };
         if !tm_status.send_general(ctx, io_ctx, &query_leader_map, helper) {
           self.exit_and_clean_up(ctx, io_ctx);
           Some(MSQueryCoordAction::NonFatalFailure(false))
         } else {
           // Populate the TMStatus accordingly.
-          es.state =
-            CoordState::Stage(Stage { stage_idx, stage_query_id: tm_status.query_id().clone() });
+          es.state = CoordState::Stage(Stage {
+            stage_idx,
+            stage_query_id: tm_status.query_id().clone(),
+            start_time_ms,
+          });
           Some(MSQueryCoordAction::ExecuteTMStatus(tm_status))
         }
       }
@@ -536,8 +676,11 @@ impl FullMSCoordES {
           Some(MSQueryCoordAction::NonFatalFailure(false))
         } else {
           // Populate the TMStatus accordingly.
-          es.state =
-            CoordState::Stage(Stage { stage_idx, stage_query_id: tm_status.query_id().clone() });
+          es.state = CoordState::Stage(Stage {
+            stage_idx,
+            stage_query_id: tm_status.query_id().clone(),
+            start_time_ms,
+          });
           Some(MSQueryCoordAction::ExecuteTMStatus(tm_status))
         }
       }
@@ -545,6 +688,7 @@ impl FullMSCoordES {
         let child_es = JoinReadES::create(
           io_ctx,
           root_query_path,
+          es.trace.clone(),
           es.timestamp.clone(),
           Rc::new(context),
           select.clone(),
@@ -553,8 +697,11 @@ impl FullMSCoordES {
         );
 
         // Move the GRQueryES to the next Stage.
-        es.state =
-          CoordState::Stage(Stage { stage_idx, stage_query_id: child_es.query_id().clone() });
+        es.state = CoordState::Stage(Stage {
+          stage_idx,
+          stage_query_id: child_es.query_id().clone(),
+          start_time_ms,
+        });
 
         // Return the subqueries for the parent server to execute.
         Some(MSQueryCoordAction::ExecuteJoinReadES(child_es))
@@ -575,8 +722,11 @@ impl FullMSCoordES {
           Some(MSQueryCoordAction::NonFatalFailure(false))
         } else {
           // Populate the TMStatus accordingly.
-          es.state =
-            CoordState::Stage(Stage { stage_idx, stage_query_id: tm_status.query_id().clone() });
+          es.state = CoordState::Stage(Stage {
+            stage_idx,
+            stage_query_id: tm_status.query_id().clone(),
+            start_time_ms,
+          });
           Some(MSQueryCoordAction::ExecuteTMStatus(tm_status))
         }
       }
@@ -598,8 +748,11 @@ impl FullMSCoordES {
           Some(MSQueryCoordAction::NonFatalFailure(false))
         } else {
           // Populate the TMStatus accordingly.
-          es.state =
-            CoordState::Stage(Stage { stage_idx, stage_query_id: tm_status.query_id().clone() });
+          es.state = CoordState::Stage(Stage {
+            stage_idx,
+            stage_query_id: tm_status.query_id().clone(),
+            start_time_ms,
+          });
           Some(MSQueryCoordAction::ExecuteTMStatus(tm_status))
         }
       }
@@ -619,8 +772,11 @@ impl FullMSCoordES {
           Some(MSQueryCoordAction::NonFatalFailure(false))
         } else {
           // Populate the TMStatus accordingly.
-          es.state =
-            CoordState::Stage(Stage { stage_idx, stage_query_id: tm_status.query_id().clone() });
+          es.state = CoordState::Stage(Stage {
+            stage_idx,
+            stage_query_id: tm_status.query_id().clone(),
+            start_time_ms,
+          });
           Some(MSQueryCoordAction::ExecuteTMStatus(tm_status))
         }
       }
@@ -658,6 +814,25 @@ impl FullMSCoordES {
   pub fn to_exec(&self) -> &MSCoordES {
     cast!(FullMSCoordES::Executing, self).unwrap()
   }
+
+  /// Returns the trace of the External request that this FullMSCoordES is executing.
+  pub fn trace(&self) -> &TraceContext {
+    match self {
+      FullMSCoordES::QueryPlanning(plan_es) => &plan_es.trace,
+      FullMSCoordES::Executing(es) => &es.trace,
+    }
+  }
+}
+
+/// Returns true iff no stage of the `sql_query` writes or locks any rows.
+fn is_read_only(sql_query: &proc::MSQuery) -> bool {
+  sql_query.trans_tables.iter().all(|(_, stage)| match stage {
+    proc::MSQueryStage::TableSelect(select) => select.lock.is_none(),
+    proc::MSQueryStage::TransTableSelect(_) | proc::MSQueryStage::JoinSelect(_) => true,
+    proc::MSQueryStage::Update(_)
+    | proc::MSQueryStage::Insert(_)
+    | proc::MSQueryStage::Delete(_) => false,
+  })
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -687,8 +862,20 @@ pub struct QueryPlanningES {
   pub timestamp: Timestamp,
   /// The query to do the planning with.
   pub iast_query: iast::Query,
+  /// The values to bind to the `$n` parameters of `iast_query` once it is planned.
+  pub params: Vec<iast::Value>,
+  /// The key of the `iast_query` in `CoordContext::prepared_stmts`, if it has parameters.
+  pub opt_prepared_key: Option<String>,
+  pub mode: QueryMode,
+  pub isolation_level: IsolationLevel,
+  /// The priority of this attempt at the query, which is boosted on every retry.
+  pub priority: Priority,
+  /// See `PerformExternalQuery::opt_max_staleness_ms`. Here, `timestamp` is already stale.
+  pub opt_max_staleness_ms: Option<u128>,
   /// The OrigP of the Task holding this MSQueryCoordPlanningES
   pub query_id: QueryId,
+  /// The trace of the External request, where the span is its root span.
+  pub trace: TraceContext,
   /// Used for managing MasterQueryPlanning
   pub state: QueryPlanningS,
 }
@@ -709,6 +896,12 @@ impl QueryPlanningES {
     io_ctx: &mut IO,
   ) -> Option<QueryPlanningAction> {
     check!(matches!(&self.state, QueryPlanningS::Start));
+
+    // If this is a prepared statement whose cached plan is still valid, we use that.
+    if let Some(master_query_plan) = self.cached_master_query_plan(ctx) {
+      return self.finish_master_query_plan(ctx, master_query_plan);
+    }
+
     let gossip = ctx.gossip.get();
     let mut view = StaticDBSchemaView {
       db_schema: gossip.db_schema,
@@ -739,6 +932,7 @@ impl QueryPlanningES {
           query_id: master_query_id.clone(),
           timestamp: self.timestamp.clone(),
           sql_query: self.iast_query.clone(),
+          trace: self.trace.clone(),
         },
       )),
     );
@@ -748,6 +942,20 @@ impl QueryPlanningES {
     None
   }
 
+  /// Returns the cached `MasterQueryPlan` of the prepared statement, if there is one and
+  /// every `Gen` in its `table_location_map` is still the current one at `timestamp`.
+  fn cached_master_query_plan(&self, ctx: &CoordContext) -> Option<msg::MasterQueryPlan> {
+    let prepared_stmt = ctx.prepared_stmts.get(self.opt_prepared_key.as_ref()?)?;
+    let master_query_plan = prepared_stmt.opt_master_query_plan.as_ref()?;
+    let gossip = ctx.gossip.get();
+    for (table_path, gen) in &master_query_plan.table_location_map {
+      if gossip.table_generation.static_read(table_path, &self.timestamp) != Some(gen) {
+        return None;
+      }
+    }
+    Some(master_query_plan.clone())
+  }
+
   /// Here, we have verified all `TablePath`s are present in the GossipData.
   fn finish_master_query_plan(
     &mut self,
@@ -755,8 +963,18 @@ impl QueryPlanningES {
     master_query_plan: msg::MasterQueryPlan,
   ) -> Option<QueryPlanningAction> {
     self.state = QueryPlanningS::Done;
+
+    // Cache the (unbound) plan for the prepared statement, and then bind the parameters.
+    if let Some(prepared_key) = &self.opt_prepared_key {
+      if let Some(prepared_stmt) = ctx.prepared_stmts.get_mut(prepared_key) {
+        prepared_stmt.opt_master_query_plan = Some(master_query_plan.clone());
+      }
+    }
+    let mut ms_query = master_query_plan.ms_query;
+    bind_params(&mut ms_query, &self.params);
+
     Some(QueryPlanningAction::Success(
-      master_query_plan.ms_query,
+      ms_query,
       CoordQueryPlan {
         all_tier_maps: master_query_plan.all_tier_maps,
         query_leader_map: self.compute_query_leader_map(ctx, &master_query_plan.table_location_map),
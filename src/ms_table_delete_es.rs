@@ -1,5 +1,7 @@
 use crate::col_usage::{col_collecting_cb, col_ref_collecting_cb, QueryIterator};
-use crate::common::{mk_qid, ColName, CoreIOCtx, OrigP, QueryESResult, WriteRegion};
+use crate::common::{
+  mk_qid, ColName, CoreIOCtx, IsolationLevel, OrigP, QueryESResult, WriteRegion,
+};
 use crate::common::{
   ColValN, ContextRow, PrimaryKey, QueryId, TablePath, TableView, TransTableName,
 };
@@ -73,8 +75,12 @@ impl SqlQueryInner for DeleteInner {
 
     // Verify that we have WriteRegion Isolation with Subsequent Reads. We abort
     // if we don't, and we amend this MSQuery's VerifyingReadWriteRegions if we do.
-    if !ctx.check_write_region_isolation(&write_region, &es.timestamp) {
+    if !ctx.check_write_region_isolation(&write_region, &es.timestamp, es.query_plan.priority) {
       Err(msg::QueryError::WriteRegionConflictWithSubsequentRead)
+    } else if es.query_plan.isolation_level == IsolationLevel::Snapshot
+      && !ctx.check_write_write_isolation(&write_region, &es.timestamp)
+    {
+      Err(msg::QueryError::WriteWriteConflict)
     } else {
       // Move the MSTableDeleteES to the Pending state with the given ReadRegion.
       let protect_qid = mk_qid(io_ctx.rand());
@@ -87,7 +93,7 @@ impl SqlQueryInner for DeleteInner {
         query_id: protect_qid.clone(),
         read_region,
       });
-      verifying.m_write_protected.insert(write_region);
+      ctx.add_m_write_protected(&es.timestamp, write_region);
 
       Ok(protect_qid)
     }
@@ -103,6 +109,7 @@ impl SqlQueryInner for DeleteInner {
     compute_subqueries(
       GRQueryConstructorView {
         root_query_path: &es.root_query_path,
+        trace: &es.trace,
         timestamp: &es.timestamp,
         sql_query: &self.sql_query,
         query_plan: &es.query_plan,
@@ -218,6 +225,8 @@ impl SqlQueryInner for DeleteInner {
         Ok(())
       },
     );
+    let mut stats = es.stats.clone();
+    stats.add_rows_read(&ctx.this_tid, context_constructor.local_table.rows_read());
 
     match eval_res {
       Ok(()) => {
@@ -228,6 +237,7 @@ impl SqlQueryInner for DeleteInner {
         Some(TPESAction::Success(QueryESResult {
           result: vec![res_table_view],
           new_rms: es.new_rms.iter().cloned().collect(),
+          stats,
         }))
       }
       Err(eval_error) => Some(TPESAction::QueryError(mk_eval_error(eval_error))),
@@ -1,4 +1,6 @@
-use crate::common::{add_item, remove_item, CoreIOCtx, QueryESResult, QueryPlan, Timestamp};
+use crate::common::{
+  add_item, remove_item, CoreIOCtx, ExecutionStats, QueryESResult, QueryPlan, Timestamp,
+};
 use crate::common::{
   CQueryPath, CTQueryPath, ColName, Context, PaxosGroupId, PaxosGroupIdTrait, QueryId,
   SlaveGroupId, TQueryPath, TablePath, TableView, TransTableName,
@@ -11,6 +13,7 @@ use crate::table_read_es::{check_gossip, does_query_plan_align, request_lock_col
 use crate::tablet::{
   ColumnsLocking, Executing, MSQueryES, Pending, TPESAction, TPESBase, TabletContext,
 };
+use crate::trace::TraceContext;
 use std::collections::BTreeSet;
 use std::rc::Rc;
 
@@ -62,6 +65,8 @@ pub enum MSTableExecutionS {
 #[derive(Debug)]
 pub struct GeneralQueryES {
   pub root_query_path: CQueryPath,
+  /// The trace that child queries are performed for, where the span is this ES.
+  pub trace: TraceContext,
   pub timestamp: Timestamp,
   pub tier: u32,
   pub context: Rc<Context>,
@@ -76,6 +81,7 @@ pub struct GeneralQueryES {
 
   // Dynamically evolving fields.
   pub new_rms: BTreeSet<TQueryPath>,
+  pub stats: ExecutionStats,
 }
 
 #[derive(Debug)]
@@ -92,6 +98,18 @@ pub struct MSTableES<SqlQueryInnerT: SqlQueryInner> {
 // -----------------------------------------------------------------------------------------------
 
 impl<SqlQueryInnerT: SqlQueryInner> MSTableES<SqlQueryInnerT> {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match &self.state {
+      MSTableExecutionS::Start => "Start",
+      MSTableExecutionS::ColumnsLocking(_) => "ColumnsLocking",
+      MSTableExecutionS::GossipDataWaiting => "GossipDataWaiting",
+      MSTableExecutionS::Pending(_) => "Pending",
+      MSTableExecutionS::Executing(_) => "Executing",
+      MSTableExecutionS::Done => "Done",
+    }
+  }
+
   // Check if the `sharding_config` in the GossipData contains the necessary data, moving on if so.
   fn check_gossip_data<IO: CoreIOCtx>(
     &mut self,
@@ -103,7 +121,9 @@ impl<SqlQueryInnerT: SqlQueryInner> MSTableES<SqlQueryInnerT> {
       // We start locking the regions.
       match self.inner.request_region_locks(ctx, io_ctx, &self.general) {
         Ok(protect_qid) => {
-          self.state = MSTableExecutionS::Pending(Pending { query_id: protect_qid });
+          let requested_time_ms = io_ctx.now().time_ms;
+          self.state =
+            MSTableExecutionS::Pending(Pending { query_id: protect_qid, requested_time_ms });
           None
         }
         Err(query_error) => {
@@ -221,6 +241,8 @@ impl<SqlQueryInnerT: SqlQueryInner> TPESBase for MSTableES<SqlQueryInnerT> {
   ) -> Option<TPESAction> {
     let pending = cast!(MSTableExecutionS::Pending, &self.state)?;
     check!(pending.query_id == protect_qid);
+    self.general.stats.read_protection_wait_ms +=
+      io_ctx.now().time_ms.saturating_sub(pending.requested_time_ms);
 
     let gr_query_ess = self.inner.compute_subqueries(ctx, io_ctx, &self.general, ms_query_es);
 
@@ -258,10 +280,12 @@ impl<SqlQueryInnerT: SqlQueryInner> TPESBase for MSTableES<SqlQueryInnerT> {
     ms_query_es: &mut MSQueryES,
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     table_views: Vec<TableView>,
   ) -> Option<TPESAction> {
     // Add the subquery results into the MSTableES.
     self.general.new_rms.extend(subquery_new_rms);
+    self.general.stats.merge(subquery_stats);
     let exec = cast!(MSTableExecutionS::Executing, &mut self.state)?;
     exec.add_subquery_result(subquery_id, table_views);
 
@@ -1,11 +1,13 @@
 use crate::common::{
-  lookup, mk_qid, ColBound, CoreIOCtx, KeyBound, OrigP, PolyColBound, QueryESResult, ReadRegion,
-  SingleBound, WriteRegion,
+  lookup, mk_qid, ColBound, CoreIOCtx, IsolationLevel, KeyBound, OrigP, PolyColBound,
+  QueryESResult, ReadRegion, SingleBound, WriteRegion,
 };
 use crate::common::{
   ColName, ColType, ColVal, ColValN, PrimaryKey, QueryId, TablePath, TableView, TransTableName,
 };
-use crate::expression::{construct_simple_cexpr, does_types_match, evaluate_c_expr, EvalError};
+use crate::expression::{
+  coerce_col_valn, construct_simple_cexpr, does_types_match, evaluate_c_expr, EvalError,
+};
 use crate::gr_query_es::GRQueryES;
 use crate::message as msg;
 use crate::ms_table_es::{GeneralQueryES, MSTableES, SqlQueryInner};
@@ -86,8 +88,9 @@ impl SqlQueryInner for InsertInner {
         ctx.table_schema.val_cols.static_read(col_name, &es.timestamp).unwrap()
       };
 
-      for row in &eval_values {
-        let col_valn = row.get(i).unwrap();
+      for row in &mut eval_values {
+        let col_valn = row.get_mut(i).unwrap();
+        *col_valn = coerce_col_valn(col_type, col_valn.take());
         if !does_types_match(col_type, col_valn.as_ref()) {
           // If types do not match for some row, we propagate up a TypeError.
           return Err(mk_eval_error(EvalError::TypeError));
@@ -155,6 +158,12 @@ impl SqlQueryInner for InsertInner {
               end: SingleBound::Included(v.clone()),
             }));
           }
+          ColVal::BigInt(v) => {
+            key_bound.col_bounds.push(PolyColBound::BigInt(ColBound {
+              start: SingleBound::Included(v.clone()),
+              end: SingleBound::Included(v.clone()),
+            }));
+          }
         }
       }
       row_region.push(key_bound);
@@ -173,8 +182,12 @@ impl SqlQueryInner for InsertInner {
 
     // Verify that we have Write Region Isolation with Subsequent Reads. We abort
     // if we do not, and we amend this MSQuery's VerifyingReadWriteRegions if we do.
-    if !ctx.check_write_region_isolation(&write_region, &es.timestamp) {
+    if !ctx.check_write_region_isolation(&write_region, &es.timestamp, es.query_plan.priority) {
       Err(msg::QueryError::WriteRegionConflictWithSubsequentRead)
+    } else if es.query_plan.isolation_level == IsolationLevel::Snapshot
+      && !ctx.check_write_write_isolation(&write_region, &es.timestamp)
+    {
+      Err(msg::QueryError::WriteWriteConflict)
     } else {
       let protect_qid = mk_qid(io_ctx.rand());
       // Move the MSTableInsertES to the Pending state with the computed update view.
@@ -193,7 +206,7 @@ impl SqlQueryInner for InsertInner {
         query_id: protect_qid.clone(),
         read_region,
       });
-      verifying.m_write_protected.insert(write_region);
+      ctx.add_m_write_protected(&es.timestamp, write_region);
 
       Ok(protect_qid)
     }
@@ -251,6 +264,7 @@ impl SqlQueryInner for InsertInner {
     Some(TPESAction::Success(QueryESResult {
       result: vec![res_table_view],
       new_rms: es.new_rms.iter().cloned().collect(),
+      stats: es.stats.clone(),
     }))
   }
 }
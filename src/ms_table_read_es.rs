@@ -1,5 +1,7 @@
 use crate::col_usage::{col_collecting_cb, QueryIterator};
-use crate::common::{mk_qid, ColName, CoreIOCtx, OrigP, QueryESResult, WriteRegion};
+use crate::common::{
+  mk_qid, ColName, CoreIOCtx, IsolationLevel, OrigP, QueryESResult, WriteRegion,
+};
 use crate::common::{
   ColType, ColVal, ColValN, ContextRow, PrimaryKey, QueryId, TablePath, TableView, TransTableName,
 };
@@ -8,7 +10,7 @@ use crate::gr_query_es::{GRQueryConstructorView, GRQueryES};
 use crate::message as msg;
 use crate::ms_table_es::{GeneralQueryES, MSTableES, SqlQueryInner};
 use crate::server::{mk_eval_error, ContextConstructor};
-use crate::sql_ast::proc;
+use crate::sql_ast::{iast, proc};
 
 use crate::storage::{GenericTable, MSStorageView};
 use crate::table_read_es::{compute_read_region, fully_evaluate_select};
@@ -35,6 +37,11 @@ impl SelectInner {
   pub fn new(sql_query: proc::TableSelect) -> Self {
     SelectInner { sql_query }
   }
+
+  /// Returns the `LockMode` set by a `FOR UPDATE` or `FOR SHARE`, if any.
+  pub fn lock(&self) -> Option<iast::LockMode> {
+    self.sql_query.lock
+  }
 }
 
 impl SqlQueryInner for SelectInner {
@@ -81,10 +88,34 @@ impl SqlQueryInner for SelectInner {
       extra_cols,
     );
 
+    // For a `FOR UPDATE`, we also lock the rows that are read by registering the ReadRegion
+    // as a WriteRegion. This way, subsequent writes to these rows by this MSQuery will not
+    // conflict with subsequent reads, since those reads must now wait for this MSQuery.
+    let opt_write_region = if self.sql_query.lock == Some(iast::LockMode::Update) {
+      let write_region = WriteRegion {
+        row_region: read_region.row_region.clone(),
+        presence: true,
+        val_col_region: read_region.val_col_region.clone(),
+      };
+      if !ctx.check_write_region_isolation(&write_region, &es.timestamp, es.query_plan.priority) {
+        return Err(msg::QueryError::WriteRegionConflictWithSubsequentRead);
+      } else if es.query_plan.isolation_level == IsolationLevel::Snapshot
+        && !ctx.check_write_write_isolation(&write_region, &es.timestamp)
+      {
+        return Err(msg::QueryError::WriteWriteConflict);
+      }
+      Some(write_region)
+    } else {
+      None
+    };
+
     // Move the MSTableReadES to the Pending state with the given ReadRegion.
     let protect_qid = mk_qid(io_ctx.rand());
 
     // Add a ReadRegion to the m_waiting_read_protected.
+    if let Some(write_region) = opt_write_region {
+      ctx.add_m_write_protected(&es.timestamp, write_region);
+    }
     let verifying = ctx.verifying_writes.get_mut(&es.timestamp).unwrap();
     verifying.m_waiting_read_protected.insert(RequestedReadProtected {
       orig_p: OrigP::new(es.query_id.clone()),
@@ -105,6 +136,7 @@ impl SqlQueryInner for SelectInner {
     compute_subqueries(
       GRQueryConstructorView {
         root_query_path: &es.root_query_path,
+        trace: &es.trace,
         timestamp: &es.timestamp,
         sql_query: &self.sql_query,
         query_plan: &es.query_plan,
@@ -160,11 +192,13 @@ impl SqlQueryInner for SelectInner {
 
     // Evaluate
     let eval_res = fully_evaluate_select(
-      context_constructor,
+      &context_constructor,
       &es.context.deref(),
       subquery_results,
       &self.sql_query,
     );
+    let mut stats = es.stats.clone();
+    stats.add_rows_read(&ctx.this_tid, context_constructor.local_table.rows_read());
 
     match eval_res {
       Ok(res_table_views) => {
@@ -172,6 +206,7 @@ impl SqlQueryInner for SelectInner {
         Some(TPESAction::Success(QueryESResult {
           result: res_table_views,
           new_rms: es.new_rms.iter().cloned().collect(),
+          stats,
         }))
       }
       Err(eval_error) => Some(TPESAction::QueryError(mk_eval_error(eval_error))),
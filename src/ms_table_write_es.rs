@@ -1,9 +1,11 @@
 use crate::col_usage::{col_collecting_cb, col_ref_collecting_cb, QueryIterator};
-use crate::common::{mk_qid, ColName, CoreIOCtx, OrigP, QueryESResult, Timestamp, WriteRegion};
+use crate::common::{
+  mk_qid, ColName, CoreIOCtx, IsolationLevel, OrigP, QueryESResult, Timestamp, WriteRegion,
+};
 use crate::common::{
   ColType, ColVal, ColValN, ContextRow, PrimaryKey, QueryId, TablePath, TableView, TransTableName,
 };
-use crate::expression::{does_types_match, is_true, EvalError};
+use crate::expression::{coerce_col_valn, does_types_match, is_true, EvalError};
 use crate::gr_query_es::{GRQueryConstructorView, GRQueryES};
 use crate::message as msg;
 use crate::ms_table_es::{GeneralQueryES, MSTableES, SqlQueryInner};
@@ -73,8 +75,12 @@ impl SqlQueryInner for UpdateInner {
 
     // Verify that we have WriteRegion Isolation with Subsequent Reads. We abort
     // if we don't, and we amend this MSQuery's VerifyingReadWriteRegions if we do.
-    if !ctx.check_write_region_isolation(&write_region, &es.timestamp) {
+    if !ctx.check_write_region_isolation(&write_region, &es.timestamp, es.query_plan.priority) {
       Err(msg::QueryError::WriteRegionConflictWithSubsequentRead)
+    } else if es.query_plan.isolation_level == IsolationLevel::Snapshot
+      && !ctx.check_write_write_isolation(&write_region, &es.timestamp)
+    {
+      Err(msg::QueryError::WriteWriteConflict)
     } else {
       // Move the MSTableWriteES to the Pending state with the given ReadRegion.
       let protect_qid = mk_qid(io_ctx.rand());
@@ -87,7 +93,7 @@ impl SqlQueryInner for UpdateInner {
         query_id: protect_qid.clone(),
         read_region,
       });
-      verifying.m_write_protected.insert(write_region);
+      ctx.add_m_write_protected(&es.timestamp, write_region);
 
       Ok(protect_qid)
     }
@@ -103,6 +109,7 @@ impl SqlQueryInner for UpdateInner {
     compute_subqueries(
       GRQueryConstructorView {
         root_query_path: &es.root_query_path,
+        trace: &es.trace,
         timestamp: &es.timestamp,
         sql_query: &self.sql_query,
         query_plan: &es.query_plan,
@@ -216,9 +223,9 @@ impl SqlQueryInner for UpdateInner {
           for (col_name, col_val) in evaluated_update.assignment {
             // We need to check that the Type of `col_val` conforms to the Table Schema.
             // Note that we only do this if `col_val` is non-NULL.
+            let col_type = ctx.table_schema.val_cols.static_read(&col_name, &es.timestamp).unwrap();
+            let col_val = coerce_col_valn(col_type, col_val);
             if let Some(val) = &col_val {
-              let col_type =
-                ctx.table_schema.val_cols.static_read(&col_name, &es.timestamp).unwrap();
               if !does_types_match(col_type, Some(val)) {
                 return Err(EvalError::TypeError);
               }
@@ -234,6 +241,8 @@ impl SqlQueryInner for UpdateInner {
         Ok(())
       },
     );
+    let mut stats = es.stats.clone();
+    stats.add_rows_read(&ctx.this_tid, context_constructor.local_table.rows_read());
 
     match eval_res {
       Ok(()) => {
@@ -244,6 +253,7 @@ impl SqlQueryInner for UpdateInner {
         Some(TPESAction::Success(QueryESResult {
           result: vec![res_table_view],
           new_rms: es.new_rms.iter().cloned().collect(),
+          stats,
         }))
       }
       Err(eval_error) => Some(TPESAction::QueryError(mk_eval_error(eval_error))),
@@ -3,8 +3,8 @@ use crate::message as msg;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use log::info;
 use std::collections::BTreeMap;
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::sync::mpsc::Sender;
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
@@ -65,22 +65,55 @@ fn recv(mut stream: &TcpStream) -> std::io::Result<Vec<u8>> {
 // (i.e. when doing `send_msg`), and once a connection is dropped, it is never re-created
 // again. Thus, we have the FIFO behavior of TCP, even after the connection drops.
 
-const SERVER_PORT: u32 = 1610;
+/// The port used for an address that does not specify one.
+pub const DEFAULT_PORT: u32 = 1610;
+
+/// Returns `address` as a `host:port` address, appending `DEFAULT_PORT` if `address`
+/// does not already specify a port.
+pub fn mk_address(address: &str) -> String {
+  mk_address_with_port(address, DEFAULT_PORT)
+}
+
+/// Returns `address` as a `host:port` address, appending `default_port` if `address` does
+/// not already specify a port. IPv6 literals are bracketed (e.g. `[::1]:1610`).
+pub fn mk_address_with_port(address: &str, default_port: u32) -> String {
+  if address.parse::<SocketAddr>().is_ok() {
+    address.to_string()
+  } else if let Ok(ip) = address.parse::<IpAddr>() {
+    SocketAddr::new(ip, default_port as u16).to_string()
+  } else if address.starts_with('[') && address.ends_with(']') {
+    format!("{}:{}", address, default_port)
+  } else if address.contains(':') {
+    address.to_string()
+  } else {
+    format!("{}:{}", address, default_port)
+  }
+}
+
+/// Returns true iff the host of the `address` that a peer advertises in its `InitMessage`
+/// resolves to the IP that the connection actually came from. Only the port may differ,
+/// which prevents a peer from impersonating a node on another host.
+fn is_advertised_address_valid(address: &str, peer_addr: &SocketAddr) -> bool {
+  match address.to_socket_addrs() {
+    Ok(mut socket_addrs) => socket_addrs
+      .any(|socket_addr| socket_addr.ip().to_canonical() == peer_addr.ip().to_canonical()),
+    Err(_) => false,
+  }
+}
 
 pub trait GenericInputTrait {
   fn from_network(eid: EndpointId, message: msg::NetworkMessage) -> Self;
 }
 
-/// Starts the Acceptor Thread, which accepts connections at `SERVER_PORT` and creates
+/// Starts the Acceptor Thread, which accepts connections at `this_address` and creates
 /// a `FromNetwork` Thread for each new connection.
 pub fn start_acceptor_thread<GenericInputT: 'static + GenericInputTrait + Send>(
   to_server_sender: &Sender<GenericInputT>,
-  this_ip: String,
+  this_address: String,
 ) {
   let to_server_sender = to_server_sender.clone();
-  let this_ip = this_ip.clone();
   thread::spawn(move || {
-    let listener = TcpListener::bind(format!("{}:{}", &this_ip, SERVER_PORT)).unwrap();
+    let listener = TcpListener::bind(&this_address).unwrap();
     for stream in listener.incoming() {
       let stream = stream.unwrap();
       handle_conn(&to_server_sender, stream);
@@ -93,7 +126,8 @@ fn handle_conn<GenericInputT: 'static + GenericInputTrait + Send>(
   to_server_sender: &Sender<GenericInputT>,
   stream: TcpStream,
 ) {
-  let ip = stream.peer_addr().unwrap().ip().to_string();
+  let peer_socket_addr = stream.peer_addr().unwrap();
+  let peer_addr = peer_socket_addr.to_string();
 
   // Configure the stream to block indefinitely for reads and writes.
   stream.set_read_timeout(None).unwrap();
@@ -101,32 +135,42 @@ fn handle_conn<GenericInputT: 'static + GenericInputTrait + Send>(
 
   // Setup FromNetwork Thread
   {
-    let ip = ip.clone();
     let to_server_sender = to_server_sender.clone();
     let stream = stream.try_clone().unwrap();
     thread::Builder::new()
-      .name(format!("FromNetwork {}", ip))
+      .name(format!("FromNetwork {}", peer_addr))
       .spawn(move || {
         let error = match recv(&stream) {
           Ok(data) => {
-            // Read the Initialization message and construct the EndpointId accordingly.
+            // Read the Initialization message and construct the EndpointId accordingly. We
+            // use the address the sender advertises (rather than `peer_addr`, whose port is
+            // ephemeral) so that we can connect back to it.
             let init_msg: msg::InitMessage = rmp_serde::from_read_ref(&data).unwrap();
-            let eid = EndpointId::new(ip.clone(), init_msg.is_internal);
+            if !is_advertised_address_valid(&init_msg.address, &peer_socket_addr) {
+              // Drop the connection, since the peer is claiming to be on another host.
+              let _ = stream.shutdown(Shutdown::Both);
+              std::io::Error::new(
+                ErrorKind::PermissionDenied,
+                format!("advertised address {} does not match the peer", init_msg.address),
+              )
+            } else {
+              let eid = EndpointId::new(init_msg.address, init_msg.is_internal);
 
-            // Read data until the connection closes.
-            loop {
-              match recv(&stream) {
-                Ok(data) => {
-                  let network_msg: msg::NetworkMessage = rmp_serde::from_read_ref(&data).unwrap();
-                  to_server_sender
-                    .send(GenericInputT::from_network(eid.clone(), network_msg))
-                    .unwrap();
-                }
-                Err(error) => {
-                  // This means that the connection effectively closed.
-                  break error;
-                }
-              };
+              // Read data until the connection closes.
+              loop {
+                match recv(&stream) {
+                  Ok(data) => {
+                    let network_msg: msg::NetworkMessage = rmp_serde::from_read_ref(&data).unwrap();
+                    to_server_sender
+                      .send(GenericInputT::from_network(eid.clone(), network_msg))
+                      .unwrap();
+                  }
+                  Err(error) => {
+                    // This means that the connection effectively closed.
+                    break error;
+                  }
+                };
+              }
             }
           }
           Err(error) => {
@@ -138,7 +182,7 @@ fn handle_conn<GenericInputT: 'static + GenericInputTrait + Send>(
         info!(
           "Thread 'FromNetwork {}' shutting down. \
          Connection closed with error: {}",
-          ip, error
+          peer_addr, error
         );
       })
       .unwrap();
@@ -198,13 +242,13 @@ impl SendAction {
 /// as a key and sinks any messages sent to it.
 ///
 /// For the case where `eid.is_internal` is false, we clean up `out_conn_map` if we detect
-/// the connection to drop. This allows a user from the same IP address to reconnect to this
+/// the connection to drop. This allows a user from the same address to reconnect to this
 /// node (most of the time, if this node actually detects that the previous connection had closed).
 pub fn send_msg(
   locked_out_conn_map: &Arc<Mutex<BTreeMap<EndpointId, Sender<SendAction>>>>,
   eid: &EndpointId,
   action: SendAction,
-  this_eid_internal: &InternalMode,
+  this_eid: &EndpointId,
 ) {
   let mut out_conn_map = locked_out_conn_map.lock().unwrap();
 
@@ -215,17 +259,32 @@ pub fn send_msg(
     out_conn_map.insert(eid.clone(), sender);
     let locked_out_conn_map = locked_out_conn_map.clone();
     let eid = eid.clone();
-    let this_eid_internal = this_eid_internal.clone();
+    let this_eid = this_eid.clone();
     thread::Builder::new()
-      .name(format!("ToNetwork {}", eid.ip))
+      .name(format!("ToNetwork {}", eid.address))
       .spawn(move || {
-        let stream = TcpStream::connect(format!("{}:{}", eid.ip, SERVER_PORT)).unwrap();
+        let stream = match TcpStream::connect(&eid.address) {
+          Ok(stream) => stream,
+          Err(error) => {
+            // If the other side is unreachable, we treat it like a connection that closed
+            // right away (so all messages sent to it are dropped).
+            if let InternalMode::External { .. } = &eid.mode {
+              let mut out_conn_map = locked_out_conn_map.lock().unwrap();
+              out_conn_map.remove(&eid).unwrap();
+            }
+            info!("Thread 'ToNetwork {:?}' could not connect: {}", eid, error);
+            return;
+          }
+        };
         // Configure the stream to block indefinitely for reads and writes.
         stream.set_read_timeout(None).unwrap();
         stream.set_write_timeout(None).unwrap();
 
         // Send Initialization message
-        let init_msg = msg::InitMessage { is_internal: this_eid_internal.clone() };
+        let init_msg = msg::InitMessage {
+          address: this_eid.address.clone(),
+          is_internal: this_eid.mode.clone(),
+        };
         let data_out = rmp_serde::to_vec(&init_msg).unwrap();
         let _ = send_bytes(&data_out, &stream);
 
@@ -1,10 +1,12 @@
 use crate::common::{
-  mk_t, FreeNodeIOCtx, GossipDataView, MasterIOCtx, NodeIOCtx, SlaveIOCtx, VersionedValue,
+  mk_t, BasicIOCtx, FreeNodeIOCtx, GossipDataView, MasterIOCtx, NodeIOCtx, SlaveIOCtx,
+  VersionedValue,
 };
-use crate::common::{CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait};
+use crate::common::{ClockId, ClockIdAllocator, CoordGroupId, EndpointId, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait};
 use crate::coord::{CoordConfig, CoordContext};
 use crate::master::{FullMasterInput, MasterConfig, MasterContext, MasterState, MasterTimerInput};
 use crate::message as msg;
+use crate::metrics::{message_type, metrics, M_MESSAGES_RECEIVED};
 use crate::net::GenericInputTrait;
 use crate::paxos::PaxosConfig;
 use crate::slave::{
@@ -17,8 +19,6 @@ use std::sync::Arc;
 #[path = "test/node_test.rs"]
 pub mod node_test;
 
-const SERVER_PORT: u32 = 1610;
-
 // -----------------------------------------------------------------------------------------------
 //  GenericInput
 // -----------------------------------------------------------------------------------------------
@@ -60,6 +60,43 @@ pub fn amend_buffer<MessageT>(
   }
 }
 
+// -----------------------------------------------------------------------------------------------
+//  External Rerouting
+// -----------------------------------------------------------------------------------------------
+
+/// Handles an External request that arrived at a node that cannot serve it, i.e. one that
+/// is not yet in a PaxosGroup, or is in a PaxosGroup of the wrong kind. `ExternalMetadataRequest`s
+/// are forwarded to the Master leader (if known), and the rest are told to find the current
+/// leader. If `message` is not an External request, it is returned back.
+fn reroute_external<IO: BasicIOCtx>(
+  io_ctx: &mut IO,
+  opt_master_eid: Option<&EndpointId>,
+  message: msg::NetworkMessage,
+) -> Option<msg::NetworkMessage> {
+  match message {
+    msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(request)) => {
+      if let msg::MasterExternalReq::ExternalMetadataRequest(_) = &request {
+        if let Some(master_eid) = opt_master_eid {
+          io_ctx.send(
+            master_eid,
+            msg::NetworkMessage::Master(msg::MasterMessage::MasterExternalReq(request)),
+          );
+        }
+      } else if let Some((eid, response)) = request.not_leader_response() {
+        io_ctx.send(&eid, response);
+      }
+      None
+    }
+    msg::NetworkMessage::Slave(msg::SlaveMessage::SlaveExternalReq(request)) => {
+      if let Some((eid, response)) = request.not_leader_response() {
+        io_ctx.send(&eid, response);
+      }
+      None
+    }
+    message => Some(message),
+  }
+}
+
 // -----------------------------------------------------------------------------------------------
 //  MessageTrait
 // -----------------------------------------------------------------------------------------------
@@ -281,7 +318,7 @@ pub struct NodeConfig {
 
 /// Build the `NodeConfig` we should use for production.
 pub fn get_prod_configs() -> NodeConfig {
-  let timestamp_suffix_divisor = 5;
+  let max_clock_offset_ms = 500;
 
   let paxos_config = PaxosConfig {
     heartbeat_threshold: 5,
@@ -291,6 +328,8 @@ pub fn get_prod_configs() -> NodeConfig {
     proposal_increment: 1000,
     remote_next_index_thresh: 100,
     max_failable: 1,
+    max_in_flight: 4,
+    max_batch_size: 8,
   };
 
   let remote_leader_changed_period_ms = 1000;
@@ -298,7 +337,7 @@ pub fn get_prod_configs() -> NodeConfig {
   let check_unconfirmed_eids_period_ms = 5000;
   let free_node_heartbeat_timer_ms = 1000;
   let master_config = MasterConfig {
-    timestamp_suffix_divisor,
+    max_clock_offset_ms,
     slave_group_size: 5,
     remote_leader_changed_period_ms,
     failure_detector_period_ms,
@@ -307,15 +346,27 @@ pub fn get_prod_configs() -> NodeConfig {
     num_coords: 3,
     free_node_heartbeat_timer_ms,
   };
+  let closed_timestamp_lag_ms = 3000;
+  let follower_read_wait_ms = 4000;
   let slave_config = SlaveConfig {
-    timestamp_suffix_divisor,
+    max_clock_offset_ms,
+    closed_timestamp_lag_ms,
+    follower_read_wait_ms,
     remote_leader_changed_period_ms,
     failure_detector_period_ms,
     check_unconfirmed_eids_period_ms,
   };
 
-  let coord_config = CoordConfig { timestamp_suffix_divisor };
-  let tablet_config = TabletConfig { timestamp_suffix_divisor };
+  let coord_config = CoordConfig {
+    max_clock_offset_ms,
+    slow_query_threshold_ms: Some(1000),
+    max_retries: 20,
+    retry_backoff_ms: 10,
+    max_retry_backoff_ms: 1000,
+    follower_read_timeout_ms: 5000,
+  };
+  let tablet_config =
+    TabletConfig { max_clock_offset_ms, closed_timestamp_lag_ms, follower_read_wait_ms };
 
   // Combine the above
   NodeConfig {
@@ -335,7 +386,8 @@ pub fn get_prod_configs() -> NodeConfig {
 #[derive(Debug)]
 enum State {
   DNEState(BTreeMap<EndpointId, Vec<msg::NetworkMessage>>),
-  FreeNodeState(LeadershipId, BTreeMap<EndpointId, Vec<msg::NetworkMessage>>),
+  /// The `u32` is the node index that the Master assigned when registering this node.
+  FreeNodeState(LeadershipId, u32, BTreeMap<EndpointId, Vec<msg::NetworkMessage>>),
   NominalSlaveState(SlaveState, NominalState<msg::SlaveMessage>),
   NominalMasterState(MasterState, NominalState<msg::MasterMessage>),
   PostExistence,
@@ -369,6 +421,10 @@ impl NodeState {
     io_ctx: &mut IOCtx,
     generic_input: GenericInput,
   ) {
+    if let GenericInput::Message(_, message) = &generic_input {
+      metrics().inc(M_MESSAGES_RECEIVED, vec![("type", message_type(message).to_string())]);
+    }
+
     match &mut self.state {
       State::DNEState(buffered_messages) => match generic_input {
         GenericInput::Message(eid, message) => {
@@ -376,14 +432,20 @@ impl NodeState {
           if let msg::NetworkMessage::FreeNode(free_node_msg) = message {
             match free_node_msg {
               msg::FreeNodeMessage::FreeNodeRegistered(registered) => {
-                self.state =
-                  State::FreeNodeState(registered.cur_lid, std::mem::take(buffered_messages));
+                self.state = State::FreeNodeState(
+                  registered.cur_lid,
+                  registered.node_index,
+                  std::mem::take(buffered_messages),
+                );
               }
               msg::FreeNodeMessage::ShutdownNode => {
                 self.state = State::PostExistence;
               }
               msg::FreeNodeMessage::StartMaster(start) => {
-                // Create the MasterState
+                // Create the MasterState. The initial Master nodes take the first node indices.
+                let this_eid = &self.this_eid;
+                let node_index =
+                  start.master_eids.iter().position(|eid| eid == this_eid).unwrap() as u32;
                 let leader = start.master_eids.get(0).unwrap();
                 let master_lid = LeadershipId { gen: Gen(0), eid: leader.clone() };
                 let mut leader_map = BTreeMap::<PaxosGroupId, LeadershipId>::new();
@@ -392,6 +454,7 @@ impl NodeState {
                   start.master_eids,
                   leader_map.clone(),
                   self.this_eid.clone(),
+                  ClockId { node_index, sub_node_index: 0 },
                   self.node_config.master_config.clone(),
                   self.node_config.paxos_config.clone(),
                 ));
@@ -417,7 +480,7 @@ impl NodeState {
               }
               _ => {}
             }
-          } else {
+          } else if let Some(message) = reroute_external(io_ctx, None, message) {
             // Otherwise, buffer the message
             amend_buffer(buffered_messages, &eid, message);
           }
@@ -429,7 +492,7 @@ impl NodeState {
         }
         _ => {}
       },
-      State::FreeNodeState(lid, buffered_messages) => match generic_input {
+      State::FreeNodeState(lid, node_index, buffered_messages) => match generic_input {
         GenericInput::Message(eid, message) => {
           // Handle FreeNode messages
           if let msg::NetworkMessage::FreeNode(free_node_msg) = message {
@@ -445,6 +508,7 @@ impl NodeState {
                 let gossip = Arc::new(create.gossip);
 
                 // Create the Coords
+                let mut clock_ids = ClockIdAllocator::new(*node_index);
                 let mut coord_positions: Vec<CoordGroupId> = Vec::new();
                 for cid in create.coord_ids {
                   let coord_context = CoordContext::new(
@@ -454,6 +518,7 @@ impl NodeState {
                     create.leader_map.clone(),
                     create.paxos_nodes.clone(),
                     self.this_eid.clone(),
+                    clock_ids.next(),
                     self.node_config.coord_config.clone(),
                   );
                   io_ctx.create_coord_full(coord_context);
@@ -468,6 +533,7 @@ impl NodeState {
                   create.leader_map,
                   create.paxos_nodes,
                   self.this_eid.clone(),
+                  clock_ids,
                   self.node_config.slave_config.clone(),
                   self.node_config.paxos_config.clone(),
                 );
@@ -506,6 +572,7 @@ impl NodeState {
                 let gossip = Arc::new(snapshot.gossip);
 
                 // Create the Coords
+                let mut clock_ids = ClockIdAllocator::new(*node_index);
                 for cid in snapshot.coord_positions.clone() {
                   let coord_context = CoordContext::new(
                     snapshot.this_sid.clone(),
@@ -514,6 +581,7 @@ impl NodeState {
                     snapshot.leader_map.clone(),
                     snapshot.paxos_driver_start.paxos_nodes.clone(),
                     self.this_eid.clone(),
+                    clock_ids.next(),
                     self.node_config.coord_config.clone(),
                   );
                   io_ctx.create_coord_full(coord_context);
@@ -526,6 +594,7 @@ impl NodeState {
                     gossip.clone(),
                     tablet_snapshot,
                     self.this_eid.clone(),
+                    clock_ids.next(),
                     self.node_config.tablet_config.clone(),
                   );
                 }
@@ -541,6 +610,7 @@ impl NodeState {
                   snapshot.create_table_ess,
                   snapshot.shard_split_ess,
                   self.this_eid.clone(),
+                  clock_ids,
                   self.node_config.slave_config.clone(),
                   self.node_config.paxos_config.clone(),
                 );
@@ -571,6 +641,7 @@ impl NodeState {
                   self.node_config.master_config.clone(),
                   self.node_config.paxos_config.clone(),
                   self.this_eid.clone(),
+                  ClockId { node_index: *node_index, sub_node_index: 0 },
                 );
 
                 // Bootstrap the Master
@@ -597,7 +668,7 @@ impl NodeState {
               }
               _ => {}
             }
-          } else {
+          } else if let Some(message) = reroute_external(io_ctx, Some(&lid.eid), message) {
             // Otherwise, buffer the message
             amend_buffer(buffered_messages, &eid, message);
           }
@@ -658,6 +729,10 @@ impl NodeState {
               // Forward the message.
               let mut inner_state = SlaveInnerState { io_ctx, state: slave_state };
               nominal_state.handle_msg(&mut inner_state, &eid, slave_msg);
+            } else {
+              let leader_map = slave_state.ctx.leader_map.value();
+              let master_eid = &leader_map.get(&PaxosGroupId::Master).unwrap().eid;
+              reroute_external(io_ctx, Some(master_eid), message);
             }
           }
           GenericInput::TimerInput(GenericTimerInput::SlaveTimerInput(timer_input)) => {
@@ -702,6 +777,8 @@ impl NodeState {
               // Forward the message.
               let mut inner_state = MasterInnerState { io_ctx, state: master_state };
               nominal_state.handle_msg(&mut inner_state, &eid, master_msg);
+            } else {
+              reroute_external(io_ctx, None, message);
             }
           }
           GenericInput::TimerInput(GenericTimerInput::MasterTimerInput(timer_input)) => {
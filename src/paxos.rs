@@ -1,11 +1,12 @@
 use crate::common::{mk_t, mk_uuid, remove_item, Timestamp, UUID};
 use crate::common::{EndpointId, Gen, LeadershipId};
 use crate::message as msg;
-use crate::message::{LeaderChanged, PLEntry, PLIndex, Rnd};
+use crate::message::{LeaderChanged, PLBatch, PLEntry, PLIndex, Rnd};
+use crate::metrics::{metrics, M_PAXOS_LOG_LENGTH, M_PAXOS_PROPOSALS};
 use rand::RngCore;
 use sqlparser::dialect::keywords::Keyword::NEXT;
 use std::cmp::{max, min};
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::Debug;
 
 // -----------------------------------------------------------------------------------------------
@@ -80,6 +81,15 @@ pub struct PaxosConfig {
   /// of reasoning about liveness (i.e. if more than this many fail, then we do not
   /// guarantee liveness).
   pub max_failable: u32,
+
+  /// The maximum number of `PLIndex`s that the Leader proposes at concurrently. If this is 1,
+  /// then a proposal is only made once the previous one has been learned.
+  pub max_in_flight: u32,
+  /// The maximum number of `UserPLEntry`s that the Leader inserts with a single proposal.
+  /// Batching is incidental: entries that are inserted while `max_in_flight` proposals are
+  /// outstanding wait and are then proposed together once a slot frees up. Entries are never
+  /// held back to fill a batch.
+  pub max_batch_size: u32,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -92,6 +102,7 @@ pub trait PaxosContextBase<BundleT> {
   /// Getters
   fn rand(&mut self) -> &mut Self::RngCoreT;
   fn this_eid(&self) -> &EndpointId;
+  fn now(&mut self) -> Timestamp;
 
   /// Methods
   fn send(&mut self, eid: &EndpointId, message: msg::PaxosDriverMessage<BundleT>);
@@ -167,13 +178,40 @@ pub struct PaxosDriver<BundleT> {
   /// The latest Leadership by `next_index`.
   leader: LeadershipId,
   leader_heartbeat: u32,
+  /// If this node has stopped hearing from the `leader`, these are the PaxosNodes that granted
+  /// its latest `PreVote`. Once this is a majority, this node tries to become the Leader.
+  pre_vote_grants: BTreeSet<EndpointId>,
+  /// The `round` of the latest `PreVote` this node sent. Only `PreVoteGrant`s for this round
+  /// are added to `pre_vote_grants`, so that grants from earlier rounds (which might no longer
+  /// hold) are never counted.
+  pre_vote_round: u64,
+
+  /// If this node is the Leader, this maps each PaxosNode to the `sent_time` of the latest
+  /// `IsLeader` that it acknowledged for the current Leadership.
+  lease_acks: BTreeMap<EndpointId, Timestamp>,
+  /// If this node is the Leader, this is the time (by this node's clock) until which no other
+  /// node can become the Leader. See `compute_lease_expiry`.
+  lease_expiry: Option<Timestamp>,
 
   /// Set of new PaxosNodes that we have not confirmed have started up yet.
   unconfirmed_eids: BTreeMap<EndpointId, bool>,
 
-  /// Insert state. Once this is set to `Some(_)`, the internal value is never modified. This is
-  /// only unset when `next_index` is incremented due to insertion.
-  next_insert: Option<(UUID, UserPLEntry<BundleT>)>,
+  /// Insert state. This maps the `PLIndex`s that this node (as the Leader) has proposed a
+  /// `PLBatch` at to the batch, which is only ever proposed at that `PLIndex`. An element is
+  /// removed once `next_index` passes its `PLIndex`.
+  in_flight: BTreeMap<PLIndex, (UUID, PLBatch<BundleT>)>,
+  /// The `UserPLEntry`s that are waiting for a proposal to be made for them.
+  pending_inserts: VecDeque<UserPLEntry<BundleT>>,
+  /// The prior Leadership might have had `PLBatch`s accepted at the `PLIndex`s before this
+  /// when the current Leadership started. These are learned but not delivered (see
+  /// `deliver_entry`), and they displace whatever this node proposed at the same `PLIndex`.
+  /// Until `next_index` reaches this, we only have one `PLBatch` in flight, which lets us
+  /// propose it again at the next `PLIndex` if it gets displaced.
+  prior_window_end: PLIndex,
+
+  /// If this node is the Leader and is handing Leadership over to another PaxosNode, this
+  /// holds that node and the number of `LeaderHeartbeat`s since the transfer started.
+  transfer: Option<(EndpointId, u32)>,
 }
 
 impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
@@ -197,8 +235,15 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
       buffered_messages: Default::default(),
       leader: LeadershipId { gen: Gen(0), eid: leader_eid },
       leader_heartbeat: 0,
+      pre_vote_grants: Default::default(),
+      pre_vote_round: 0,
+      lease_acks: Default::default(),
+      lease_expiry: None,
       unconfirmed_eids: Default::default(),
-      next_insert: None,
+      in_flight: Default::default(),
+      pending_inserts: Default::default(),
+      prior_window_end: 0,
+      transfer: None,
     }
   }
 
@@ -244,8 +289,15 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
       buffered_messages: Default::default(),
       leader: start.leader,
       leader_heartbeat: 0,
+      pre_vote_grants: Default::default(),
+      pre_vote_round: 0,
+      lease_acks: Default::default(),
+      lease_expiry: None,
       unconfirmed_eids,
-      next_insert: None,
+      in_flight: Default::default(),
+      pending_inserts: Default::default(),
+      prior_window_end: 0,
+      transfer: None,
     }
   }
 
@@ -272,6 +324,17 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
     &self.leader.eid == ctx.this_eid()
   }
 
+  /// Returns the time until which this node holds the leader lease, if it is the Leader and
+  /// has a lease. Until then, no other node can become the Leader.
+  pub fn lease_expiry(&self) -> Option<&Timestamp> {
+    self.lease_expiry.as_ref()
+  }
+
+  /// Returns the PaxosNode that this node is handing Leadership over to, if any.
+  pub fn transfer_target(&self) -> Option<&EndpointId> {
+    self.transfer.as_ref().map(|(target_eid, _)| target_eid)
+  }
+
   // -----------------------------------------------------------------------------------------------
   //  Reconfiguration Utils
   // -----------------------------------------------------------------------------------------------
@@ -398,6 +461,11 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
     // Poll and process all `buffered_messages` which an index that is low enough.
     self.handle_buffered_messages(ctx, &mut learned_entries);
 
+    // Learning entries might have freed up room for more proposals.
+    if !learned_entries.is_empty() {
+      self.propose_pending(ctx);
+    }
+
     // Note that the caller is responsible for processing Reconfig PLms where
     // this node had been kicked out.
     learned_entries
@@ -427,13 +495,22 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
           // We drop this message if it is below `min_complete_index`
           return Vec::new();
         } else if index > self.next_index {
-          // We buffer this message if the `index` is too high.
-          if let Some(messages) = self.buffered_messages.get_mut(&index) {
-            messages.push_back(multi);
-          } else {
-            self.buffered_messages.insert(index, VecDeque::from([multi]));
+          // Within the window that the Leader might propose at concurrently, we handle
+          // `Prepare`s, `Promise`s, and `Accept`s right away, since accepting a value does not
+          // depend on the prior `PLIndex`s. (Recall that the Leader does not propose past a
+          // `ReconfigBundle` that is in flight, so the `paxos_nodes` in these are correct.)
+          // However, we buffer `Learn`s and everything beyond the window, since learning
+          // depends on the PaxosNodes as of `index`.
+          let window_end = self.next_index + self.paxos_config.max_in_flight as u128;
+          let is_learn = matches!(&multi.paxos_message, msg::PaxosMessage::Learn(_));
+          if index > window_end || is_learn {
+            if let Some(messages) = self.buffered_messages.get_mut(&index) {
+              messages.push_back(multi);
+            } else {
+              self.buffered_messages.insert(index, VecDeque::from([multi]));
+            }
+            return Vec::new();
           }
-          return Vec::new();
         }
 
         // Create a PaxosInstance if it does not exist already
@@ -473,6 +550,11 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
             }
           }
           msg::PaxosMessage::Promise(promise) => {
+            if !self.paxos_nodes.contains(&multi.sender_eid) {
+              // Recall that the Leader can send `Prepare`s for a `PLIndex` before it learns a
+              // prior `ReconfigBundle`, so a node that was removed could respond to it.
+              return Vec::new();
+            }
             let state = paxos_instance.proposer_state.proposals.get_mut(&promise.rnd).unwrap();
             state.promises.push(promise.vrnd_vval);
             if state.promises.len() == majority(&self.paxos_nodes) {
@@ -521,9 +603,12 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
               state.rnd = accept.crnd.clone();
               state.vrnd_vval = Some((accept.crnd.clone(), accept.cval.clone()));
 
-              // Broadcast
+              // Broadcast. If this is beyond `next_index`, we might not know the PaxosNodes
+              // as of `index` yet, so we use those of the sender.
               let this_eid = ctx.this_eid().clone();
-              for eid in &self.paxos_nodes {
+              let learn_eids =
+                if index > self.next_index { &multi.paxos_nodes } else { &self.paxos_nodes };
+              for eid in learn_eids {
                 ctx.send(
                   &eid,
                   msg::PaxosDriverMessage::MultiPaxosMessage(msg::MultiPaxosMessage {
@@ -586,6 +671,75 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
         if is_leader.lid == self.leader {
           // Reset heartbeat
           self.leader_heartbeat = 0;
+
+          // Acknowledge, which lets the Leader extend its lease. Recall that after resetting
+          // the heartbeat, we drop `Prepare`s from other nodes for `heartbeat_threshold`
+          // heartbeats, which is what makes the lease safe.
+          let this_eid = ctx.this_eid().clone();
+          let leader_eid = is_leader.lid.eid.clone();
+          ctx.send(
+            &leader_eid,
+            msg::PaxosDriverMessage::IsLeaderAck(msg::IsLeaderAck {
+              sender_eid: this_eid,
+              lid: is_leader.lid,
+              sent_time: is_leader.sent_time,
+            }),
+          );
+        }
+      }
+      msg::PaxosDriverMessage::IsLeaderAck(ack) => {
+        // We do not extend the lease while handing Leadership over, since the next Leader
+        // can only take over once it has expired.
+        if ack.lid == self.leader && self.is_leader(ctx) && self.transfer.is_none() {
+          if let Some(sent_time) = self.lease_acks.get_mut(&ack.sender_eid) {
+            *sent_time = max(sent_time.clone(), ack.sent_time);
+          } else {
+            self.lease_acks.insert(ack.sender_eid, ack.sent_time);
+          }
+          self.lease_expiry = self.compute_lease_expiry();
+        }
+      }
+      msg::PaxosDriverMessage::PreVote(pre_vote) => {
+        // We only help the sender become the Leader if we have also stopped hearing from the
+        // Leader (or if we are the Leader, if we no longer hold the lease), and if its PaxosLog
+        // is not behind ours. This way, a node that was partitioned away cannot disrupt a
+        // healthy Leader.
+        let leader_alive = if self.is_leader(ctx) {
+          let now = ctx.now();
+          self.lease_expiry.as_ref().map_or(false, |lease_expiry| lease_expiry > &now)
+        } else {
+          self.leader_heartbeat <= self.paxos_config.heartbeat_threshold
+        };
+        if !leader_alive && pre_vote.next_index >= self.next_index {
+          let this_eid = ctx.this_eid().clone();
+          ctx.send(
+            &pre_vote.sender_eid,
+            msg::PaxosDriverMessage::PreVoteGrant(msg::PreVoteGrant {
+              sender_eid: this_eid,
+              lid: pre_vote.lid,
+              round: pre_vote.round,
+            }),
+          );
+        }
+      }
+      msg::PaxosDriverMessage::PreVoteGrant(grant) => {
+        if grant.lid == self.leader
+          && grant.round == self.pre_vote_round
+          && self.leader_heartbeat > self.paxos_config.heartbeat_threshold
+          && self.paxos_nodes.contains(&grant.sender_eid)
+        {
+          self.pre_vote_grants.insert(grant.sender_eid);
+          if self.pre_vote_grants.len() == majority(&self.paxos_nodes) {
+            // A majority has stopped hearing from the Leader, so this node tries
+            // proposing itself as the Leader.
+            let gen = self.leader.gen.next();
+            let eid = ctx.this_eid().clone();
+            self.propose_at(
+              ctx,
+              self.next_index,
+              PLEntry::LeaderChanged(LeaderChanged { lid: LeadershipId { gen, eid } }),
+            );
+          }
         }
       }
       msg::PaxosDriverMessage::InformLearned(inform_learned) => {
@@ -684,14 +838,24 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
       msg::PaxosDriverMessage::NextIndexResponse(response) => {
         let orig_min_index = self.min_complete_index();
 
-        // Update remote index
-        let cur_remote_index = self.remote_next_indices.get_mut(&response.responder_eid).unwrap();
-        *cur_remote_index = max(*cur_remote_index, response.next_index);
+        // Update remote index. Recall that the responder might have been removed by a
+        // `ReconfigBundle` learned after the `NextIndexRequest` was sent, which we ignore.
+        let responder_eid = &response.responder_eid;
+        if let Some(cur_remote_index) = self.remote_next_indices.get_mut(responder_eid) {
+          *cur_remote_index = max(*cur_remote_index, response.next_index);
+        } else {
+          return Vec::new();
+        }
 
         // Purge all PaxosInstances prior to the new min_index
         for index in orig_min_index..self.min_complete_index() {
           self.paxos_instances.remove(&index);
         }
+
+        // If the transfer target has caught up, the transfer might be able to finish.
+        if self.transfer_target() == Some(&response.responder_eid) {
+          self.advance_transfer(ctx);
+        }
       }
       msg::PaxosDriverMessage::StartNewNode(start) => {
         // Here, we simply respond with a `NewNodeStarted`. Recall that redundant
@@ -743,74 +907,270 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
   fn deliver_learned_entries(&mut self) -> Vec<PLEntry<BundleT>> {
     // Collect all newly learned entries
     let mut new_entries = Vec::<PLEntry<BundleT>>::new();
+    let orig_next_index = self.next_index;
+    let mut displaced: Option<(UUID, PLBatch<BundleT>)> = None;
     loop {
       if let Some(instance_entry) = self.paxos_instances.get(&self.next_index) {
         if let Some((_, learned_val)) = &instance_entry.learned_rnd_val {
           // There is a learned_val for this index.
-          self.next_index += 1;
-          match learned_val {
-            PLEntry::Bundle(_) => {}
-            PLEntry::ReconfigBundle(reconfig) => {
-              // Process new_eids
-              for eid in &reconfig.new_eids {
-                self.remote_next_indices.insert(eid.clone(), self.next_index.clone());
-                self.unconfirmed_eids.insert(eid.clone(), false);
-                self.paxos_nodes.push(eid.clone());
-              }
-
-              // Process rem_eids
-              for eid in &reconfig.rem_eids {
-                self.remote_next_indices.remove(eid);
-                self.unconfirmed_eids.remove(eid);
-                remove_item(&mut self.paxos_nodes, eid);
+          let learned_val = learned_val.clone();
+          if let PLEntry::Batch(learned_batch) = &learned_val {
+            if let Some((uuid, batch)) = self.in_flight.get(&self.next_index) {
+              if learned_batch.lid != self.leader && batch.lid == self.leader {
+                displaced = Some((uuid.clone(), batch.clone()));
               }
             }
-            PLEntry::LeaderChanged(leader_changed) => {
-              self.leader = leader_changed.lid.clone();
-              self.leader_heartbeat = 0;
-            }
           }
-
-          new_entries.push(learned_val.clone());
+          self.next_index += 1;
+          self.deliver_entry(learned_val, &mut new_entries);
           continue;
         }
       }
       break;
     }
 
-    if !new_entries.is_empty() {
-      // If a PLEntry was inserted, we clear `next_insert` to avoid accidentally
-      // inserting it at an index it was not meant for.
-      self.next_insert = None;
+    if self.next_index > orig_next_index {
+      // We remove the batches at the `PLIndex`s that were just learned (regardless of whether
+      // they were what was learned) to avoid accidentally inserting them at a later index.
+      self.in_flight = self.in_flight.split_off(&self.next_index);
+      metrics().set(M_PAXOS_LOG_LENGTH, vec![], self.next_index as i64);
+    }
+
+    // If our batch was displaced by one from the prior Leadership (which had no effect), and
+    // there is nothing after it in flight, we move it to `next_index`. Its `RetryInsert`
+    // will propose it there.
+    if let Some((uuid, batch)) = displaced {
+      if batch.lid == self.leader && self.in_flight.is_empty() {
+        self.in_flight.insert(self.next_index, (uuid, batch));
+      }
     }
 
     return new_entries;
   }
 
+  /// Applies the effects of a learned `entry` on this `PaxosDriver` and adds it to
+  /// `new_entries`. A `PLBatch` is flattened into its constituent entries.
+  fn deliver_entry(&mut self, entry: PLEntry<BundleT>, new_entries: &mut Vec<PLEntry<BundleT>>) {
+    if let PLEntry::Batch(batch) = entry {
+      // We only deliver the batch if it was proposed by the current Leadership, since
+      // otherwise, the entries might have been created from a stale view of the PaxosLog.
+      if batch.lid == self.leader {
+        for entry in batch.entries {
+          self.deliver_entry(entry, new_entries);
+        }
+      }
+      return;
+    }
+
+    match &entry {
+      PLEntry::Bundle(_) => {}
+      PLEntry::ReconfigBundle(reconfig) => {
+        // Process new_eids
+        for eid in &reconfig.new_eids {
+          self.remote_next_indices.insert(eid.clone(), self.next_index.clone());
+          self.unconfirmed_eids.insert(eid.clone(), false);
+          self.paxos_nodes.push(eid.clone());
+        }
+
+        // Process rem_eids
+        for eid in &reconfig.rem_eids {
+          self.remote_next_indices.remove(eid);
+          self.unconfirmed_eids.remove(eid);
+          remove_item(&mut self.paxos_nodes, eid);
+        }
+
+        // A transfer to a node that was just removed is abandoned.
+        if let Some(target_eid) = self.transfer_target() {
+          if reconfig.rem_eids.contains(target_eid) {
+            self.transfer = None;
+          }
+        }
+      }
+      PLEntry::LeaderChanged(leader_changed) => {
+        self.leader = leader_changed.lid.clone();
+        self.leader_heartbeat = 0;
+        self.pre_vote_grants.clear();
+        self.lease_acks.clear();
+        self.lease_expiry = None;
+
+        // Any insertions and transfers from the prior Leadership are abandoned.
+        self.in_flight.clear();
+        self.pending_inserts.clear();
+        self.transfer = None;
+
+        // The prior Leadership could only have proposed before its `next_index` plus
+        // `max_in_flight`, and its `next_index` was at most that of this `LeaderChanged`.
+        self.prior_window_end = self.next_index + self.paxos_config.max_in_flight as PLIndex;
+      }
+      PLEntry::Batch(_) => {}
+    }
+
+    new_entries.push(entry);
+  }
+
   // -----------------------------------------------------------------------------------------------
   //  Bundle Insertion
   // -----------------------------------------------------------------------------------------------
 
+  /// Inserts `user_entry` if this node is the Leader. If there is no room to propose it right
+  /// away, it waits in `pending_inserts`. If there are already `max_in_flight * max_batch_size`
+  /// entries being inserted, `user_entry` is dropped.
   pub fn insert_bundle<PaxosContextBaseT: PaxosContextBase<BundleT>>(
     &mut self,
     ctx: &mut PaxosContextBaseT,
     user_entry: UserPLEntry<BundleT>,
   ) {
-    // We guard for the case that this node is the leader and that there is not already
-    // something that is attempting to be inserted.
-    if self.is_leader(ctx) && self.next_insert.is_none() {
+    let max_inserting = self.paxos_config.max_in_flight * self.paxos_config.max_batch_size;
+    if self.is_leader(ctx) && self.num_inserting() < max_inserting as usize {
+      self.pending_inserts.push_back(user_entry);
+      self.propose_pending(ctx);
+    }
+  }
+
+  /// Returns true iff this node is the Leader and a `UserPLEntry` inserted now would be
+  /// proposed right away, rather than wait for a prior proposal to be learned. The caller
+  /// can use this to insert its next bundle early.
+  pub fn can_propose_now<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+    &self,
+    ctx: &PaxosContextBaseT,
+  ) -> bool {
+    self.is_leader(ctx)
+      && self.transfer.is_none()
+      && self.pending_inserts.is_empty()
+      && self.in_flight.len() < self.max_in_flight()
+      && !self.in_flight.values().any(|(_, batch)| has_reconfig(batch))
+  }
+
+  /// The number of `PLBatch`s that can currently be in flight (see `prior_window_end`).
+  fn max_in_flight(&self) -> usize {
+    if self.next_index < self.prior_window_end {
+      1
+    } else {
+      self.paxos_config.max_in_flight as usize
+    }
+  }
+
+  /// Returns true iff there are `UserPLEntry`s that are either in flight or pending.
+  pub fn is_inserting(&self) -> bool {
+    self.num_inserting() > 0
+  }
+
+  /// Proposes `pending_inserts` in batches of at most `max_batch_size` until there are
+  /// `max_in_flight` batches in flight. Note that there is no linger time: a batch is proposed
+  /// as soon as there is a free slot, so batching only happens for entries that are inserted
+  /// while all `max_in_flight` slots are taken. We do not propose past a `ReconfigBundle` until it is
+  /// learned, since the subsequent `PLIndex`s should be decided by the new PaxosNodes. We also
+  /// do not propose anything while handing Leadership over, so that the target can catch up.
+  fn propose_pending<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+    &mut self,
+    ctx: &mut PaxosContextBaseT,
+  ) {
+    if !self.is_leader(ctx) || self.transfer.is_some() {
+      return;
+    }
+
+    while !self.pending_inserts.is_empty()
+      && self.in_flight.len() < self.max_in_flight()
+      && !self.in_flight.values().any(|(_, batch)| has_reconfig(batch))
+    {
+      // Take as many `pending_inserts` as possible, ending the batch at a `ReconfigBundle`.
+      let mut entries = Vec::<PLEntry<BundleT>>::new();
+      while entries.len() < self.paxos_config.max_batch_size as usize {
+        if let Some(user_entry) = self.pending_inserts.pop_front() {
+          let is_reconfig = matches!(user_entry, UserPLEntry::ReconfigBundle(_));
+          entries.push(user_entry.convert());
+          if is_reconfig {
+            break;
+          }
+        } else {
+          break;
+        }
+      }
+
+      // Propose the batch at the `PLIndex` after the last one in flight.
+      let index = if let Some(last_index) = self.in_flight.keys().next_back() {
+        last_index + 1
+      } else {
+        self.next_index
+      };
+      let batch = PLBatch { lid: self.leader.clone(), entries };
       let uuid = mk_uuid(ctx.rand());
-      self.next_insert = Some((uuid.clone(), user_entry.clone()));
+      self.in_flight.insert(index, (uuid.clone(), batch.clone()));
 
       // Schedule a retry in `retry_defer_time_ms` ms.
       let defer_time = self.paxos_config.retry_defer_time_ms.clone();
       ctx.defer(defer_time, PaxosTimerEvent::RetryInsert(uuid));
 
-      // Propose the bundle at the next index.
-      self.propose_next_index(ctx, user_entry.convert());
+      self.propose_at(ctx, index, PLEntry::Batch(batch));
     }
   }
 
+  /// Starts handing Leadership over to `target_eid`. Until the transfer finishes, nothing new
+  /// is proposed and the leader lease is not extended. Once the lease has expired (so that
+  /// `target_eid` cannot serve writes below a read this node served with it) and
+  /// `target_eid` has caught up on the PaxosLog, this node proposes a `LeaderChanged` for it.
+  /// If this does not get learned within `3 * heartbeat_threshold` heartbeats, the transfer
+  /// is abandoned. This returns false (and does nothing) if this node is not the Leader,
+  /// `target_eid` is not another PaxosNode, or a transfer is already in progress.
+  pub fn transfer_leadership<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+    &mut self,
+    ctx: &mut PaxosContextBaseT,
+    target_eid: EndpointId,
+  ) -> bool {
+    if !self.is_leader(ctx)
+      || self.transfer.is_some()
+      || &target_eid == ctx.this_eid()
+      || !self.paxos_nodes.contains(&target_eid)
+    {
+      return false;
+    }
+
+    self.transfer = Some((target_eid, 0));
+    self.advance_transfer(ctx);
+    true
+  }
+
+  /// Proposes the `LeaderChanged` for the transfer target if it can take over now. Otherwise,
+  /// this helps the target catch up and asks it how far along it is.
+  fn advance_transfer<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+    &mut self,
+    ctx: &mut PaxosContextBaseT,
+  ) {
+    if let Some((target_eid, _)) = &self.transfer {
+      let target_eid = target_eid.clone();
+      let now = ctx.now();
+      let lease_expired = self.lease_expiry.as_ref().map_or(true, |expiry| expiry <= &now);
+      let caught_up = match self.remote_next_indices.get(&target_eid) {
+        Some(next_index) => next_index >= &self.next_index,
+        None => false,
+      };
+      if self.in_flight.is_empty() && lease_expired && caught_up {
+        let gen = self.leader.gen.next();
+        self.propose_at(
+          ctx,
+          self.next_index,
+          PLEntry::LeaderChanged(LeaderChanged { lid: LeadershipId { gen, eid: target_eid } }),
+        );
+      } else {
+        let this_eid = ctx.this_eid().clone();
+        self.send_inform_learned(ctx, &target_eid);
+        ctx.send(
+          &target_eid,
+          msg::PaxosDriverMessage::NextIndexRequest(msg::NextIndexRequest { sender_eid: this_eid }),
+        );
+      }
+    }
+  }
+
+  /// Returns the number of `UserPLEntry`s that are either in flight or pending.
+  fn num_inserting(&self) -> usize {
+    let mut num_inserting = self.pending_inserts.len();
+    for (_, batch) in self.in_flight.values() {
+      num_inserting += batch.entries.len();
+    }
+    num_inserting
+  }
+
   // -----------------------------------------------------------------------------------------------
   //  Timer Events
   // -----------------------------------------------------------------------------------------------
@@ -838,15 +1198,25 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
     ctx: &mut PaxosContextBaseT,
     uuid: UUID,
   ) {
-    if let Some((cur_uuid, user_entry)) = &self.next_insert {
-      // Check if the incoming `uuid` is meant for this `next_insert`.
+    // Check if the incoming `uuid` is meant for a batch that is still in flight.
+    let mut to_retry: Option<(PLIndex, PLBatch<BundleT>)> = None;
+    for (index, (cur_uuid, batch)) in &self.in_flight {
       if cur_uuid == &uuid {
-        // Schedule a retry in `retry_defer_time_ms` ms.
-        let defer_time = self.paxos_config.retry_defer_time_ms.clone();
-        ctx.defer(defer_time, PaxosTimerEvent::RetryInsert(uuid));
+        to_retry = Some((index.clone(), batch.clone()));
+      }
+    }
+
+    if let Some((index, batch)) = to_retry {
+      // Schedule a retry in `retry_defer_time_ms` ms.
+      let defer_time = self.paxos_config.retry_defer_time_ms.clone();
+      ctx.defer(defer_time, PaxosTimerEvent::RetryInsert(uuid));
 
-        // Propose the bundle at the next index.
-        self.propose_next_index(ctx, user_entry.clone().convert());
+      // Propose the batch at the same index again. We only do this for the first batch in
+      // flight; the later ones cannot be learned before it anyway, and re-proposing them
+      // would preempt rounds that might already be succeeding. They are retried once they
+      // become the first.
+      if index == self.next_index {
+        self.propose_at(ctx, index, PLEntry::Batch(batch));
       }
     }
   }
@@ -856,11 +1226,26 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
     ctx: &mut PaxosContextBaseT,
   ) {
     if self.is_leader(ctx) {
+      // Make progress on the transfer, abandoning it if it is taking too long.
+      if let Some((_, num_heartbeats)) = &mut self.transfer {
+        *num_heartbeats += 1;
+        if *num_heartbeats > 3 * self.paxos_config.heartbeat_threshold {
+          self.transfer = None;
+          self.propose_pending(ctx);
+        } else {
+          self.advance_transfer(ctx);
+        }
+      }
+
       // Send out IsLeader to each PaxosNode.
+      let sent_time = ctx.now();
       for eid in &self.paxos_nodes {
         ctx.send(
           &eid,
-          msg::PaxosDriverMessage::IsLeader(msg::IsLeader { lid: self.leader.clone() }),
+          msg::PaxosDriverMessage::IsLeader(msg::IsLeader {
+            lid: self.leader.clone(),
+            sent_time: sent_time.clone(),
+          }),
         );
       }
 
@@ -870,13 +1255,23 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
       // Increment Heartbeat counter
       self.leader_heartbeat += 1;
       if self.leader_heartbeat > self.paxos_config.heartbeat_threshold {
-        // This node tries proposing itself as the leader.
-        let gen = self.leader.gen.next();
-        let eid = ctx.this_eid().clone();
-        self.propose_next_index(
-          ctx,
-          PLEntry::LeaderChanged(LeaderChanged { lid: LeadershipId { gen, eid } }),
-        );
+        // Before trying to become the Leader, this node checks that a majority has also
+        // stopped hearing from the Leader, so that it does not needlessly bump the `Rnd`s of
+        // the PaxosNodes (which would disrupt the Leader's insertions).
+        self.pre_vote_grants.clear();
+        self.pre_vote_round += 1;
+        let this_eid = ctx.this_eid().clone();
+        for eid in &self.paxos_nodes {
+          ctx.send(
+            &eid,
+            msg::PaxosDriverMessage::PreVote(msg::PreVote {
+              sender_eid: this_eid.clone(),
+              lid: self.leader.clone(),
+              next_index: self.next_index,
+              round: self.pre_vote_round,
+            }),
+          );
+        }
 
         // Broadcast `InformLearned` to all PaxosNodes
         self.broadcast_inform_learned(ctx);
@@ -921,26 +1316,53 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
     true
   }
 
-  /// Propose `entry` at the `self.next_index` once.
-  fn propose_next_index<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+  /// Computes the leader lease from `lease_acks`. Once a PaxosNode acknowledges an `IsLeader`
+  /// sent at `sent_time`, it will not respond to a `Prepare` from another node until it misses
+  /// more than `heartbeat_threshold` heartbeats, which takes at least `heartbeat_threshold *
+  /// heartbeat_period_ms`. Thus, if a majority has acknowledged `IsLeader`s sent at or after
+  /// `sent_time`, no other node can become the Leader until `sent_time` plus that duration.
+  fn compute_lease_expiry(&self) -> Option<Timestamp> {
+    let mut sent_times = Vec::<&Timestamp>::new();
+    for eid in &self.paxos_nodes {
+      if let Some(sent_time) = self.lease_acks.get(eid) {
+        sent_times.push(sent_time);
+      }
+    }
+
+    if sent_times.len() < majority(&self.paxos_nodes) {
+      None
+    } else {
+      // Sort in descending order, so that the majority-th element is the latest `sent_time`
+      // that a majority has acknowledged.
+      sent_times.sort_by(|a, b| b.cmp(a));
+      let sent_time = sent_times[majority(&self.paxos_nodes) - 1];
+      let duration_ms = self.paxos_config.heartbeat_threshold as u128
+        * self.paxos_config.heartbeat_period_ms.time_ms;
+      Some(sent_time.add(mk_t(duration_ms)))
+    }
+  }
+
+  /// Propose `entry` at the `index` once.
+  fn propose_at<PaxosContextBaseT: PaxosContextBase<BundleT>>(
     &mut self,
     ctx: &mut PaxosContextBaseT,
+    index: PLIndex,
     entry: PLEntry<BundleT>,
   ) {
     // Create a PaxosInstance if it does not exist already and start inserting.
-    if let Some(instance_entry) = self.paxos_instances.get_mut(&self.next_index) {
+    if let Some(instance_entry) = self.paxos_instances.get_mut(&index) {
       if instance_entry.instance.is_none() {
         instance_entry.instance = Some(PaxosInstance::new());
       }
     } else {
       self.paxos_instances.insert(
-        self.next_index.clone(),
+        index.clone(),
         InstanceEntry { instance: Some(PaxosInstance::new()), learned_rnd_val: None },
       );
     }
 
     // Get PaxosInstance
-    let instance_entry = self.paxos_instances.get_mut(&self.next_index).unwrap();
+    let instance_entry = self.paxos_instances.get_mut(&index).unwrap();
     let paxos_instance = instance_entry.instance.as_mut().unwrap();
 
     // Compute next proposal number.
@@ -953,6 +1375,7 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
       .proposals
       .insert(next_rnd, Proposal { crnd: next_rnd, cval: entry.clone(), promises: vec![] });
     paxos_instance.proposer_state.latest_crnd = next_rnd;
+    metrics().inc(M_PAXOS_PROPOSALS, vec![]);
 
     // Send out Prepare
     let this_eid = ctx.this_eid().clone();
@@ -962,7 +1385,7 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
         msg::PaxosDriverMessage::MultiPaxosMessage(msg::MultiPaxosMessage {
           sender_eid: this_eid.clone(),
           paxos_nodes: self.paxos_nodes.clone(),
-          index: self.next_index.clone(),
+          index: index.clone(),
           paxos_message: msg::PaxosMessage::Prepare(msg::Prepare { crnd: next_rnd }),
         }),
       );
@@ -973,24 +1396,38 @@ impl<BundleT: Clone + Debug> PaxosDriver<BundleT> {
   fn broadcast_inform_learned<PaxosContextBaseT: PaxosContextBase<BundleT>>(
     &mut self,
     ctx: &mut PaxosContextBaseT,
+  ) {
+    for eid in self.paxos_nodes.clone() {
+      self.send_inform_learned(ctx, &eid);
+    }
+  }
+
+  /// Send `InformLearned` to the PaxosNode `eid`.
+  fn send_inform_learned<PaxosContextBaseT: PaxosContextBase<BundleT>>(
+    &self,
+    ctx: &mut PaxosContextBaseT,
+    eid: &EndpointId,
   ) {
     let this_eid = ctx.this_eid().clone();
-    for eid in &self.paxos_nodes {
-      let mut should_learned = BTreeMap::<PLIndex, Rnd>::new();
-      for index in *self.remote_next_indices.get(eid).unwrap()..self.next_index {
-        // Note that `index` will exist in the `paxos_instances` and there will surely be a
-        // learned value (since it is less than `self.next_index`).
-        let instance_entry = self.paxos_instances.get(&index).unwrap();
-        let (vrnd, _) = instance_entry.learned_rnd_val.clone().unwrap();
-        should_learned.insert(index.clone(), vrnd);
-      }
-      ctx.send(
-        &eid,
-        msg::PaxosDriverMessage::InformLearned(msg::InformLearned {
-          sender_eid: this_eid.clone(),
-          should_learned,
-        }),
-      );
+    let mut should_learned = BTreeMap::<PLIndex, Rnd>::new();
+    for index in *self.remote_next_indices.get(eid).unwrap()..self.next_index {
+      // Note that `index` will exist in the `paxos_instances` and there will surely be a
+      // learned value (since it is less than `self.next_index`).
+      let instance_entry = self.paxos_instances.get(&index).unwrap();
+      let (vrnd, _) = instance_entry.learned_rnd_val.clone().unwrap();
+      should_learned.insert(index.clone(), vrnd);
     }
+    ctx.send(
+      eid,
+      msg::PaxosDriverMessage::InformLearned(msg::InformLearned {
+        sender_eid: this_eid,
+        should_learned,
+      }),
+    );
   }
 }
+
+/// Returns whether `batch` contains a `ReconfigBundle`.
+fn has_reconfig<BundleT>(batch: &PLBatch<BundleT>) -> bool {
+  batch.entries.iter().any(|entry| matches!(entry, PLEntry::ReconfigBundle(_)))
+}
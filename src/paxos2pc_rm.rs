@@ -131,6 +131,23 @@ pub enum Paxos2PCRMAction {
 }
 
 impl<T: PayloadTypes, InnerT: Paxos2PCRMInner<T>> Paxos2PCRMOuter<T, InnerT> {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match self {
+      Paxos2PCRMOuter::Committed => "Committed",
+      Paxos2PCRMOuter::Aborted => "Aborted",
+      Paxos2PCRMOuter::Paxos2PCRMExecOuter(es) => match &es.state {
+        State::Follower => "Follower",
+        State::WaitingInsertingPrepared(_) => "WaitingInsertingPrepared",
+        State::InsertingPrepared(_) => "InsertingPrepared",
+        State::Prepared => "Prepared",
+        State::InsertingCommitted => "InsertingCommitted",
+        State::InsertingPreparedAborted => "InsertingPreparedAborted",
+        State::InsertingAborted => "InsertingAborted",
+      },
+    }
+  }
+
   fn new(
     ctx: &mut T::RMContext,
     query_id: QueryId,
@@ -558,6 +575,7 @@ pub fn handle_rm_msg<
 ) -> (QueryId, Paxos2PCRMAction) {
   match msg {
     RMMessage::Prepare(prepare) => {
+      ctx.clock().update(io_ctx, &prepare.sender_time);
       if let Some(es) = con.get_mut(&prepare.query_id) {
         (prepare.query_id, es.handle_prepare(ctx, io_ctx))
       } else {
@@ -607,6 +625,7 @@ pub fn handle_rm_msg<
       }
     }
     RMMessage::Commit(commit) => {
+      ctx.clock().update(io_ctx, &commit.sender_time);
       let query_id = commit.query_id.clone();
       if let Some(es) = con.get_mut(&query_id) {
         (query_id, es.handle_commit(ctx, io_ctx))
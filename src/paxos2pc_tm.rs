@@ -1,5 +1,6 @@
-use crate::common::{BasicIOCtx, LeaderMap, RemoteLeaderChangedPLm};
+use crate::common::{BasicIOCtx, HybridClock, LeaderMap, RemoteLeaderChangedPLm, Timestamp};
 use crate::common::{LeadershipId, PaxosGroupId, PaxosGroupIdTrait, QueryId, SlaveGroupId};
+use crate::trace::TraceContext;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
@@ -23,6 +24,8 @@ pub trait RMServerContext<T: PayloadTypes> {
 
   fn is_leader(&self) -> bool;
 
+  fn clock(&mut self) -> &mut HybridClock;
+
   fn leader_map(&self) -> &LeaderMap;
 }
 
@@ -41,6 +44,8 @@ pub trait TMServerContext<T: PayloadTypes> {
   fn mk_node_path(&self) -> T::TMPath;
 
   fn is_leader(&self) -> bool;
+
+  fn clock(&mut self) -> &mut HybridClock;
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -121,6 +126,10 @@ pub struct Prepare<T: PayloadTypes> {
   /// This typically contains references to resources that this Paxos2PC
   /// should take over in the RM.
   pub payload: T::Prepare,
+  /// The trace that this Paxos2PC is being done for, if any.
+  pub trace: Option<TraceContext>,
+  /// The time of the TM's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -139,6 +148,9 @@ pub struct Abort<T: PayloadTypes> {
 pub struct Commit<T: PayloadTypes> {
   pub query_id: QueryId,
   pub tm: T::TMPath,
+  pub trace: Option<TraceContext>,
+  /// The time of the TM's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -240,15 +252,26 @@ pub enum Paxos2PCTMAction {
 pub struct Paxos2PCTMOuter<T: PayloadTypes, InnerT> {
   /// The `QueryId` identifying the Paxos2PC instance.
   pub query_id: QueryId,
+  /// The trace that is sent to the RMs, where the span is that of this TM (if any).
+  pub trace: Option<TraceContext>,
   pub state: State<T>,
   pub inner: InnerT,
 }
 
 impl<T: PayloadTypes, InnerT: Paxos2PCTMInner<T>> Paxos2PCTMOuter<T, InnerT> {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match &self.state {
+      State::Preparing(_) => "Preparing",
+      State::CheckingPrepared(_) => "CheckingPrepared",
+    }
+  }
+
   pub fn start_orig<IO: BasicIOCtx<T::NetworkMessageT>>(
     ctx: &mut T::TMContext,
     io_ctx: &mut IO,
     query_id: QueryId,
+    trace: Option<TraceContext>,
     inner: InnerT,
     prepare_payloads: BTreeMap<T::RMPath, T::Prepare>,
   ) -> Self {
@@ -261,12 +284,15 @@ impl<T: PayloadTypes, InnerT: Paxos2PCTMInner<T>> Paxos2PCTMOuter<T, InnerT> {
         tm: ctx.mk_node_path(),
         rms: all_rms.clone(),
         payload,
+        trace: trace.clone(),
+        sender_time: ctx.clock().now(io_ctx),
       };
       rms_remaining.insert(rm.clone(), prepare.clone());
       ctx.send_to_rm(io_ctx, &rm, T::rm_msg(RMMessage::Prepare(prepare)));
     }
     Paxos2PCTMOuter {
       query_id,
+      trace,
       state: State::Preparing(PreparingSt { all_rms, rms_remaining }),
       inner,
     }
@@ -288,6 +314,7 @@ impl<T: PayloadTypes, InnerT: Paxos2PCTMInner<T>> Paxos2PCTMOuter<T, InnerT> {
     }
     Paxos2PCTMOuter {
       query_id,
+      trace: None,
       state: State::CheckingPrepared(CheckingPreparedSt { all_rms, rms_remaining }),
       inner,
     }
@@ -303,14 +330,13 @@ impl<T: PayloadTypes, InnerT: Paxos2PCTMInner<T>> Paxos2PCTMOuter<T, InnerT> {
     all_rms: &Vec<T::RMPath>,
   ) -> Paxos2PCTMAction {
     for rm in all_rms {
-      ctx.send_to_rm(
-        io_ctx,
-        rm,
-        T::rm_msg(RMMessage::Commit(Commit {
-          query_id: self.query_id.clone(),
-          tm: ctx.mk_node_path(),
-        })),
-      )
+      let commit = Commit {
+        query_id: self.query_id.clone(),
+        tm: ctx.mk_node_path(),
+        trace: self.trace.clone(),
+        sender_time: ctx.clock().now(io_ctx),
+      };
+      ctx.send_to_rm(io_ctx, rm, T::rm_msg(RMMessage::Commit(commit)))
     }
     self.inner.committed(ctx, io_ctx);
     Paxos2PCTMAction::Exit
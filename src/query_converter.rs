@@ -1577,6 +1577,20 @@ impl<'b, ErrorT: ErrorTrait + Debug, ViewT: 'b + DBSchemaView<ErrorT = ErrorT>>
     let mut jln_to_table_map = BTreeMap::<String, TablePath>::new();
     mk_jln_to_table_map_r(&self.trans_table_map, &select.from, &mut jln_to_table_map);
 
+    // Only Selects that read directly from a single Table can lock rows.
+    if select.lock.is_some() {
+      let is_table_select = match &select.from {
+        iast::JoinNode::JoinLeaf(iast::JoinLeaf {
+          source: iast::JoinNodeSource::Table(table_name),
+          ..
+        }) => !self.trans_table_map.contains_key(table_name),
+        _ => false,
+      };
+      if !is_table_select {
+        return Err(ErrorT::mk_error(msg::QueryPlanningError::InvalidLock));
+      }
+    }
+
     match &select.from {
       iast::JoinNode::JoinLeaf(iast::JoinLeaf {
         source: iast::JoinNodeSource::Table(table_name),
@@ -1602,6 +1616,7 @@ impl<'b, ErrorT: ErrorTrait + Debug, ViewT: 'b + DBSchemaView<ErrorT = ErrorT>>
               alias: alias.clone().unwrap(),
             },
             selection: self.flatten_val_expr_r(&select.selection)?,
+            lock: select.lock,
             schema: self.compute_schema(assignment_name),
           }))
         }
@@ -1781,6 +1796,7 @@ impl<'b, ErrorT: ErrorTrait + Debug, ViewT: 'b + DBSchemaView<ErrorT = ErrorT>>
             projection,
             from: proc::TableSource { table_path, alias },
             selection: proc::ValExpr::Value { val: iast::Value::Boolean(true) },
+            lock: None,
             schema,
           })
         };
@@ -2247,3 +2263,24 @@ pub fn collect_jlns(node: &proc::JoinNode) -> Vec<String> {
   collect_jlns_r(node, &mut jlns);
   return jlns;
 }
+
+// -----------------------------------------------------------------------------------------------
+//  Bind Parameters
+// -----------------------------------------------------------------------------------------------
+
+/// Replaces every `iast::Value::Param(n)` in `ms_query` with `params[n - 1]`. Since this is
+/// done after planning, the bound values are only ever treated as data. The caller must
+/// make sure that `params` has a value for every `Param` in `ms_query`.
+pub fn bind_params(ms_query: &mut proc::MSQuery, params: &Vec<iast::Value>) {
+  let query_iterator = QueryIteratorMut::new();
+  query_iterator.iterate_ms_query(
+    &mut |elem| {
+      if let QueryElementMut::ValExpr(proc::ValExpr::Value { val }) = elem {
+        if let iast::Value::Param(n) = *val {
+          *val = params.get(n as usize - 1).unwrap().clone();
+        }
+      }
+    },
+    ms_query,
+  );
+}
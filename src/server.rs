@@ -1,5 +1,6 @@
 use crate::common::{
-  lookup_pos, BasicIOCtx, CoreIOCtx, FullGen, GossipData, LeaderMap, TableSchema, Timestamp,
+  hash_bucket, lookup_pos, BasicIOCtx, CoreIOCtx, FullGen, GossipData, HybridClock, LeaderMap,
+  TableSchema, Timestamp,
 };
 use crate::common::{
   CNodePath, CSubNodePath, CTNodePath, CTQueryPath, CTSubNodePath, ColName, ColVal, ColValN,
@@ -216,6 +217,7 @@ pub trait CTServerContext: ServerContextBase {
   fn this_sid(&self) -> &SlaveGroupId;
   fn sub_node_path(&self) -> &CTSubNodePath;
   fn gossip(&self) -> &Arc<GossipData>;
+  fn clock(&mut self) -> &mut HybridClock;
 
   /// Construct a `NodePath` for a `TabletGroupId`.
   /// NOTE: the `tid` must exist in the `gossip` at this point.
@@ -278,9 +280,35 @@ pub trait CTServerContext: ServerContextBase {
     let (gen, _) = full_gen;
     let table_path_gen = (table_source.table_path.clone(), gen.clone());
     let key_cols = &self.gossip().get().db_schema.get(&table_path_gen).unwrap().key_cols;
+    let row_region = compute_key_region(selection, BTreeMap::new(), &table_source.alias, key_cols);
+
+    // If the Table is hash-partitioned and every `KeyBound` pins the hashed KeyCol to a
+    // single value, we only need the Tablets of the buckets that those values hash to.
+    let table_path_full_gen = (table_source.table_path.clone(), full_gen.clone());
+    let shards = self.gossip().get().sharding_config.get(&table_path_full_gen).unwrap();
+    if let Some(Some(hash)) = shards.first().map(|(range, _)| &range.hash) {
+      let mut buckets = BTreeSet::<u32>::new();
+      for key_bound in &row_region {
+        if let Some(key) = key_bound.col_bounds.get(hash.key_idx).and_then(|b| b.point()) {
+          buckets.insert(hash_bucket(&key, hash.num_buckets));
+        } else {
+          return self.get_all_tablets(table_source, full_gen);
+        }
+      }
+
+      let mut tids = Vec::<TabletGroupId>::new();
+      for (range, tid) in shards {
+        if buckets.contains(&range.hash.as_ref().unwrap().bucket) {
+          tids.push(tid.clone());
+        }
+      }
+      if !tids.is_empty() {
+        return tids;
+      }
+    }
 
-    // TODO: We use a trivial implementation for now. Do a proper implementation later.
-    let _ = compute_key_region(selection, BTreeMap::new(), &table_source.alias, key_cols);
+    // TODO: We use a trivial implementation for range-partitioned Tables for now.
+    // Do a proper implementation later.
     self.get_all_tablets(table_source, full_gen)
   }
 
@@ -527,7 +555,10 @@ pub fn evaluate_delete(
 }
 
 pub fn mk_eval_error(eval_error: EvalError) -> msg::QueryError {
-  msg::QueryError::TypeError { msg: format!("{:?}", eval_error) }
+  match eval_error {
+    EvalError::Overflow => msg::QueryError::RuntimeError { msg: format!("{:?}", eval_error) },
+    _ => msg::QueryError::TypeError { msg: format!("{:?}", eval_error) },
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1,5 +1,6 @@
 use crate::common::{
-  CTSubNodePath, PaxosGroupId, PaxosGroupIdTrait, QueryId, SlaveIOCtx, TNodePath, TabletGroupId,
+  mk_t, CTSubNodePath, HybridClock, PaxosGroupId, PaxosGroupIdTrait, QueryId, SlaveIOCtx,
+  TNodePath, TabletGroupId,
 };
 use crate::paxos2pc_tm::Paxos2PCContainer;
 use crate::server::ServerContextBase;
@@ -120,22 +121,28 @@ impl PendingShardingES {
     // Create the new Tablet
     io_ctx.create_tablet(TabletContext {
       tablet_config: TabletConfig {
-        timestamp_suffix_divisor: ctx.slave_config.timestamp_suffix_divisor,
+        max_clock_offset_ms: ctx.slave_config.max_clock_offset_ms,
+        closed_timestamp_lag_ms: ctx.slave_config.closed_timestamp_lag_ms,
+        follower_read_wait_ms: ctx.slave_config.follower_read_wait_ms,
       },
       this_sid: ctx.this_sid.clone(),
       this_gid: ctx.this_sid.to_gid(),
       this_tid: snapshot.this_tid.clone(),
       sub_node_path: CTSubNodePath::Tablet(snapshot.this_tid.clone()),
       this_eid: ctx.this_eid.clone(),
+      clock: HybridClock::new(ctx.clock_ids.next(), ctx.slave_config.max_clock_offset_ms),
       gossip: ctx.gossip.clone(),
       leader_map: ctx.leader_map.value().clone(),
       storage: snapshot.storage,
       this_table_path: snapshot.this_table_path,
+      prior_table_paths: snapshot.prior_table_paths,
       this_sharding_gen: snapshot.this_sharding_gen,
       this_tablet_key_range: snapshot.this_table_key_range,
       sharding_done: true,
       table_schema: snapshot.table_schema,
       presence_timestamp: snapshot.presence_timestamp,
+      col_conversions: snapshot.col_conversions,
+      inserting_col_conversion: false,
       verifying_writes: Default::default(),
       inserting_prepared_writes: Default::default(),
       prepared_writes: Default::default(),
@@ -143,10 +150,15 @@ impl PendingShardingES {
       waiting_read_protected: Default::default(),
       inserting_read_protected: Default::default(),
       read_protected: snapshot.read_protected,
+      preempted_ms_queries: Default::default(),
+      lease_expiry: None,
+      lease_floor: mk_t(0),
+      closed_timestamp: snapshot.closed_timestamp,
       waiting_locked_cols: Default::default(),
       inserting_locked_cols: Default::default(),
       ms_root_query_map: Default::default(),
       tablet_bundle: vec![],
+      spans: Default::default(),
     });
   }
 
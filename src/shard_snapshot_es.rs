@@ -83,13 +83,16 @@ impl ShardingSnapshotES {
     let snapshot = ShardingSnapshot {
       this_tid: self.target.tid.clone(),
       this_table_path: ctx.this_table_path.clone(),
+      prior_table_paths: ctx.prior_table_paths.clone(),
       this_sharding_gen: ctx.this_sharding_gen.clone(),
       this_table_key_range: self.target.range.clone(),
       storage: compute_range_storage(&ctx.storage, &self.target.range),
       table_schema: ctx.table_schema.clone(),
       presence_timestamp: ctx.presence_timestamp.clone(),
+      col_conversions: ctx.col_conversions.clone(),
       committed_writes: ctx.committed_writes.clone(),
       read_protected: ctx.read_protected.clone(),
+      closed_timestamp: ctx.closed_timestamp.clone(),
     };
 
     // Send the Snapshot
@@ -1,5 +1,5 @@
-use crate::common::{cur_timestamp, QueryId, Timestamp};
-use crate::common::{mk_t, BasicIOCtx};
+use crate::common::{mk_t, BasicIOCtx, HybridClock};
+use crate::common::{QueryId, Timestamp};
 use crate::common::{TNodePath, TabletGroupId};
 use crate::message as msg;
 use crate::server::ServerContextBase;
@@ -77,6 +77,10 @@ impl RMServerContext<ShardSplitSlaveRMPayloadTypes> for SlaveContext {
   fn is_leader(&self) -> bool {
     SlaveContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
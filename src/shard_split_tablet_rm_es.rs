@@ -1,5 +1,5 @@
-use crate::common::{cur_timestamp, QueryId, Timestamp};
-use crate::common::{mk_t, BasicIOCtx};
+use crate::common::{mk_t, BasicIOCtx, HybridClock};
+use crate::common::{QueryId, Timestamp};
 use crate::common::{
   ShardingGen, SlaveGroupId, TNodePath, TablePath, TabletGroupId, TabletKeyRange,
 };
@@ -78,6 +78,10 @@ impl RMServerContext<ShardSplitTabletRMPayloadTypes> for TabletContext {
   fn is_leader(&self) -> bool {
     TabletContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
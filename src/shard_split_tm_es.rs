@@ -1,5 +1,5 @@
 use crate::alter_table_tm_es::{get_rms, ResponseData};
-use crate::common::{cur_timestamp, mk_t, BasicIOCtx, FullGen, GeneralTraceMessage, Timestamp};
+use crate::common::{mk_t, BasicIOCtx, FullGen, GeneralTraceMessage, HybridClock, Timestamp};
 use crate::common::{
   PaxosGroupId, PaxosGroupIdTrait, ShardingGen, SlaveGroupId, TNodePath, TSubNodePath, TablePath,
   TabletGroupId, TabletKeyRange,
@@ -140,6 +140,10 @@ impl TMServerContext<ShardSplitTMPayloadTypes> for MasterContext {
   fn is_leader(&self) -> bool {
     MasterContext::is_leader(self)
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -352,11 +356,7 @@ impl STMPaxos2PCTMInner<ShardSplitTMPayloadTypes> for ShardSplitTMInner {
     ctx: &mut MasterContext,
     io_ctx: &mut IO,
   ) -> ShardSplitTMClosed {
-    let timestamp_hint = if self.did_commit {
-      Some(cur_timestamp(io_ctx, ctx.master_config.timestamp_suffix_divisor))
-    } else {
-      None
-    };
+    let timestamp_hint = if self.did_commit { Some(ctx.clock.now(io_ctx)) } else { None };
     ShardSplitTMClosed { timestamp_hint }
   }
 
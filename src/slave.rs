@@ -1,16 +1,16 @@
 use crate::common::{
-  lookup, mk_t, update_all_eids, update_leader_map, BasicIOCtx, CTSubNodePath, CoordGroupId,
-  EndpointId, Gen, GeneralTraceMessage, GossipData, LeaderMap, LeadershipId, PaxosGroupId,
-  PaxosGroupIdTrait, QueryId, RemoteLeaderChangedPLm, SlaveGroupId, SlaveIOCtx, SlaveTraceMessage,
-  TabletGroupId, Timestamp, VersionedValue,
+  lookup, mk_t, update_all_eids, update_leader_map, BasicIOCtx, CTNodePath, CTQueryPath,
+  CTSubNodePath, ClockIdAllocator, CoordGroupId, EndpointId, Gen, GeneralTraceMessage, GossipData,
+  HybridClock, LeaderMap, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, QueryId, RemoteLeaderChangedPLm,
+  RequestId, SlaveGroupId, SlaveIOCtx, SlaveTraceMessage, TabletGroupId, Timestamp, VersionedValue,
 };
-use crate::coord::CoordForwardMsg;
+use crate::coord::{CoordForwardMsg, CoordTimerInput};
 use crate::create_table_rm_es::{CreateTableRMAction, CreateTableRMES, CreateTableRMPayloadTypes};
 use crate::create_table_tm_es::CreateTableTMPayloadTypes;
 use crate::message as msg;
 use crate::network_driver::{NetworkDriver, NetworkDriverContext};
 use crate::paxos::{PaxosConfig, PaxosContextBase, PaxosDriver, PaxosTimerEvent, UserPLEntry};
-use crate::server::ServerContextBase;
+use crate::server::{CommonQuery, ServerContextBase};
 use crate::shard_pending_es::{ShardSplitESS, ShardingSplitPLm};
 use crate::shard_split_slave_rm_es::{
   ShardSplitSlaveRMAction, ShardSplitSlaveRMES, ShardSplitSlaveRMPayloadTypes,
@@ -57,6 +57,16 @@ pub struct SharedPaxosBundle {
   tablet: BTreeMap<TabletGroupId, TabletBundle>,
 }
 
+impl SharedPaxosBundle {
+  /// Returns true iff this holds nothing to persist.
+  fn is_empty(&self) -> bool {
+    self.slave.remote_leader_changes.is_empty()
+      && self.slave.gossip_data.is_none()
+      && self.slave.plms.is_empty()
+      && self.tablet.values().all(|bundle| bundle.is_empty())
+  }
+}
+
 // -----------------------------------------------------------------------------------------------
 //  SlavePaxosContext
 // -----------------------------------------------------------------------------------------------
@@ -78,6 +88,10 @@ impl<'a, IO: SlaveIOCtx> PaxosContextBase<SharedPaxosBundle> for SlavePaxosConte
     self.this_eid
   }
 
+  fn now(&mut self) -> Timestamp {
+    self.io_ctx.now()
+  }
+
   fn send(&mut self, eid: &EndpointId, message: msg::PaxosDriverMessage<SharedPaxosBundle>) {
     self
       .io_ctx
@@ -119,10 +133,30 @@ pub struct TabletBundleInsertion {
   pub bundle: TabletBundle,
 }
 
+/// Sent from Tablets and Coords to the Slave in response to a `ShowQueries`, describing
+/// the ESs running there.
+#[derive(Debug)]
+pub struct RunningQueries {
+  /// The `RequestId` of the `ExternalShowQueries` being responded to.
+  pub request_id: RequestId,
+  pub queries: Vec<msg::RunningQuery>,
+}
+
+/// Sent from a Coord to the Slave to defer `timer_input` (since Coords cannot defer events
+/// themselves). The Slave forwards it back to the Coord after `defer_time`.
+#[derive(Debug)]
+pub struct CoordDefer {
+  pub cid: CoordGroupId,
+  pub defer_time: Timestamp,
+  pub timer_input: CoordTimerInput,
+}
+
 #[derive(Debug)]
 pub enum SlaveBackMessage {
   TabletBundleInsertion(TabletBundleInsertion),
   TabletSnapshot(TabletSnapshot),
+  RunningQueries(RunningQueries),
+  CoordDefer(CoordDefer),
 }
 
 /// Messages deferred by the Slave to be run on the Slave.
@@ -137,6 +171,8 @@ pub enum SlaveTimerInput {
   /// A timer event to detect if there are any `unconfirmed_eids` in the PaxosDriver. We
   /// use this to start constructing a `SlaveSnapshot` if there is.
   CheckUnconfirmedEids,
+  /// A timer event deferred by the Coord at the `CoordGroupId` (see `CoordDefer`).
+  CoordTimerInput(CoordGroupId, CoordTimerInput),
 }
 
 pub enum FullSlaveInput {
@@ -187,12 +223,28 @@ pub struct Statuses {
   /// `do_reconfig` before the next time a SharedPaxosBundle is computed for insertion, since
   /// we don’t want to accidentally make that into a ReconfigBundle too.
   do_reconfig: Option<(Vec<EndpointId>, Vec<EndpointId>)>,
+  /// Whether the `ReconfigBundle` for `do_reconfig` is being inserted. Since several bundles
+  /// can be in flight, we use this to avoid inserting the same reconfig twice.
+  do_reconfig_inserted: bool,
   /// This is populated whenever we start building a `SlaveSnapshot`. We call the PaxosDriver
   /// to get the current set of `unconfirmed_eids` that map to `false` (held in `Vec<EndpointId>`),
   /// which also returns the `paxos_driver_start`. We send `ConstructTabletSnapshot` to the current
   /// set of Tablets and we remember `io_ctx.num_tablets` so that we can determine when all Tablets
   /// have responded with their their snapshots.
   pending_snapshot: Option<(SlaveSnapshot, Vec<EndpointId>, usize)>,
+  /// The `ExternalShowQueries` that are waiting for Tablets and Coords to respond.
+  pending_show_queries: BTreeMap<RequestId, PendingShowQueries>,
+  /// The `ExternalTransferLeadership` that this Leader is carrying out, if any.
+  leadership_transfer: Option<msg::ExternalTransferLeadership>,
+}
+
+/// An `ExternalShowQueries` that is gathering `RunningQueries` from the Tablets and Coords.
+#[derive(Debug)]
+struct PendingShowQueries {
+  sender_eid: EndpointId,
+  /// The number of Tablets and Coords that have yet to respond.
+  num_remaining: usize,
+  queries: Vec<msg::RunningQuery>,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -201,10 +253,13 @@ pub struct Statuses {
 
 #[derive(Debug, Clone)]
 pub struct SlaveConfig {
-  /// This is used for generate the `suffix` of a Timestamp, where we just generate
-  /// a random `u64` and take the remainder after dividing by `timestamp_suffix_divisor`.
-  /// This cannot be 0; the default value is 1, making the suffix always be 0.
-  pub timestamp_suffix_divisor: u64,
+  /// The maximum amount (in ms) that the clock of another node can be ahead of ours before
+  /// we consider it to be skewed. See `HybridClock`.
+  pub max_clock_offset_ms: u128,
+  /// See `TabletConfig::closed_timestamp_lag_ms`.
+  pub closed_timestamp_lag_ms: u128,
+  /// See `TabletConfig::follower_read_wait_ms`.
+  pub follower_read_wait_ms: u128,
 
   /// Timer events
   pub remote_leader_changed_period_ms: u128,
@@ -249,6 +304,10 @@ pub struct SlaveContext {
   pub this_sid: SlaveGroupId,
   pub this_gid: PaxosGroupId, // self.this_sid.to_gid()
   pub this_eid: EndpointId,
+  /// The clock used to generate all `Timestamp`s at this server.
+  pub clock: HybridClock,
+  /// Hands out the `ClockId`s of the Tablets that are created at this node.
+  pub clock_ids: ClockIdAllocator,
 
   /// Between calls to `handle_input`, we should maintain the following properties:
   /// 1. `leader_map` should contain an entry for every `PaxosGroupId` in the
@@ -270,7 +329,8 @@ pub struct SlaveContext {
 
   // Paxos
   pub slave_bundle: SlaveBundle,
-  /// After a `SharedPaxosBundle` is inserted, this is cleared.
+  /// After a `SharedPaxosBundle` is inserted, this is cleared. If a Tablet sends multiple
+  /// `TabletBundle`s in the meantime (see `TabletForwardMsg::FlushBundle`), they are appended.
   pub tablet_bundles: BTreeMap<TabletGroupId, TabletBundle>,
   pub paxos_driver: PaxosDriver<SharedPaxosBundle>,
 }
@@ -282,6 +342,7 @@ impl Debug for SlaveContext {
     let _ = debug_trait_builder.field("slave_config", &self.slave_config);
     let _ = debug_trait_builder.field("this_sid", &self.this_sid);
     let _ = debug_trait_builder.field("this_gid", &self.this_gid);
+    let _ = debug_trait_builder.field("clock_ids", &self.clock_ids);
     let _ = debug_trait_builder.field("this_eid", &self.this_eid);
     // let _ = debug_trait_builder.field("gossip", &self.gossip);
     let _ = debug_trait_builder.field("leader_map", &self.leader_map);
@@ -310,12 +371,20 @@ impl SlaveState {
     create_table_ess: BTreeMap<QueryId, CreateTableRMES>,
     shard_split_ess: ShardSplitESS,
     this_eid: EndpointId,
+    mut clock_ids: ClockIdAllocator,
     slave_config: SlaveConfig,
     paxos_config: PaxosConfig,
   ) -> SlaveState {
     // Create Statuses
-    let statuses =
-      Statuses { create_table_ess, shard_split_ess, do_reconfig: None, pending_snapshot: None };
+    let statuses = Statuses {
+      create_table_ess,
+      shard_split_ess,
+      do_reconfig: None,
+      do_reconfig_inserted: false,
+      pending_snapshot: None,
+      pending_show_queries: Default::default(),
+      leadership_transfer: None,
+    };
 
     // Create the SlaveCtx
     let paxos_nodes = paxos_driver_start.paxos_nodes.clone();
@@ -332,12 +401,15 @@ impl SlaveState {
     all_eids.extend(paxos_nodes.clone());
 
     let network_driver = NetworkDriver::new(&leader_map);
+    let clock = HybridClock::new(clock_ids.next(), slave_config.max_clock_offset_ms);
     let ctx = SlaveContext {
       coord_positions,
       slave_config,
       this_sid: this_sid.clone(),
       this_gid: this_sid.to_gid(),
       this_eid: this_eid.clone(),
+      clock,
+      clock_ids,
       gossip,
       leader_map,
       all_eids: VersionedValue::new(all_eids),
@@ -418,6 +490,7 @@ impl SlaveContext {
     mut leader_map: LeaderMap,
     paxos_nodes: Vec<EndpointId>,
     this_eid: EndpointId,
+    mut clock_ids: ClockIdAllocator,
     slave_config: SlaveConfig,
     paxos_config: PaxosConfig,
   ) -> SlaveContext {
@@ -435,12 +508,15 @@ impl SlaveContext {
     all_eids.extend(paxos_nodes.clone());
 
     let network_driver = NetworkDriver::new(&leader_map);
+    let clock = HybridClock::new(clock_ids.next(), slave_config.max_clock_offset_ms);
     SlaveContext {
       coord_positions,
       slave_config,
       this_sid: this_sid.clone(),
       this_gid: this_sid.to_gid(),
       this_eid,
+      clock,
+      clock_ids,
       gossip,
       leader_map,
       all_eids: VersionedValue::new(all_eids),
@@ -459,9 +535,16 @@ impl SlaveContext {
     message: msg::SlaveMessage,
   ) {
     match message {
+      msg::SlaveMessage::SlaveExternalReq(msg::SlaveExternalReq::ExternalShowQueries(show)) => {
+        // Every node answers for the ESs running on it, whether or not it is the Leader.
+        self.start_show_queries(io_ctx, statuses, show);
+      }
       msg::SlaveMessage::SlaveExternalReq(request) => {
         if self.is_leader() {
           self.handle_input(io_ctx, statuses, SlaveForwardMsg::SlaveExternalReq(request))
+        } else if let Some((eid, response)) = request.not_leader_response() {
+          // Tell the External to find the current leader and resend.
+          io_ctx.send(&eid, response);
         }
       }
       msg::SlaveMessage::RemoteMessage(remote_message) => {
@@ -502,17 +585,70 @@ impl SlaveContext {
       }
       msg::SlaveMessage::MasterGossip(master_gossip) => {
         if self.is_leader() {
-          self.handle_master_gossip(master_gossip);
+          self.handle_master_gossip(io_ctx, master_gossip);
+        }
+      }
+      msg::SlaveMessage::FollowerRead(follower_read) => {
+        // Every node can serve a FollowerRead, whether or not it is the Leader.
+        let tid = follower_read.tid.clone();
+        let forward_msg = TabletForwardMsg::FollowerRead(follower_read);
+        if let Err(TabletForwardMsg::FollowerRead(follower_read)) =
+          io_ctx.tablet_forward(&tid, forward_msg)
+        {
+          // The Tablet does not exist here (e.g. it was dropped), so we respond with an abort.
+          let perform_query = follower_read.perform_query;
+          let sender_path = perform_query.sender_path;
+          let query = CommonQuery::QueryAborted(msg::QueryAborted {
+            return_qid: sender_path.query_id,
+            responder_path: CTQueryPath {
+              node_path: CTNodePath { sid: self.this_sid.clone(), sub: CTSubNodePath::Tablet(tid) },
+              query_id: perform_query.query_id,
+            },
+            payload: msg::AbortedData::QueryError(msg::QueryError::InvalidQueryPlan),
+          });
+          let payload = query.into_remote_payload(sender_path.node_path.sub);
+          io_ctx.send(
+            &follower_read.sender_eid,
+            msg::NetworkMessage::Slave(msg::SlaveMessage::FollowerReadResponse(
+              msg::FollowerReadResponse { payload },
+            )),
+          );
+        }
+      }
+      msg::SlaveMessage::FollowerReadResponse(response) => {
+        // The Coord that sent the FollowerRead only exists if this node is still the Leader.
+        if self.is_leader() {
+          self.handle_input(
+            io_ctx,
+            statuses,
+            SlaveForwardMsg::SlaveRemotePayload(response.payload),
+          );
         }
       }
       msg::SlaveMessage::PaxosDriverMessage(paxos_message) => {
+        let prev_lease_expiry = self.paxos_driver.lease_expiry().cloned();
         let bundles = self.paxos_driver.handle_paxos_message(
           &mut SlavePaxosContext { io_ctx, this_eid: &self.this_eid },
           paxos_message,
         );
+
+        // If the leader lease was extended, forward it to all Tablets so that they can
+        // serve reads locally (see `TabletContext::lease_expiry`).
+        if let Some(lease_expiry) = self.paxos_driver.lease_expiry() {
+          if Some(lease_expiry) != prev_lease_expiry.as_ref() {
+            for tid in io_ctx.all_tids() {
+              let forward_msg = TabletForwardMsg::LeaderLease(lease_expiry.clone());
+              io_ctx.tablet_forward(&tid, forward_msg).unwrap();
+            }
+          }
+        }
+
         for shared_bundle in bundles {
           match shared_bundle {
             msg::PLEntry::LeaderChanged(leader_changed) => {
+              // Respond to the `ExternalTransferLeadership` that was being carried out.
+              self.finish_leadership_transfer(io_ctx, statuses, &leader_changed.lid);
+
               // Forward the LeaderChanged to all Tablets.
               let all_tids = io_ctx.all_tids();
               for tid in all_tids {
@@ -555,6 +691,7 @@ impl SlaveContext {
                 // We clear this here to guarantee that we do not accidentally
                 // insert another ReconfigBundle.
                 statuses.do_reconfig = None;
+                statuses.do_reconfig_inserted = false;
 
                 // Process the persisted data only first.
                 let remote_leader_changed = reconfig.bundle.slave.remote_leader_changes.clone();
@@ -591,6 +728,10 @@ impl SlaveContext {
                 self.deliver_blocked_messages(io_ctx, statuses, remote_leader_changed);
               }
             }
+            msg::PLEntry::Batch(_) => {
+              // Recall that the PaxosDriver delivers the entries in a `Batch` individually.
+              debug_assert!(false);
+            }
           }
         }
       }
@@ -689,7 +830,7 @@ impl SlaveContext {
       SlaveForwardMsg::SlaveBackMessage(slave_back_msg) => match slave_back_msg {
         SlaveBackMessage::TabletBundleInsertion(insert) => {
           if self.leader_map.value().get(&self.this_gid).unwrap() == &insert.lid {
-            self.tablet_bundles.insert(insert.tid, insert.bundle);
+            self.tablet_bundles.entry(insert.tid).or_default().extend(insert.bundle);
             self.maybe_start_insert(io_ctx, statuses);
           }
         }
@@ -713,12 +854,34 @@ impl SlaveContext {
             statuses.pending_snapshot = None;
           }
         }
+        SlaveBackMessage::RunningQueries(running) => {
+          if let Some(pending) = statuses.pending_show_queries.get_mut(&running.request_id) {
+            pending.queries.extend(running.queries);
+            pending.num_remaining -= 1;
+            if pending.num_remaining == 0 {
+              let pending = statuses.pending_show_queries.remove(&running.request_id).unwrap();
+              self.send_show_queries_response(io_ctx, running.request_id, pending);
+            }
+          }
+        }
+        SlaveBackMessage::CoordDefer(defer) => {
+          let timer_input = SlaveTimerInput::CoordTimerInput(defer.cid, defer.timer_input);
+          io_ctx.defer(defer.defer_time, timer_input);
+        }
       },
       SlaveForwardMsg::SlaveTimerInput(timer_input) => match timer_input {
         SlaveTimerInput::PaxosTimerEvent(timer_event) => {
           self
             .paxos_driver
             .timer_event(&mut SlavePaxosContext { io_ctx, this_eid: &self.this_eid }, timer_event);
+
+          // Check if the PaxosDriver abandoned the `ExternalTransferLeadership`.
+          if self.paxos_driver.transfer_target().is_none() {
+            if let Some(transfer) = statuses.leadership_transfer.take() {
+              let (eid, response) = transfer.response(msg::TransferLeadershipResult::Aborted);
+              io_ctx.send(&eid, response);
+            }
+          }
         }
         SlaveTimerInput::RemoteLeaderChanged => {
           if self.is_leader() {
@@ -764,6 +927,9 @@ impl SlaveContext {
           let defer_time = mk_t(self.slave_config.check_unconfirmed_eids_period_ms);
           io_ctx.defer(defer_time, SlaveTimerInput::CheckUnconfirmedEids);
         }
+        SlaveTimerInput::CoordTimerInput(cid, timer_input) => {
+          io_ctx.coord_forward(&cid, CoordForwardMsg::CoordTimerInput(timer_input));
+        }
       },
       SlaveForwardMsg::SlaveBundle(bundle) => {
         for paxos_log_msg in bundle {
@@ -792,11 +958,20 @@ impl SlaveContext {
           self.maybe_start_insert(io_ctx, statuses);
         }
       }
+      SlaveForwardMsg::SlaveExternalReq(msg::SlaveExternalReq::ExternalTransferLeadership(
+        transfer,
+      )) => {
+        self.start_leadership_transfer(io_ctx, statuses, transfer);
+      }
       SlaveForwardMsg::SlaveExternalReq(request) => {
         // Compute the hash of the request Id.
         let request_id = match &request {
           msg::SlaveExternalReq::PerformExternalQuery(perform) => perform.request_id.clone(),
           msg::SlaveExternalReq::CancelExternalQuery(cancel) => cancel.request_id.clone(),
+          // Route to the Coord that the target request was routed to.
+          msg::SlaveExternalReq::ExternalKillQuery(kill) => kill.target_request_id.clone(),
+          msg::SlaveExternalReq::ExternalShowQueries(_) => unreachable!(),
+          msg::SlaveExternalReq::ExternalTransferLeadership(_) => unreachable!(),
         };
         let mut hasher = DefaultHasher::new();
         request_id.hash(&mut hasher);
@@ -818,7 +993,7 @@ impl SlaveContext {
             statuses.shard_split_ess.handle_rm_msg(self, io_ctx, message);
           }
           msg::SlaveRemotePayload::MasterGossip(master_gossip) => {
-            self.handle_master_gossip(master_gossip);
+            self.handle_master_gossip(io_ctx, master_gossip);
           }
           msg::SlaveRemotePayload::TabletMessage(tid, tablet_msg) => {
             statuses.shard_split_ess.handle_tablet_msg(io_ctx, tid, tablet_msg);
@@ -919,6 +1094,7 @@ impl SlaveContext {
 
         // MasterReconfig
         statuses.do_reconfig = None;
+        statuses.do_reconfig_inserted = false;
 
         // Inform the NetworkDriver
         self.network_driver.leader_changed();
@@ -965,6 +1141,8 @@ impl SlaveContext {
 
   /// Checks whether all Tablets have forwarded their `TabletBundle`s back up to the Slave, and
   /// if so, send it to the PaxosDriver for insertion. We also clear the current set of Bundles.
+  /// If the PaxosDriver can propose yet another bundle right away, we ask the Tablets for their
+  /// next `TabletBundle`s now, rather than once the inserted bundle is learned.
   fn maybe_start_insert<IO: SlaveIOCtx>(&mut self, io_ctx: &mut IO, statuses: &mut Statuses) {
     if self.tablet_bundles.len() == io_ctx.num_tablets() {
       let bundle = SharedPaxosBundle {
@@ -972,15 +1150,33 @@ impl SlaveContext {
         tablet: std::mem::take(&mut self.tablet_bundles),
       };
 
-      // Check if we should reconfigure, construct the appropriate `UserPLEntry`.
-      let user_entry = if let Some((rem_eids, new_eids)) = statuses.do_reconfig.clone() {
+      // Check if we should reconfigure, construct the appropriate `UserPLEntry`. If nothing is
+      // being inserted, then the `ReconfigBundle` we inserted (if any) was dropped, so we insert
+      // it again.
+      if !self.paxos_driver.is_inserting() {
+        statuses.do_reconfig_inserted = false;
+      }
+      let reconfig = statuses.do_reconfig.clone().filter(|_| !statuses.do_reconfig_inserted);
+      let user_entry = if let Some((rem_eids, new_eids)) = reconfig {
+        statuses.do_reconfig_inserted = true;
         UserPLEntry::ReconfigBundle(msg::ReconfigBundle { rem_eids, new_eids, bundle })
+      } else if bundle.is_empty() && self.paxos_driver.is_inserting() {
+        // There is nothing to persist, and the bundles in flight already keep the insert
+        // cycle going, so we do not insert this one. However, we keep the `TabletBundle`s
+        // for the next insert, since a Tablet that was just created is not in any bundle in
+        // flight, and so only joins the insert cycle through its entry here.
+        self.tablet_bundles = bundle.tablet;
+        return;
       } else {
         UserPLEntry::Bundle(bundle)
       };
-      self
-        .paxos_driver
-        .insert_bundle(&mut SlavePaxosContext { io_ctx, this_eid: &self.this_eid }, user_entry);
+      let mut paxos_ctx = SlavePaxosContext { io_ctx, this_eid: &self.this_eid };
+      self.paxos_driver.insert_bundle(&mut paxos_ctx, user_entry);
+      if self.paxos_driver.can_propose_now(&paxos_ctx) {
+        for tid in io_ctx.all_tids() {
+          io_ctx.tablet_forward(&tid, TabletForwardMsg::FlushBundle).unwrap();
+        }
+      }
     }
   }
 
@@ -1054,12 +1250,14 @@ impl SlaveContext {
       CreateTableRMAction::Wait => {}
       CreateTableRMAction::Exit(maybe_commit_action) => {
         statuses.create_table_ess.remove(&query_id);
-        if let Some(tablet_ctx) = maybe_commit_action {
-          // This means the ES had Committed, so we create the Tablet.
-          let this_tid = tablet_ctx.this_tid.clone();
-          io_ctx.create_tablet(tablet_ctx);
-          // We amend tablet_bundles with an initial value, as per SharedPaxosInserter
-          self.tablet_bundles.insert(this_tid, TabletBundle::default());
+        if let Some(tablet_ctxs) = maybe_commit_action {
+          // This means the ES had Committed, so we create the Tablets.
+          for tablet_ctx in tablet_ctxs {
+            let this_tid = tablet_ctx.this_tid.clone();
+            io_ctx.create_tablet(tablet_ctx);
+            // We amend tablet_bundles with an initial value, as per SharedPaxosInserter
+            self.tablet_bundles.insert(this_tid, TabletBundle::default());
+          }
         }
       }
     }
@@ -1067,7 +1265,12 @@ impl SlaveContext {
 
   /// Checks if the incoming `master_gossip` has a more recent `gen`, and starts inserting
   /// that into the `slave_bundle` if so.
-  fn handle_master_gossip(&mut self, master_gossip: msg::MasterGossip) {
+  fn handle_master_gossip<IO: SlaveIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    master_gossip: msg::MasterGossip,
+  ) {
+    self.clock.update(io_ctx, &master_gossip.sender_time);
     let incoming_gossip = master_gossip.gossip_data;
     let incoming_leader_map = master_gossip.leader_map;
     if self.gossip.get_gen() < incoming_gossip.get_gen() {
@@ -1082,6 +1285,115 @@ impl SlaveContext {
     }
   }
 
+  /// Starts gathering the ESs running at this node for `show`. The ESs at the Slave are
+  /// listed directly, and every Tablet and Coord is asked for theirs.
+  fn start_show_queries<IO: SlaveIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    show: msg::ExternalShowQueries,
+  ) {
+    let server = format!("Slave {}", self.this_sid.0);
+    let mut queries = Vec::<msg::RunningQuery>::new();
+    for (query_id, es) in &statuses.create_table_ess {
+      queries.push(msg::RunningQuery {
+        server: server.clone(),
+        es_type: "CreateTableRMES".to_string(),
+        query_id: query_id.clone(),
+        root_query_id: query_id.clone(),
+        request_id: None,
+        state: es.state_name().to_string(),
+        age_ms: None,
+      });
+    }
+
+    let all_tids = io_ctx.all_tids();
+    let all_cids = io_ctx.all_cids();
+    let pending = PendingShowQueries {
+      sender_eid: show.sender_eid,
+      num_remaining: all_tids.len() + all_cids.len(),
+      queries,
+    };
+    if pending.num_remaining == 0 {
+      self.send_show_queries_response(io_ctx, show.request_id, pending);
+    } else {
+      for tid in all_tids {
+        let forward_msg = TabletForwardMsg::ShowQueries(show.request_id.clone());
+        io_ctx.tablet_forward(&tid, forward_msg).unwrap();
+      }
+      for cid in all_cids {
+        io_ctx.coord_forward(&cid, CoordForwardMsg::ShowQueries(show.request_id.clone()));
+      }
+      statuses.pending_show_queries.insert(show.request_id, pending);
+    }
+  }
+
+  /// Responds to an `ExternalShowQueries` once all ESs have been gathered.
+  fn send_show_queries_response<IO: SlaveIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    request_id: RequestId,
+    pending: PendingShowQueries,
+  ) {
+    io_ctx.send(
+      &pending.sender_eid,
+      msg::NetworkMessage::External(msg::ExternalMessage::ExternalShowQueriesResponse(
+        msg::ExternalShowQueriesResponse { request_id, queries: pending.queries },
+      )),
+    );
+  }
+
+  /// Starts handing Leadership of this SlaveGroup over to the `target_eid` of `transfer`
+  /// (see `PaxosDriver::transfer_leadership`). The response is sent once it finishes.
+  fn start_leadership_transfer<IO: SlaveIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    transfer: msg::ExternalTransferLeadership,
+  ) {
+    let result = if transfer.gid != self.this_gid
+      || !self.paxos_driver.paxos_nodes().contains(&transfer.target_eid)
+    {
+      msg::TransferLeadershipResult::InvalidTarget
+    } else if transfer.target_eid == self.this_eid {
+      // Here, the target is already the Leader.
+      let lid = self.leader_map.value().get(&self.this_gid).unwrap();
+      msg::TransferLeadershipResult::Transferred(lid.clone())
+    } else if statuses.leadership_transfer.is_none()
+      && self.paxos_driver.transfer_leadership(
+        &mut SlavePaxosContext { io_ctx, this_eid: &self.this_eid },
+        transfer.target_eid.clone(),
+      )
+    {
+      statuses.leadership_transfer = Some(transfer);
+      return;
+    } else {
+      msg::TransferLeadershipResult::Aborted
+    };
+
+    let (eid, response) = transfer.response(result);
+    io_ctx.send(&eid, response);
+  }
+
+  /// Responds to the `ExternalTransferLeadership` that this node was carrying out (if any)
+  /// now that the Leadership has changed to `lid`.
+  fn finish_leadership_transfer<IO: SlaveIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    lid: &LeadershipId,
+  ) {
+    if let Some(transfer) = statuses.leadership_transfer.take() {
+      let result = if lid.eid == transfer.target_eid {
+        msg::TransferLeadershipResult::Transferred(lid.clone())
+      } else {
+        msg::TransferLeadershipResult::Aborted
+      };
+      let (eid, response) = transfer.response(result);
+      io_ctx.send(&eid, response);
+    }
+  }
+
   /// Returns true iff this is the Leader.
   pub fn is_leader(&self) -> bool {
     let lid = self.leader_map.value().get(&self.this_gid).unwrap();
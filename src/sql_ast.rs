@@ -3,8 +3,8 @@
 // -------------------------------------------------------------------------------------------------
 
 pub mod proc {
-  use crate::common::{ColName, ColType, TablePath, TransTableName};
-  use crate::sql_ast::iast::{BinaryOp, JoinType, UnaryAggregateOp, UnaryOp, Value};
+  use crate::common::{ColName, ColType, ColVal, TablePath, TransTableName};
+  use crate::sql_ast::iast::{BinaryOp, JoinType, LockMode, UnaryAggregateOp, UnaryOp, Value};
   use serde::{Deserialize, Serialize};
   use std::collections::BTreeMap;
 
@@ -71,6 +71,8 @@ pub mod proc {
     pub projection: Vec<SelectItem>,
     pub from: TableSource,
     pub selection: ValExpr,
+    /// This is only set for `MSQueryStage`s.
+    pub lock: Option<LockMode>,
 
     /// The TransTable Schema produced by this query
     pub schema: Vec<Option<ColName>>,
@@ -231,14 +233,67 @@ pub mod proc {
     pub table_path: TablePath,
     pub key_cols: Vec<(ColName, ColType)>,
     pub val_cols: Vec<(ColName, ColType)>,
+    /// If present, the Table is sharded by hashing a KeyCol instead of by key ranges.
+    pub hash_partition: Option<HashPartition>,
+    /// The values of the first KeyCol at which the Table should initially be split, in
+    /// ascending order. This is empty if the Table should not be pre-split.
+    pub split_keys: Vec<ColVal>,
   }
 
   #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-  pub struct AlterOp {
+  pub struct HashPartition {
+    /// This must be one of the KeyCols.
+    pub col_name: ColName,
+    pub num_partitions: u32,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct AlterColumn {
     pub col_name: ColName,
     /// If the `ColName` is being deleted, then this is `None`. Otherwise, it takes
     /// on the target `ColType`.
     pub maybe_col_type: Option<ColType>,
+    /// The DEFAULT value of the column being added, if any.
+    pub maybe_default: Option<ColVal>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct RenameColumn {
+    /// This must be a ValCol.
+    pub old_col_name: ColName,
+    pub new_col_name: ColName,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct RenameTable {
+    pub new_table_path: TablePath,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct ChangeColumnType {
+    /// This must be a ValCol.
+    pub col_name: ColName,
+    pub col_type: ColType,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub enum AlterOp {
+    AlterColumn(AlterColumn),
+    RenameColumn(RenameColumn),
+    RenameTable(RenameTable),
+    ChangeColumnType(ChangeColumnType),
+  }
+
+  impl AlterOp {
+    /// Returns the ValCols whose presence or type in the Table this `AlterOp` changes.
+    pub fn cols(&self) -> Vec<ColName> {
+      match self {
+        AlterOp::AlterColumn(op) => vec![op.col_name.clone()],
+        AlterOp::RenameColumn(op) => vec![op.old_col_name.clone(), op.new_col_name.clone()],
+        AlterOp::RenameTable(_) => vec![],
+        AlterOp::ChangeColumnType(op) => vec![op.col_name.clone()],
+      }
+    }
   }
 
   #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -324,6 +379,9 @@ pub mod iast {
     QuotedString(String),
     Boolean(bool),
     Null,
+    /// A `$n` bind parameter of a prepared statement (where `n` starts from 1). These are
+    /// replaced with the bound values after the query is planned.
+    Param(u32),
   }
 
   // Join
@@ -441,6 +499,19 @@ pub mod iast {
     pub projection: Vec<SelectItem>,
     pub from: JoinNode,
     pub selection: ValExpr, // The where clause
+    /// Set by a trailing `FOR UPDATE` or `FOR SHARE`.
+    pub lock: Option<LockMode>,
+  }
+
+  /// The lock that a `SELECT ... FOR UPDATE` or `SELECT ... FOR SHARE` takes on the rows
+  /// that it reads, which is held until the MSQuery finishes.
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  pub enum LockMode {
+    /// The ReadRegion is also registered as a WriteRegion, so that no subsequent read
+    /// can observe the rows before the MSQuery finishes.
+    Update,
+    /// The ReadRegion is kept read protected even under `IsolationLevel::Snapshot`.
+    Share,
   }
 
   #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -1,10 +1,14 @@
-use crate::common::TablePath;
-use crate::common::{ColName, ColType};
+use crate::common::{lookup, IsolationLevel, TablePath};
+use crate::common::{ColName, ColType, ColVal, ColValN};
+use crate::expression::{coerce_col_valn, construct_colvaln, does_types_match};
 use crate::sql_ast::{iast, proc};
 use sqlparser::ast;
+use sqlparser::dialect::keywords::Keyword;
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::{Parser, ParserError};
 use sqlparser::test_utils::table;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace, Word};
+use std::cmp::max;
 use std::collections::BTreeMap;
 
 // -----------------------------------------------------------------------------------------------
@@ -59,6 +63,14 @@ fn get_column_ref(idents: Vec<ast::Ident>) -> Result<iast::ValExpr, String> {
 /// we can transform all DML and DQL transactions together into a single Query, which is
 /// what we do here.
 pub fn convert_ast(raw_query: Vec<ast::Statement>) -> Result<iast::Query, String> {
+  convert_locked_ast(raw_query.into_iter().map(|stmt| (stmt, None)).collect())
+}
+
+/// Same as `convert_ast`, except each statement is paired with the `LockMode` of its
+/// trailing `FOR UPDATE` or `FOR SHARE` clause, if it has one.
+fn convert_locked_ast(
+  raw_query: Vec<(ast::Statement, Option<iast::LockMode>)>,
+) -> Result<iast::Query, String> {
   if raw_query.is_empty() {
     return Err(format!("A SQL Transaction with no stages is not supported."));
   }
@@ -68,12 +80,13 @@ pub fn convert_ast(raw_query: Vec<ast::Statement>) -> Result<iast::Query, String
 
   // Add all prior stages as CTEs by setting their results to Transient Tables.
   let mut ctes = Vec::<(String, iast::Query)>::new();
-  while let Some((idx, stmt)) = it.next() {
-    ctes.push((format!("\\rtt{:?}", idx), convert_stage(stmt)?));
+  while let Some((idx, (stmt, lock))) = it.next() {
+    ctes.push((format!("\\rtt{:?}", idx), convert_locked_stage(stmt, lock)?));
   }
 
   // Add the final stage to the query
-  let mut ret_query = convert_stage(final_stmt)?;
+  let (final_stmt, final_lock) = final_stmt;
+  let mut ret_query = convert_locked_stage(final_stmt, final_lock)?;
   ctes.extend(ret_query.ctes);
   ret_query.ctes = ctes;
   Ok(ret_query)
@@ -95,6 +108,22 @@ pub fn convert_stage(stmt: ast::Statement) -> Result<iast::Query, String> {
   }
 }
 
+/// Converts `stmt` like `convert_stage`, and then applies `lock` to it. Only a SELECT can
+/// have a `LockMode`.
+fn convert_locked_stage(
+  stmt: ast::Statement,
+  lock: Option<iast::LockMode>,
+) -> Result<iast::Query, String> {
+  let mut query = convert_stage(stmt)?;
+  if let Some(lock) = lock {
+    match &mut query.body {
+      iast::QueryBody::Select(select) => select.lock = Some(lock),
+      _ => return Err(format!("{} is only supported on a SELECT.", lock_clause(lock))),
+    }
+  }
+  Ok(query)
+}
+
 fn convert_query(query: ast::Query) -> Result<iast::Query, String> {
   let mut ictes = Vec::<(String, iast::Query)>::new();
   if let Some(with) = query.with {
@@ -136,6 +165,7 @@ fn convert_select(select: ast::Select) -> Result<iast::QueryBody, String> {
     } else {
       iast::ValExpr::Value { val: iast::Value::Boolean(true) }
     },
+    lock: None,
   }))
 }
 
@@ -377,7 +407,10 @@ fn convert_value(value: ast::Value) -> Result<iast::Value, String> {
 
 pub fn convert_expr(expr: ast::Expr) -> Result<iast::ValExpr, String> {
   Ok(match expr {
-    ast::Expr::Identifier(ident) => get_column_ref(vec![ident])?,
+    ast::Expr::Identifier(ident) => match param_index(&ident) {
+      Some(n) => iast::ValExpr::Value { val: iast::Value::Param(n) },
+      None => get_column_ref(vec![ident])?,
+    },
     ast::Expr::CompoundIdentifier(idents) => get_column_ref(idents)?,
     ast::Expr::UnaryOp { op, expr } => {
       let iop = match op {
@@ -530,7 +563,13 @@ pub fn convert_ddl_ast(raw_query: Vec<ast::Statement>) -> Result<DDLQuery, Strin
           val_cols.push((col_name, col_type));
         }
       }
-      Ok(DDLQuery::Create(proc::CreateTable { table_path, key_cols, val_cols }))
+      Ok(DDLQuery::Create(proc::CreateTable {
+        table_path,
+        key_cols,
+        val_cols,
+        hash_partition: None,
+        split_keys: vec![],
+      }))
     }
     ast::Statement::Drop { names, .. } => {
       let name = names.into_iter().next().unwrap();
@@ -538,20 +577,55 @@ pub fn convert_ddl_ast(raw_query: Vec<ast::Statement>) -> Result<DDLQuery, Strin
       Ok(DDLQuery::Drop(proc::DropTable { table_path }))
     }
     ast::Statement::AlterTable { name, operation } => match operation {
-      ast::AlterTableOperation::AddColumn { column_def } => Ok(DDLQuery::Alter(proc::AlterTable {
-        table_path: TablePath(get_table_name(name.0.clone())?),
-        alter_op: proc::AlterOp {
-          col_name: ColName(column_def.name.value.clone()),
-          maybe_col_type: Some(convert_data_type(&column_def.data_type)?),
-        },
-      })),
+      ast::AlterTableOperation::AddColumn { column_def } => {
+        let col_type = convert_data_type(&column_def.data_type)?;
+        // Read the DEFAULT value, if there is one.
+        let mut maybe_default = None;
+        for option_def in &column_def.options {
+          match &option_def.option {
+            ast::ColumnOption::Default(expr) => {
+              maybe_default = coerce_col_valn(&col_type, convert_literal(expr)?);
+              if !does_types_match(&col_type, maybe_default.as_ref()) {
+                return Err(format!("Invalid default {:?}.", expr));
+              }
+            }
+            _ => return Err(format!("Unsupported column option {:?}.", option_def)),
+          }
+        }
+        Ok(DDLQuery::Alter(proc::AlterTable {
+          table_path: TablePath(get_table_name(name.0.clone())?),
+          alter_op: proc::AlterOp::AlterColumn(proc::AlterColumn {
+            col_name: ColName(column_def.name.value.clone()),
+            maybe_col_type: Some(col_type),
+            maybe_default,
+          }),
+        }))
+      }
       ast::AlterTableOperation::DropColumn { column_name, .. } => {
         Ok(DDLQuery::Alter(proc::AlterTable {
           table_path: TablePath(get_table_name(name.0.clone())?),
-          alter_op: proc::AlterOp {
+          alter_op: proc::AlterOp::AlterColumn(proc::AlterColumn {
             col_name: ColName(column_name.value.clone()),
             maybe_col_type: None,
-          },
+            maybe_default: None,
+          }),
+        }))
+      }
+      ast::AlterTableOperation::RenameColumn { old_column_name, new_column_name } => {
+        Ok(DDLQuery::Alter(proc::AlterTable {
+          table_path: TablePath(get_table_name(name.0.clone())?),
+          alter_op: proc::AlterOp::RenameColumn(proc::RenameColumn {
+            old_col_name: ColName(old_column_name.value.clone()),
+            new_col_name: ColName(new_column_name.value.clone()),
+          }),
+        }))
+      }
+      ast::AlterTableOperation::RenameTable { table_name } => {
+        Ok(DDLQuery::Alter(proc::AlterTable {
+          table_path: TablePath(get_table_name(name.0.clone())?),
+          alter_op: proc::AlterOp::RenameTable(proc::RenameTable {
+            new_table_path: TablePath(get_table_name(table_name.0.clone())?),
+          }),
         }))
       }
       _ => Err(format!("Unsupported ast::Statement {:?}", stmt)),
@@ -563,6 +637,7 @@ pub fn convert_ddl_ast(raw_query: Vec<ast::Statement>) -> Result<DDLQuery, Strin
 pub fn convert_data_type(raw_data_type: &ast::DataType) -> Result<ColType, String> {
   match raw_data_type {
     ast::DataType::Int => Ok(ColType::Int),
+    ast::DataType::BigInt => Ok(ColType::BigInt),
     ast::DataType::Boolean => Ok(ColType::Bool),
     ast::DataType::String => Ok(ColType::String),
     ast::DataType::Varchar(_) => Ok(ColType::String),
@@ -570,10 +645,545 @@ pub fn convert_data_type(raw_data_type: &ast::DataType) -> Result<ColType, Strin
   }
 }
 
+/// Extract the error message from a `ParserError`.
+fn parser_error_msg(parse_error: ParserError) -> String {
+  match parse_error {
+    ParserError::TokenizerError(err_msg) => err_msg,
+    ParserError::ParserError(err_msg) => err_msg,
+  }
+}
+
+/// Consumes the next token, which must be the (non-reserved) word `expected`.
+fn expect_word(parser: &mut Parser, expected: &str) -> Result<(), ParserError> {
+  match parser.next_token() {
+    Token::Word(word) if word.value.to_uppercase() == expected => Ok(()),
+    token => Err(ParserError::ParserError(format!("Expected {}, found: {}", expected, token))),
+  }
+}
+
+/// The maximum number of partitions a hash-partitioned Table may be created with. Each
+/// partition becomes its own Tablet in a single `CreateTable`, so this is kept small.
+pub const MAX_HASH_PARTITIONS: u64 = 256;
+
+/// Parses the remainder of `PARTITION BY HASH(col) PARTITIONS n`, where `PARTITION BY`
+/// has already been consumed.
+fn parse_hash_partition(parser: &mut Parser) -> Result<proc::HashPartition, ParserError> {
+  expect_word(parser, "HASH")?;
+  parser.expect_token(&Token::LParen)?;
+  let col_name = ColName(parser.parse_identifier()?.value);
+  parser.expect_token(&Token::RParen)?;
+  parser.expect_keyword(Keyword::PARTITIONS)?;
+  let num_partitions = parser.parse_literal_uint()?;
+  if num_partitions == 0 || num_partitions > MAX_HASH_PARTITIONS {
+    return Err(ParserError::ParserError(format!(
+      "Invalid number of partitions {}.",
+      num_partitions
+    )));
+  }
+  Ok(proc::HashPartition { col_name, num_partitions: num_partitions as u32 })
+}
+
+/// Parses the remainder of `SPLIT AT (v1), (v2), ...`, where `SPLIT` has already been consumed.
+fn parse_split_at(parser: &mut Parser) -> Result<Vec<ast::Expr>, ParserError> {
+  parser.expect_keyword(Keyword::AT)?;
+  parser.parse_comma_separated(|parser| {
+    parser.expect_token(&Token::LParen)?;
+    let expr = parser.parse_expr()?;
+    parser.expect_token(&Token::RParen)?;
+    Ok(expr)
+  })
+}
+
+/// Parses `ALTER TABLE t ALTER [COLUMN] c [SET DATA] TYPE <type>`, which `sqlparser` does
+/// not support. This returns `None` if the tokens do not start such a statement.
+fn parse_alter_column_type(parser: &mut Parser) -> Option<Result<proc::AlterTable, ParserError>> {
+  if !parser.parse_keywords(&[Keyword::ALTER, Keyword::TABLE]) {
+    return None;
+  }
+  let table_name = match parser.parse_object_name() {
+    Ok(table_name) => table_name,
+    Err(err) => return Some(Err(err)),
+  };
+  if !parser.parse_keyword(Keyword::ALTER) {
+    return None;
+  }
+  Some((|| {
+    let _ = parser.parse_keyword(Keyword::COLUMN); // The COLUMN keyword is optional.
+    let col_name = ColName(parser.parse_identifier()?.value);
+    if parser.parse_keyword(Keyword::SET) {
+      expect_word(parser, "DATA")?;
+    }
+    expect_word(parser, "TYPE")?;
+    let col_type =
+      convert_data_type(&parser.parse_data_type()?).map_err(ParserError::ParserError)?;
+    let table_path = TablePath(get_table_name(table_name.0).map_err(ParserError::ParserError)?);
+    Ok(proc::AlterTable {
+      table_path,
+      alter_op: proc::AlterOp::ChangeColumnType(proc::ChangeColumnType { col_name, col_type }),
+    })
+  })())
+}
+
+/// Consumes any trailing semicolons, and then verifies there is nothing left.
+fn expect_end_of_statement(parser: &mut Parser) -> Result<(), String> {
+  while parser.consume_token(&Token::SemiColon) {}
+  if parser.peek_token() != Token::EOF {
+    Err(format!("Expected end of statement, found: {}", parser.peek_token()))
+  } else {
+    Ok(())
+  }
+}
+
+/// Converts `expr` into an `iast::Value`, where only literals (possibly negated) are allowed.
+fn convert_literal_value(expr: &ast::Expr) -> Result<iast::Value, String> {
+  match convert_expr(expr.clone())? {
+    iast::ValExpr::Value { val: iast::Value::Param(_) } => {
+      Err(format!("Invalid literal {:?}.", expr))
+    }
+    iast::ValExpr::Value { val } => Ok(val),
+    iast::ValExpr::UnaryExpr { op: iast::UnaryOp::Minus, expr: inner } => match *inner {
+      iast::ValExpr::Value { val: iast::Value::Number(num) } => {
+        Ok(iast::Value::Number(format!("-{}", num)))
+      }
+      _ => Err(format!("Invalid literal {:?}.", expr)),
+    },
+    _ => Err(format!("Invalid literal {:?}.", expr)),
+  }
+}
+
+/// Converts `expr` into a value, where only literals (possibly negated) are allowed.
+fn convert_literal(expr: &ast::Expr) -> Result<ColValN, String> {
+  let val = convert_literal_value(expr)?;
+  construct_colvaln(val).map_err(|_| format!("Invalid literal {:?}.", expr))
+}
+
+/// Converts the `split_exprs` of a `SPLIT AT` clause into values of the first KeyCol
+/// of `create_table`, verifying that they are in strictly ascending order.
+fn convert_split_keys(
+  create_table: &proc::CreateTable,
+  split_exprs: Vec<ast::Expr>,
+) -> Result<Vec<ColVal>, String> {
+  let (_, first_key_type) = match create_table.key_cols.first() {
+    Some(first_key) => first_key,
+    None => return Err(format!("Cannot split a Table without KeyCols.")),
+  };
+  let mut split_keys = Vec::<ColVal>::new();
+  for split_expr in split_exprs {
+    let split_key = convert_literal(&split_expr)
+      .ok()
+      .flatten()
+      .and_then(|split_key| coerce_col_valn(first_key_type, Some(split_key)))
+      .filter(|split_key| does_types_match(first_key_type, Some(split_key)))
+      .ok_or(format!("Invalid split key {:?}.", split_expr))?;
+    if let Some(prev_split_key) = split_keys.last() {
+      if prev_split_key >= &split_key {
+        return Err(format!("Split keys must be in strictly ascending order."));
+      }
+    }
+    split_keys.push(split_key);
+  }
+  Ok(split_keys)
+}
+
+/// Parses a DDL query into an internal DDL struct. Beyond what `sqlparser` supports, this
+/// also accepts a trailing `PARTITION BY HASH(col) PARTITIONS n` or `SPLIT AT (v1), ...`
+/// in a CREATE TABLE, and `ALTER TABLE t ALTER COLUMN c TYPE <type>`.
+pub fn parse_ddl(query: &str) -> Result<DDLQuery, String> {
+  let dialect = GenericDialect {};
+  let tokens = Tokenizer::new(&dialect, query)
+    .tokenize()
+    .map_err(|err| parser_error_msg(ParserError::from(err)))?;
+
+  // Try parsing an ALTER COLUMN first, since `sqlparser` cannot.
+  let mut alter_parser = Parser::new(tokens.clone(), &dialect);
+  if let Some(alter_table) = parse_alter_column_type(&mut alter_parser) {
+    let alter_table = alter_table.map_err(parser_error_msg)?;
+    expect_end_of_statement(&mut alter_parser)?;
+    return Ok(DDLQuery::Alter(alter_table));
+  }
+
+  let mut parser = Parser::new(tokens, &dialect);
+  let stmt = parser.parse_statement().map_err(parser_error_msg)?;
+
+  // Parse the clauses that can follow a CREATE TABLE.
+  let mut hash_partition = None;
+  let mut split_exprs = vec![];
+  if let ast::Statement::CreateTable { .. } = &stmt {
+    if parser.parse_keywords(&[Keyword::PARTITION, Keyword::BY]) {
+      hash_partition = Some(parse_hash_partition(&mut parser).map_err(parser_error_msg)?);
+    } else if let Token::Word(word) = parser.peek_token() {
+      if word.value.to_uppercase() == "SPLIT" {
+        parser.next_token();
+        split_exprs = parse_split_at(&mut parser).map_err(parser_error_msg)?;
+      }
+    }
+  }
+
+  // Only a single statement (with optional trailing semicolons) is allowed.
+  expect_end_of_statement(&mut parser)?;
+
+  let mut ddl_query = convert_ddl_ast(vec![stmt])?;
+  if let DDLQuery::Create(create_table) = &mut ddl_query {
+    if let Some(hash_partition) = &hash_partition {
+      if lookup(&create_table.key_cols, &hash_partition.col_name).is_none() {
+        return Err(format!(
+          "Hash partitioning column {:?} is not a KeyCol.",
+          hash_partition.col_name
+        ));
+      }
+    }
+    create_table.hash_partition = hash_partition;
+    if !split_exprs.is_empty() {
+      create_table.split_keys = convert_split_keys(create_table, split_exprs)?;
+    }
+  }
+  Ok(ddl_query)
+}
+
 /// Computes whether this SQL Query is a DDL query by attempting to parse it as such.
 pub fn is_ddl(query: &str) -> bool {
-  match Parser::parse_sql(&GenericDialect {}, &query) {
-    Ok(parsed_ast) => convert_ddl_ast(parsed_ast).is_ok(),
+  parse_ddl(query).is_ok()
+}
+
+/// Returns true iff `query` only reads data, so running it more than once has no effect.
+pub fn is_read_only(query: &str) -> bool {
+  match parse_sql_with_params(query) {
+    Ok((stmts, _)) => stmts.iter().all(|stmt| match stmt {
+      ast::Statement::Query(query) => is_read_only_query(query),
+      ast::Statement::SetTransaction { .. } => true,
+      ast::Statement::Explain { analyze: false, .. } => true,
+      ast::Statement::Explain { analyze: true, statement, .. } => match statement.as_ref() {
+        ast::Statement::Query(query) => is_read_only_query(query),
+        _ => false,
+      },
+      _ => false,
+    }),
     Err(_) => false,
   }
 }
+
+fn is_read_only_query(query: &ast::Query) -> bool {
+  if let Some(with) = &query.with {
+    if !with.cte_tables.iter().all(|cte| is_read_only_query(&cte.query)) {
+      return false;
+    }
+  }
+  is_read_only_set_expr(&query.body)
+}
+
+fn is_read_only_set_expr(set_expr: &ast::SetExpr) -> bool {
+  match set_expr {
+    ast::SetExpr::Query(query) => is_read_only_query(query),
+    ast::SetExpr::SetOperation { left, right, .. } => {
+      is_read_only_set_expr(left) && is_read_only_set_expr(right)
+    }
+    ast::SetExpr::Insert(_) => false,
+    ast::SetExpr::Select(_) | ast::SetExpr::Values(_) => true,
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Bind Parameters
+// -----------------------------------------------------------------------------------------------
+
+/// Returns `n` if `ident` is the `$n` bind parameter. Recall that `tokenize_with_params`
+/// turns these into identifiers, which otherwise cannot start with a `$`.
+fn param_index(ident: &ast::Ident) -> Option<u32> {
+  if ident.quote_style.is_none() && ident.value.starts_with('$') {
+    ident.value[1..].parse::<u32>().ok().filter(|n| *n > 0)
+  } else {
+    None
+  }
+}
+
+/// Tokenizes `query`, turning every `$n` bind parameter into an identifier (since `sqlparser`
+/// cannot parse them otherwise). This also returns the number of parameters, which is the
+/// highest `n` that is used. Similarly, the isolation level `SNAPSHOT` is turned into
+/// `REPEATABLE READ` (see `convert_isolation_level`).
+fn tokenize_with_params(query: &str) -> Result<(Vec<Token>, u32), ParserError> {
+  let tokens = Tokenizer::new(&GenericDialect {}, query).tokenize()?;
+  let mut num_params = 0;
+  let mut param_tokens = Vec::<Token>::new();
+  let mut it = tokens.into_iter().peekable();
+  while let Some(token) = it.next() {
+    if token == Token::Char('$') {
+      let opt_n = match it.peek() {
+        Some(Token::Number(digits, false)) => digits.parse::<u32>().ok().filter(|n| *n > 0),
+        _ => None,
+      };
+      if let Some(n) = opt_n {
+        it.next();
+        num_params = max(num_params, n);
+        param_tokens.push(Token::Word(Word {
+          value: format!("${}", n),
+          quote_style: None,
+          keyword: Keyword::NoKeyword,
+        }));
+        continue;
+      }
+    }
+    if is_snapshot_isolation_level(&param_tokens, &token) {
+      param_tokens.push(Token::make_keyword("REPEATABLE"));
+      param_tokens.push(Token::Whitespace(Whitespace::Space));
+      param_tokens.push(Token::make_keyword("READ"));
+      continue;
+    }
+    param_tokens.push(token);
+  }
+  Ok((param_tokens, num_params))
+}
+
+/// Returns true iff `token` is `SNAPSHOT` and `prev_tokens` ends with `ISOLATION LEVEL`.
+fn is_snapshot_isolation_level(prev_tokens: &Vec<Token>, token: &Token) -> bool {
+  match token {
+    Token::Word(word) if word.value.to_uppercase() == "SNAPSHOT" => {
+      let mut prev_words = prev_tokens.iter().rev().filter_map(|token| match token {
+        Token::Whitespace(_) => None,
+        Token::Word(word) => Some(word.keyword),
+        _ => Some(Keyword::NoKeyword),
+      });
+      prev_words.next() == Some(Keyword::LEVEL) && prev_words.next() == Some(Keyword::ISOLATION)
+    }
+    _ => false,
+  }
+}
+
+/// Parses `query` into (possibly multiple) `sqlparser` statements, where `$n` bind parameters
+/// are allowed. This also returns the number of parameters.
+pub fn parse_sql_with_params(query: &str) -> Result<(Vec<ast::Statement>, u32), String> {
+  let (stmts, num_params) = parse_sql_with_locks(query)?;
+  Ok((stmts.into_iter().map(|(stmt, _)| stmt).collect(), num_params))
+}
+
+/// Same as `parse_sql_with_params`, except each statement is also paired with the `LockMode`
+/// of its trailing `FOR UPDATE` or `FOR SHARE` clause (which `sqlparser` cannot parse).
+fn parse_sql_with_locks(
+  query: &str,
+) -> Result<(Vec<(ast::Statement, Option<iast::LockMode>)>, u32), String> {
+  let (tokens, num_params) = tokenize_with_params(query).map_err(parser_error_msg)?;
+  let (tokens, locks) = take_lock_clauses(tokens);
+  let mut parser = Parser::new(tokens, &GenericDialect {});
+  let mut stmts = Vec::<(ast::Statement, Option<iast::LockMode>)>::new();
+  let mut expecting_delimiter = false;
+  loop {
+    while parser.consume_token(&Token::SemiColon) {
+      expecting_delimiter = false;
+    }
+    if parser.peek_token() == Token::EOF {
+      break;
+    }
+    if expecting_delimiter {
+      return Err(format!("Expected end of statement, found: {}", parser.peek_token()));
+    }
+    let stmt = parser.parse_statement().map_err(parser_error_msg)?;
+    let lock = locks.get(stmts.len()).cloned().flatten();
+    stmts.push((stmt, lock));
+    expecting_delimiter = true;
+  }
+  Ok((stmts, num_params))
+}
+
+/// Removes the `FOR UPDATE` or `FOR SHARE` clause from the end of every statement in
+/// `tokens`, returning the `LockMode` of each (non-empty) statement. We cannot parse these
+/// clauses after the statement, since `sqlparser` would take `FOR` as a table alias.
+fn take_lock_clauses(tokens: Vec<Token>) -> (Vec<Token>, Vec<Option<iast::LockMode>>) {
+  let mut stmts_tokens = Vec::<Token>::new();
+  let mut locks = Vec::<Option<iast::LockMode>>::new();
+  for (idx, stmt_tokens) in tokens.split(|token| token == &Token::SemiColon).enumerate() {
+    let mut words = stmt_tokens.iter().enumerate().rev().filter_map(|(i, token)| match token {
+      Token::Whitespace(_) => None,
+      Token::Word(word) => Some((i, word.value.to_uppercase())),
+      _ => Some((i, String::new())),
+    });
+    let last_word = words.next();
+    let lock = match (&last_word, words.next()) {
+      (Some((_, last)), Some((i, second_last))) if second_last == "FOR" => match last.as_str() {
+        "UPDATE" => Some((i, iast::LockMode::Update)),
+        "SHARE" => Some((i, iast::LockMode::Share)),
+        _ => None,
+      },
+      _ => None,
+    };
+    if idx > 0 {
+      stmts_tokens.push(Token::SemiColon);
+    }
+    if let Some((i, lock)) = lock {
+      stmts_tokens.extend(stmt_tokens[..i].iter().cloned());
+      locks.push(Some(lock));
+    } else {
+      stmts_tokens.extend(stmt_tokens.iter().cloned());
+      if last_word.is_some() {
+        locks.push(None);
+      }
+    }
+  }
+  (stmts_tokens, locks)
+}
+
+/// Returns the SQL clause that sets `lock`.
+fn lock_clause(lock: iast::LockMode) -> &'static str {
+  match lock {
+    iast::LockMode::Update => "FOR UPDATE",
+    iast::LockMode::Share => "FOR SHARE",
+  }
+}
+
+/// How the Coord should handle a DQL query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryMode {
+  /// Run the query.
+  Execute,
+  /// Only plan the query, and respond with a description of the plan (i.e. `EXPLAIN`).
+  Explain,
+  /// Run the query, and respond with a description of the plan along with statistics
+  /// about how each stage executed (i.e. `EXPLAIN ANALYZE`).
+  ExplainAnalyze,
+}
+
+/// Parses the DQL `query` into an `iast::Query`, where `$n` bind parameters are allowed. This
+/// also returns the number of parameters, whether the query should be explained, and the
+/// `IsolationLevel` set by a leading `SET TRANSACTION`.
+pub fn parse_dql(query: &str) -> Result<(iast::Query, u32, QueryMode, IsolationLevel), String> {
+  let (mut stmts, num_params) = parse_sql_with_locks(query)?;
+  let isolation_level = take_isolation_level(&mut stmts)?;
+  let mode = match stmts.as_slice() {
+    [(ast::Statement::Explain { .. }, _)] => match stmts.pop().unwrap() {
+      (ast::Statement::Explain { statement, analyze, .. }, lock) => {
+        stmts.push((*statement, lock));
+        if analyze {
+          QueryMode::ExplainAnalyze
+        } else {
+          QueryMode::Explain
+        }
+      }
+      _ => unreachable!(),
+    },
+    _ => QueryMode::Execute,
+  };
+  Ok((convert_locked_ast(stmts)?, num_params, mode, isolation_level))
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Isolation Levels
+// -----------------------------------------------------------------------------------------------
+
+/// Removes the leading `SET TRANSACTION` from `stmts` if there is one, returning the
+/// `IsolationLevel` that it sets. Otherwise, this returns the default `IsolationLevel`.
+fn take_isolation_level(
+  stmts: &mut Vec<(ast::Statement, Option<iast::LockMode>)>,
+) -> Result<IsolationLevel, String> {
+  if !matches!(stmts.first(), Some((ast::Statement::SetTransaction { .. }, None))) {
+    return Ok(IsolationLevel::default());
+  }
+
+  let mut isolation_level = IsolationLevel::default();
+  if let (ast::Statement::SetTransaction { modes }, _) = stmts.remove(0) {
+    for mode in modes {
+      match mode {
+        ast::TransactionMode::IsolationLevel(level) => {
+          isolation_level = convert_isolation_level(level)?;
+        }
+        ast::TransactionMode::AccessMode(_) => {
+          return Err(format!("Setting the transaction access mode is not supported."));
+        }
+      }
+    }
+  }
+  Ok(isolation_level)
+}
+
+/// Converts the isolation level. Like PostgreSQL, we provide SNAPSHOT isolation under the
+/// name `REPEATABLE READ`, which is also what `tokenize_with_params` turns `SNAPSHOT` into.
+fn convert_isolation_level(
+  level: ast::TransactionIsolationLevel,
+) -> Result<IsolationLevel, String> {
+  match level {
+    ast::TransactionIsolationLevel::Serializable => Ok(IsolationLevel::Serializable),
+    ast::TransactionIsolationLevel::RepeatableRead => Ok(IsolationLevel::Snapshot),
+    level => Err(format!("Isolation level {} is not supported.", level)),
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Prepared Statements
+// -----------------------------------------------------------------------------------------------
+
+/// The SQL statements that manage the prepared statements of a session. These are handled by
+/// the External itself, which sends the prepared query text along with the bound values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreparedStmtCommand {
+  /// `PREPARE name [(type, ...)] AS query`
+  Prepare { name: String, query: String },
+  /// `EXECUTE name [(value, ...)]`
+  Execute { name: String, params: Vec<iast::Value> },
+  /// `DEALLOCATE [PREPARE] name`
+  Deallocate { name: String },
+}
+
+/// Parses `query` as a `PreparedStmtCommand`. This returns `None` if `query` is not one.
+pub fn parse_prepared_stmt_command(query: &str) -> Option<Result<PreparedStmtCommand, String>> {
+  let (stmt, lock) = match parse_sql_with_locks(query) {
+    Ok((stmts, _)) if stmts.len() == 1 => stmts.into_iter().next().unwrap(),
+    _ => return None,
+  };
+  match stmt {
+    ast::Statement::Prepare { name, statement, .. } => {
+      // A trailing `FOR UPDATE` or `FOR SHARE` belongs to the prepared statement.
+      let mut query = statement.to_string();
+      if let Some(lock) = lock {
+        query = format!("{} {}", query, lock_clause(lock));
+      }
+      Some(Ok(PreparedStmtCommand::Prepare { name: name.value, query }))
+    }
+    ast::Statement::Execute { name, parameters } => Some((|| {
+      let mut params = Vec::<iast::Value>::new();
+      for param in &parameters {
+        params.push(convert_literal_value(param)?);
+      }
+      Ok(PreparedStmtCommand::Execute { name: name.value, params })
+    })()),
+    ast::Statement::Deallocate { name, .. } => {
+      Some(Ok(PreparedStmtCommand::Deallocate { name: name.value }))
+    }
+    _ => None,
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Admin Commands
+// -----------------------------------------------------------------------------------------------
+
+/// The statements for inspecting and controlling the requests running in the system. These
+/// are not SQL, so they are recognized before `query` is handed to the SQL parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+  /// `SHOW QUERIES`
+  ShowQueries,
+  /// `KILL QUERY request_id`, where `request_id` may be quoted.
+  KillQuery { request_id: String },
+}
+
+/// Parses `query` as an `AdminCommand`. This returns `None` if `query` is not one.
+pub fn parse_admin_command(query: &str) -> Option<Result<AdminCommand, String>> {
+  let query = query.trim().trim_end_matches(';');
+  let words: Vec<&str> = query.split_whitespace().collect();
+  let keyword = |i: usize| words.get(i).map(|word| word.to_uppercase());
+  match (keyword(0).as_deref(), keyword(1).as_deref()) {
+    (Some("SHOW"), Some("QUERIES")) => {
+      if words.len() == 2 {
+        Some(Ok(AdminCommand::ShowQueries))
+      } else {
+        Some(Err("SHOW QUERIES takes no arguments.".to_string()))
+      }
+    }
+    (Some("KILL"), Some("QUERY")) => {
+      if words.len() == 3 {
+        let request_id = words[2].trim_matches(|c| c == '\'' || c == '"');
+        Some(Ok(AdminCommand::KillQuery { request_id: request_id.to_string() }))
+      } else {
+        Some(Err("KILL QUERY takes a single RequestId.".to_string()))
+      }
+    }
+    _ => None,
+  }
+}
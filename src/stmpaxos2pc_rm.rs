@@ -1,5 +1,5 @@
-use crate::common::BasicIOCtx;
 use crate::common::QueryId;
+use crate::common::{BasicIOCtx, HybridClock};
 use crate::paxos2pc_tm::Paxos2PCContainer;
 use crate::stmpaxos2pc_tm::{Closed, Commit, Prepared, RMMessage, TMMessage, TMPayloadTypes};
 use serde::de::DeserializeOwned;
@@ -24,6 +24,8 @@ pub trait RMServerContext<T: RMPayloadTypes> {
   fn mk_node_path(&self) -> <<T as RMPayloadTypes>::TM as TMPayloadTypes>::RMPath;
 
   fn is_leader(&self) -> bool;
+
+  fn clock(&mut self) -> &mut HybridClock;
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -206,6 +208,19 @@ impl<T: RMPayloadTypes, InnerT: STMPaxos2PCRMInner<T>> STMPaxos2PCRMOuter<T, Inn
     STMPaxos2PCRMOuter { query_id, tm, follower: None, state: State::Working, inner }
   }
 
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match &self.state {
+      State::Follower => "Follower",
+      State::Working => "Working",
+      State::InsertingPrepared => "InsertingPrepared",
+      State::Prepared(_) => "Prepared",
+      State::InsertingCommitted => "InsertingCommitted",
+      State::InsertingPreparedAborted => "InsertingPreparedAborted",
+      State::InsertingAborted => "InsertingAborted",
+    }
+  }
+
   /// This is only called when the `PreparedPLm` is insert at a Follower node.
   fn init_follower<
     IO: BasicIOCtx<<<T as RMPayloadTypes>::TM as TMPayloadTypes>::NetworkMessageT>,
@@ -537,6 +552,7 @@ pub fn handle_rm_msg<
 ) -> (QueryId, STMPaxos2PCRMAction<T>) {
   match msg {
     RMMessage::Prepare(prepare) => {
+      ctx.clock().update(io_ctx, &prepare.sender_time);
       if let Some(es) = con.get_mut(&prepare.query_id) {
         (prepare.query_id, es.handle_prepare(ctx, io_ctx))
       } else {
@@ -567,6 +583,7 @@ pub fn handle_rm_msg<
       }
     }
     RMMessage::Commit(commit) => {
+      ctx.clock().update(io_ctx, &commit.sender_time);
       let query_id = commit.query_id.clone();
       if let Some(es) = con.get_mut(&query_id) {
         (query_id, es.handle_commit(ctx, io_ctx, commit.clone()))
@@ -1,4 +1,4 @@
-use crate::common::{BasicIOCtx, RemoteLeaderChangedPLm};
+use crate::common::{BasicIOCtx, HybridClock, RemoteLeaderChangedPLm, Timestamp};
 use crate::common::{PaxosGroupId, PaxosGroupIdTrait, QueryId};
 use crate::paxos2pc_tm::Paxos2PCContainer;
 use serde::de::DeserializeOwned;
@@ -23,6 +23,8 @@ pub trait TMServerContext<T: TMPayloadTypes> {
   fn mk_node_path(&self) -> T::TMPath;
 
   fn is_leader(&self) -> bool;
+
+  fn clock(&mut self) -> &mut HybridClock;
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -100,6 +102,8 @@ pub struct Prepare<T: TMPayloadTypes> {
   pub query_id: QueryId,
   pub tm: T::TMPath,
   pub payload: T::Prepare,
+  /// The time of the TM's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -114,6 +118,8 @@ pub struct Commit<T: TMPayloadTypes> {
   pub query_id: QueryId,
   pub tm: T::TMPath,
   pub payload: T::Commit,
+  /// The time of the TM's `HybridClock` when this was sent.
+  pub sender_time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -393,7 +399,12 @@ impl<T: TMPayloadTypes, InnerT: STMPaxos2PCTMInner<T>> STMPaxos2PCTMOuter<T, Inn
   ) {
     let mut rms_remaining = BTreeSet::<T::RMPath>::new();
     for (rm, payload) in prepare_payloads.clone() {
-      let prepare = Prepare { query_id: self.query_id.clone(), tm: ctx.mk_node_path(), payload };
+      let prepare = Prepare {
+        query_id: self.query_id.clone(),
+        tm: ctx.mk_node_path(),
+        payload,
+        sender_time: ctx.clock().now(io_ctx),
+      };
       ctx.send_to_rm(io_ctx, &rm, RMMessage::Prepare(prepare));
       rms_remaining.insert(rm);
     }
@@ -426,7 +437,12 @@ impl<T: TMPayloadTypes, InnerT: STMPaxos2PCTMInner<T>> STMPaxos2PCTMOuter<T, Inn
   ) {
     let mut rms_remaining = BTreeSet::<T::RMPath>::new();
     for (rm, payload) in commit_payloads.clone() {
-      let commit = Commit { query_id: self.query_id.clone(), tm: ctx.mk_node_path(), payload };
+      let commit = Commit {
+        query_id: self.query_id.clone(),
+        tm: ctx.mk_node_path(),
+        payload,
+        sender_time: ctx.clock().now(io_ctx),
+      };
       ctx.send_to_rm(io_ctx, &rm, RMMessage::Commit(commit));
       rms_remaining.insert(rm);
     }
@@ -609,8 +625,12 @@ impl<T: TMPayloadTypes, InnerT: STMPaxos2PCTMInner<T>> STMPaxos2PCTMOuter<T, Inn
           // If the RM has not responded and its Leadership changed, we resend Prepare.
           if rm.to_gid() == remote_leader_changed.gid {
             let payload = prepare_payloads.get(rm).unwrap().clone();
-            let prepare =
-              Prepare { query_id: self.query_id.clone(), tm: ctx.mk_node_path(), payload };
+            let prepare = Prepare {
+              query_id: self.query_id.clone(),
+              tm: ctx.mk_node_path(),
+              payload,
+              sender_time: ctx.clock().now(io_ctx),
+            };
             ctx.send_to_rm(io_ctx, &rm, RMMessage::Prepare(prepare));
           }
         }
@@ -622,8 +642,12 @@ impl<T: TMPayloadTypes, InnerT: STMPaxos2PCTMInner<T>> STMPaxos2PCTMOuter<T, Inn
           // If the RM has not responded and its Leadership changed, we resend Commit.
           if rm.to_gid() == remote_leader_changed.gid {
             let payload = commit_payloads.get(rm).unwrap().clone();
-            let commit =
-              Commit { query_id: self.query_id.clone(), tm: ctx.mk_node_path(), payload };
+            let commit = Commit {
+              query_id: self.query_id.clone(),
+              tm: ctx.mk_node_path(),
+              payload,
+              sender_time: ctx.clock().now(io_ctx),
+            };
             ctx.send_to_rm(io_ctx, &rm, RMMessage::Commit(commit));
           }
         }
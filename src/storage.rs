@@ -3,6 +3,7 @@ use crate::common::{
   Timestamp,
 };
 use crate::common::{ColName, ColType, ColVal, ColValN, PrimaryKey, TableView};
+use crate::expression::convert_col_valn;
 use std::cmp::max;
 use std::collections::{BTreeMap, Bound};
 
@@ -32,6 +33,20 @@ pub type PresenceSnapshot = BTreeMap<PrimaryKey, Vec<(ColName, ColValN)>>;
 /// A constant that indicates that a row is present.
 pub const PRESENCE_VALN: ColValN = Some(ColVal::Int(0));
 
+/// Returns the number of rows in `storage` whose latest Presence Row indicates that they
+/// are present.
+pub fn num_present_rows(storage: &GenericMVTable) -> usize {
+  let mut num_rows = 0;
+  for ((_, col), versions) in storage {
+    if col.is_none() {
+      if let Some((_, Some(_))) = versions.last() {
+        num_rows += 1;
+      }
+    }
+  }
+  num_rows
+}
+
 /// Finds the Non-Strict Prior Version in `versions` at `timestamp`.
 pub fn find_version<'a, V>(
   versions: &'a Vec<(Timestamp, Option<V>)>,
@@ -183,7 +198,13 @@ impl<'a> StorageView for SimpleStorageView<'a> {
       self.amend_all_prior_versions(&mut all_prior_versions, &key_bound, &val_cols, timestamp);
     }
 
-    prior_versions_to_presence_snapshot(&self.table_schema, val_cols, timestamp, all_prior_versions)
+    prior_versions_to_presence_snapshot(
+      self.storage,
+      self.table_schema,
+      val_cols,
+      timestamp,
+      all_prior_versions,
+    )
   }
 }
 
@@ -272,7 +293,13 @@ impl<'a> StorageView for MSStorageView<'a> {
       self.amend_all_prior_versions(&mut all_prior_versions, &key_bound, &val_cols, timestamp);
     }
 
-    prior_versions_to_presence_snapshot(&self.table_schema, val_cols, timestamp, all_prior_versions)
+    prior_versions_to_presence_snapshot(
+      self.storage,
+      self.table_schema,
+      val_cols,
+      timestamp,
+      all_prior_versions,
+    )
   }
 }
 
@@ -323,6 +350,68 @@ pub fn commit_to_storage(
   }
 }
 
+/// Computes the next chunk of rewriting the value of `col` at `timestamp` as a value of type
+/// `col_type`, for the rows present at `timestamp` with a `PrimaryKey` after `after_key`. This
+/// covers at most `max_rows` rows, and also returns the last `PrimaryKey` covered, or `None` if
+/// there are no rows left. This is used to change the type of a ValCol, where the conversion
+/// must be safe (see `is_safe_type_conversion`). Note that `NULL`s are also rewritten.
+pub fn convert_col_chunk(
+  storage: &GenericMVTable,
+  table_schema: &TableSchema,
+  col: &ColName,
+  col_type: &ColType,
+  timestamp: &Timestamp,
+  after_key: &Option<PrimaryKey>,
+  max_rows: usize,
+) -> (GenericTable, Option<PrimaryKey>) {
+  let start = match after_key {
+    Some(pkey) => Bound::Excluded((pkey.clone(), None)),
+    None => Bound::Unbounded,
+  };
+
+  // Compute the prior versions of the Presence Rows and `col` for the next `max_rows` rows.
+  let mut all_prior_versions =
+    BTreeMap::<(PrimaryKey, Option<ColName>), (Timestamp, ColValN)>::new();
+  let mut num_rows = 0;
+  let mut last_key = None;
+  for ((pkey, ci), versions) in storage.range((start, Bound::Unbounded)) {
+    if ci.is_some() {
+      continue;
+    }
+    if let Some((pkey_ts, val)) = find_version(versions, timestamp) {
+      if val == &PRESENCE_VALN {
+        all_prior_versions.insert((pkey.clone(), None), (pkey_ts.clone(), val.clone()));
+        let storage_key = (pkey.clone(), Some(col.clone()));
+        let maybe_versions = storage.get(&storage_key);
+        if let Some(version) = maybe_versions.and_then(|versions| find_version(versions, timestamp))
+        {
+          all_prior_versions.insert(storage_key, version.clone());
+        }
+
+        num_rows += 1;
+        if num_rows == max_rows {
+          last_key = Some(pkey.clone());
+          break;
+        }
+      }
+    }
+  }
+
+  let presence_snapshot = prior_versions_to_presence_snapshot(
+    storage,
+    table_schema,
+    &vec![col.clone()],
+    timestamp,
+    all_prior_versions,
+  );
+  let mut converted_view = GenericTable::new();
+  for (pkey, snapshot_row) in presence_snapshot {
+    let (_, val) = snapshot_row.into_iter().next().unwrap();
+    converted_view.insert((pkey, Some(col.clone())), convert_col_valn(col_type, val));
+  }
+  (converted_view, last_key)
+}
+
 // -----------------------------------------------------------------------------------------------
 //  Subtable Utils
 // -----------------------------------------------------------------------------------------------
@@ -414,6 +503,11 @@ fn check_inclusion(key_bound: &KeyBound, pkey: &PrimaryKey) -> KeyBoundInclusion
           return res;
         }
       }
+      (ColVal::BigInt(val), PolyColBound::BigInt(col_bound)) => {
+        if let Some(res) = check_col_inclusion(&mut prefix_ge, val, col_bound) {
+          return res;
+        }
+      }
       _ => panic!(),
     }
   }
@@ -449,6 +543,12 @@ fn compute_range_query(key_bound: &KeyBound) -> RangeQuery {
         }
         _ => break,
       },
+      PolyColBound::BigInt(col_bound) => match &col_bound.start {
+        SingleBound::Included(v) | SingleBound::Excluded(v) => {
+          start_prefix.push(ColVal::BigInt(v.clone()));
+        }
+        _ => break,
+      },
     }
   }
 
@@ -459,6 +559,7 @@ fn compute_range_query(key_bound: &KeyBound) -> RangeQuery {
 /// such a value is missing in `all_prior_version`), where the prior version of `val_cols`
 /// in `table_schema.val_cols` is `Some(_)`, compute the `PresenceSnapshot`.
 fn prior_versions_to_presence_snapshot(
+  storage: &GenericMVTable,
   table_schema: &TableSchema,
   val_cols: &Vec<ColName>,
   timestamp: &Timestamp,
@@ -495,15 +596,11 @@ fn prior_versions_to_presence_snapshot(
     let mut snapshot_row = Vec::<(ColName, ColValN)>::new();
     for (col, col_ts) in &col_timestamps {
       let storage_key = (pkey.clone(), Some(col.clone()));
-      // Compute the Resolved Value (where we ignore `val` if `timestamp` is too early).
-      let resolved_val = if let Some((timestamp, val)) = all_prior_versions.get(&storage_key) {
-        if timestamp >= max(pkey_ts, col_ts) {
-          val.clone()
-        } else {
-          None
-        }
-      } else {
-        None
+      // Compute the Resolved Value (where we ignore `val` if `timestamp` is too early). If
+      // there is no such value, we fall back to `resolve_unwritten_val`.
+      let resolved_val = match all_prior_versions.get(&storage_key) {
+        Some((timestamp, val)) if timestamp >= max(pkey_ts, col_ts) => val.clone(),
+        _ => resolve_unwritten_val(storage, table_schema, pkey, pkey_ts, col, col_ts),
       };
       snapshot_row.push((col.clone(), resolved_val));
     }
@@ -512,6 +609,44 @@ fn prior_versions_to_presence_snapshot(
   presence_snapshot
 }
 
+/// Computes the value of `col` in the row `pkey` (which has been present since `pkey_ts`) when
+/// nothing has been written to the version of `col` at `col_ts`. If that version has a
+/// `ColSource` and the row was already present then, this is the value in the `ColSource` as
+/// of `col_ts`, converted to the type of the version. Otherwise, this is the DEFAULT of the
+/// version.
+fn resolve_unwritten_val(
+  storage: &GenericMVTable,
+  table_schema: &TableSchema,
+  pkey: &PrimaryKey,
+  pkey_ts: &Timestamp,
+  col: &ColName,
+  col_ts: &Timestamp,
+) -> ColValN {
+  match table_schema.get_source(col, col_ts) {
+    Some(source) if pkey_ts <= col_ts => {
+      // Recall that the `source` cannot be written to at `col_ts` (it is read protected).
+      let storage_key = (pkey.clone(), Some(source.col.clone()));
+      let versions = storage.get(&storage_key);
+      let val = match versions.and_then(|versions| find_version(versions, col_ts)) {
+        Some((timestamp, val)) if timestamp >= max(pkey_ts, &source.col_timestamp) => val.clone(),
+        _ => resolve_unwritten_val(
+          storage,
+          table_schema,
+          pkey,
+          pkey_ts,
+          &source.col,
+          &source.col_timestamp,
+        ),
+      };
+      match table_schema.val_cols.static_read_version(col, col_ts) {
+        Some((_, Some(col_type))) => convert_col_valn(col_type, val),
+        _ => val,
+      }
+    }
+    _ => table_schema.get_default(col, col_ts),
+  }
+}
+
 /// Convert a `PresenceSnapshot` to a `TableView` by selecting the columns in `selection`.
 /// Note that every `ColName` in `selection` must either be in the key or the value in
 /// `presence_snapshot`. Also note that `selection` need not be distinct.
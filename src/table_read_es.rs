@@ -1,7 +1,8 @@
 use crate::col_usage::{col_collecting_cb, col_ref_collecting_cb, QueryElement, QueryIterator};
 use crate::common::{
-  add_item, btree_multimap_insert, lookup, mk_qid, remove_item, CoreIOCtx, GossipData,
-  GossipDataView, KeyBound, OrigP, QueryESResult, QueryPlan, ReadRegion, TabletKeyRange, Timestamp,
+  add_item, btree_multimap_insert, lookup, mk_qid, remove_item, CoreIOCtx, ExecutionStats,
+  GossipData, GossipDataView, KeyBound, OrigP, QueryESResult, QueryPlan, ReadRegion,
+  TabletKeyRange, Timestamp,
 };
 use crate::common::{
   CQueryPath, CTQueryPath, ColName, ColType, ColVal, ColValN, Context, ContextRow, PaxosGroupId,
@@ -11,7 +12,7 @@ use crate::expression::{
   compress_row_region, compute_key_region, evaluate_c_expr, is_true, range_row_region_intersection,
   CExpr, EvalError,
 };
-use crate::gr_query_es::{GRQueryConstructorView, GRQueryES};
+use crate::gr_query_es::{GRQueryConstructorView, GRQueryES, SubqueryComputableSql};
 use crate::master_query_planning_es::ColPresenceReq;
 use crate::message as msg;
 use crate::server::{
@@ -26,6 +27,7 @@ use crate::tablet::{
   compute_col_map, compute_subqueries, ColSet, ColumnsLocking, Executing, Pending,
   RequestedReadProtected, StorageLocalTable, TPESAction, TPESBase, TabletContext,
 };
+use crate::trace::TraceContext;
 use std::collections::BTreeSet;
 use std::iter::FromIterator;
 use std::ops::Deref;
@@ -43,7 +45,7 @@ pub fn request_lock_columns<IO: CoreIOCtx>(
   query_plan: &QueryPlan,
 ) -> QueryId {
   let mut col_set =
-    if let Some(col_presence_req) = query_plan.col_presence_req.get(&ctx.this_table_path) {
+    if let Some(col_presence_req) = query_plan.col_presence_req.get(ctx.table_path_at(timestamp)) {
       match col_presence_req {
         ColPresenceReq::ReqPresentAbsent(req) => {
           let mut all_cols = Vec::<ColName>::new();
@@ -67,7 +69,14 @@ pub fn does_query_plan_align(
   timestamp: &Timestamp,
   query_plan: &QueryPlan,
 ) -> bool {
-  if let Some(col_presence_req) = query_plan.col_presence_req.get(&ctx.this_table_path) {
+  // Check that the QueryPlan refers to this Table by the `TablePath` it has at `timestamp`,
+  // which might not be the case if the Table was renamed.
+  let table_path = ctx.table_path_at(timestamp);
+  if !query_plan.table_location_map.contains_key(table_path) {
+    return false;
+  }
+
+  if let Some(col_presence_req) = query_plan.col_presence_req.get(table_path) {
     match col_presence_req {
       ColPresenceReq::ReqPresentAbsent(req) => {
         // Check the `present_cols`
@@ -164,6 +173,50 @@ pub fn compute_read_region(
   ReadRegion { val_col_region, row_region }
 }
 
+/// Evaluates a `FollowerRead` directly against the local storage. The caller must ensure
+/// that `query.timestamp` is at or below the `closed_timestamp`, so that no write can still
+/// land in the region being read and no read protection is needed.
+pub fn perform_follower_read(
+  ctx: &TabletContext,
+  query: &msg::TableSelectQuery,
+) -> Result<(Vec<TableView>, ExecutionStats), msg::QueryError> {
+  // Verify that the QueryPlan is still valid here, and that there are no subqueries
+  // (which Followers cannot send out).
+  let is_valid = match query.query_plan.table_location_map.get(ctx.table_path_at(&query.timestamp))
+  {
+    Some((_, sharding_gen)) => sharding_gen >= &ctx.this_sharding_gen,
+    None => false,
+  };
+  if !is_valid
+    || !does_query_plan_align(ctx, &query.timestamp, &query.query_plan)
+    || !query.sql_query.collect_subqueries().is_empty()
+  {
+    return Err(msg::QueryError::InvalidQueryPlan);
+  }
+
+  // Create the ContextConstructor.
+  let context_constructor = ContextConstructor::new(
+    query.context.context_schema.clone(),
+    StorageLocalTable::new(
+      &ctx.table_schema,
+      &query.timestamp,
+      &query.sql_query.from,
+      &ctx.this_tablet_key_range,
+      &query.sql_query.selection,
+      SimpleStorageView::new(&ctx.storage, &ctx.table_schema),
+    ),
+    vec![],
+  );
+
+  // Evaluate
+  let result =
+    fully_evaluate_select(&context_constructor, &query.context, vec![], &query.sql_query)
+      .map_err(mk_eval_error)?;
+  let mut stats = ExecutionStats::default();
+  stats.add_rows_read(&ctx.this_tid, context_constructor.local_table.rows_read());
+  Ok((result, stats))
+}
+
 // -----------------------------------------------------------------------------------------------
 //  TableReadES
 // -----------------------------------------------------------------------------------------------
@@ -181,6 +234,8 @@ pub enum ExecutionS {
 #[derive(Debug)]
 pub struct TableReadES {
   pub root_query_path: CQueryPath,
+  /// The trace that child queries are performed for, where the span is this ES.
+  pub trace: TraceContext,
   pub timestamp: Timestamp,
   pub context: Rc<Context>,
 
@@ -194,6 +249,7 @@ pub struct TableReadES {
 
   // Dynamically evolving fields.
   pub new_rms: BTreeSet<TQueryPath>,
+  pub stats: ExecutionStats,
   pub waiting_global_locks: BTreeSet<QueryId>,
   pub state: ExecutionS,
   pub child_queries: Vec<QueryId>,
@@ -294,11 +350,14 @@ impl TPESBase for TableReadES {
   ) -> Option<TPESAction> {
     let pending = cast!(ExecutionS::Pending, &self.state)?;
     check!(pending.query_id == protect_qid);
+    self.stats.read_protection_wait_ms +=
+      io_ctx.now().time_ms.saturating_sub(pending.requested_time_ms);
 
     self.waiting_global_locks.insert(protect_qid);
     let gr_query_ess = compute_subqueries(
       GRQueryConstructorView {
         root_query_path: &self.root_query_path,
+        trace: &self.trace,
         timestamp: &self.timestamp,
         sql_query: &self.sql_query,
         query_plan: &self.query_plan,
@@ -360,10 +419,12 @@ impl TPESBase for TableReadES {
     _: &mut (),
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     table_views: Vec<TableView>,
   ) -> Option<TPESAction> {
     // Add the subquery results into the TableReadES.
     self.new_rms.extend(subquery_new_rms);
+    self.stats.merge(subquery_stats);
     let exec = cast!(ExecutionS::Executing, &mut self.state)?;
     exec.add_subquery_result(subquery_id, table_views);
 
@@ -395,6 +456,19 @@ impl TPESBase for TableReadES {
 }
 
 impl TableReadES {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match &self.state {
+      ExecutionS::Start => "Start",
+      ExecutionS::ColumnsLocking(_) => "ColumnsLocking",
+      ExecutionS::GossipDataWaiting => "GossipDataWaiting",
+      ExecutionS::Pending(_) => "Pending",
+      ExecutionS::Executing(_) => "Executing",
+      ExecutionS::WaitingGlobalLockedCols(_) => "WaitingGlobalLockedCols",
+      ExecutionS::Done => "Done",
+    }
+  }
+
   /// Check if the `sharding_config` in the GossipData contains the necessary data, moving on if so.
   fn check_gossip_data<IO: CoreIOCtx>(
     &mut self,
@@ -496,7 +570,8 @@ impl TableReadES {
 
     // Move the TableReadES to the Pending state
     let protect_qid = mk_qid(io_ctx.rand());
-    self.state = ExecutionS::Pending(Pending { query_id: protect_qid.clone() });
+    let requested_time_ms = io_ctx.now().time_ms;
+    self.state = ExecutionS::Pending(Pending { query_id: protect_qid.clone(), requested_time_ms });
 
     // Add a read protection requested
     btree_multimap_insert(
@@ -539,17 +614,19 @@ impl TableReadES {
 
     // Evaluate
     let eval_res = fully_evaluate_select(
-      context_constructor,
+      &context_constructor,
       &self.context.deref(),
       subquery_results,
       &self.sql_query,
     );
+    self.stats.add_rows_read(&ctx.this_tid, context_constructor.local_table.rows_read());
 
     match eval_res {
       Ok(res_table_views) => {
         let res = QueryESResult {
           result: res_table_views,
           new_rms: self.new_rms.iter().cloned().collect(),
+          stats: self.stats.clone(),
         };
 
         if self.waiting_global_locks.is_empty() {
@@ -668,7 +745,7 @@ impl BasicSelectQuery for proc::JoinSelect {
 
 /// Fully evaluate a `Select` query, including aggregation.
 pub fn fully_evaluate_select<LocalTableT: LocalTable, SelectQueryT: SingleTableSelectQuery>(
-  context_constructor: ContextConstructor<LocalTableT>,
+  context_constructor: &ContextConstructor<LocalTableT>,
   context: &Context,
   subquery_results: Vec<Vec<TableView>>,
   sql_query: &SelectQueryT,
@@ -810,6 +887,7 @@ pub fn perform_aggregation<SelectQueryT: BasicSelectQuery>(
         fn sum_op(column: &TableView) -> Result<ColValN, EvalError> {
           let mut all_null = true; // Keeps track of if all ColVals are all NULL.
           let mut total_sum = 0;
+          let mut big_total_sum: Option<i64> = None; // Set if the column holds `BigInt`s.
           for (val_row, count) in &column.rows {
             let val = val_row.iter().next().unwrap();
             match val {
@@ -818,13 +896,23 @@ pub fn perform_aggregation<SelectQueryT: BasicSelectQuery>(
                 total_sum += int_val * (*count) as i32;
                 all_null = false;
               }
+              Some(ColVal::BigInt(int_val)) => {
+                *big_total_sum.get_or_insert(0) += int_val * (*count) as i64;
+                all_null = false;
+              }
               Some(_) => return Err(EvalError::GenericError),
             }
           }
 
           // In SQL, there are no non-NULL ColVals, then the SUM evaluate to NULL. This
           // includes the case of an empty table.
-          Ok(if all_null { None } else { Some(ColVal::Int(total_sum)) })
+          Ok(if all_null {
+            None
+          } else if let Some(big_total_sum) = big_total_sum {
+            Some(ColVal::BigInt(big_total_sum + total_sum as i64))
+          } else {
+            Some(ColVal::Int(total_sum))
+          })
         }
 
         // TODO: This should actually be returning a float
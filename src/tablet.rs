@@ -8,25 +8,26 @@ use crate::col_usage::{
 };
 use crate::common::{
   btree_multimap_insert, lookup, map_insert, mk_qid, mk_t, remove_item, update_leader_map,
-  update_leader_map_unversioned, BasicIOCtx, BoundType, CoreIOCtx, GossipData, KeyBound, LeaderMap,
-  OrigP, QueryESResult, QueryPlan, ReadRegion, RemoteLeaderChangedPLm, ShardingGen, TableSchema,
-  Timestamp, VersionedValue, WriteRegion,
+  update_leader_map_unversioned, BasicIOCtx, BoundType, CoreIOCtx, GossipData, IsolationLevel,
+  KeyBound, LeaderMap, OrigP, Priority, QueryESResult, QueryPlan, ReadRegion,
+  RemoteLeaderChangedPLm, ShardingGen, TableSchema, Timestamp, VersionedValue, WriteRegion,
 };
 use crate::common::{
-  CNodePath, CQueryPath, CTQueryPath, CTSubNodePath, ColType, ColVal, ColValN, Context, ContextRow,
+  CNodePath, CQueryPath, CTQueryPath, CTSubNodePath, ClockId, ColType, ColVal, ColValN, Context, ContextRow,
   ContextSchema, Gen, LeadershipId, PaxosGroupId, PaxosGroupIdTrait, PrimaryKey, TNodePath,
   TQueryPath, TSubNodePath, TableView, TransTableName,
 };
 use crate::common::{
-  ColName, EndpointId, QueryId, SlaveGroupId, TablePath, TabletGroupId, TabletKeyRange,
+  ColName, EndpointId, ExecutionStats, HybridClock, QueryId, RequestId, SlaveGroupId, TablePath,
+  TabletGroupId, TabletKeyRange,
 };
 use crate::drop_table_rm_es::{
   DropTableRMAction, DropTableRMES, DropTableRMInner, DropTableRMPayloadTypes,
 };
 use crate::drop_table_tm_es::DropTableTMPayloadTypes;
 use crate::expression::{
-  compute_key_region, is_surely_isolated_multiread, is_surely_isolated_multiwrite,
-  range_row_region_intersection, EvalError,
+  compute_key_region, is_surely_disjoint_multiwrite, is_surely_isolated_multiread,
+  is_surely_isolated_multiwrite, range_row_region_intersection, EvalError,
 };
 use crate::finish_query_rm_es::{FinishQueryRMES, FinishQueryRMInner};
 use crate::finish_query_tm_es::FinishQueryPayloadTypes;
@@ -34,6 +35,7 @@ use crate::gr_query_es::{GRQueryAction, GRQueryConstructorView, GRQueryES, Subqu
 use crate::join_read_es::JoinReadES;
 use crate::join_util::compute_children_general;
 use crate::message as msg;
+use crate::metrics::{metrics, M_ACTIVE_ESS, M_FOLLOWER_READS, M_LEASE_READS, M_TABLET_ROWS};
 use crate::ms_table_delete_es::{DeleteInner, MSTableDeleteES};
 use crate::ms_table_es::{GeneralQueryES, MSTableES, MSTableExecutionS, SqlQueryInner};
 use crate::ms_table_insert_es::{InsertInner, MSTableInsertES};
@@ -48,17 +50,22 @@ use crate::shard_snapshot_es::{ShardingConfirmedPLm, ShardingSnapshotAction, Sha
 use crate::shard_split_tablet_rm_es::{
   ShardSplitTabletRMAction, ShardSplitTabletRMES, ShardSplitTabletRMPayloadTypes,
 };
-use crate::slave::{SlaveBackMessage, TabletBundleInsertion};
+use crate::slave::{RunningQueries, SlaveBackMessage, TabletBundleInsertion};
 use crate::sql_ast::proc;
 use crate::stmpaxos2pc_rm;
 use crate::stmpaxos2pc_tm;
-use crate::storage::{GenericMVTable, GenericTable, StorageView};
-use crate::table_read_es::{ExecutionS, TableReadES};
+use crate::storage::{
+  commit_to_storage, convert_col_chunk, num_present_rows, GenericMVTable, GenericTable,
+  StorageView,
+};
+use crate::table_read_es::{perform_follower_read, ExecutionS, TableReadES};
 use crate::tm_status::TMStatus;
+use crate::trace::{OpenSpans, TraceContext};
 use crate::trans_table_read_es::{TransExecutionS, TransTableReadES};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use sqlparser::test_utils::table;
+use std::cell::Cell;
 use std::cmp::max;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
@@ -80,6 +87,8 @@ pub struct ColumnsLocking {
 #[derive(Debug)]
 pub struct Pending {
   pub query_id: QueryId,
+  /// The time at which read protection was requested (for `ExecutionStats`).
+  pub requested_time_ms: u128,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -198,6 +207,26 @@ pub struct JoinReadESWrapper {
   pub es: JoinReadES,
 }
 
+// -----------------------------------------------------------------------------------------------
+//  ColConversion
+// -----------------------------------------------------------------------------------------------
+
+/// The rewrite of the existing values of a ValCol after an ALTER COLUMN TYPE commits at
+/// `timestamp`. The Leader does this a chunk at a time with `ConvertedCol` PLms, so that the
+/// Tablet is not blocked. Until it is done, reads convert the remaining values through the
+/// `ColSource` of the new version of the column.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ColConversion {
+  pub col_name: ColName,
+  pub col_type: ColType,
+  pub timestamp: Timestamp,
+  /// The rows that still need to be rewritten are those after this `PrimaryKey`.
+  pub after_key: Option<PrimaryKey>,
+}
+
+/// The maximum number of rows rewritten by a single `ConvertedCol` PLm.
+const COL_CONVERSION_CHUNK_SIZE: usize = 100;
+
 // -----------------------------------------------------------------------------------------------
 //  TabletSnapshot
 // -----------------------------------------------------------------------------------------------
@@ -210,6 +239,7 @@ pub struct TabletSnapshot {
   pub sub_node_path: CTSubNodePath, // Wraps `this_tablet_group_id` for expedience
   pub leader_map: LeaderMap,
   pub this_table_path: TablePath,
+  pub prior_table_paths: BTreeMap<Timestamp, TablePath>,
 
   // Sharding
   pub this_sharding_gen: ShardingGen,
@@ -222,11 +252,13 @@ pub struct TabletSnapshot {
   // Schema
   pub table_schema: TableSchema,
   pub presence_timestamp: Timestamp,
+  pub col_conversions: Vec<ColConversion>,
 
   // Region Isolation Algorithm
   pub prepared_writes: BTreeMap<Timestamp, ReadWriteRegion>,
   pub committed_writes: BTreeMap<Timestamp, ReadWriteRegion>,
   pub read_protected: BTreeMap<Timestamp, BTreeSet<ReadRegion>>,
+  pub closed_timestamp: Timestamp,
 
   // Statuses
   /// If this is a Follower, we copy over the ESs in `Statuses` to the below. If this
@@ -248,6 +280,7 @@ pub struct ShardingSnapshot {
   /// Metadata
   pub this_tid: TabletGroupId, // The `TabletGroupId` of the new Tablet.
   pub this_table_path: TablePath,
+  pub prior_table_paths: BTreeMap<Timestamp, TablePath>,
 
   /// Sharding. Here, `this_table_key_range` is that of the target Tablet.  
   pub this_sharding_gen: ShardingGen,
@@ -260,10 +293,12 @@ pub struct ShardingSnapshot {
   // Schema
   pub table_schema: TableSchema,
   pub presence_timestamp: Timestamp,
+  pub col_conversions: Vec<ColConversion>,
 
   // Region Isolation Algorithm
   pub committed_writes: BTreeMap<Timestamp, ReadWriteRegion>,
   pub read_protected: BTreeMap<Timestamp, BTreeSet<ReadRegion>>,
+  pub closed_timestamp: Timestamp,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -362,6 +397,7 @@ pub trait TPESBase {
     _es_ctx: &mut Self::ESContext,
     _subquery_id: QueryId,
     _subquery_new_rms: BTreeSet<TQueryPath>,
+    _subquery_stats: ExecutionStats,
     _results: Vec<TableView>,
   ) -> Option<TPESAction> {
     None
@@ -450,6 +486,10 @@ pub enum TabletAction {
 //  Status
 // -----------------------------------------------------------------------------------------------
 
+/// The maximum number of entries in `Statuses::follower_reads`. Beyond this, `FollowerRead`s
+/// are answered immediately with an error so that the Coord redirects them to the Leader.
+const MAX_FOLLOWER_READS: usize = 1000;
+
 #[derive(Debug, Default)]
 struct TopLevelStatuses {
   table_read_ess: BTreeMap<QueryId, TableReadES>,
@@ -478,6 +518,10 @@ pub struct Statuses {
   ms_query_ess: BTreeMap<QueryId, MSQueryES>,
   top: TopLevelStatuses,
 
+  /// `FollowerRead`s whose `Timestamp` is still beyond the `closed_timestamp`, along with
+  /// the time (in ms) at which they arrived. This holds at most `MAX_FOLLOWER_READS`.
+  follower_reads: Vec<(u128, msg::FollowerRead)>,
+
   // DDL
   ddl_es: DDLES,
 
@@ -984,6 +1028,8 @@ pub struct RequestedReadProtected {
 #[derive(Debug, Clone)]
 pub struct VerifyingReadWriteRegion {
   pub orig_p: OrigP,
+  /// The `Priority` of the MSQuery.
+  pub priority: Priority,
   pub m_waiting_read_protected: BTreeSet<RequestedReadProtected>,
   pub m_read_protected: BTreeSet<ReadRegion>,
   pub m_write_protected: BTreeSet<WriteRegion>,
@@ -1042,6 +1088,8 @@ pub struct StorageLocalTable<'a, StorageViewT: StorageView> {
   storage: StorageViewT,
   /// A flattened view of the Table schema
   schema: Vec<Option<ColName>>,
+  /// The number of rows that have been read from the `storage` (for `ExecutionStats`).
+  rows_read: Cell<u64>,
 }
 
 impl<'a, StorageViewT: StorageView> StorageLocalTable<'a, StorageViewT> {
@@ -1062,8 +1110,14 @@ impl<'a, StorageViewT: StorageView> StorageLocalTable<'a, StorageViewT> {
       tablet_key_range,
       storage,
       schema,
+      rows_read: Cell::new(0),
     }
   }
+
+  /// Returns the number of rows that have been read from storage so far.
+  pub fn rows_read(&self) -> u64 {
+    self.rows_read.get()
+  }
 }
 
 impl<'a, StorageViewT: StorageView> LocalTable for StorageLocalTable<'a, StorageViewT> {
@@ -1101,7 +1155,9 @@ impl<'a, StorageViewT: StorageView> LocalTable for StorageLocalTable<'a, Storage
       &self.tablet_key_range,
       compute_key_region(&self.selection, col_map, &self.source.alias, &self.table_schema.key_cols),
     );
-    self.storage.compute_subtable(&key_bounds, &col_names, self.timestamp)
+    let rows = self.storage.compute_subtable(&key_bounds, &col_names, self.timestamp);
+    self.rows_read.set(self.rows_read.get() + rows.len() as u64);
+    rows
   }
 }
 
@@ -1131,7 +1187,7 @@ pub fn compute_col_map(
 
 pub mod plm {
   use crate::common::{CQueryPath, TQueryPath};
-  use crate::common::{ColName, QueryId};
+  use crate::common::{ColName, PrimaryKey, QueryId};
   use crate::common::{ReadRegion, Timestamp};
   use crate::storage::GenericTable;
   use crate::tablet::{ColSet, ReadWriteRegion};
@@ -1154,12 +1210,34 @@ pub mod plm {
     pub timestamp: Timestamp,
     pub region: ReadRegion,
   }
+
+  // ClosedTimestamp
+
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct ClosedTimestamp {
+    pub timestamp: Timestamp,
+  }
+
+  // ConvertedCol
+
+  /// A chunk of the `ColConversion` of `col_name` at `timestamp`, covering the rows after
+  /// `after_key` up to and including `last_key` (or all remaining rows if that is `None`).
+  #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+  pub struct ConvertedCol {
+    pub col_name: ColName,
+    pub timestamp: Timestamp,
+    pub after_key: Option<PrimaryKey>,
+    pub last_key: Option<PrimaryKey>,
+    pub update_view: GenericTable,
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum TabletPLm {
   LockedCols(plm::LockedCols),
   ReadProtected(plm::ReadProtected),
+  ClosedTimestamp(plm::ClosedTimestamp),
+  ConvertedCol(plm::ConvertedCol),
   FinishQuery(paxos2pc_tm::RMPLm<FinishQueryPayloadTypes>),
   AlterTable(stmpaxos2pc_rm::RMPLm<AlterTableRMPayloadTypes>),
   DropTable(stmpaxos2pc_rm::RMPLm<DropTableRMPayloadTypes>),
@@ -1180,7 +1258,17 @@ pub enum TabletForwardMsg {
   GossipData(Arc<GossipData>, LeaderMap),
   RemoteLeaderChanged(RemoteLeaderChangedPLm),
   LeaderChanged(msg::LeaderChanged),
+  /// Sent by the Slave when its leader lease is extended, holding the new expiry time.
+  LeaderLease(Timestamp),
+  /// Sent by the Slave to every node of the SlaveGroup, not just the Leader.
+  FollowerRead(msg::FollowerRead),
   ConstructTabletSnapshot,
+  /// Sent by the Slave when it can insert another `SharedPaxosBundle` before the ones in flight
+  /// are learned, asking the Leader for its `TabletBundle` right away.
+  FlushBundle,
+  /// Asks for the ESs running at this Tablet. The `RequestId` is that of the
+  /// `ExternalShowQueries` that the Slave is gathering them for.
+  ShowQueries(RequestId),
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1209,6 +1297,10 @@ impl paxos2pc_tm::RMServerContext<FinishQueryPayloadTypes> for TabletContext {
     TabletContext::is_leader(self)
   }
 
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
+
   fn leader_map(&self) -> &BTreeMap<PaxosGroupId, LeadershipId> {
     &self.leader_map
   }
@@ -1220,10 +1312,14 @@ impl paxos2pc_tm::RMServerContext<FinishQueryPayloadTypes> for TabletContext {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TabletConfig {
-  /// This is used for generate the `suffix` of a Timestamp, where we just generate
-  /// a random `u64` and take the remainder after dividing by `timestamp_suffix_divisor`.
-  /// This cannot be 0; the default value is 1, making the suffix always be 0.
-  pub timestamp_suffix_divisor: u64,
+  /// The maximum amount (in ms) that the clock of another node can be ahead of ours before
+  /// we consider it to be skewed. See `HybridClock`.
+  pub max_clock_offset_ms: u128,
+  /// How far (in ms) behind the current time the Leader keeps the `closed_timestamp`.
+  pub closed_timestamp_lag_ms: u128,
+  /// How long (in ms) a `FollowerRead` may wait for the `closed_timestamp` to pass its
+  /// `Timestamp` before it is answered with `FollowerReadUnavailable`.
+  pub follower_read_wait_ms: u128,
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1256,6 +1352,10 @@ impl CTServerContext for TabletContext {
   fn gossip(&self) -> &Arc<GossipData> {
     &self.gossip
   }
+
+  fn clock(&mut self) -> &mut HybridClock {
+    &mut self.clock
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -1278,6 +1378,7 @@ impl Debug for TabletContext {
     let _ = debug_trait_builder.field("sub_node_path", &self.sub_node_path);
     let _ = debug_trait_builder.field("this_eid", &self.this_eid);
     let _ = debug_trait_builder.field("this_table_path", &self.this_table_path);
+    let _ = debug_trait_builder.field("prior_table_paths", &self.prior_table_paths);
     // let _ = debug_trait_builder.field("gossip", &self.gossip);
     // let _ = debug_trait_builder.field("leader_map", &self.leader_map);
     let _ = debug_trait_builder.field("this_sharding_gen", &self.this_sharding_gen);
@@ -1286,6 +1387,8 @@ impl Debug for TabletContext {
     // let _ = debug_trait_builder.field("storage", &self.storage);
     // let _ = debug_trait_builder.field("table_schema", &self.table_schema);
     let _ = debug_trait_builder.field("presence_timestamp", &self.presence_timestamp);
+    let _ = debug_trait_builder.field("col_conversions", &self.col_conversions);
+    let _ = debug_trait_builder.field("inserting_col_conversion", &self.inserting_col_conversion);
     let _ = debug_trait_builder.field("verifying_writes", &self.verifying_writes);
     let _ = debug_trait_builder.field("inserting_prepared_writes", &self.inserting_prepared_writes);
     let _ = debug_trait_builder.field("prepared_writes", &self.prepared_writes);
@@ -1293,6 +1396,10 @@ impl Debug for TabletContext {
     let _ = debug_trait_builder.field("waiting_read_protected", &self.waiting_read_protected);
     let _ = debug_trait_builder.field("inserting_read_protected", &self.inserting_read_protected);
     // let _ = debug_trait_builder.field("read_protected", &self.read_protected);
+    let _ = debug_trait_builder.field("preempted_ms_queries", &self.preempted_ms_queries);
+    let _ = debug_trait_builder.field("lease_expiry", &self.lease_expiry);
+    let _ = debug_trait_builder.field("lease_floor", &self.lease_floor);
+    let _ = debug_trait_builder.field("closed_timestamp", &self.closed_timestamp);
     let _ = debug_trait_builder.field("waiting_locked_cols", &self.waiting_locked_cols);
     let _ = debug_trait_builder.field("inserting_locked_cols", &self.inserting_locked_cols);
     let _ = debug_trait_builder.field("ms_root_query_map", &self.ms_root_query_map);
@@ -1309,7 +1416,12 @@ pub struct TabletContext {
   pub this_tid: TabletGroupId,
   pub sub_node_path: CTSubNodePath, // Wraps `this_tablet_group_id` for expedience
   pub this_eid: EndpointId,
+  /// The clock used to generate all `Timestamp`s at this server.
+  pub clock: HybridClock,
   pub this_table_path: TablePath,
+  /// Maps the `Timestamp` of every RENAME TABLE of this Table to the `TablePath` it had
+  /// before then. Use `table_path_at` to find the `TablePath` at a given `Timestamp`.
+  pub prior_table_paths: BTreeMap<Timestamp, TablePath>,
 
   /// Gossip
   pub gossip: Arc<GossipData>,
@@ -1332,6 +1444,11 @@ pub struct TabletContext {
   // Schema
   pub table_schema: TableSchema,
   pub presence_timestamp: Timestamp,
+  /// The `ColConversion`s that are not done yet, in the order they were committed. Only the
+  /// first one is advanced at a time.
+  pub col_conversions: Vec<ColConversion>,
+  /// If this is the Leader, this is `true` iff a `ConvertedCol` PLm is being inserted.
+  pub inserting_col_conversion: bool,
 
   // Region Isolation Algorithm
   pub verifying_writes: BTreeMap<Timestamp, VerifyingReadWriteRegion>,
@@ -1342,6 +1459,26 @@ pub struct TabletContext {
   pub waiting_read_protected: BTreeMap<Timestamp, BTreeSet<RequestedReadProtected>>,
   pub inserting_read_protected: BTreeMap<Timestamp, BTreeSet<RequestedReadProtected>>,
   pub read_protected: BTreeMap<Timestamp, BTreeSet<ReadRegion>>,
+  /// The `MSQueryES`s whose ReadRegions were written to by a preceding `MSQueryES` with a
+  /// higher priority (see `add_m_write_protected`). These are exited in the main loop.
+  pub preempted_ms_queries: BTreeSet<QueryId>,
+
+  // Leader Lease
+  /// If this is the Leader, this is the time until which it holds the leader lease (see
+  /// `PaxosDriver::lease_expiry`). While the lease is held, ReadProtection for `TableReadES`s
+  /// is granted without being persisted, which saves a round of Paxos.
+  pub lease_expiry: Option<Timestamp>,
+  /// Writes at or below this `Timestamp` do not have Region Isolation, since a prior Leader
+  /// might have granted ReadProtection up to here with its leader lease. This is set when
+  /// this node becomes the Leader.
+  pub lease_floor: Timestamp,
+
+  // Follower Reads
+  /// Every write at or below this `Timestamp` has been committed or aborted (and no more
+  /// can be prepared), and the schema is locked up to here. The Leader advances this with a
+  /// `ClosedTimestamp` PLm, so a Follower that has applied the PaxosLog up to that PLm can
+  /// serve `FollowerRead`s at or below it from its own `storage`.
+  pub closed_timestamp: Timestamp,
 
   // Schema Change and Locking
   pub waiting_locked_cols: BTreeMap<QueryId, RequestedLockedCols>,
@@ -1354,6 +1491,10 @@ pub struct TabletContext {
 
   // Paxos
   pub tablet_bundle: TabletBundle,
+
+  // Tracing
+  /// The spans of the ESs at this Tablet that are being done for a trace.
+  pub spans: OpenSpans,
 }
 
 impl TabletState {
@@ -1365,6 +1506,7 @@ impl TabletState {
     gossip: Arc<GossipData>,
     snapshot: TabletSnapshot,
     this_eid: EndpointId,
+    clock_id: ClockId,
     tablet_config: TabletConfig,
   ) -> TabletState {
     // Create Statuses
@@ -1374,6 +1516,7 @@ impl TabletState {
     statuses.sharding_state = snapshot.sharding_state;
 
     // Create the TabletCtx
+    let clock = HybridClock::new(clock_id, tablet_config.max_clock_offset_ms);
     let ctx = TabletContext {
       tablet_config,
       this_sid: snapshot.this_sid.clone(),
@@ -1381,15 +1524,19 @@ impl TabletState {
       this_tid: snapshot.this_tid,
       sub_node_path: snapshot.sub_node_path,
       this_eid,
+      clock,
       gossip,
       leader_map: snapshot.leader_map,
       storage: snapshot.storage,
       this_table_path: snapshot.this_table_path,
+      prior_table_paths: snapshot.prior_table_paths,
       this_sharding_gen: snapshot.this_sharding_gen,
       sharding_done: snapshot.sharding_done,
       this_tablet_key_range: snapshot.this_table_key_range,
       table_schema: snapshot.table_schema,
       presence_timestamp: snapshot.presence_timestamp,
+      col_conversions: snapshot.col_conversions,
+      inserting_col_conversion: false,
       verifying_writes: Default::default(),
       inserting_prepared_writes: Default::default(),
       prepared_writes: snapshot.prepared_writes,
@@ -1397,17 +1544,53 @@ impl TabletState {
       waiting_read_protected: Default::default(),
       inserting_read_protected: Default::default(),
       read_protected: snapshot.read_protected,
+      preempted_ms_queries: Default::default(),
+      lease_expiry: None,
+      lease_floor: mk_t(0),
+      closed_timestamp: snapshot.closed_timestamp,
       waiting_locked_cols: Default::default(),
       inserting_locked_cols: Default::default(),
       ms_root_query_map: Default::default(),
       tablet_bundle: Default::default(),
+      spans: Default::default(),
     };
 
     TabletState { ctx, statuses }
   }
 
   pub fn handle_input<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO, coord_input: TabletForwardMsg) {
+    let is_bundle = matches!(&coord_input, TabletForwardMsg::TabletBundle(_));
     self.ctx.handle_input(io_ctx, &mut self.statuses, coord_input);
+    self.record_metrics(is_bundle);
+  }
+
+  /// Updates the metrics of this Tablet. We only recount the rows if `is_bundle` is set,
+  /// since `storage` is only modified when a `TabletBundle` is processed.
+  fn record_metrics(&self, is_bundle: bool) {
+    let tid = &self.ctx.this_tid.0;
+    let statuses = &self.statuses;
+    let active_ess = [
+      ("finish_query", statuses.finish_query_ess.len()),
+      ("gr_query", statuses.gr_query_ess.len()),
+      ("join_read", statuses.join_query_ess.len()),
+      ("tm_status", statuses.tm_statuss.len()),
+      ("ms_query", statuses.ms_query_ess.len()),
+      ("table_read", statuses.top.table_read_ess.len()),
+      ("trans_table_read", statuses.top.trans_table_read_ess.len()),
+      ("ms_table_read", statuses.top.ms_table_read_ess.len()),
+      ("ms_table_write", statuses.top.ms_table_write_ess.len()),
+      ("ms_table_insert", statuses.top.ms_table_insert_ess.len()),
+      ("ms_table_delete", statuses.top.ms_table_delete_ess.len()),
+    ];
+    for (kind, count) in active_ess {
+      let labels = vec![("group", tid.clone()), ("kind", kind.to_string())];
+      metrics().set(M_ACTIVE_ESS, labels, count as i64);
+    }
+
+    if is_bundle {
+      let rows = num_present_rows(&self.ctx.storage);
+      metrics().set(M_TABLET_ROWS, vec![("tablet", tid.clone())], rows as i64);
+    }
   }
 }
 
@@ -1461,11 +1644,33 @@ impl TabletContext {
                 self.grant_global_read_protected(io_ctx, statuses, req);
               }
             }
+            TabletPLm::ClosedTimestamp(closed) => {
+              // Lock the schema up to the `closed_timestamp`, as if every column was locked.
+              self.table_schema.val_cols.update_all_lats(closed.timestamp.clone());
+              self.presence_timestamp =
+                max(self.presence_timestamp.clone(), closed.timestamp.clone());
+              self.closed_timestamp = max(self.closed_timestamp.clone(), closed.timestamp);
+            }
+            TabletPLm::ConvertedCol(converted) => {
+              if self.is_leader() {
+                self.inserting_col_conversion = false;
+              }
+              self.apply_converted_col(converted);
+            }
             // FinishQuery
             TabletPLm::FinishQuery(plm) => {
+              // The FinishQueryRMES lingers after it Commits or Aborts (to handle duplicate
+              // messages), so we finish its span once the outcome is persisted.
+              let decided = match &plm {
+                paxos2pc_tm::RMPLm::Prepared(_) => false,
+                paxos2pc_tm::RMPLm::Committed(_) | paxos2pc_tm::RMPLm::Aborted(_) => true,
+              };
               let (query_id, action) =
                 paxos2pc_rm::handle_rm_plm(self, io_ctx, &mut statuses.finish_query_ess, plm);
-              self.handle_finish_query_es_action(statuses, query_id, action);
+              if decided {
+                self.spans.finish(io_ctx, &self.this_eid, &query_id);
+              }
+              self.handle_finish_query_es_action(io_ctx, statuses, query_id, action);
             }
             // AlterTable
             TabletPLm::AlterTable(plm) => {
@@ -1529,17 +1734,23 @@ impl TabletContext {
             }
           }
 
+          // Advance the `closed_timestamp` so that Followers can serve more reads.
+          self.maybe_close_timestamp(io_ctx, statuses);
+
+          // Rewrite the next chunk of the first `ColConversion`.
+          self.maybe_convert_col(statuses);
+
           // Dispatch the TabletBundle for insertion and start a new one.
-          io_ctx.slave_forward(SlaveBackMessage::TabletBundleInsertion(TabletBundleInsertion {
-            tid: self.this_tid.clone(),
-            lid: self.leader_map.get(&self.this_sid.to_gid()).unwrap().clone(),
-            bundle: std::mem::replace(&mut self.tablet_bundle, Vec::default()),
-          }));
+          self.dispatch_tablet_bundle(io_ctx);
         }
+
+        // The `closed_timestamp` might have advanced.
+        self.serve_follower_reads(io_ctx, statuses);
       }
       TabletForwardMsg::TabletMessage(message) => {
         match message {
           msg::TabletMessage::PerformQuery(perform_query) => {
+            self.clock.update(io_ctx, &perform_query.sender_time);
             // If the `root_sid` is in the GossipData, then we move forward. Otherwise, we wait.
             // This is needed for the `RegisterQuery` that we do with MSTable*ESs.
             let root_sid = &perform_query.root_query_path.node_path.sid;
@@ -1570,6 +1781,36 @@ impl TabletContext {
             self.handle_query_success(io_ctx, statuses, query_success);
           }
           msg::TabletMessage::FinishQuery(message) => {
+            // Start the span of the FinishQueryRMES. We do not do this if the FinishQueryRMES
+            // has already decided, which might be the case if this message is a duplicate. For
+            // a `Commit`, we also require the FinishQueryRMES to exist.
+            match &message {
+              paxos2pc_tm::RMMessage::Prepare(paxos2pc_tm::Prepare {
+                query_id,
+                trace: Some(trace),
+                ..
+              }) if !matches!(
+                statuses.finish_query_ess.get(query_id),
+                Some(paxos2pc_rm::Paxos2PCRMOuter::Committed)
+                  | Some(paxos2pc_rm::Paxos2PCRMOuter::Aborted)
+              ) =>
+              {
+                self.spans.start_rm(io_ctx, query_id, trace, "FinishQueryRMES");
+              }
+              paxos2pc_tm::RMMessage::Commit(paxos2pc_tm::Commit {
+                query_id,
+                trace: Some(trace),
+                ..
+              }) if matches!(
+                statuses.finish_query_ess.get(query_id),
+                Some(paxos2pc_rm::Paxos2PCRMOuter::Paxos2PCRMExecOuter(_))
+              ) =>
+              {
+                self.spans.start_rm(io_ctx, query_id, trace, "FinishQueryRMES");
+              }
+              _ => {}
+            }
+
             let (query_id, action) = paxos2pc_rm::handle_rm_msg(
               self,
               io_ctx,
@@ -1577,7 +1818,11 @@ impl TabletContext {
               &mut statuses.ms_query_ess,
               message,
             );
-            self.handle_finish_query_es_action(statuses, query_id, action);
+            if !statuses.finish_query_ess.contains_key(&query_id) {
+              // The `Prepare` failed, so the FinishQueryRMES was never created.
+              self.spans.finish(io_ctx, &self.this_eid, &query_id);
+            }
+            self.handle_finish_query_es_action(io_ctx, statuses, query_id, action);
           }
           msg::TabletMessage::AlterTable(message) => {
             let (query_id, action) =
@@ -1740,7 +1985,7 @@ impl TabletContext {
                   io_ctx,
                   remote_leader_changed.clone(),
                 );
-                self.handle_finish_query_es_action(statuses, query_id.clone(), action);
+                self.handle_finish_query_es_action(io_ctx, statuses, query_id.clone(), action);
               }
 
               // Inform ShardingState
@@ -1763,9 +2008,18 @@ impl TabletContext {
         let this_gid = self.this_sid.to_gid();
         self.leader_map.insert(this_gid, leader_changed.lid); // Update the LeadershipId
 
+        // Any leader lease that was held is no longer valid.
+        self.lease_expiry = None;
+
+        // A `ConvertedCol` PLm that was being inserted might never be, so we compute it again.
+        self.inserting_col_conversion = false;
         if self.is_leader() {
           // By the SharedPaxosInserter, this must be empty at the start of Leadership.
           self.tablet_bundle = TabletBundle::default();
+
+          // The prior Leader's lease has expired by now (otherwise, this node could not have
+          // become the Leader), so it could not have granted ReadProtection beyond our clock.
+          self.lease_floor = self.clock.now(io_ctx);
         }
 
         // Inform FinishQueryRMES
@@ -1773,7 +2027,7 @@ impl TabletContext {
         for query_id in query_ids {
           let finish_query_es = statuses.finish_query_ess.get_mut(&query_id).unwrap();
           let action = finish_query_es.leader_changed(self, io_ctx);
-          self.handle_finish_query_es_action(statuses, query_id.clone(), action);
+          self.handle_finish_query_es_action(io_ctx, statuses, query_id.clone(), action);
         }
 
         // Inform DDLESs
@@ -1809,7 +2063,8 @@ impl TabletContext {
 
         // Check if this node just lost Leadership
         if !self.is_leader() {
-          // Wink away all TM ESs.
+          // Wink away all TM ESs, as well as their spans.
+          self.spans = OpenSpans::default();
           statuses.perform_query_buffer.clear();
           statuses.gr_query_ess.clear();
           statuses.join_query_ess.clear();
@@ -1840,13 +2095,35 @@ impl TabletContext {
           self.run_main_loop(io_ctx, statuses);
 
           // If this node becomes the Leader, then we continue the insert cycle.
-          io_ctx.slave_forward(SlaveBackMessage::TabletBundleInsertion(TabletBundleInsertion {
-            tid: self.this_tid.clone(),
-            lid: self.leader_map.get(&self.this_sid.to_gid()).unwrap().clone(),
-            bundle: std::mem::replace(&mut self.tablet_bundle, TabletBundle::default()),
-          }));
+          self.dispatch_tablet_bundle(io_ctx);
+        }
+      }
+      TabletForwardMsg::FlushBundle => {
+        if self.is_leader() {
+          self.dispatch_tablet_bundle(io_ctx);
         }
       }
+      TabletForwardMsg::LeaderLease(lease_expiry) => {
+        if self.is_leader() {
+          self.lease_expiry = Some(lease_expiry);
+        }
+      }
+      TabletForwardMsg::FollowerRead(follower_read) => {
+        self.clock.update(io_ctx, &follower_read.perform_query.sender_time);
+        if statuses.follower_reads.len() < MAX_FOLLOWER_READS {
+          statuses.follower_reads.push((io_ctx.now().time_ms, follower_read));
+          self.serve_follower_reads(io_ctx, statuses);
+        } else {
+          let query_error = msg::QueryError::FollowerReadUnavailable;
+          self.respond_follower_read_error(io_ctx, follower_read, query_error);
+        }
+      }
+      TabletForwardMsg::ShowQueries(request_id) => {
+        // Followers also respond, since they might hold ESs (e.g. those of Paxos2PC).
+        let queries = self.running_queries(io_ctx, statuses);
+        io_ctx
+          .slave_forward(SlaveBackMessage::RunningQueries(RunningQueries { request_id, queries }));
+      }
       TabletForwardMsg::ConstructTabletSnapshot => {
         let mut snapshot = TabletSnapshot {
           this_sid: self.this_sid.clone(),
@@ -1855,14 +2132,17 @@ impl TabletContext {
           leader_map: self.leader_map.clone(),
           storage: self.storage.clone(),
           this_table_path: self.this_table_path.clone(),
+          prior_table_paths: self.prior_table_paths.clone(),
           table_schema: self.table_schema.clone(),
           presence_timestamp: self.presence_timestamp.clone(),
+          col_conversions: self.col_conversions.clone(),
           this_sharding_gen: self.this_sharding_gen.clone(),
           this_table_key_range: self.this_tablet_key_range.clone(),
           sharding_done: self.sharding_done.clone(),
           prepared_writes: self.prepared_writes.clone(),
           committed_writes: self.committed_writes.clone(),
           read_protected: self.read_protected.clone(),
+          closed_timestamp: self.closed_timestamp.clone(),
           finish_query_ess: Default::default(),
           ddl_es: DDLES::None,
           sharding_state: ShardingState::None,
@@ -1923,17 +2203,54 @@ impl TabletContext {
     statuses: &mut Statuses,
     perform_query: msg::PerformQuery,
   ) {
-    // If the `ShardingGen` is behind, then we abort.
+    // Start the span of the ES that will perform the query. We inspect the TierMap to see
+    // whether a TableSelectQuery will be done by an MSTableReadES or a TableReadES.
+    let es_type = match &perform_query.query {
+      msg::GeneralQuery::TransTableSelectQuery(_) => "TransTableReadES",
+      msg::GeneralQuery::TableSelectQuery(query) => {
+        if query.query_plan.tier_map.map.contains_key(&query.sql_query.from.table_path) {
+          "MSTableReadES"
+        } else {
+          "TableReadES"
+        }
+      }
+      msg::GeneralQuery::UpdateQuery(_) => "MSTableWriteES",
+      msg::GeneralQuery::InsertQuery(_) => "MSTableInsertES",
+      msg::GeneralQuery::DeleteQuery(_) => "MSTableDeleteES",
+    };
+    self.spans.start(io_ctx, &perform_query.query_id, &perform_query.trace, es_type);
+    let trace = perform_query.trace.child(&perform_query.query_id);
+
+    // If the `ShardingGen` is behind, then we abort. We also abort if the QueryPlan does not
+    // refer to this Table by the `TablePath` it has at the query's Timestamp, which happens if
+    // a RENAME TABLE has committed in the Master but not yet here.
     let query_plan = perform_query.get_query_plan();
-    let (_, sharding_gen) = query_plan.table_location_map.get(&self.this_table_path).unwrap();
-    if sharding_gen < &self.this_sharding_gen {
-      self.send_query_error(
-        io_ctx,
-        perform_query.sender_path,
-        perform_query.query_id,
-        msg::QueryError::InvalidQueryPlan,
-      );
-      return;
+    let m_timestamp = match &perform_query.query {
+      msg::GeneralQuery::TransTableSelectQuery(query) => {
+        // If the GRQueryES no longer exists, we abort below instead.
+        let gr_query = statuses.gr_query_ess.get(&query.location_prefix.source.query_id);
+        gr_query.map(|gr_query| gr_query.es.timestamp.clone())
+      }
+      msg::GeneralQuery::TableSelectQuery(query) => Some(query.timestamp.clone()),
+      msg::GeneralQuery::UpdateQuery(query) => Some(query.timestamp.clone()),
+      msg::GeneralQuery::InsertQuery(query) => Some(query.timestamp.clone()),
+      msg::GeneralQuery::DeleteQuery(query) => Some(query.timestamp.clone()),
+    };
+    if let Some(timestamp) = m_timestamp {
+      let is_valid = match query_plan.table_location_map.get(self.table_path_at(&timestamp)) {
+        Some((_, sharding_gen)) => sharding_gen >= &self.this_sharding_gen,
+        None => false,
+      };
+      if !is_valid {
+        self.spans.finish(io_ctx, &self.this_eid, &perform_query.query_id);
+        self.send_query_error(
+          io_ctx,
+          perform_query.sender_path,
+          perform_query.query_id,
+          msg::QueryError::InvalidQueryPlan,
+        );
+        return;
+      }
     }
 
     // Otherwise, we may process the PerformQuery
@@ -1948,6 +2265,7 @@ impl TabletContext {
             &perform_query.query_id,
             TransTableReadES {
               root_query_path: perform_query.root_query_path,
+              trace,
               location_prefix: query.location_prefix,
               context: Rc::new(query.context),
               sender_path: perform_query.sender_path,
@@ -1955,6 +2273,7 @@ impl TabletContext {
               sql_query: query.sql_query,
               query_plan: query.query_plan,
               new_rms: Default::default(),
+              stats: Default::default(),
               state: TransExecutionS::Start,
               child_queries: vec![],
               timestamp: gr_query.es.timestamp.clone(),
@@ -1966,6 +2285,7 @@ impl TabletContext {
         } else {
           // This means that the target GRQueryES was deleted, so we send back
           // an Abort with LateralError.
+          self.spans.finish(io_ctx, &self.this_eid, &perform_query.query_id);
           self.send_query_error(
             io_ctx,
             perform_query.sender_path,
@@ -1983,6 +2303,7 @@ impl TabletContext {
             io_ctx,
             statuses,
             perform_query.root_query_path,
+            trace,
             perform_query.sender_path,
             perform_query.query_id,
             query.timestamp,
@@ -1997,6 +2318,7 @@ impl TabletContext {
             &perform_query.query_id,
             TableReadES {
               root_query_path: perform_query.root_query_path,
+              trace,
               timestamp: query.timestamp,
               context: Rc::new(query.context),
               sender_path: perform_query.sender_path.clone(),
@@ -2004,6 +2326,7 @@ impl TabletContext {
               sql_query: query.sql_query,
               query_plan: query.query_plan,
               new_rms: Default::default(),
+              stats: Default::default(),
               waiting_global_locks: Default::default(),
               state: ExecutionS::Start,
               child_queries: vec![],
@@ -2018,6 +2341,7 @@ impl TabletContext {
           io_ctx,
           statuses,
           perform_query.root_query_path,
+          trace,
           perform_query.sender_path,
           perform_query.query_id,
           query.timestamp,
@@ -2031,6 +2355,7 @@ impl TabletContext {
           io_ctx,
           statuses,
           perform_query.root_query_path,
+          trace,
           perform_query.sender_path,
           perform_query.query_id,
           query.timestamp,
@@ -2044,6 +2369,7 @@ impl TabletContext {
           io_ctx,
           statuses,
           perform_query.root_query_path,
+          trace,
           perform_query.sender_path,
           perform_query.query_id,
           query.timestamp,
@@ -2060,6 +2386,7 @@ impl TabletContext {
     io_ctx: &mut IO,
     statuses: &mut Statuses,
     root_query_path: CQueryPath,
+    trace: TraceContext,
     sender_path: CTQueryPath,
     query_id: QueryId,
     timestamp: Timestamp,
@@ -2096,6 +2423,7 @@ impl TabletContext {
             child_queries: vec![],
             general: GeneralQueryES {
               root_query_path,
+              trace,
               timestamp,
               tier,
               context: Rc::new(context),
@@ -2103,6 +2431,7 @@ impl TabletContext {
               query_plan,
               ms_query_id,
               new_rms: vec![ms_query_path].into_iter().collect(),
+              stats: Default::default(),
             },
             state: MSTableExecutionS::Start,
             inner,
@@ -2113,6 +2442,7 @@ impl TabletContext {
       }
       Err(query_error) => {
         // The MSTableES could not be constructed.
+        self.spans.finish(io_ctx, &self.this_eid, &query_id);
         self.send_query_error(io_ctx, sender_path, query_id, query_error);
       }
     }
@@ -2135,15 +2465,14 @@ impl TabletContext {
       return Ok(ms_query_id.clone());
     }
 
-    // Otherwise, we need to create one. First check whether the Timestamp is available or not.
-    if self.verifying_writes.contains_key(&timestamp)
-      || self.inserting_prepared_writes.contains_key(&timestamp)
-      || self.prepared_writes.contains_key(&timestamp)
-      || self.committed_writes.contains_key(&timestamp)
-    {
-      // This means the Timestamp is already in use, so we return an error.
-      return Err(msg::QueryError::TimestampConflict);
-    }
+    // Otherwise, we need to create one. The Timestamp cannot already be in use, since every
+    // `HybridClock` has a distinct `ClockId` and never generates the same Timestamp twice.
+    debug_assert!(
+      !self.verifying_writes.contains_key(&timestamp)
+        && !self.inserting_prepared_writes.contains_key(&timestamp)
+        && !self.prepared_writes.contains_key(&timestamp)
+        && !self.committed_writes.contains_key(&timestamp)
+    );
 
     let ms_query_id = mk_qid(io_ctx.rand());
 
@@ -2168,7 +2497,7 @@ impl TabletContext {
     );
 
     // This means that we can add an MSQueryES at the Timestamp
-    let full_gen = query_plan.table_location_map.get(&self.this_table_path).unwrap();
+    let full_gen = query_plan.table_location_map.get(self.table_path_at(&timestamp)).unwrap();
     let (_, sharding_gen) = full_gen.clone();
     statuses.ms_query_ess.insert(
       ms_query_id.clone(),
@@ -2191,6 +2520,7 @@ impl TabletContext {
       timestamp,
       VerifyingReadWriteRegion {
         orig_p: OrigP::new(ms_query_id.clone()),
+        priority: query_plan.priority,
         m_waiting_read_protected: BTreeSet::new(),
         m_read_protected: BTreeSet::new(),
         m_write_protected: BTreeSet::new(),
@@ -2240,6 +2570,12 @@ impl TabletContext {
     return None;
   }
 
+  /// Returns true iff `timestamp` is at or below the `lease_floor` or the `closed_timestamp`,
+  /// which means no write at or below it can be prepared here anymore.
+  pub fn is_below_write_floor(&self, timestamp: &Timestamp) -> bool {
+    timestamp <= &self.lease_floor || timestamp <= &self.closed_timestamp
+  }
+
   /// This removes the Read Protection request from `inserting_read_protected` with the given
   /// `query_id` at the given `timestamp`, if it exists, and returns it.
   pub fn remove_inserting_read_protected_request(
@@ -2283,18 +2619,29 @@ impl TabletContext {
     return None;
   }
 
-  /// Checks if the give `write_region` has a Region Isolation with subsequent reads.
+  /// Checks if the give `write_region` has a Region Isolation with subsequent reads. Here,
+  /// subsequent MSQueries that have not been prepared and that have a lower priority than
+  /// `priority` are ignored, since they will be preempted (see `add_m_write_protected`).
   pub fn check_write_region_isolation(
     &self,
     write_region: &WriteRegion,
     timestamp: &Timestamp,
+    priority: Priority,
   ) -> bool {
+    // We treat everything at or below the `lease_floor` or the `closed_timestamp` as read.
+    if self.is_below_write_floor(timestamp) {
+      return false;
+    }
+
     // We iterate through every subsequent Reads that this `write_region` can conflict
     // with, and check if there is indeed a conflict.
 
     // First, verify Region Isolation with ReadRegions of subsequent *_writes.
     let bound = (Bound::Excluded(timestamp), Bound::Unbounded);
     for (_, verifying_write) in self.verifying_writes.range(bound) {
+      if verifying_write.priority < priority {
+        continue;
+      }
       if !is_surely_isolated_multiread(write_region, &verifying_write.m_read_protected) {
         return false;
       }
@@ -2336,6 +2683,51 @@ impl TabletContext {
     return true;
   }
 
+  /// Adds `write_region` to the `m_write_protected` of the `verifying_write` at `timestamp`.
+  /// Every subsequent `verifying_write` whose ReadRegions conflict with it must have a lower
+  /// priority (see `check_write_region_isolation`), and so it is marked for preemption.
+  pub fn add_m_write_protected(&mut self, timestamp: &Timestamp, write_region: WriteRegion) {
+    let bound = (Bound::Excluded(timestamp), Bound::Unbounded);
+    for (_, subsequent_write) in self.verifying_writes.range(bound) {
+      if !is_surely_isolated_multiread(&write_region, &subsequent_write.m_read_protected) {
+        self.preempted_ms_queries.insert(subsequent_write.orig_p.query_id.clone());
+      }
+    }
+    self.verifying_writes.get_mut(timestamp).unwrap().m_write_protected.insert(write_region);
+  }
+
+  /// Checks if the given `write_region` does not overlap with the WriteRegions of subsequent
+  /// writes. This is how `IsolationLevel::Snapshot` detects write-write conflicts.
+  pub fn check_write_write_isolation(
+    &self,
+    write_region: &WriteRegion,
+    timestamp: &Timestamp,
+  ) -> bool {
+    let bound = (Bound::Excluded(timestamp), Bound::Unbounded);
+    for (_, verifying_write) in self.verifying_writes.range(bound) {
+      if !is_surely_disjoint_multiwrite(&verifying_write.m_write_protected, write_region) {
+        return false;
+      }
+    }
+    for (_, prepared_write) in self.prepared_writes.range(bound) {
+      if !is_surely_disjoint_multiwrite(&prepared_write.m_write_protected, write_region) {
+        return false;
+      }
+    }
+    for (_, inserting_prepared_write) in self.inserting_prepared_writes.range(bound) {
+      let write_regions = &inserting_prepared_write.m_write_protected;
+      if !is_surely_disjoint_multiwrite(write_regions, write_region) {
+        return false;
+      }
+    }
+    for (_, committed_write) in self.committed_writes.range(bound) {
+      if !is_surely_disjoint_multiwrite(&committed_write.m_write_protected, write_region) {
+        return false;
+      }
+    }
+    return true;
+  }
+
   // The Main Loop
   fn run_main_loop<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO, statuses: &mut Statuses) {
     while self.run_main_loop_iteration(io_ctx, statuses) {}
@@ -2387,9 +2779,12 @@ impl TabletContext {
           return true;
         }
         DDLES::Alter(es) => {
-          if !(req.cols.contains(&es.inner.alter_op.col_name)
-            && req.timestamp >= es.inner.prepared_timestamp)
-          {
+          // Recall that renaming the Table affects all columns.
+          let is_conflicting = match &es.inner.alter_op {
+            proc::AlterOp::RenameTable(_) => true,
+            alter_op => alter_op.cols().iter().any(|col_name| req.cols.contains(col_name)),
+          };
+          if !(is_conflicting && req.timestamp >= es.inner.prepared_timestamp) {
             // Grant LocalLockedCols
             let query_id = req.query_id.clone();
             self.grant_local_locked_cols(io_ctx, statuses, query_id);
@@ -2436,6 +2831,8 @@ impl TabletContext {
           cur_timestamp.clone(),
           VerifyingReadWriteRegion {
             orig_p: prepared_write.orig_p.clone(),
+            // Prepared writes cannot be preempted, so their priority is irrelevant here.
+            priority: Priority(u32::MAX),
             m_waiting_read_protected: Default::default(),
             m_read_protected: prepared_write.m_read_protected.clone(),
             m_write_protected: prepared_write.m_write_protected.clone(),
@@ -2543,7 +2940,8 @@ impl TabletContext {
       }
     }
 
-    // Next, we search for any DeadlockSafetyWriteAbort.
+    // Next, we search for any DeadlockSafetyWriteAbort. We abort the side with the lower
+    // priority, favoring the read if they are equal.
     for (timestamp, set) in &self.waiting_read_protected {
       if let Some(verifying_write) = self.verifying_writes.get(timestamp) {
         for protect_request in set {
@@ -2551,18 +2949,46 @@ impl TabletContext {
             &verifying_write.m_write_protected,
             &protect_request.read_region,
           ) {
-            self.deadlock_safety_write_abort(
-              io_ctx,
-              statuses,
-              verifying_write.orig_p.clone(),
-              timestamp.clone(),
-            );
+            let orig_p = protect_request.orig_p.clone();
+            match statuses.top.table_read_ess.get(&orig_p.query_id) {
+              Some(es) if es.query_plan.priority < verifying_write.priority => {
+                let protect_qid = protect_request.query_id.clone();
+                self.deadlock_safety_read_abort(
+                  io_ctx,
+                  statuses,
+                  orig_p,
+                  timestamp.clone(),
+                  protect_qid,
+                );
+              }
+              _ => {
+                self.deadlock_safety_write_abort(
+                  io_ctx,
+                  statuses,
+                  verifying_write.orig_p.clone(),
+                  timestamp.clone(),
+                );
+              }
+            }
             return true;
           }
         }
       }
     }
 
+    // Finally, we preempt any MSQueryES whose ReadRegions were written to by a preceding
+    // MSQueryES with a higher priority (see `add_m_write_protected`). Note that the MSQueryES
+    // might have already finished or been prepared since then.
+    while let Some(query_id) = self.preempted_ms_queries.pop_first() {
+      if let Some(ms_query_es) = statuses.ms_query_ess.get(&query_id) {
+        if !self.verifying_writes.contains_key(&ms_query_es.timestamp) {
+          continue;
+        }
+        self.exit_ms_query_es(io_ctx, statuses, query_id, msg::QueryError::Preempted);
+        return true;
+      }
+    }
+
     return false;
   }
 
@@ -2644,7 +3070,10 @@ impl TabletContext {
     statuses.execute_once::<_, _, Cb>(self, io_ctx, orig_p.query_id, ());
   }
 
-  /// Move the ProtectRequest in `waiting_read_protected` forward.
+  /// Move the ProtectRequest in `waiting_read_protected` forward. Like in
+  /// `grant_m_local_read_protected`, `TableReadES`s with `IsolationLevel::Snapshot` do not
+  /// keep their ReadRegion if it is below the write floor, so there is nothing to persist for
+  /// them either.
   fn grant_local_read_protected<IO: CoreIOCtx>(
     &mut self,
     io_ctx: &mut IO,
@@ -2653,12 +3082,36 @@ impl TabletContext {
     protect_request: RequestedReadProtected,
   ) {
     self.remove_read_protected_request(&timestamp, &protect_request.query_id).unwrap();
-    btree_multimap_insert(&mut self.inserting_read_protected, &timestamp, protect_request.clone());
-    self.tablet_bundle.push(TabletPLm::ReadProtected(plm::ReadProtected {
-      query_id: protect_request.query_id.clone(),
-      timestamp,
-      region: protect_request.read_region,
-    }));
+
+    let is_snapshot_read = match statuses.top.table_read_ess.get(&protect_request.orig_p.query_id) {
+      Some(es) => es.query_plan.isolation_level == IsolationLevel::Snapshot,
+      None => false,
+    } && self.is_below_write_floor(&timestamp);
+
+    // If this Leader holds a lease, we grant ReadProtection to `TableReadES`s without
+    // persisting it. Other ReadProtection requests (e.g. from DDL) are always persisted.
+    let lease_read = !is_snapshot_read
+      && self.has_lease_at(io_ctx, &timestamp)
+      && statuses.top.table_read_ess.contains_key(&protect_request.orig_p.query_id);
+    if lease_read {
+      btree_multimap_insert(
+        &mut self.read_protected,
+        &timestamp,
+        protect_request.read_region.clone(),
+      );
+      metrics().inc(M_LEASE_READS, vec![]);
+    } else if !is_snapshot_read {
+      btree_multimap_insert(
+        &mut self.inserting_read_protected,
+        &timestamp,
+        protect_request.clone(),
+      );
+      self.tablet_bundle.push(TabletPLm::ReadProtected(plm::ReadProtected {
+        query_id: protect_request.query_id.clone(),
+        timestamp,
+        region: protect_request.read_region.clone(),
+      }));
+    }
 
     // Inform the ES.
     struct Cb;
@@ -2677,9 +3130,15 @@ impl TabletContext {
     statuses.execute_once_ctx::<_, _, Cb>(
       self,
       io_ctx,
-      protect_request.orig_p.query_id,
-      protect_request.query_id,
+      protect_request.orig_p.query_id.clone(),
+      protect_request.query_id.clone(),
     );
+
+    // If the ReadProtection was granted with the lease (or not kept at all), it is
+    // already global.
+    if is_snapshot_read || lease_read {
+      self.grant_global_read_protected(io_ctx, statuses, protect_request);
+    }
   }
 
   /// By here, the `ReadRegion` has been persisted.
@@ -2704,7 +3163,12 @@ impl TabletContext {
     statuses.execute_once::<_, _, Cb>(self, io_ctx, req.orig_p.query_id, req.query_id);
   }
 
-  /// Move the ProtectRequest in `m_waiting_read_protected` forward.
+  /// Move the ProtectRequest in `m_waiting_read_protected` forward. Reads of MSQueries with
+  /// `IsolationLevel::Snapshot` do not keep their ReadRegion if their Timestamp is below the
+  /// write floor (see `is_below_write_floor`), since no write can land beneath them anyway.
+  /// Otherwise, a write with a lower Timestamp could change what a later stage of the MSQuery
+  /// reads, so they keep it like any other read. Writes and locking reads (i.e. those with a
+  /// `FOR UPDATE` or `FOR SHARE`) always keep theirs.
   fn grant_m_local_read_protected<IO: CoreIOCtx>(
     &mut self,
     io_ctx: &mut IO,
@@ -2712,9 +3176,19 @@ impl TabletContext {
     timestamp: Timestamp,
     protect_request: RequestedReadProtected,
   ) {
+    let is_snapshot_read =
+      match statuses.top.ms_table_read_ess.get(&protect_request.orig_p.query_id) {
+        Some(es) => {
+          es.general.query_plan.isolation_level == IsolationLevel::Snapshot
+            && es.inner.lock().is_none()
+        }
+        None => false,
+      } && self.is_below_write_floor(&timestamp);
     let verifying_write = self.verifying_writes.get_mut(&timestamp).unwrap();
     verifying_write.m_waiting_read_protected.remove(&protect_request);
-    verifying_write.m_read_protected.insert(protect_request.read_region);
+    if !is_snapshot_read {
+      verifying_write.m_read_protected.insert(protect_request.read_region);
+    }
 
     // Inform the ES.
     struct Cb;
@@ -2738,6 +3212,21 @@ impl TabletContext {
     );
   }
 
+  /// Aborts the TableReadES that made the read protection request `protect_qid`, which
+  /// will not be granted anymore.
+  fn deadlock_safety_read_abort<IO: CoreIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    statuses: &mut Statuses,
+    orig_p: OrigP,
+    timestamp: Timestamp,
+    protect_qid: QueryId,
+  ) {
+    self.remove_read_protected_request(&timestamp, &protect_qid);
+    let action = Some(TPESAction::QueryError(msg::QueryError::DeadlockSafetyAbortion));
+    self.handle_tp_es_action(io_ctx, statuses, orig_p.query_id, action);
+  }
+
   /// Simply aborts the MSQueryES, which will clean up everything to do with it.
   fn deadlock_safety_write_abort<IO: CoreIOCtx>(
     &mut self,
@@ -2767,13 +3256,14 @@ impl TabletContext {
       if tm_status.is_complete() {
         // Remove the TMStatus and take ownership before forwarding the results back.
         let tm_status = statuses.tm_statuss.remove(&tm_query_id).unwrap();
-        let (orig_p, results, new_rms) = tm_status.get_results();
+        let (orig_p, results, new_rms, stats) = tm_status.get_results();
         let gr_query_id = orig_p.query_id;
 
         // Then, inform the GRQueryES
         let gr_query = statuses.gr_query_ess.get_mut(&gr_query_id).unwrap();
         remove_item(&mut gr_query.child_queries, &tm_query_id);
-        let action = gr_query.es.handle_tm_success(self, io_ctx, tm_query_id, new_rms, results);
+        let action =
+          gr_query.es.handle_tm_success(self, io_ctx, tm_query_id, new_rms, stats, results);
         self.handle_gr_query_es_action(io_ctx, statuses, gr_query_id, action);
       }
     }
@@ -2810,16 +3300,18 @@ impl TabletContext {
     orig_p: OrigP,
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     result: Vec<TableView>,
   ) {
+    type Args = (QueryId, BTreeSet<TQueryPath>, ExecutionStats, Vec<TableView>);
     struct Cb;
-    impl CallbackWithContextOnce<(QueryId, BTreeSet<TQueryPath>, Vec<TableView>)> for Cb {
+    impl CallbackWithContextOnce<Args> for Cb {
       fn call<IOCtx: CoreIOCtx, TPEST: TPESBase>(
         ctx: &mut TabletContext,
         io_ctx: &mut IOCtx,
         es: &mut TPEST,
         es_ctx: &mut TPEST::ESContext,
-        (subquery_id, subquery_new_rms, result): (QueryId, BTreeSet<TQueryPath>, Vec<TableView>),
+        (subquery_id, subquery_new_rms, subquery_stats, result): Args,
       ) -> TabletAction {
         es.remove_subquery(&subquery_id);
         TabletAction::TPESAction(es.handle_subquery_done(
@@ -2828,6 +3320,7 @@ impl TabletContext {
           es_ctx,
           subquery_id,
           subquery_new_rms,
+          subquery_stats,
           result,
         ))
       }
@@ -2836,8 +3329,14 @@ impl TabletContext {
     // JoinReadES
     if let Some(join_select) = statuses.join_query_ess.get_mut(&orig_p.query_id) {
       remove_item(&mut join_select.child_queries, &subquery_id);
-      let action =
-        join_select.es.handle_subquery_done(self, io_ctx, subquery_id, subquery_new_rms, result);
+      let action = join_select.es.handle_subquery_done(
+        self,
+        io_ctx,
+        subquery_id,
+        subquery_new_rms,
+        subquery_stats,
+        result,
+      );
       self.handle_join_read_action(io_ctx, statuses, orig_p.query_id, action);
     }
     // TPESBase
@@ -2846,7 +3345,7 @@ impl TabletContext {
         self,
         io_ctx,
         orig_p.query_id,
-        (subquery_id, subquery_new_rms, result),
+        (subquery_id, subquery_new_rms, subquery_stats, result),
       );
     }
   }
@@ -3030,8 +3529,9 @@ impl TabletContext {
   }
 
   /// Handles the actions produced by a FinishQueryRMES.
-  fn handle_finish_query_es_action(
+  fn handle_finish_query_es_action<IO: CoreIOCtx>(
     &mut self,
+    io_ctx: &mut IO,
     statuses: &mut Statuses,
     query_id: QueryId,
     action: paxos2pc_rm::Paxos2PCRMAction,
@@ -3040,6 +3540,7 @@ impl TabletContext {
       paxos2pc_rm::Paxos2PCRMAction::Wait => {}
       paxos2pc_rm::Paxos2PCRMAction::Exit => {
         statuses.finish_query_ess.remove(&query_id);
+        self.spans.finish(io_ctx, &self.this_eid, &query_id);
       }
     }
   }
@@ -3141,6 +3642,7 @@ impl TabletContext {
             success: QueryESResult,
           ) -> TabletAction {
             let (query_id, sender_path, child_queries) = es.deregister(es_ctx);
+            ctx.spans.finish(io_ctx, &ctx.this_eid, &query_id);
             let responder_path = ctx.mk_query_path(query_id).into_ct();
             // This is the originating Leadership.
             ctx.send_to_ct(
@@ -3151,6 +3653,7 @@ impl TabletContext {
                 responder_path,
                 result: success.result,
                 new_rms: success.new_rms,
+                stats: success.stats,
               }),
             );
             TabletAction::ExitAll(child_queries)
@@ -3170,6 +3673,7 @@ impl TabletContext {
             query_error: msg::QueryError,
           ) -> TabletAction {
             let (query_id, sender_path, child_queries) = es.deregister(es_ctx);
+            ctx.spans.finish(io_ctx, &ctx.this_eid, &query_id);
             let responder_path = ctx.mk_query_path(query_id).into_ct();
             // This is the originating Leadership.
             ctx.send_to_ct(
@@ -3219,6 +3723,7 @@ impl TabletContext {
               io_ctx,
               query_id,
               success.new_rms,
+              success.stats,
               success.result,
             );
             self.handle_gr_query_es_action(io_ctx, statuses, orig_qid, action);
@@ -3280,6 +3785,7 @@ impl TabletContext {
           gr_query.es.orig_p,
           gr_query.es.query_id,
           res.new_rms,
+          res.stats,
           res.result,
         );
       }
@@ -3321,6 +3827,9 @@ impl TabletContext {
     statuses: &mut Statuses,
     query_id: QueryId,
   ) {
+    // Finish the span of the ES, if there is one.
+    self.spans.finish(io_ctx, &self.this_eid, &query_id);
+
     // Buffered PerformQuery
     if let Some(_) = statuses.perform_query_buffer.remove(&query_id) {
       // No-op
@@ -3380,6 +3889,89 @@ impl TabletContext {
     }
   }
 
+  /// Describes the ESs at this Tablet (for `SHOW QUERIES`). The age of an ES is measured
+  /// from the `Timestamp` that it operates at, if it has one.
+  fn running_queries<IO: CoreIOCtx>(
+    &self,
+    io_ctx: &mut IO,
+    statuses: &Statuses,
+  ) -> Vec<msg::RunningQuery> {
+    let now = io_ctx.now();
+    let server = format!("Tablet {}", self.this_tid.0);
+    let mut queries = Vec::<msg::RunningQuery>::new();
+    let mut push = |es_type: &str,
+                    query_id: &QueryId,
+                    root_query_id: &QueryId,
+                    state: &str,
+                    timestamp: Option<&Timestamp>| {
+      queries.push(msg::RunningQuery {
+        server: server.clone(),
+        es_type: es_type.to_string(),
+        query_id: query_id.clone(),
+        root_query_id: root_query_id.clone(),
+        request_id: None,
+        state: state.to_string(),
+        age_ms: timestamp.map(|timestamp| now.time_ms.saturating_sub(timestamp.time_ms)),
+      });
+    };
+
+    // TP
+    let top = &statuses.top;
+    for (query_id, es) in &top.table_read_ess {
+      let root_query_id = &es.root_query_path.query_id;
+      push("TableReadES", query_id, root_query_id, es.state_name(), Some(&es.timestamp));
+    }
+    for (query_id, es) in &top.trans_table_read_ess {
+      let root_query_id = &es.root_query_path.query_id;
+      push("TransTableReadES", query_id, root_query_id, es.state_name(), Some(&es.timestamp));
+    }
+    let ms_table_ess = (top.ms_table_read_ess.iter())
+      .map(|(qid, es)| ("MSTableReadES", qid, &es.general, es.state_name()))
+      .chain(
+        (top.ms_table_write_ess.iter())
+          .map(|(qid, es)| ("MSTableWriteES", qid, &es.general, es.state_name())),
+      )
+      .chain(
+        (top.ms_table_insert_ess.iter())
+          .map(|(qid, es)| ("MSTableInsertES", qid, &es.general, es.state_name())),
+      )
+      .chain(
+        (top.ms_table_delete_ess.iter())
+          .map(|(qid, es)| ("MSTableDeleteES", qid, &es.general, es.state_name())),
+      );
+    for (es_type, query_id, general, state) in ms_table_ess {
+      let root_query_id = &general.root_query_path.query_id;
+      push(es_type, query_id, root_query_id, state, Some(&general.timestamp));
+    }
+
+    // Paxos2PC
+    for (query_id, es) in &statuses.finish_query_ess {
+      let timestamp = match es {
+        paxos2pc_rm::Paxos2PCRMOuter::Paxos2PCRMExecOuter(es) => Some(&es.inner.timestamp),
+        _ => None,
+      };
+      push("FinishQueryRMES", query_id, query_id, es.state_name(), timestamp);
+    }
+
+    // DDL
+    match &statuses.ddl_es {
+      DDLES::Alter(es) => {
+        let timestamp = Some(&es.inner.prepared_timestamp);
+        push("AlterTableRMES", &es.query_id, &es.query_id, es.state_name(), timestamp);
+      }
+      DDLES::Drop(es) => {
+        let timestamp = Some(&es.inner.prepared_timestamp);
+        push("DropTableRMES", &es.query_id, &es.query_id, es.state_name(), timestamp);
+      }
+      DDLES::ShardSplit(es) => {
+        push("ShardSplitTabletRMES", &es.query_id, &es.query_id, es.state_name(), None);
+      }
+      DDLES::None | DDLES::Dropped(_) => {}
+    }
+
+    queries
+  }
+
   /// Construct NodePath of this Tablet.
   pub fn mk_node_path(&self) -> TNodePath {
     TNodePath { sid: self.this_sid.clone(), sub: TSubNodePath::Tablet(self.this_tid.clone()) }
@@ -3396,6 +3988,192 @@ impl TabletContext {
     lid.eid == self.this_eid
   }
 
+  /// Returns true iff this Leader currently holds a leader lease that covers reads at
+  /// `timestamp`. We require `timestamp` to be `max_clock_offset_ms` before the expiry, so
+  /// that the next Leader's `lease_floor` (which is taken from its clock once this lease
+  /// expires) will be at or above `timestamp`.
+  fn has_lease_at<IO: CoreIOCtx>(&self, io_ctx: &mut IO, timestamp: &Timestamp) -> bool {
+    if let Some(lease_expiry) = &self.lease_expiry {
+      let offset_ms = self.tablet_config.max_clock_offset_ms;
+      io_ctx.now() < *lease_expiry && timestamp.time_ms + offset_ms < lease_expiry.time_ms
+    } else {
+      false
+    }
+  }
+
+  /// Sends the `tablet_bundle` to the Slave for insertion and starts a new one.
+  fn dispatch_tablet_bundle<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO) {
+    io_ctx.slave_forward(SlaveBackMessage::TabletBundleInsertion(TabletBundleInsertion {
+      tid: self.this_tid.clone(),
+      lid: self.leader_map.get(&self.this_sid.to_gid()).unwrap().clone(),
+      bundle: std::mem::replace(&mut self.tablet_bundle, TabletBundle::default()),
+    }));
+  }
+
+  /// Advances the `closed_timestamp` of this Leader to `closed_timestamp_lag_ms` before the
+  /// current time, and adds a `ClosedTimestamp` PLm so that the Followers learn of it. This is
+  /// only done if no write at or below the new `closed_timestamp` can still be prepared and no
+  /// DDL or Sharding is happening. To avoid bloating the PaxosLog, we also only do this once
+  /// the `closed_timestamp` would advance by at least half of the lag.
+  fn maybe_close_timestamp<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO, statuses: &Statuses) {
+    let lag_ms = self.tablet_config.closed_timestamp_lag_ms;
+    let timestamp = mk_t(io_ctx.now().time_ms.saturating_sub(lag_ms));
+    if timestamp.time_ms < self.closed_timestamp.time_ms + max(lag_ms / 2, 1) {
+      return;
+    }
+    if !matches!(statuses.ddl_es, DDLES::None)
+      || !matches!(statuses.sharding_state, ShardingState::None)
+    {
+      return;
+    }
+
+    // Check that every write at or below `timestamp` has been committed or aborted.
+    let first_writes = [
+      self.verifying_writes.keys().next(),
+      self.inserting_prepared_writes.keys().next(),
+      self.prepared_writes.keys().next(),
+    ];
+    if first_writes.iter().flatten().any(|write_timestamp| *write_timestamp <= &timestamp) {
+      return;
+    }
+
+    // Recall that `check_write_region_isolation` rejects writes at or below this from now on.
+    self.closed_timestamp = timestamp.clone();
+    self.tablet_bundle.push(TabletPLm::ClosedTimestamp(plm::ClosedTimestamp { timestamp }));
+  }
+
+  /// Adds a `ConvertedCol` PLm for the next chunk of the first `ColConversion`, if there is one
+  /// and no other such PLm is being inserted. Like `maybe_close_timestamp`, this is not done
+  /// while a DDL or Sharding is happening, so that a chunk never covers rows that a
+  /// ShardSplit is moving out of this Tablet.
+  fn maybe_convert_col(&mut self, statuses: &Statuses) {
+    if self.inserting_col_conversion {
+      return;
+    }
+    if !matches!(statuses.ddl_es, DDLES::None)
+      || !matches!(statuses.sharding_state, ShardingState::None)
+    {
+      return;
+    }
+
+    if let Some(conversion) = self.col_conversions.first() {
+      let (update_view, last_key) = convert_col_chunk(
+        &self.storage,
+        &self.table_schema,
+        &conversion.col_name,
+        &conversion.col_type,
+        &conversion.timestamp,
+        &conversion.after_key,
+        COL_CONVERSION_CHUNK_SIZE,
+      );
+      self.inserting_col_conversion = true;
+      self.tablet_bundle.push(TabletPLm::ConvertedCol(plm::ConvertedCol {
+        col_name: conversion.col_name.clone(),
+        timestamp: conversion.timestamp.clone(),
+        after_key: conversion.after_key.clone(),
+        last_key,
+        update_view,
+      }));
+    }
+  }
+
+  /// Writes the chunk in `converted` to `storage`, and advances the first `ColConversion`. Once
+  /// it is done, every row has its value at `timestamp`, so we remove the `ColSource`. We
+  /// ignore `converted` if it does not continue from where the `ColConversion` is, which can
+  /// happen if it was inserted by a prior Leader.
+  fn apply_converted_col(&mut self, converted: plm::ConvertedCol) {
+    if let Some(conversion) = self.col_conversions.first_mut() {
+      if conversion.col_name == converted.col_name
+        && conversion.timestamp == converted.timestamp
+        && conversion.after_key == converted.after_key
+      {
+        commit_to_storage(&mut self.storage, &converted.timestamp, converted.update_view);
+        if converted.last_key.is_some() {
+          conversion.after_key = converted.last_key;
+        } else {
+          let conversion = self.col_conversions.remove(0);
+          let source_key = (conversion.col_name, conversion.timestamp);
+          self.table_schema.val_col_sources.remove(&source_key);
+        }
+      }
+    }
+  }
+
+  /// Serves the `FollowerRead`s at or below the `closed_timestamp`, sending the responses
+  /// directly to the nodes that sent them. This can be done by any node, not just the Leader.
+  /// The `closed_timestamp` might stop advancing (e.g. while a DDL is active), so those that
+  /// have waited for longer than `follower_read_wait_ms` are answered with an error instead.
+  fn serve_follower_reads<IO: CoreIOCtx>(&mut self, io_ctx: &mut IO, statuses: &mut Statuses) {
+    let now_ms = io_ctx.now().time_ms;
+    for (arrival_ms, follower_read) in std::mem::take(&mut statuses.follower_reads) {
+      let perform_query = &follower_read.perform_query;
+      let query = match &perform_query.query {
+        msg::GeneralQuery::TableSelectQuery(query) => query,
+        _ => continue,
+      };
+      if query.timestamp > self.closed_timestamp {
+        if now_ms < arrival_ms + self.tablet_config.follower_read_wait_ms {
+          statuses.follower_reads.push((arrival_ms, follower_read));
+        } else {
+          let query_error = msg::QueryError::FollowerReadUnavailable;
+          self.respond_follower_read_error(io_ctx, follower_read, query_error);
+        }
+        continue;
+      }
+
+      let sender_path = perform_query.sender_path.clone();
+      let return_qid = sender_path.query_id;
+      let responder_path = self.mk_query_path(perform_query.query_id.clone()).into_ct();
+      let query = match perform_follower_read(self, query) {
+        Ok((result, stats)) => {
+          metrics().inc(M_FOLLOWER_READS, vec![]);
+          CommonQuery::QuerySuccess(msg::QuerySuccess {
+            return_qid,
+            responder_path,
+            result,
+            new_rms: vec![],
+            stats,
+          })
+        }
+        Err(query_error) => CommonQuery::QueryAborted(msg::QueryAborted {
+          return_qid,
+          responder_path,
+          payload: msg::AbortedData::QueryError(query_error),
+        }),
+      };
+      let payload = query.into_remote_payload(sender_path.node_path.sub);
+      io_ctx.send(
+        &follower_read.sender_eid,
+        msg::NetworkMessage::Slave(msg::SlaveMessage::FollowerReadResponse(
+          msg::FollowerReadResponse { payload },
+        )),
+      );
+    }
+  }
+
+  /// Answers the `follower_read` with `query_error` without serving it.
+  fn respond_follower_read_error<IO: CoreIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    follower_read: msg::FollowerRead,
+    query_error: msg::QueryError,
+  ) {
+    let perform_query = follower_read.perform_query;
+    let sender_path = perform_query.sender_path;
+    let query = CommonQuery::QueryAborted(msg::QueryAborted {
+      return_qid: sender_path.query_id,
+      responder_path: self.mk_query_path(perform_query.query_id).into_ct(),
+      payload: msg::AbortedData::QueryError(query_error),
+    });
+    let payload = query.into_remote_payload(sender_path.node_path.sub);
+    io_ctx.send(
+      &follower_read.sender_eid,
+      msg::NetworkMessage::Slave(msg::SlaveMessage::FollowerReadResponse(
+        msg::FollowerReadResponse { payload },
+      )),
+    );
+  }
+
   /// Check whether the `pkey` falls in the range of this Tablet's `TabletKeyRange`. The `pkey`
   /// must conform the tablets KeyCol schema (which the `TabletKeyRange` also does).
   pub fn check_range_inclusion(&self, pkey: &PrimaryKey) -> bool {
@@ -3407,6 +4185,16 @@ impl TabletContext {
   pub fn pause_ddl(&self) -> bool {
     !self.sharding_done
   }
+
+  /// Returns the `TablePath` that this Table had at `timestamp`.
+  pub fn table_path_at(&self, timestamp: &Timestamp) -> &TablePath {
+    let bound = (Bound::Excluded(timestamp), Bound::Unbounded);
+    if let Some((_, table_path)) = self.prior_table_paths.range(bound).next() {
+      table_path
+    } else {
+      &self.this_table_path
+    }
+  }
 }
 
 // -----------------------------------------------------------------------------------------------
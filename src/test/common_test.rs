@@ -1,7 +1,27 @@
-use crate::common::Timestamp;
+use crate::common::{hash_bucket, ColVal, HashBucket, PrimaryKey, TabletKeyRange, Timestamp};
 
 #[test]
 fn timestamp_test() {
   assert_eq!(Timestamp::new(1, 2).add(Timestamp::new(1, 1)), Timestamp::new(2, 3));
   assert_eq!(Timestamp::new(1, 2).add(Timestamp::new(1, u64::MAX)), Timestamp::new(3, 1));
 }
+
+#[test]
+fn hash_key_range_test() {
+  // Every key falls into exactly one of the buckets, decided by the hashed KeyCol alone.
+  let num_buckets = 3;
+  let ranges: Vec<TabletKeyRange> = (0..num_buckets)
+    .map(|bucket| TabletKeyRange {
+      start: None,
+      end: None,
+      hash: Some(HashBucket { key_idx: 1, bucket, num_buckets }),
+    })
+    .collect();
+  for i in 0..20 {
+    let key = ColVal::String(format!("key{}", i));
+    let pkey = PrimaryKey::new(vec![ColVal::Int(i), key.clone()]);
+    let containing: Vec<_> = ranges.iter().filter(|r| r.contains_pkey(&pkey)).collect();
+    assert_eq!(containing.len(), 1);
+    assert_eq!(containing[0].hash.as_ref().unwrap().bucket, hash_bucket(&key, num_buckets));
+  }
+}
@@ -19,8 +19,8 @@ fn external_request_id_map_consistency(coord: &CoordState) {
     // If this is a Leader, we make sure all RequestIds in the ESs exist in
     // the `external_request_id_map`.
     for (qid, es) in &statuses.ms_coord_ess {
-      if let Some(stored_qid) = ctx.external_request_id_map.get(&es.request_id) {
-        assert_eq!(stored_qid, qid);
+      if let Some(request) = ctx.external_request_id_map.get(&es.request_id) {
+        assert_eq!(&request.query_id, qid);
       } else {
         panic!();
       }
@@ -28,8 +28,8 @@ fn external_request_id_map_consistency(coord: &CoordState) {
 
     for (qid, es) in &statuses.finish_query_tm_ess {
       if let Some(response_data) = &es.inner.response_data {
-        if let Some(stored_qid) = ctx.external_request_id_map.get(&response_data.request_id) {
-          assert_eq!(stored_qid, qid);
+        if let Some(request) = ctx.external_request_id_map.get(&response_data.request_id) {
+          assert_eq!(&request.query_id, qid);
         } else {
           panic!();
         }
@@ -37,7 +37,8 @@ fn external_request_id_map_consistency(coord: &CoordState) {
     }
 
     // Next, we see if all entries in `external_request_id_map` are in an ES.
-    for (rid, qid) in &ctx.external_request_id_map {
+    for (rid, request) in &ctx.external_request_id_map {
+      let qid = &request.query_id;
       if let Some(es) = &statuses.ms_coord_ess.get(qid) {
         assert_eq!(&es.request_id, rid);
         assert!(!statuses.finish_query_tm_ess.contains_key(qid));
@@ -10,7 +10,7 @@ use crate::test_utils::CheckCtx;
 pub fn check_node_clean(node: &NodeState, check_ctx: &mut CheckCtx) {
   match &node.state {
     State::DNEState(_) => {}
-    State::FreeNodeState(_, _) => {}
+    State::FreeNodeState(_, _, _) => {}
     State::NominalSlaveState(slave_state, _) => {
       check_slave_clean(&slave_state, check_ctx);
     }
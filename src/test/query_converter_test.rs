@@ -17,6 +17,7 @@ fn basic_select(table_ref: &str) -> iast::Select {
     projection: vec![],
     from: basic_join_node(table_ref.to_string(), None),
     selection: iast::ValExpr::Value { val: iast::Value::Boolean(true) },
+    lock: None,
   }
 }
 
@@ -59,6 +60,7 @@ fn test_basic_rename() {
             projection: iast::SelectClause::SelectList(vec![]),
             from: basic_join_node("t2".to_string(), None),
             selection: iast::ValExpr::Value { val: iast::Value::Boolean(true) },
+            lock: None,
           }),
         },
       ),
@@ -74,6 +76,7 @@ fn test_basic_rename() {
                 projection: iast::SelectClause::SelectList(vec![]),
                 from: basic_join_node("tt\\0\\tt1".to_string(), Some("tt1".to_string())),
                 selection: iast::ValExpr::Value { val: iast::Value::Boolean(true) },
+                lock: None,
               }),
             },
           )],
@@ -82,6 +85,7 @@ fn test_basic_rename() {
             projection: iast::SelectClause::SelectList(vec![]),
             from: basic_join_node("tt\\1\\tt1".to_string(), Some("tt1".to_string())),
             selection: iast::ValExpr::Value { val: iast::Value::Boolean(true) },
+            lock: None,
           }),
         },
       ),
@@ -91,6 +95,7 @@ fn test_basic_rename() {
       projection: iast::SelectClause::SelectList(vec![]),
       from: basic_join_node("tt\\2\\tt2".to_string(), Some("tt2".to_string())),
       selection: iast::ValExpr::Value { val: iast::Value::Boolean(true) },
+      lock: None,
     }),
   };
 
@@ -131,6 +136,7 @@ fn test_basic_flatten() {
             alias: None,
           },
           selection: proc::ValExpr::Value { val: iast::Value::Boolean(true) },
+          lock: None,
         }),
       ),
       (
@@ -145,6 +151,7 @@ fn test_basic_flatten() {
             alias: None,
           },
           selection: proc::ValExpr::Value { val: iast::Value::Boolean(true) },
+          lock: None,
         }),
       ),
       (
@@ -159,6 +166,7 @@ fn test_basic_flatten() {
             alias: None,
           },
           selection: proc::ValExpr::Value { val: iast::Value::Boolean(true) },
+          lock: None,
         }),
       ),
       (
@@ -173,6 +181,7 @@ fn test_basic_flatten() {
             alias: None,
           },
           selection: proc::ValExpr::Value { val: iast::Value::Boolean(true) },
+          lock: None,
         }),
       ),
     ]
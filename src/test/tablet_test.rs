@@ -132,5 +132,8 @@ pub fn check_tablet_clean(tablet: &TabletState, check_ctx: &mut CheckCtx) {
   check_ctx.check(ctx.waiting_locked_cols.is_empty());
   check_ctx.check(ctx.inserting_locked_cols.is_empty());
 
+  check_ctx.check(ctx.col_conversions.is_empty());
+  check_ctx.check(!ctx.inserting_col_conversion);
+
   check_ctx.check(ctx.ms_root_query_map.is_empty());
 }
@@ -20,6 +20,10 @@ pub fn cvi(i: i32) -> ColVal {
   ColVal::Int(i)
 }
 
+pub fn cvbi(i: i64) -> ColVal {
+  ColVal::BigInt(i)
+}
+
 pub fn cvb(b: bool) -> ColVal {
   ColVal::Bool(b)
 }
@@ -1,10 +1,12 @@
-use crate::common::{merge_table_views, mk_qid, CoreIOCtx, OrigP};
+use crate::common::{merge_table_views, mk_qid, CoreIOCtx, ExecutionStats, OrigP};
 use crate::common::{
-  CQueryPath, CTNodePath, ColName, LeadershipId, PaxosGroupIdTrait, QueryId, SlaveGroupId,
-  TQueryPath, TableView, TabletGroupId, TransTableLocationPrefix,
+  CQueryPath, CTNodePath, CTSubNodePath, ColName, EndpointId, LeadershipId, PaxosGroupIdTrait,
+  QueryId, SlaveGroupId, TQueryPath, TableView, TabletGroupId, TransTableLocationPrefix,
 };
 use crate::message as msg;
 use crate::server::{CTServerContext, CommonQuery};
+use crate::trace::TraceContext;
+use rand::RngCore;
 use std::collections::{BTreeMap, BTreeSet};
 
 // -----------------------------------------------------------------------------------------------
@@ -14,12 +16,18 @@ use std::collections::{BTreeMap, BTreeSet};
 pub enum SendHelper {
   TableQuery(msg::GeneralQuery, Vec<TabletGroupId>),
   TransTableQuery(msg::GeneralQuery, TransTableLocationPrefix),
+  /// Sends the query directly to a node of each Tablet's PaxosGroup, which serves it once
+  /// its `closed_timestamp` passes the query's `Timestamp`. Nodes in the `BTreeSet` (which
+  /// recently did not respond to a `FollowerRead`) are avoided.
+  FollowerRead(msg::GeneralQuery, Vec<TabletGroupId>, BTreeSet<EndpointId>),
 }
 
 // These are used to perform PCSA over the network for reads and writes.
 #[derive(Debug)]
 pub struct TMStatus {
   root_query_path: CQueryPath,
+  /// The trace that is sent with the PerformQuery.
+  trace: TraceContext,
   /// The QueryId of the TMStatus.
   pub query_id: QueryId,
   /// This is the QueryId of the PerformQuery. We keep this distinct from the TMStatus'
@@ -27,6 +35,8 @@ pub struct TMStatus {
   child_query_id: QueryId,
   /// Accumulates all transitively accessed Tablets where an `MSQueryES` was used.
   new_rms: BTreeSet<TQueryPath>,
+  /// Accumulates the `ExecutionStats` of all child queries, as well as the messages sent here.
+  stats: ExecutionStats,
   /// The current set of Leaderships that this TMStatus is waiting on. Thus, in order to
   /// contact an RM, we just use the `LeadershipId` found here.
   pub leaderships: BTreeMap<SlaveGroupId, LeadershipId>,
@@ -34,6 +44,12 @@ pub struct TMStatus {
   responded_count: usize,
   /// Holds all child Querys, initially mapping to `None`. As results come in, we hold them here.
   tm_state: BTreeMap<CTNodePath, Option<Vec<TableView>>>,
+  /// The `GeneralQuery` of the `FollowerRead`s that were sent, if any. This is used to send
+  /// them to the Leader instead (see `redirect_follower_read`).
+  follower_read_query: Option<msg::GeneralQuery>,
+  /// The RMs that were sent a `FollowerRead` which has neither been responded to nor been
+  /// redirected to the Leader, mapping to the node that it was sent to.
+  pending_follower_reads: BTreeMap<CTNodePath, EndpointId>,
   pub orig_p: OrigP,
 }
 
@@ -41,16 +57,21 @@ impl TMStatus {
   pub fn new<IO: CoreIOCtx>(
     io_ctx: &mut IO,
     root_query_path: CQueryPath,
+    trace: TraceContext,
     orig_p: OrigP,
   ) -> TMStatus {
     TMStatus {
       root_query_path,
+      trace,
       query_id: mk_qid(io_ctx.rand()),
       child_query_id: mk_qid(io_ctx.rand()),
       new_rms: Default::default(),
+      stats: Default::default(),
       leaderships: Default::default(),
       responded_count: 0,
       tm_state: Default::default(),
+      follower_read_query: None,
+      pending_follower_reads: Default::default(),
       orig_p,
     }
   }
@@ -112,11 +133,47 @@ impl TMStatus {
         let to_node_path = location_prefix.source.node_path.clone();
         self.send_perform(ctx, io_ctx, general_query, to_node_path, to_lid.clone());
       }
+      SendHelper::FollowerRead(general_query, tids, avoid_eids) => {
+        // Here, no LeadershipId needs to be validated, since any node can respond.
+        assert!(tids.len() > 0);
+        self.follower_read_query = Some(general_query.clone());
+        for tid in tids {
+          let to_node_path = ctx.mk_tablet_node_path(tid).into_ct();
+          let sid = &to_node_path.sid;
+          let to_lid = query_leader_map.get(sid).or(ctx.leader_map().get(&sid.to_gid())).unwrap();
+          self.send_follower_read(
+            ctx,
+            io_ctx,
+            general_query.clone(),
+            to_node_path,
+            to_lid.clone(),
+            &avoid_eids,
+          );
+        }
+      }
     }
 
     true
   }
 
+  /// Construct the `PerformQuery` that is sent to every RM.
+  fn mk_perform_query<IO: CoreIOCtx, Ctx: CTServerContext>(
+    &self,
+    ctx: &mut Ctx,
+    io_ctx: &mut IO,
+    general_query: msg::GeneralQuery,
+  ) -> msg::PerformQuery {
+    let sender_path = ctx.mk_this_query_path(self.query_id.clone());
+    msg::PerformQuery {
+      root_query_path: self.root_query_path.clone(),
+      sender_path,
+      query_id: self.child_query_id.clone(),
+      query: general_query,
+      trace: self.trace.clone(),
+      sender_time: ctx.clock().now(io_ctx),
+    }
+  }
+
   /// Cleans up all currently owned resources, and goes to Done.
   pub fn send_perform<IO: CoreIOCtx, Ctx: CTServerContext>(
     &mut self,
@@ -126,41 +183,120 @@ impl TMStatus {
     to_node_path: CTNodePath,
     to_lid: LeadershipId,
   ) {
-    let sender_path = ctx.mk_this_query_path(self.query_id.clone());
     // Construct PerformQuery
-    let perform_query = msg::PerformQuery {
-      root_query_path: self.root_query_path.clone(),
-      sender_path,
-      query_id: self.child_query_id.clone(),
-      query: general_query,
-    };
+    let perform_query = self.mk_perform_query(ctx, io_ctx, general_query);
 
     // Send out PerformQuery. Recall that this could only be a Tablet.
     let common_query = CommonQuery::PerformQuery(perform_query);
     ctx.send_to_ct_lid(io_ctx, to_node_path.clone(), common_query, to_lid.clone());
+    self.stats.messages_sent += 1;
+
+    // Add the TabletGroup into the TMStatus.
+    self.leaderships.insert(to_node_path.sid.clone(), to_lid);
+    self.tm_state.insert(to_node_path, None);
+  }
+
+  /// Sends a `FollowerRead` to a node in the PaxosGroup of `to_node_path` that is not in
+  /// `avoid_eids`, preferring this node. If there is no such node, we send a `PerformQuery`
+  /// to the Leader instead. We still record `to_lid` so that a Leadership change there is
+  /// handled like for `send_perform`.
+  fn send_follower_read<IO: CoreIOCtx, Ctx: CTServerContext>(
+    &mut self,
+    ctx: &mut Ctx,
+    io_ctx: &mut IO,
+    general_query: msg::GeneralQuery,
+    to_node_path: CTNodePath,
+    to_lid: LeadershipId,
+    avoid_eids: &BTreeSet<EndpointId>,
+  ) {
+    let eids = ctx.gossip().get().slave_address_config.get(&to_node_path.sid).unwrap().clone();
+    let eids: Vec<EndpointId> = eids.into_iter().filter(|eid| !avoid_eids.contains(eid)).collect();
+    let eid = if eids.contains(ctx.this_eid()) {
+      ctx.this_eid().clone()
+    } else if !eids.is_empty() {
+      eids[io_ctx.rand().next_u32() as usize % eids.len()].clone()
+    } else {
+      self.send_perform(ctx, io_ctx, general_query, to_node_path, to_lid);
+      return;
+    };
+
+    let perform_query = self.mk_perform_query(ctx, io_ctx, general_query);
+    let tid = cast!(CTSubNodePath::Tablet, &to_node_path.sub).unwrap().clone();
+    let follower_read =
+      msg::FollowerRead { sender_eid: ctx.this_eid().clone(), tid, perform_query };
+    io_ctx.send(&eid, msg::NetworkMessage::Slave(msg::SlaveMessage::FollowerRead(follower_read)));
+    self.stats.messages_sent += 1;
 
     // Add the TabletGroup into the TMStatus.
     self.leaderships.insert(to_node_path.sid.clone(), to_lid);
+    self.pending_follower_reads.insert(to_node_path.clone(), eid);
     self.tm_state.insert(to_node_path, None);
   }
 
+  /// Returns true iff there is a `FollowerRead` that has neither been responded to nor
+  /// been redirected to the Leader.
+  pub fn has_pending_follower_reads(&self) -> bool {
+    !self.pending_follower_reads.is_empty()
+  }
+
+  /// Sends the `FollowerRead` to the RM at `node_path` to its Leader instead, if it is still
+  /// pending. This returns the node that the `FollowerRead` had been sent to.
+  pub fn redirect_follower_read<IO: CoreIOCtx, Ctx: CTServerContext>(
+    &mut self,
+    ctx: &mut Ctx,
+    io_ctx: &mut IO,
+    node_path: &CTNodePath,
+  ) -> Option<EndpointId> {
+    let eid = self.pending_follower_reads.remove(node_path)?;
+    let general_query = self.follower_read_query.clone().unwrap();
+    let perform_query = self.mk_perform_query(ctx, io_ctx, general_query);
+    let to_lid = self.leaderships.get(&node_path.sid).unwrap().clone();
+    let common_query = CommonQuery::PerformQuery(perform_query);
+    ctx.send_to_ct_lid(io_ctx, node_path.clone(), common_query, to_lid);
+    self.stats.messages_sent += 1;
+    Some(eid)
+  }
+
+  /// Redirects all pending `FollowerRead`s to their Leaders (see `redirect_follower_read`),
+  /// returning the nodes that they had been sent to.
+  pub fn redirect_follower_reads<IO: CoreIOCtx, Ctx: CTServerContext>(
+    &mut self,
+    ctx: &mut Ctx,
+    io_ctx: &mut IO,
+  ) -> Vec<EndpointId> {
+    let node_paths: Vec<CTNodePath> = self.pending_follower_reads.keys().cloned().collect();
+    let mut eids = Vec::<EndpointId>::new();
+    for node_path in node_paths {
+      eids.extend(self.redirect_follower_read(ctx, io_ctx, &node_path));
+    }
+    eids
+  }
+
   /// We accumulate the results of the `query_success` here.
   pub fn handle_query_success(&mut self, query_success: msg::QuerySuccess) {
     let node_path = query_success.responder_path.node_path;
+    // If a `FollowerRead` was redirected to the Leader, both might respond. We take the first.
+    if !matches!(self.tm_state.get(&node_path), Some(None)) {
+      return;
+    }
+    self.pending_follower_reads.remove(&node_path);
     self.tm_state.insert(node_path, Some(query_success.result.clone()));
     self.new_rms.extend(query_success.new_rms);
+    self.stats.merge(query_success.stats);
+    // Account for the `QuerySuccess` itself.
+    self.stats.messages_sent += 1;
     self.responded_count += 1;
   }
 
   /// Merge there `TableView`s together. Note that this should be only called when
   /// all child queries have responded.
-  pub fn get_results(self) -> (OrigP, Vec<TableView>, BTreeSet<TQueryPath>) {
+  pub fn get_results(self) -> (OrigP, Vec<TableView>, BTreeSet<TQueryPath>, ExecutionStats) {
     debug_assert!(self.is_complete());
     let mut results = Vec::<Vec<TableView>>::new();
     for (_, rm_result) in self.tm_state {
       results.push(rm_result.unwrap());
     }
-    (self.orig_p, merge_table_views(results), self.new_rms)
+    (self.orig_p, merge_table_views(results), self.new_rms, self.stats)
   }
 
   pub fn is_complete(&self) -> bool {
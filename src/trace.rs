@@ -0,0 +1,279 @@
+use crate::common::{
+  mk_qid, rand_string, BasicIOCtx, EndpointId, GeneralTraceMessage, QueryId, RequestId, Timestamp,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Distributed tracing follows an External request across every node that does work for it.
+/// When a Coord receives a request, it starts a trace with a root span. Every message that asks
+/// another node to do work on behalf of the request (`PerformQuery`, `PerformMasterQueryPlanning`,
+/// and the Paxos2PC `Prepare` and `Commit`) carries a `TraceContext`. The receiving node starts
+/// a span for the ES that does the work, parented to the span of the sender.
+///
+/// Spans are emitted through `GeneralTraceMessage::Span` once they finish. In production, each
+/// node appends them to a local trace log (see `Span::to_log_line`), and `stitch_timeline`
+/// combines the logs of all nodes into a timeline for a single `RequestId`.
+
+// -----------------------------------------------------------------------------------------------
+//  Trace Data
+// -----------------------------------------------------------------------------------------------
+
+/// A global identifier of a trace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TraceId(pub String);
+
+pub fn mk_trace_id<R: Rng>(rng: &mut R) -> TraceId {
+  TraceId(rand_string(rng))
+}
+
+/// This is sent with a message so that the receiver can attribute the work it does to the
+/// trace `trace_id`. The `span_id` is the span on whose behalf the message was sent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+  pub trace_id: TraceId,
+  pub span_id: QueryId,
+}
+
+impl TraceContext {
+  /// Returns the `TraceContext` for work done on behalf of the span `span_id` in this trace.
+  pub fn child(&self, span_id: &QueryId) -> TraceContext {
+    TraceContext { trace_id: self.trace_id.clone(), span_id: span_id.clone() }
+  }
+}
+
+/// The work done by a single ES at a node for a trace. Spans are usually identified by the
+/// `QueryId` of the ES. The root span of a trace has no parent, and holds the `RequestId` of the External
+/// request that started the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+  pub trace_id: TraceId,
+  pub span_id: QueryId,
+  pub parent_span_id: Option<QueryId>,
+  pub request_id: Option<RequestId>,
+  /// The address of the node that did the work.
+  pub node: String,
+  pub es_type: String,
+  pub start_ms: u128,
+  pub end_ms: u128,
+}
+
+impl Span {
+  /// Renders this `Span` as a line of a trace log. The fields are separated by tabs, where
+  /// absent fields are rendered as `-`.
+  pub fn to_log_line(&self) -> String {
+    let opt_str = |s: Option<&String>| s.cloned().unwrap_or("-".to_string());
+    format!(
+      "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+      self.trace_id.0,
+      self.span_id.0,
+      opt_str(self.parent_span_id.as_ref().map(|span_id| &span_id.0)),
+      opt_str(self.request_id.as_ref().map(|request_id| &request_id.0)),
+      self.node,
+      self.es_type,
+      self.start_ms,
+      self.end_ms
+    )
+  }
+
+  /// Parses a line produced by `to_log_line`, returning `None` if it is malformed.
+  pub fn parse_log_line(line: &str) -> Option<Span> {
+    let fields: Vec<&str> = line.trim_end_matches(&['\r', '\n'][..]).split('\t').collect();
+    if fields.len() != 8 {
+      return None;
+    }
+    let opt_field = |field: &str| if field == "-" { None } else { Some(field.to_string()) };
+    Some(Span {
+      trace_id: TraceId(fields[0].to_string()),
+      span_id: QueryId(fields[1].to_string()),
+      parent_span_id: opt_field(fields[2]).map(QueryId),
+      request_id: opt_field(fields[3]).map(RequestId),
+      node: fields[4].to_string(),
+      es_type: fields[5].to_string(),
+      start_ms: fields[6].parse().ok()?,
+      end_ms: fields[7].parse().ok()?,
+    })
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  OpenSpans
+// -----------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct OpenSpan {
+  span_id: QueryId,
+  trace_id: TraceId,
+  parent_span_id: Option<QueryId>,
+  request_id: Option<RequestId>,
+  es_type: &'static str,
+  start: Timestamp,
+}
+
+/// Holds the spans at a node that have started but not finished yet, keyed by the `QueryId`
+/// of the ES doing the work.
+#[derive(Debug, Default)]
+pub struct OpenSpans {
+  spans: BTreeMap<QueryId, OpenSpan>,
+}
+
+impl OpenSpans {
+  /// Starts the root span `span_id` of the trace `trace_id` for the External request `request_id`.
+  pub fn start_root<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    span_id: &QueryId,
+    trace_id: &TraceId,
+    request_id: &RequestId,
+    es_type: &'static str,
+  ) {
+    let request_id = Some(request_id.clone());
+    self.insert(io_ctx, span_id, span_id.clone(), trace_id, None, request_id, es_type);
+  }
+
+  /// Starts the span `span_id` for work that an ES of type `es_type` is doing on behalf of
+  /// `trace`. If the span is already open (e.g. due to a resent message), this does nothing.
+  pub fn start<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    span_id: &QueryId,
+    trace: &TraceContext,
+    es_type: &'static str,
+  ) {
+    let parent_span_id = Some(trace.span_id.clone());
+    self.insert(io_ctx, span_id, span_id.clone(), &trace.trace_id, parent_span_id, None, es_type);
+  }
+
+  /// Starts the span for an RM of the Paxos2PC `query_id`. Since `query_id` already identifies
+  /// the span of the TM, the RM's span is given a fresh span id.
+  pub fn start_rm<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    query_id: &QueryId,
+    trace: &TraceContext,
+    es_type: &'static str,
+  ) {
+    if !self.spans.contains_key(query_id) {
+      let span_id = mk_qid(io_ctx.rand());
+      let parent_span_id = Some(trace.span_id.clone());
+      self.insert(io_ctx, query_id, span_id, &trace.trace_id, parent_span_id, None, es_type);
+    }
+  }
+
+  fn insert<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    key: &QueryId,
+    span_id: QueryId,
+    trace_id: &TraceId,
+    parent_span_id: Option<QueryId>,
+    request_id: Option<RequestId>,
+    es_type: &'static str,
+  ) {
+    if !self.spans.contains_key(key) {
+      let start = io_ctx.now();
+      let trace_id = trace_id.clone();
+      let span = OpenSpan { span_id, trace_id, parent_span_id, request_id, es_type, start };
+      self.spans.insert(key.clone(), span);
+    }
+  }
+
+  /// Drops the span of the ES `query_id` without emitting it. This is used when the ES exits
+  /// without finishing its work.
+  pub fn abandon(&mut self, query_id: &QueryId) {
+    self.spans.remove(query_id);
+  }
+
+  /// Finishes the span of the ES `query_id` (if it is open) and emits it.
+  pub fn finish<IO: BasicIOCtx>(
+    &mut self,
+    io_ctx: &mut IO,
+    this_eid: &EndpointId,
+    query_id: &QueryId,
+  ) {
+    if let Some(span) = self.spans.remove(query_id) {
+      let end = io_ctx.now();
+      io_ctx.general_trace(GeneralTraceMessage::Span(Span {
+        trace_id: span.trace_id,
+        span_id: span.span_id,
+        parent_span_id: span.parent_span_id,
+        request_id: span.request_id,
+        node: this_eid.address.clone(),
+        es_type: span.es_type.to_string(),
+        start_ms: span.start.time_ms,
+        end_ms: end.time_ms,
+      }));
+    }
+  }
+}
+
+// -----------------------------------------------------------------------------------------------
+//  Timeline
+// -----------------------------------------------------------------------------------------------
+
+/// Stitches the `spans` (collected from the trace logs of all nodes) into a timeline of the
+/// trace of the External request `request_id`. Every line describes a span, where children are
+/// indented below their parent and siblings are ordered by start time. Times are relative to
+/// the start of the root span. Spans whose parent is missing (e.g. because a node's trace log
+/// was not provided) are listed at the end. This returns `None` if there is no root span
+/// for `request_id`.
+pub fn stitch_timeline(spans: &Vec<Span>, request_id: &RequestId) -> Option<Vec<String>> {
+  let root = spans.iter().find(|span| span.request_id.as_ref() == Some(request_id))?;
+  let trace_spans: Vec<&Span> =
+    spans.iter().filter(|span| span.trace_id == root.trace_id).collect();
+
+  // Group the spans by their parent.
+  let mut children = BTreeMap::<Option<&QueryId>, Vec<&Span>>::new();
+  for span in &trace_spans {
+    children.entry(span.parent_span_id.as_ref()).or_default().push(span);
+  }
+  for siblings in children.values_mut() {
+    siblings.sort_by_key(|span| (span.start_ms, span.end_ms));
+  }
+
+  fn push_span(
+    lines: &mut Vec<String>,
+    children: &BTreeMap<Option<&QueryId>, Vec<&Span>>,
+    base_ms: u128,
+    span: &Span,
+    depth: usize,
+  ) {
+    lines.push(format!(
+      "{}{} at {}: +{} ms, took {} ms",
+      "  ".repeat(depth),
+      span.es_type,
+      span.node,
+      span.start_ms.saturating_sub(base_ms),
+      span.end_ms.saturating_sub(span.start_ms)
+    ));
+    if let Some(span_children) = children.get(&Some(&span.span_id)) {
+      for child in span_children {
+        push_span(lines, children, base_ms, child, depth + 1);
+      }
+    }
+  }
+
+  let mut lines = Vec::<String>::new();
+  lines.push(format!("Trace {} for Request {}", root.trace_id.0, request_id.0));
+  push_span(&mut lines, &children, root.start_ms, root, 1);
+
+  // List the spans whose parent is missing.
+  let span_ids: Vec<&QueryId> = trace_spans.iter().map(|span| &span.span_id).collect();
+  let mut orphans: Vec<&Span> = trace_spans
+    .iter()
+    .filter(|span| match &span.parent_span_id {
+      Some(parent_span_id) => !span_ids.contains(&parent_span_id),
+      None => false,
+    })
+    .cloned()
+    .collect();
+  if !orphans.is_empty() {
+    orphans.sort_by_key(|span| (span.start_ms, span.end_ms));
+    lines.push("Spans with missing parents".to_string());
+    for orphan in orphans {
+      push_span(&mut lines, &children, root.start_ms, orphan, 1);
+    }
+  }
+
+  Some(lines)
+}
@@ -1,5 +1,5 @@
 use crate::common::{
-  add_item, mk_qid, remove_item, CoreIOCtx, QueryESResult, QueryPlan, Timestamp,
+  add_item, mk_qid, remove_item, CoreIOCtx, ExecutionStats, QueryESResult, QueryPlan, Timestamp,
 };
 use crate::common::{
   CQueryPath, ColName, ColValN, ContextRow, ContextSchema, PaxosGroupId, PaxosGroupIdTrait,
@@ -17,6 +17,7 @@ use crate::table_read_es::{check_gossip, fully_evaluate_select};
 use crate::tablet::{
   compute_children, compute_contexts, Executing, TPESAction, TPESBase, TabletContext,
 };
+use crate::trace::TraceContext;
 use std::collections::BTreeSet;
 use std::iter::FromIterator;
 use std::ops::Deref;
@@ -38,6 +39,8 @@ pub enum TransExecutionS {
 #[derive(Debug)]
 pub struct TransTableReadES {
   pub root_query_path: CQueryPath,
+  /// The trace that child queries are performed for, where the span is this ES.
+  pub trace: TraceContext,
   pub location_prefix: TransTableLocationPrefix,
   pub context: Rc<Context>,
 
@@ -51,6 +54,7 @@ pub struct TransTableReadES {
 
   // Dynamically evolving fields.
   pub new_rms: BTreeSet<TQueryPath>,
+  pub stats: ExecutionStats,
   pub state: TransExecutionS,
   pub child_queries: Vec<QueryId>,
 
@@ -148,6 +152,16 @@ impl<'a, SourceT: TransTableSource> LocalTable for TransLocalTable<'a, SourceT>
 // -----------------------------------------------------------------------------------------------
 
 impl TransTableReadES {
+  /// Returns the name of the current state (for `SHOW QUERIES`).
+  pub fn state_name(&self) -> &'static str {
+    match &self.state {
+      TransExecutionS::Start => "Start",
+      TransExecutionS::GossipDataWaiting => "GossipDataWaiting",
+      TransExecutionS::Executing(_) => "Executing",
+      TransExecutionS::Done => "Done",
+    }
+  }
+
   pub fn sender_sid(&self) -> &SlaveGroupId {
     &self.sender_path.node_path.sid
   }
@@ -236,6 +250,7 @@ impl TransTableReadES {
     // Finally, compute the GRQueryESs.
     let subquery_view = GRQueryConstructorView {
       root_query_path: &self.root_query_path,
+      trace: &self.trace,
       timestamp: &self.timestamp,
       sql_query: &self.sql_query,
       query_plan: &self.query_plan,
@@ -286,10 +301,12 @@ impl TransTableReadES {
     trans_table_source: &SourceT,
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     table_views: Vec<TableView>,
   ) -> Option<TPESAction> {
     // Add the subquery results into the TableReadES.
     self.new_rms.extend(subquery_new_rms);
+    self.stats.merge(subquery_stats);
     let exec = cast!(TransExecutionS::Executing, &mut self.state)?;
     exec.add_subquery_result(subquery_id, table_views);
 
@@ -327,7 +344,7 @@ impl TransTableReadES {
 
     // Evaluate
     let eval_res = fully_evaluate_select(
-      context_constructor,
+      &context_constructor,
       &self.context.deref(),
       subquery_results,
       &self.sql_query,
@@ -340,6 +357,7 @@ impl TransTableReadES {
         Some(TPESAction::Success(QueryESResult {
           result: res_table_views,
           new_rms: self.new_rms.iter().cloned().collect(),
+          stats: self.stats.clone(),
         }))
       }
       Err(eval_error) => {
@@ -430,6 +448,7 @@ impl TPESBase for TransTableReadES {
     es_ctx: &mut Self::ESContext,
     subquery_id: QueryId,
     subquery_new_rms: BTreeSet<TQueryPath>,
+    subquery_stats: ExecutionStats,
     results: Vec<TableView>,
   ) -> Option<TPESAction> {
     TransTableReadES::handle_subquery_done(
@@ -439,6 +458,7 @@ impl TPESBase for TransTableReadES {
       es_ctx,
       subquery_id,
       subquery_new_rms,
+      subquery_stats,
       results,
     )
   }